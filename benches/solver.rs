@@ -1,61 +1,38 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 use putnam::{solve, types::*};
+use putnam::solver::Solver;
+use putnam::solver::dpll::Propagation;
+use putnam::gen::{random_ksat, threshold_clauses};
+use putnam::parser::{parse_and_convert, write_dimacs};
+use putnam::gen::families::{pigeonhole, tseitin};
 
 fn create_simple_sat() -> (Formula, usize) {
     // (x0 ∨ x1) ∧ (¬x0 ∨ x2) ∧ (¬x1 ∨ ¬x2)
     let formula = vec![
-        vec![Lit { var: 0, neg: false }, Lit { var: 1, neg: false }],
-        vec![Lit { var: 0, neg: true }, Lit { var: 2, neg: false }],
-        vec![Lit { var: 1, neg: true }, Lit { var: 2, neg: true }],
+        vec![Lit::positive(Var::new(0)), Lit::positive(Var::new(1))],
+        vec![Lit::negative(Var::new(0)), Lit::positive(Var::new(2))],
+        vec![Lit::negative(Var::new(1)), Lit::negative(Var::new(2))],
     ];
     (formula, 3)
 }
 
-fn create_pigeonhole(n: usize) -> (Formula, usize) {
-    // n+1 pigeons, n holes - classically UNSAT
-    let mut formula = Vec::new();
-    let num_vars = (n + 1) * n;
-    
-    // Each pigeon must be in at least one hole
-    for pigeon in 0..=n {
-        let clause: Vec<Lit> = (0..n)
-            .map(|hole| Lit { var: pigeon * n + hole, neg: false })
-            .collect();
-        formula.push(clause);
-    }
-    
-    // No two pigeons in same hole
-    for hole in 0..n {
-        for p1 in 0..=n {
-            for p2 in (p1 + 1)..=n {
-                formula.push(vec![
-                    Lit { var: p1 * n + hole, neg: true },
-                    Lit { var: p2 * n + hole, neg: true },
-                ]);
-            }
-        }
-    }
-    
-    (formula, num_vars)
-}
-
 fn create_chain_sat(n: usize) -> (Formula, usize) {
     // (x0 ∨ x1) ∧ (¬x0 ∨ x2) ∧ (¬x1 ∨ x3) ∧ ... - chain of implications
     let mut formula = Vec::new();
     
     formula.push(vec![
-        Lit { var: 0, neg: false },
-        Lit { var: 1, neg: false },
+        Lit::positive(Var::new(0)),
+        Lit::positive(Var::new(1)),
     ]);
     
     for i in 0..(n - 2) {
         formula.push(vec![
-            Lit { var: i, neg: true },
-            Lit { var: i + 2, neg: false },
+            Lit::negative(Var::new(i)),
+            Lit::positive(Var::new(i + 2)),
         ]);
         formula.push(vec![
-            Lit { var: i + 1, neg: true },
-            Lit { var: i + 2, neg: false },
+            Lit::negative(Var::new(i + 1)),
+            Lit::positive(Var::new(i + 2)),
         ]);
     }
     
@@ -74,7 +51,7 @@ fn bench_pigeonhole(c: &mut Criterion) {
     let mut group = c.benchmark_group("pigeonhole");
     
     for n in [3, 4, 5].iter() {
-        let (formula, num_vars) = create_pigeonhole(*n);
+        let (formula, num_vars, _) = pigeonhole(*n);
         group.bench_with_input(format!("php_{}_{}", n + 1, n), n, |b, _| {
             b.iter(|| solve(black_box(&formula), black_box(num_vars)))
         });
@@ -96,5 +73,64 @@ fn bench_chain_sat(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_simple_sat, bench_pigeonhole, bench_chain_sat);
+fn bench_tseitin(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tseitin");
+    
+    for n in [6, 8, 10].iter() {
+        let (formula, num_vars, info) = tseitin(*n, 3, 1);
+        group.bench_with_input(info.name, n, |b, _| {
+            b.iter(|| solve(black_box(&formula), black_box(num_vars)))
+        });
+    }
+    
+    group.finish();
+}
+
+fn bench_random_3sat(c: &mut Criterion) {
+    let mut group = c.benchmark_group("random_3sat");
+    
+    for n in [20, 30, 40].iter() {
+        let (formula, num_vars) = random_ksat(*n, threshold_clauses(*n, 3), 3, 1);
+        group.bench_with_input(format!("threshold_{}", n), n, |b, _| {
+            b.iter(|| solve(black_box(&formula), black_box(num_vars)))
+        });
+    }
+    
+    group.finish();
+}
+
+fn bench_propagation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("propagation");
+    
+    let (php, php_vars, _) = pigeonhole(5);
+    let (random, random_vars) = random_ksat(40, threshold_clauses(40, 3), 3, 1);
+    for (name, formula, num_vars) in [("php_6_5", php, php_vars), ("threshold_40", random, random_vars)] {
+        for propagation in [Propagation::Scan, Propagation::Counters] {
+            let mut solver = Solver::from_formula(formula.clone(), num_vars);
+            solver.set_propagation(propagation);
+            solver.set_caching(false);
+            group.bench_function(format!("{}_{:?}", name, propagation), |b| {
+                b.iter(|| solver.solve())
+            });
+        }
+    }
+    
+    group.finish();
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    
+    let (formula, num_vars) = random_ksat(100_000, 420_000, 3, 1);
+    let mut dimacs = Vec::new();
+    write_dimacs(&mut dimacs, &formula, num_vars).unwrap();
+    group.throughput(Throughput::Bytes(dimacs.len() as u64));
+    group.bench_function("random_3sat_100k", |b| {
+        b.iter(|| parse_and_convert(black_box(dimacs.as_slice())).unwrap())
+    });
+    
+    group.finish();
+}
+
+criterion_group!(benches, bench_simple_sat, bench_pigeonhole, bench_chain_sat, bench_tseitin, bench_random_3sat, bench_propagation, bench_parse);
 criterion_main!(benches);
\ No newline at end of file
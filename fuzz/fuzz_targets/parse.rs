@@ -0,0 +1,18 @@
+//! Arbitrary bytes as a DIMACS file: parsing must fail with an error or give
+//! a formula that the solver decides, with a model that satisfies it.
+//!
+//! `fuzz/seeds/parse` holds inputs that once crashed; pass it as a second
+//! corpus: `cargo fuzz run parse fuzz/corpus/parse fuzz/seeds/parse`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use putnam::parser::parse_and_convert;
+use putnam::solver::dpll::SolveResult;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok((formula, num_vars)) = parse_and_convert(data) else { return };
+    if let SolveResult::Sat(model) = putnam::solve(&formula, num_vars) {
+        assert!(formula.iter().all(|clause| clause.iter().any(|&lit| model.is_true(lit))));
+    }
+});
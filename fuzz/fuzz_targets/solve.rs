@@ -0,0 +1,29 @@
+//! Arbitrary formulas, including malformed ones: literals beyond the
+//! variable count, repeated and complementary literals, empty clauses. Both
+//! entry points must answer, agree, and give models that satisfy the formula.
+//!
+//! The first byte is the variable count; each further byte is a literal
+//! (variable in the upper bits, sign in the lowest), with 0 ending a clause.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use putnam::solver::dpll::{self, SolveResult};
+use putnam::types::{Formula, Lit, Var};
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&num_vars, rest)) = data.split_first() else { return };
+    let num_vars = usize::from(num_vars % 32);
+    let formula: Formula = rest
+        .split(|&byte| byte == 0)
+        .map(|clause| clause.iter().map(|&byte| Lit::new(Var::new(usize::from(byte >> 1) % 40), byte & 1 == 1)).collect())
+        .collect();
+    let satisfies = |result: &SolveResult| match result {
+        SolveResult::Sat(model) => formula.iter().all(|clause| clause.iter().any(|&lit| model.is_true(lit))),
+        SolveResult::Unsat => true,
+    };
+    let special = putnam::solve(&formula, num_vars);
+    let search = dpll::solve(&formula, num_vars);
+    assert!(satisfies(&special) && satisfies(&search));
+    assert_eq!(matches!(special, SolveResult::Sat(_)), matches!(search, SolveResult::Sat(_)));
+});
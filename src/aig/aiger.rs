@@ -0,0 +1,280 @@
+//! AIGER format parser
+//!
+//! This module reads circuits in the AIGER format (version 1.9), both the
+//! ASCII variant (header `aag`) and the binary variant (header `aig`).
+//!
+//! The header is `aag M I L O A [B C J F]` where `M` is the maximum
+//! variable index and `I`, `L`, `O`, `A`, `B`, `C` count inputs, latches,
+//! outputs, AND gates, bad-state properties and invariant constraints.
+//! Justice and fairness properties (`J`, `F`) are not supported.
+//!
+//! In the binary variant inputs and latch literals are implicit and AND
+//! gates are stored as two variable-length delta-encoded integers each.
+//! Symbol tables and comments after the circuit are ignored.
+//!
+//! # Example
+//!
+//! ```
+//! use putnam::aig::{aiger::parse_aiger, Property};
+//! use putnam::solver::dpll::SolveResult;
+//!
+//! // Output = x ∧ y
+//! let aig = parse_aiger("aag 3 2 0 1 1\n2\n4\n6\n6 2 4\n".as_bytes())?;
+//! let (formula, num_vars) = aig.property_cnf(Property::Output(0));
+//! assert!(matches!(putnam::solve(&formula, num_vars), SolveResult::Sat(_)));
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::io::{self, Read};
+
+use super::*;
+
+/// Parses an AIGER circuit (ASCII or binary) from a reader.
+///
+/// # Arguments
+///
+/// * `reader` - A reader positioned at the AIGER header
+///
+/// # Returns
+///
+/// * `Ok(Aig)` - The parsed circuit
+/// * `Err(io::Error)` - If reading fails, or with `ErrorKind::InvalidData`
+///   if the input is not a well-formed AIGER file
+pub fn parse_aiger<R: Read>(mut reader: R) -> io::Result<Aig> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    Cursor { bytes: &bytes, pos: 0 }.parse()
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Reading position in the raw input.
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    /// Reads the next line (without the trailing newline).
+    fn line(&mut self) -> io::Result<&str> {
+        let rest = &self.bytes[self.pos..];
+        if rest.is_empty() {
+            return Err(invalid("unexpected end of file"));
+        }
+        let len = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+        self.pos += (len + 1).min(rest.len());
+        std::str::from_utf8(&rest[..len]).map_err(|_| invalid("invalid UTF-8 in text section"))
+    }
+
+    /// Reads a line of whitespace-separated numbers.
+    fn numbers(&mut self) -> io::Result<Vec<usize>> {
+        self.line()?
+            .split_whitespace()
+            .map(|tok| tok.parse().map_err(|_| invalid(format!("expected a number, got '{}'", tok))))
+            .collect()
+    }
+
+    /// Reads one 7-bit variable-length encoded integer (binary AND section).
+    fn varint(&mut self) -> io::Result<usize> {
+        let mut value = 0usize;
+        let mut shift = 0;
+        loop {
+            let byte = *self.bytes.get(self.pos).ok_or_else(|| invalid("truncated binary AND section"))?;
+            self.pos += 1;
+            if shift >= usize::BITS {
+                return Err(invalid("binary delta overflows"));
+            }
+            value |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+
+    fn parse(&mut self) -> io::Result<Aig> {
+        let header = self.line()?.to_string();
+        let mut fields = header.split_whitespace();
+        let binary = match fields.next() {
+            Some("aag") => false,
+            Some("aig") => true,
+            _ => return Err(invalid("expected 'aag' or 'aig' header")),
+        };
+        let counts: Vec<usize> = fields
+            .map(|tok| tok.parse().map_err(|_| invalid(format!("bad header field '{}'", tok))))
+            .collect::<io::Result<_>>()?;
+        if counts.len() < 5 || counts.len() > 9 {
+            return Err(invalid("header must have between 5 and 9 counts"));
+        }
+        let count = |i: usize| counts.get(i).copied().unwrap_or(0);
+        let (m, i, l, o, a, b, c) = (count(0), count(1), count(2), count(3), count(4), count(5), count(6));
+        if count(7) != 0 || count(8) != 0 {
+            return Err(invalid("justice and fairness properties are not supported"));
+        }
+        match i.checked_add(l).and_then(|sum| sum.checked_add(a)) {
+            Some(sum) if sum <= m => {}
+            _ => return Err(invalid("M is smaller than I + L + A")),
+        }
+
+        // Literals up to 2M + 1 must be representable
+        let max_lit = m.checked_mul(2).and_then(|lit| lit.checked_add(1)).ok_or_else(|| invalid("M is too large"))?;
+        let check = |lit: usize| {
+            if lit > max_lit {
+                Err(invalid(format!("literal {} exceeds maximum {}", lit, max_lit)))
+            } else {
+                Ok(lit)
+            }
+        };
+        let mut aig = Aig { max_var: m, ..Aig::default() };
+
+        for k in 0..i {
+            if binary {
+                aig.inputs.push(2 * (k + 1));
+            } else {
+                let lit = self.single()?;
+                if lit < 2 || lit & 1 == 1 {
+                    return Err(invalid(format!("invalid input literal {}", lit)));
+                }
+                aig.inputs.push(check(lit)?);
+            }
+        }
+
+        for k in 0..l {
+            let nums = self.numbers()?;
+            let (lit, rest) = if binary {
+                (2 * (i + k + 1), &nums[..])
+            } else {
+                let (&lit, rest) = nums.split_first().ok_or_else(|| invalid("empty latch line"))?;
+                (lit, rest)
+            };
+            let next = *rest.first().ok_or_else(|| invalid("latch without next state"))?;
+            let init = match rest.get(1) {
+                None | Some(0) => LatchInit::Zero,
+                Some(1) => LatchInit::One,
+                Some(&x) if x == lit => LatchInit::Undef,
+                Some(x) => return Err(invalid(format!("invalid latch reset value {}", x))),
+            };
+            aig.latches.push(Latch { lit: check(lit)?, next: check(next)?, init });
+        }
+
+        for _ in 0..o {
+            aig.outputs.push(check(self.single()?)?);
+        }
+        for _ in 0..b {
+            aig.bad.push(check(self.single()?)?);
+        }
+        for _ in 0..c {
+            aig.constraints.push(check(self.single()?)?);
+        }
+
+        for k in 0..a {
+            let gate = if binary {
+                let lhs = 2 * (i + l + k + 1);
+                let rhs0 = lhs.checked_sub(self.varint()?).ok_or_else(|| invalid("negative AND input"))?;
+                let rhs1 = rhs0.checked_sub(self.varint()?).ok_or_else(|| invalid("negative AND input"))?;
+                AndGate { lhs, rhs0, rhs1 }
+            } else {
+                match self.numbers()?[..] {
+                    [lhs, rhs0, rhs1] => AndGate { lhs: check(lhs)?, rhs0: check(rhs0)?, rhs1: check(rhs1)? },
+                    _ => return Err(invalid("AND line must have three literals")),
+                }
+            };
+            if gate.lhs < 2 || gate.lhs & 1 == 1 {
+                return Err(invalid(format!("invalid AND output literal {}", gate.lhs)));
+            }
+            aig.ands.push(gate);
+        }
+
+        Ok(aig)
+    }
+
+    /// Reads a line containing exactly one number.
+    fn single(&mut self) -> io::Result<usize> {
+        match self.numbers()?[..] {
+            [n] => Ok(n),
+            _ => Err(invalid("expected exactly one literal per line")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bmc::{check, BmcResult};
+    use crate::solver::dpll::SolveResult;
+
+    /// Half adder from the AIGER specification: sum = x ⊕ y, carry = x ∧ y
+    const HALF_ADDER: &str = "\
+aag 7 2 0 2 3
+2
+4
+6
+12
+6 13 15
+12 2 4
+14 3 5
+i0 x
+i1 y
+o0 s
+o1 c
+c
+half adder
+";
+
+    #[test]
+    fn ascii_half_adder() {
+        let aig = parse_aiger(HALF_ADDER.as_bytes()).unwrap();
+        assert_eq!(aig.inputs, vec![2, 4]);
+        assert_eq!(aig.outputs, vec![6, 12]);
+        assert_eq!(aig.ands.len(), 3);
+
+        for (x, y) in [(false, false), (false, true), (true, false), (true, true)] {
+            let values = aig.simulate(&[x, y], &[]);
+            assert_eq!(values[3], x != y);
+            assert_eq!(values[6], x && y);
+        }
+    }
+
+    #[test]
+    fn binary_and_gate() {
+        // From the AIGER specification: "aig 3 2 0 1 1", output 6, gate 6 = 4 ∧ 2
+        let aig = parse_aiger(&b"aig 3 2 0 1 1\n6\n\x02\x02"[..]).unwrap();
+        assert_eq!(aig.inputs, vec![2, 4]);
+        assert_eq!(aig.ands, vec![AndGate { lhs: 6, rhs0: 4, rhs1: 2 }]);
+
+        let (formula, num_vars) = aig.property_cnf(Property::Output(0));
+        match crate::solve(&formula, num_vars) {
+            SolveResult::Sat(model) => {
+                assert_eq!(model.value(Var::new(1)), Val::True);
+                assert_eq!(model.value(Var::new(2)), Val::True);
+            }
+            SolveResult::Unsat => panic!("Expected SAT"),
+        }
+    }
+
+    #[test]
+    fn binary_latch_with_bad_state() {
+        // Toggle latch (var 1) with bad = latch, header "aig 1 0 1 0 0 1"
+        let aig = parse_aiger(&b"aig 1 0 1 0 0 1\n3\n2\n"[..]).unwrap();
+        assert_eq!(aig.latches, vec![Latch { lit: 2, next: 3, init: LatchInit::Zero }]);
+        assert_eq!(aig.bad, vec![2]);
+
+        let sys = aig.to_transition_system(Property::Bad(0));
+        assert!(matches!(check(&sys, 3), BmcResult::Counterexample { bound: 1, .. }));
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert!(parse_aiger("aaa 1 1 0 0 0\n".as_bytes()).is_err());
+        assert!(parse_aiger("aag 1 1 0 0 0\n".as_bytes()).is_err()); // missing input line
+        assert!(parse_aiger("aag 1 1 0 1 0\n2\n9\n".as_bytes()).is_err()); // literal out of range
+        assert!(parse_aiger("aag 1 0 0 0 0 0 0 1\n".as_bytes()).is_err()); // justice
+        assert!(parse_aiger(&b"aig 3 2 0 1 1\n6\n\x82"[..]).is_err()); // truncated
+        let huge = usize::MAX;
+        let overflowing = format!("aag {} {} {} 0 {}\n", huge, huge, huge, huge);
+        assert!(parse_aiger(overflowing.as_bytes()).is_err()); // I + L + A overflows
+        assert!(parse_aiger(format!("aag {} 0 0 0 0\n", huge).as_bytes()).is_err()); // 2M + 1 overflows
+    }
+}
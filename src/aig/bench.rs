@@ -0,0 +1,287 @@
+//! ISCAS BENCH netlist parser
+//!
+//! This module reads gate-level netlists in the BENCH format used by the
+//! classic ISCAS'85/'89 ATPG and equivalence checking benchmarks and
+//! converts them into an [`Aig`], from which CNF can be produced.
+//!
+//! ```text
+//! # c17
+//! INPUT(G1)
+//! INPUT(G2)
+//! OUTPUT(G3)
+//! G3 = NAND(G1, G2)
+//! ```
+//!
+//! Supported gates are `AND`, `NAND`, `OR`, `NOR`, `XOR`, `XNOR` (any
+//! number of inputs), `NOT`, `BUF`/`BUFF`, and `DFF`, which becomes a latch
+//! reset to 0. Signals may be used before they are defined; combinational
+//! cycles are rejected. Inputs and outputs keep their declaration order.
+//!
+//! # Example
+//!
+//! ```
+//! use putnam::aig::{bench::parse_bench, Property};
+//! use putnam::solver::dpll::SolveResult;
+//!
+//! let src = "INPUT(a)\nOUTPUT(z)\nna = NOT(a)\nz = AND(a, na)\n";
+//! let aig = parse_bench(src.as_bytes())?;
+//!
+//! // a ∧ ¬a can never be 1
+//! let (formula, num_vars) = aig.property_cnf(Property::Output(0));
+//! assert_eq!(putnam::solve(&formula, num_vars), SolveResult::Unsat);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+
+use super::*;
+
+/// Gate types of the BENCH format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GateKind {
+    And,
+    Nand,
+    Or,
+    Nor,
+    Xor,
+    Xnor,
+    Not,
+    Buf,
+    Dff,
+}
+
+/// A gate definition `name = KIND(args)`.
+struct Gate {
+    kind: GateKind,
+    args: Vec<String>,
+    line: usize,
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Parses a BENCH netlist and converts it into an AIG.
+///
+/// # Arguments
+///
+/// * `reader` - A reader implementing `BufRead` trait
+///
+/// # Returns
+///
+/// * `Ok(Aig)` - The circuit, with one AIG input per `INPUT`, one output per
+///   `OUTPUT`, and one latch per `DFF`
+/// * `Err(io::Error)` - If reading fails, or with `ErrorKind::InvalidData`
+///   for syntax errors, unknown gates, undefined signals, or combinational cycles
+pub fn parse_bench<R: BufRead>(reader: R) -> io::Result<Aig> {
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    let mut gates: HashMap<String, Gate> = HashMap::new();
+
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line?;
+        let lineno = lineno + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some((lhs, rhs)) = line.split_once('=') {
+            let (kind, args) = call(rhs.trim(), lineno)?;
+            let kind = match kind.to_ascii_uppercase().as_str() {
+                "AND" => GateKind::And,
+                "NAND" => GateKind::Nand,
+                "OR" => GateKind::Or,
+                "NOR" => GateKind::Nor,
+                "XOR" => GateKind::Xor,
+                "XNOR" => GateKind::Xnor,
+                "NOT" => GateKind::Not,
+                "BUF" | "BUFF" => GateKind::Buf,
+                "DFF" => GateKind::Dff,
+                other => return Err(invalid(format!("line {}: unknown gate '{}'", lineno, other))),
+            };
+            let arity_ok = match kind {
+                GateKind::Not | GateKind::Buf | GateKind::Dff => args.len() == 1,
+                _ => !args.is_empty(),
+            };
+            if !arity_ok {
+                return Err(invalid(format!("line {}: wrong number of inputs", lineno)));
+            }
+            let name = lhs.trim().to_string();
+            if gates.insert(name.clone(), Gate { kind, args, line: lineno }).is_some() {
+                return Err(invalid(format!("line {}: signal '{}' defined twice", lineno, name)));
+            }
+        } else {
+            let (kind, mut args) = call(line, lineno)?;
+            if args.len() != 1 {
+                return Err(invalid(format!("line {}: expected a single signal", lineno)));
+            }
+            match kind.to_ascii_uppercase().as_str() {
+                "INPUT" => inputs.push(args.remove(0)),
+                "OUTPUT" => outputs.push(args.remove(0)),
+                other => return Err(invalid(format!("line {}: unknown declaration '{}'", lineno, other))),
+            }
+        }
+    }
+
+    let mut builder = Builder { aig: Aig::default(), lits: HashMap::new(), gates: &gates, active: Vec::new() };
+    for name in &inputs {
+        let lit = builder.aig.add_input();
+        builder.lits.insert(name.clone(), lit);
+    }
+    // Latches first, so that feedback through DFFs is not a cycle
+    let mut dffs: Vec<(&String, &Gate)> = gates.iter().filter(|(_, g)| g.kind == GateKind::Dff).collect();
+    dffs.sort_by_key(|(_, g)| g.line);
+    for (name, _) in &dffs {
+        let lit = builder.aig.add_latch(FALSE, LatchInit::Zero);
+        builder.lits.insert((*name).clone(), lit);
+    }
+    for (i, (_, gate)) in dffs.iter().enumerate() {
+        let next = builder.resolve(&gate.args[0])?;
+        builder.aig.latches[i].next = next;
+    }
+    for name in &outputs {
+        let lit = builder.resolve(name)?;
+        builder.aig.outputs.push(lit);
+    }
+    Ok(builder.aig)
+}
+
+/// Splits `NAME(a, b, ...)` into the name and its arguments.
+fn call(text: &str, lineno: usize) -> io::Result<(&str, Vec<String>)> {
+    let syntax = || invalid(format!("line {}: expected NAME(args)", lineno));
+    let (name, rest) = text.split_once('(').ok_or_else(syntax)?;
+    let args = rest.trim_end().strip_suffix(')').ok_or_else(syntax)?;
+    let args = args.split(',').map(|a| a.trim().to_string()).filter(|a| !a.is_empty()).collect();
+    Ok((name.trim(), args))
+}
+
+/// Converts named gates into AIG nodes on demand.
+struct Builder<'a> {
+    aig: Aig,
+    lits: HashMap<String, AigLit>,
+    gates: &'a HashMap<String, Gate>,
+    /// Signals currently being resolved, for cycle detection
+    active: Vec<String>,
+}
+
+impl Builder<'_> {
+    fn resolve(&mut self, name: &str) -> io::Result<AigLit> {
+        if let Some(&lit) = self.lits.get(name) {
+            return Ok(lit);
+        }
+        let gate = self.gates.get(name).ok_or_else(|| invalid(format!("undefined signal '{}'", name)))?;
+        if self.active.iter().any(|n| n == name) {
+            return Err(invalid(format!("line {}: combinational cycle through '{}'", gate.line, name)));
+        }
+
+        self.active.push(name.to_string());
+        let args = gate
+            .args
+            .iter()
+            .map(|arg| self.resolve(arg))
+            .collect::<io::Result<Vec<_>>>()?;
+        self.active.pop();
+
+        let fold = |aig: &mut Aig, op: fn(&mut Aig, AigLit, AigLit) -> AigLit| {
+            args[1..].iter().fold(args[0], |acc, &x| op(aig, acc, x))
+        };
+        let lit = match gate.kind {
+            GateKind::And => fold(&mut self.aig, Aig::add_and),
+            GateKind::Nand => fold(&mut self.aig, Aig::add_and) ^ 1,
+            GateKind::Or => fold(&mut self.aig, Aig::add_or),
+            GateKind::Nor => fold(&mut self.aig, Aig::add_or) ^ 1,
+            GateKind::Xor => fold(&mut self.aig, Aig::add_xor),
+            GateKind::Xnor => fold(&mut self.aig, Aig::add_xor) ^ 1,
+            GateKind::Not => args[0] ^ 1,
+            GateKind::Buf => args[0],
+            GateKind::Dff => unreachable!("latches are created before resolution"),
+        };
+        self.lits.insert(name.to_string(), lit);
+        Ok(lit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bmc::{check, BmcResult};
+
+    /// ISCAS'85 c17
+    const C17: &str = "\
+# c17
+INPUT(1)
+INPUT(2)
+INPUT(3)
+INPUT(6)
+INPUT(7)
+OUTPUT(22)
+OUTPUT(23)
+10 = NAND(1, 3)
+11 = NAND(3, 6)
+16 = NAND(2, 11)
+19 = NAND(11, 7)
+22 = NAND(10, 16)
+23 = NAND(16, 19)
+";
+
+    fn nand(a: bool, b: bool) -> bool {
+        !(a && b)
+    }
+
+    #[test]
+    fn c17_matches_reference() {
+        let aig = parse_bench(C17.as_bytes()).unwrap();
+        assert_eq!(aig.inputs.len(), 5);
+        assert_eq!(aig.outputs.len(), 2);
+
+        for bits in 0..32u32 {
+            let v: Vec<bool> = (0..5).map(|i| (bits >> i) & 1 == 1).collect();
+            let (g1, g2, g3, g6, g7) = (v[0], v[1], v[2], v[3], v[4]);
+            let g10 = nand(g1, g3);
+            let g11 = nand(g3, g6);
+            let g16 = nand(g2, g11);
+            let g19 = nand(g11, g7);
+
+            let values = aig.simulate(&v, &[]);
+            let out = |lit: AigLit| values[lit >> 1] != (lit & 1 == 1);
+            assert_eq!(out(aig.outputs[0]), nand(g10, g16));
+            assert_eq!(out(aig.outputs[1]), nand(g16, g19));
+        }
+    }
+
+    #[test]
+    fn multi_input_gates() {
+        let src = "INPUT(a)\nINPUT(b)\nINPUT(c)\nOUTPUT(x)\nOUTPUT(y)\nx = XOR(a, b, c)\ny = NOR(a, b, c)\n";
+        let aig = parse_bench(src.as_bytes()).unwrap();
+        for bits in 0..8u32 {
+            let v: Vec<bool> = (0..3).map(|i| (bits >> i) & 1 == 1).collect();
+            let values = aig.simulate(&v, &[]);
+            let out = |lit: AigLit| values[lit >> 1] != (lit & 1 == 1);
+            assert_eq!(out(aig.outputs[0]), v[0] ^ v[1] ^ v[2]);
+            assert_eq!(out(aig.outputs[1]), !(v[0] || v[1] || v[2]));
+        }
+    }
+
+    #[test]
+    fn dff_feedback_becomes_latch() {
+        // q toggles every cycle; output is q
+        let src = "OUTPUT(q)\nq = DFF(nq)\nnq = NOT(q)\n";
+        let mut aig = parse_bench(src.as_bytes()).unwrap();
+        assert_eq!(aig.latches.len(), 1);
+
+        aig.bad.push(aig.outputs[0]);
+        let sys = aig.to_transition_system(Property::Bad(0));
+        assert!(matches!(check(&sys, 3), BmcResult::Counterexample { bound: 1, .. }));
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert!(parse_bench("OUTPUT(x)\nx = FOO(a)\n".as_bytes()).is_err());
+        assert!(parse_bench("OUTPUT(x)\nx = AND(y)\n".as_bytes()).is_err()); // undefined
+        assert!(parse_bench("OUTPUT(x)\nx = NOT(y)\ny = NOT(x)\n".as_bytes()).is_err()); // cycle
+        assert!(parse_bench("INPUT(a\n".as_bytes()).is_err());
+        assert!(parse_bench("INPUT(a)\nOUTPUT(x)\nx = NOT(a, a)\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,435 @@
+//! And-Inverter Graphs and their CNF encoding
+//!
+//! An And-Inverter Graph (AIG) represents a circuit using only two-input
+//! AND gates and inverters on edges. It is the lingua franca of hardware
+//! verification: most model checking and equivalence checking benchmarks
+//! are distributed in the AIGER format (see [`aiger`]). Gate-level
+//! netlists in the ISCAS BENCH format are converted to AIGs by [`bench`].
+//!
+//! Literals follow the AIGER conventions: variable `v` has the positive
+//! literal `2v` and the negative literal `2v + 1`, and variable 0 is the
+//! constant FALSE (so literal 0 is FALSE and literal 1 is TRUE).
+//!
+//! # CNF encoding
+//!
+//! Circuits are converted to CNF with the Tseitin transformation. AIG
+//! variable `v` becomes solver variable `v`, solver variable 0 is forced
+//! false, and each AND gate `l = a ∧ b` contributes three clauses:
+//!
+//! ```text
+//! (¬l ∨ a) ∧ (¬l ∨ b) ∧ (l ∨ ¬a ∨ ¬b)
+//! ```
+//!
+//! # Structural hashing
+//!
+//! [`Aig::add_and`] hash-conses gates: requesting an AND of the same two
+//! literals again returns the existing output instead of creating a new
+//! gate, so repeated structure in programmatically built circuits (and in
+//! the two halves of a miter) is encoded once.
+//!
+//! Sequential circuits (with latches) can be turned into a
+//! [`TransitionSystem`] for bounded model checking with [`crate::bmc`].
+
+use std::collections::HashMap;
+
+use crate::bmc::TransitionSystem;
+use crate::types::*;
+
+pub mod aiger;
+pub mod bench;
+
+/// An AIG literal: `2 * var + negated`.
+pub type AigLit = usize;
+
+/// The constant FALSE literal.
+pub const FALSE: AigLit = 0;
+/// The constant TRUE literal.
+pub const TRUE: AigLit = 1;
+
+/// A two-input AND gate `lhs = rhs0 ∧ rhs1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AndGate {
+    /// Output literal (always positive)
+    pub lhs: AigLit,
+    /// First input literal
+    pub rhs0: AigLit,
+    /// Second input literal
+    pub rhs1: AigLit,
+}
+
+/// Reset value of a latch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LatchInit {
+    /// The latch starts at 0
+    Zero,
+    /// The latch starts at 1
+    One,
+    /// The initial value is unconstrained
+    Undef,
+}
+
+/// A latch (state bit) with its next-state function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Latch {
+    /// Current-state literal (always positive)
+    pub lit: AigLit,
+    /// Next-state literal
+    pub next: AigLit,
+    /// Initial value
+    pub init: LatchInit,
+}
+
+/// A property to check on a circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Property {
+    /// The `i`-th output is 1
+    Output(usize),
+    /// The `i`-th bad-state literal is 1
+    Bad(usize),
+}
+
+/// An And-Inverter Graph with optional latches and properties.
+#[derive(Clone, Debug, Default)]
+pub struct Aig {
+    /// Largest variable index used
+    pub max_var: usize,
+    /// Primary input literals
+    pub inputs: Vec<AigLit>,
+    /// Latches
+    pub latches: Vec<Latch>,
+    /// Output literals
+    pub outputs: Vec<AigLit>,
+    /// Bad-state literals
+    pub bad: Vec<AigLit>,
+    /// Invariant constraint literals, assumed to hold in every step
+    pub constraints: Vec<AigLit>,
+    /// AND gates
+    pub ands: Vec<AndGate>,
+    /// Structural hash table: `(rhs0, rhs1)` → output literal
+    strash: HashMap<(AigLit, AigLit), AigLit>,
+    /// Number of gates of `ands` entered into `strash`
+    hashed: usize,
+}
+
+/// Equality ignores the structural hash table, which is only a cache.
+impl PartialEq for Aig {
+    fn eq(&self, other: &Self) -> bool {
+        self.max_var == other.max_var
+            && self.inputs == other.inputs
+            && self.latches == other.latches
+            && self.outputs == other.outputs
+            && self.bad == other.bad
+            && self.constraints == other.constraints
+            && self.ands == other.ands
+    }
+}
+
+/// Converts an AIG literal into a solver literal over variable `lit / 2`.
+pub fn to_lit(lit: AigLit) -> Lit {
+    Lit { var: Var::new(lit >> 1), neg: lit & 1 == 1 }
+}
+
+impl Aig {
+    /// Adds a primary input and returns its literal.
+    pub fn add_input(&mut self) -> AigLit {
+        self.max_var += 1;
+        let lit = 2 * self.max_var;
+        self.inputs.push(lit);
+        lit
+    }
+
+    /// Adds a latch with the given next-state literal and reset value, and
+    /// returns its current-state literal.
+    ///
+    /// The next-state literal may be patched later through
+    /// [`latches`](Aig::latches) when it is not known yet.
+    pub fn add_latch(&mut self, next: AigLit, init: LatchInit) -> AigLit {
+        self.max_var += 1;
+        let lit = 2 * self.max_var;
+        self.latches.push(Latch { lit, next, init });
+        lit
+    }
+
+    /// Adds an AND gate and returns its output literal.
+    ///
+    /// Trivial cases (constant or identical/complementary inputs) are
+    /// simplified without creating a gate, and if a gate with the same
+    /// inputs already exists (in either order) its output is returned.
+    /// Gates pushed directly onto [`ands`](Aig::ands), e.g. by the parsers,
+    /// take part in the hashing as well.
+    pub fn add_and(&mut self, a: AigLit, b: AigLit) -> AigLit {
+        if a == FALSE || b == FALSE || a == b ^ 1 {
+            return FALSE;
+        }
+        if a == TRUE || a == b {
+            return b;
+        }
+        if b == TRUE {
+            return a;
+        }
+        let key = (a.max(b), a.min(b));
+        for gate in &self.ands[self.hashed..] {
+            self.strash.entry((gate.rhs0.max(gate.rhs1), gate.rhs0.min(gate.rhs1))).or_insert(gate.lhs);
+        }
+        self.hashed = self.ands.len();
+        if let Some(&lhs) = self.strash.get(&key) {
+            return lhs;
+        }
+
+        self.max_var += 1;
+        let lhs = 2 * self.max_var;
+        self.ands.push(AndGate { lhs, rhs0: key.0, rhs1: key.1 });
+        self.strash.insert(key, lhs);
+        self.hashed = self.ands.len();
+        lhs
+    }
+
+    /// Adds `a ∨ b` (as `¬(¬a ∧ ¬b)`) and returns its literal.
+    pub fn add_or(&mut self, a: AigLit, b: AigLit) -> AigLit {
+        self.add_and(a ^ 1, b ^ 1) ^ 1
+    }
+
+    /// Adds `a ⊕ b` (as `¬(a ∧ b) ∧ ¬(¬a ∧ ¬b)`) and returns its literal.
+    pub fn add_xor(&mut self, a: AigLit, b: AigLit) -> AigLit {
+        let both = self.add_and(a, b);
+        let neither = self.add_and(a ^ 1, b ^ 1);
+        self.add_and(both ^ 1, neither ^ 1)
+    }
+
+    /// Returns the number of solver variables of the CNF encoding.
+    pub fn num_vars(&self) -> usize {
+        self.max_var + 1
+    }
+
+    /// Returns the literal checked by a property.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the property index is out of range.
+    pub fn property_lit(&self, property: Property) -> AigLit {
+        match property {
+            Property::Output(i) => self.outputs[i],
+            Property::Bad(i) => self.bad[i],
+        }
+    }
+
+    /// Returns the Tseitin clauses defining every AND gate, plus the unit
+    /// clause forcing the constant variable 0 to FALSE.
+    pub fn tseitin(&self) -> Formula {
+        let mut formula = Vec::with_capacity(3 * self.ands.len() + 1);
+        formula.push(vec![Lit::negative(Var::new(0))]);
+        for gate in &self.ands {
+            let (l, a, b) = (to_lit(gate.lhs), to_lit(gate.rhs0), to_lit(gate.rhs1));
+            formula.push(vec![!l, a]);
+            formula.push(vec![!l, b]);
+            formula.push(vec![l, !a, !b]);
+        }
+        formula
+    }
+
+    /// Encodes the combinational part of the circuit as CNF.
+    ///
+    /// Latches are treated as free inputs.
+    ///
+    /// # Returns
+    ///
+    /// The Tseitin formula and its [`num_vars`](Aig::num_vars) variables
+    pub fn to_cnf(&self) -> (Formula, usize) {
+        (self.tseitin(), self.num_vars())
+    }
+
+    /// Encodes "the property can be 1" for the combinational circuit.
+    ///
+    /// The formula is satisfiable exactly when some input (and latch)
+    /// assignment satisfying all constraints makes the property literal 1.
+    pub fn property_cnf(&self, property: Property) -> (Formula, usize) {
+        let mut formula = self.tseitin();
+        formula.push(vec![to_lit(self.property_lit(property))]);
+        formula.extend(self.constraints.iter().map(|&c| vec![to_lit(c)]));
+        (formula, self.num_vars())
+    }
+
+    /// Builds a transition system for bounded model checking of a property.
+    ///
+    /// A frame consists of all [`num_vars`](Aig::num_vars) circuit
+    /// variables. `init` fixes the latch reset values, `trans` connects each
+    /// latch to its next-state function, and `bad` asserts the property
+    /// literal. Gate definitions and constraints are included in every frame.
+    pub fn to_transition_system(&self, property: Property) -> TransitionSystem {
+        let n = self.num_vars();
+        let gates = self.tseitin();
+        let constraints = self.constraints.iter().map(|&c| vec![to_lit(c)]);
+
+        let init = self
+            .latches
+            .iter()
+            .filter_map(|latch| match latch.init {
+                LatchInit::Zero => Some(vec![!to_lit(latch.lit)]),
+                LatchInit::One => Some(vec![to_lit(latch.lit)]),
+                LatchInit::Undef => None,
+            })
+            .collect();
+
+        let mut trans = gates.clone();
+        trans.extend(constraints.clone());
+        for latch in &self.latches {
+            // next-frame latch ↔ current-frame next-state literal
+            let next_state = Lit::positive(Var::new(n + (latch.lit >> 1)));
+            let f = to_lit(latch.next);
+            trans.push(vec![!next_state, f]);
+            trans.push(vec![next_state, !f]);
+        }
+
+        let mut bad = gates;
+        bad.extend(constraints);
+        bad.push(vec![to_lit(self.property_lit(property))]);
+
+        TransitionSystem { frame_vars: n, init, trans, bad }
+    }
+
+    /// Evaluates the combinational circuit on given input and latch values.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Values of the primary inputs, in order
+    /// * `latches` - Current values of the latches, in order
+    ///
+    /// # Returns
+    ///
+    /// The value of every variable, indexed by variable number.
+    pub fn simulate(&self, inputs: &[bool], latches: &[bool]) -> Vec<bool> {
+        let mut values = vec![false; self.num_vars()];
+        for (&lit, &v) in self.inputs.iter().zip(inputs) {
+            values[lit >> 1] = v;
+        }
+        for (latch, &v) in self.latches.iter().zip(latches) {
+            values[latch.lit >> 1] = v;
+        }
+        let eval = |values: &[bool], lit: AigLit| values[lit >> 1] != (lit & 1 == 1);
+        for &g in &self.topological_order() {
+            let gate = self.ands[g];
+            values[gate.lhs >> 1] = eval(&values, gate.rhs0) && eval(&values, gate.rhs1);
+        }
+        values
+    }
+
+    /// Returns the indices of the AND gates ordered so that every gate comes
+    /// after the gates defining its inputs.
+    ///
+    /// The binary AIGER format guarantees this order, but ASCII files may
+    /// list gates in any order.
+    pub fn topological_order(&self) -> Vec<usize> {
+        let mut gate_of = vec![None; self.num_vars()];
+        for (g, gate) in self.ands.iter().enumerate() {
+            gate_of[gate.lhs >> 1] = Some(g);
+        }
+
+        let mut order = Vec::with_capacity(self.ands.len());
+        let mut visited = vec![false; self.ands.len()];
+        for root in 0..self.ands.len() {
+            // Iterative post-order DFS: (gate, children already pushed)
+            let mut stack = vec![(root, false)];
+            while let Some((g, expanded)) = stack.pop() {
+                if expanded {
+                    order.push(g);
+                    continue;
+                }
+                if std::mem::replace(&mut visited[g], true) {
+                    continue;
+                }
+                stack.push((g, true));
+                for input in [self.ands[g].rhs0, self.ands[g].rhs1] {
+                    if let Some(child) = gate_of[input >> 1] {
+                        if !visited[child] {
+                            stack.push((child, false));
+                        }
+                    }
+                }
+            }
+        }
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bmc::{check, BmcResult};
+    use crate::solver::dpll::SolveResult;
+
+    /// Single AND gate: output = x ∧ ¬y
+    fn and_not() -> Aig {
+        Aig {
+            max_var: 3,
+            inputs: vec![2, 4],
+            outputs: vec![6],
+            ands: vec![AndGate { lhs: 6, rhs0: 2, rhs1: 5 }],
+            ..Aig::default()
+        }
+    }
+
+    #[test]
+    fn tseitin_models_match_simulation() {
+        let aig = and_not();
+        for x in [false, true] {
+            for y in [false, true] {
+                let (mut formula, num_vars) = aig.to_cnf();
+                formula.push(vec![Lit { var: Var::new(1), neg: !x }]);
+                formula.push(vec![Lit { var: Var::new(2), neg: !y }]);
+                match crate::solve(&formula, num_vars) {
+                    SolveResult::Sat(model) => {
+                        let expected = aig.simulate(&[x, y], &[])[3];
+                        assert_eq!(model.value(Var::new(3)) == Val::True, expected);
+                        assert_eq!(expected, x && !y);
+                    }
+                    SolveResult::Unsat => panic!("Expected SAT"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn property_cnf() {
+        let mut aig = and_not();
+        let (formula, num_vars) = aig.property_cnf(Property::Output(0));
+        assert!(matches!(crate::solve(&formula, num_vars), SolveResult::Sat(_)));
+
+        // Constrain y = 1: output can no longer be 1
+        aig.constraints.push(4);
+        let (formula, num_vars) = aig.property_cnf(Property::Output(0));
+        assert_eq!(crate::solve(&formula, num_vars), SolveResult::Unsat);
+    }
+
+    #[test]
+    fn structural_hashing_shares_gates() {
+        let mut aig = Aig::default();
+        let a = aig.add_input();
+        let b = aig.add_input();
+        let x = aig.add_xor(a, b);
+        assert_eq!(aig.add_xor(b, a), x);
+        assert_eq!(aig.add_and(b, a), aig.add_and(a, b));
+        assert_eq!(aig.add_or(a ^ 1, b ^ 1), aig.add_and(a, b) ^ 1);
+        assert_eq!(aig.ands.len(), 3);
+
+        // Parsed gates are hashed too
+        let mut parsed = and_not();
+        assert_eq!(parsed.add_and(5, 2), 6);
+        assert_eq!(parsed, and_not());
+    }
+
+    #[test]
+    fn toggle_latch_bmc() {
+        // Latch q (var 1) starts at 0 and toggles; bad = q
+        let aig = Aig {
+            max_var: 1,
+            latches: vec![Latch { lit: 2, next: 3, init: LatchInit::Zero }],
+            bad: vec![2],
+            ..Aig::default()
+        };
+        let sys = aig.to_transition_system(Property::Bad(0));
+        match check(&sys, 4) {
+            BmcResult::Counterexample { bound, .. } => assert_eq!(bound, 1),
+            BmcResult::Safe { .. } => panic!("Expected counterexample"),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! Anonymization of instances for bug reports
+//!
+//! Instances from industrial applications are often proprietary: variable
+//! numbers follow the encoder's layout, clause order follows the source
+//! model, and comments or `c var` lines name the original signals.
+//! [`anonymize`] hides all three. It renumbers the variables by a random
+//! permutation, shuffles the clauses and the literals within each clause,
+//! and returns the formula alone, so written out with
+//! [`write_dimacs`](crate::parser::write_dimacs) it carries no comments or
+//! names. The result is satisfiable exactly when the input is, and usually
+//! behaves the same for the solver up to the order of its choices, so it
+//! still reproduces most bugs.
+//!
+//! The [`Renaming`] is the key back: [`Renaming::restore`] translates a
+//! model of the anonymized formula into a model of the original. It is meant
+//! to stay with the owner of the instance, and is written and read with
+//! [`write_renaming`] and [`read_renaming`].
+//!
+//! The shuffling uses the crate's seeded generator, which is not
+//! cryptographic, and the structure of the formula (clause lengths, which
+//! variables share clauses) is kept by design. Anonymization hides names
+//! and layout from readers of a bug report, not from a determined analyst
+//! who knows the encoding.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::anonymize::anonymize;
+//! use putnam::solve;
+//! use putnam::solver::dpll::SolveResult;
+//!
+//! let (formula, num_vars) = putnam::gen::random_ksat(20, 60, 3, 1);
+//! let (shared, renaming) = anonymize(&formula, num_vars, 42);
+//! assert_eq!(shared.len(), formula.len());
+//!
+//! let SolveResult::Sat(model) = solve(&shared, num_vars) else { unreachable!() };
+//! let model = renaming.restore(&model);
+//! assert!(formula.iter().all(|clause| clause.iter().any(|&lit| model.is_true(lit))));
+//! ```
+
+use std::io::{self, BufRead, Write};
+
+use crate::rng::Rng;
+use crate::types::*;
+
+/// The variable permutation applied by [`anonymize`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Renaming {
+    /// The new variable of each original variable
+    new_vars: Vec<Var>,
+}
+
+impl Renaming {
+    /// Returns the number of variables renamed.
+    pub fn num_vars(&self) -> usize {
+        self.new_vars.len()
+    }
+
+    /// Returns the variable that replaces `original`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `original` was not renamed.
+    pub fn new_var(&self, original: Var) -> Var {
+        self.new_vars[original.index()]
+    }
+
+    /// Translates `lit` of the original formula to the anonymized one.
+    pub fn apply(&self, lit: Lit) -> Lit {
+        Lit::new(self.new_var(lit.var), lit.neg)
+    }
+
+    /// Translates a model of the anonymized formula into a model of the
+    /// original. Variables that `model` does not cover stay unassigned.
+    pub fn restore(&self, model: &Model) -> Model {
+        let values: Vec<Option<bool>> = self
+            .new_vars
+            .iter()
+            .map(|&var| if var.index() < model.num_vars() { model.value(var).into() } else { None })
+            .collect();
+        Model::from(values)
+    }
+}
+
+/// Renumbers the variables of `formula` at random and shuffles its clauses
+/// and literals, see the [module documentation](self).
+///
+/// # Arguments
+///
+/// * `formula` - The formula to anonymize
+/// * `num_vars` - The number of variables (raised if the formula uses more),
+///   which the anonymized formula keeps
+/// * `seed` - Seed of the shuffling; equal seeds give equal results
+///
+/// # Returns
+///
+/// The anonymized formula and the renaming that produced it
+pub fn anonymize(formula: &Formula, num_vars: usize, seed: u64) -> (Formula, Renaming) {
+    let num_vars = formula.iter().flatten().map(|l| l.var.index() + 1).max().unwrap_or(0).max(num_vars);
+    let mut rng = Rng::new(seed);
+    let mut new_vars: Vec<Var> = Var::range(num_vars).collect();
+    rng.shuffle(&mut new_vars);
+    let renaming = Renaming { new_vars };
+
+    let mut anonymized: Formula = formula.iter().map(|clause| clause.iter().map(|&lit| renaming.apply(lit)).collect()).collect();
+    rng.shuffle(&mut anonymized);
+    for clause in &mut anonymized {
+        rng.shuffle(clause);
+    }
+    (anonymized, renaming)
+}
+
+/// Writes a renaming: a comment line, then one line per variable with its
+/// original and its new DIMACS number.
+pub fn write_renaming<W: Write>(mut writer: W, renaming: &Renaming) -> io::Result<()> {
+    writeln!(writer, "c putnam anonymization: original variable, anonymized variable")?;
+    for (index, var) in renaming.new_vars.iter().enumerate() {
+        writeln!(writer, "{} {}", index + 1, var.index() + 1)?;
+    }
+    Ok(())
+}
+
+/// Reads a renaming written by [`write_renaming`].
+///
+/// Blank lines and lines starting with `c` are ignored; the other lines
+/// may come in any order.
+///
+/// # Returns
+///
+/// * `Ok(Renaming)` - The renaming
+/// * `Err(io::Error)` - If reading fails, or with `ErrorKind::InvalidData`
+///   if a line is malformed or the lines do not form a permutation of
+///   `1..=n`
+pub fn read_renaming<R: BufRead>(reader: R) -> io::Result<Renaming> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let mut pairs = Vec::new();
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('c') {
+            continue;
+        }
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let var = |token: &str| token.parse::<usize>().ok()?.checked_sub(1).and_then(Var::try_new);
+        let [original, new] = tokens[..] else {
+            return Err(invalid(format!("line {}: expected two variable numbers", lineno + 1)));
+        };
+        let (Some(original), Some(new)) = (var(original), var(new)) else {
+            return Err(invalid(format!("line {}: expected two variable numbers", lineno + 1)));
+        };
+        pairs.push((original, new));
+    }
+    let mut new_vars = vec![None; pairs.len()];
+    let mut used = vec![false; pairs.len()];
+    for (original, new) in pairs {
+        let (Some(slot), Some(taken)) = (new_vars.get_mut(original.index()), used.get_mut(new.index())) else {
+            return Err(invalid(format!("variables beyond the {} renamed", used.len())));
+        };
+        if slot.is_some() || std::mem::replace(taken, true) {
+            return Err(invalid(format!("variable {} or {} renamed twice", original.index() + 1, new.index() + 1)));
+        }
+        *slot = Some(new);
+    }
+    Ok(Renaming { new_vars: new_vars.into_iter().flatten().collect() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renamings_roundtrip_and_restore_models() {
+        let (formula, num_vars) = crate::gen::random_ksat(12, 40, 3, 5);
+        let (anonymized, renaming) = anonymize(&formula, num_vars, 9);
+        assert_eq!(anonymize(&formula, num_vars, 9).0, anonymized);
+
+        // The same clauses up to renaming and order
+        let canonical = |formula: &Formula| {
+            let mut clauses: Vec<Clause> = formula.iter().map(|clause| {
+                let mut clause = clause.clone();
+                clause.sort();
+                clause
+            }).collect();
+            clauses.sort();
+            clauses
+        };
+        let renamed: Formula = formula.iter().map(|clause| clause.iter().map(|&lit| renaming.apply(lit)).collect()).collect();
+        assert_eq!(canonical(&renamed), canonical(&anonymized));
+
+        let mut out = Vec::new();
+        write_renaming(&mut out, &renaming).unwrap();
+        assert_eq!(read_renaming(out.as_slice()).unwrap(), renaming);
+        assert!(read_renaming("1 2\n2 2\n".as_bytes()).is_err());
+        assert!(read_renaming("1 3\n2 1\n".as_bytes()).is_err());
+        assert!(read_renaming("1 0\n".as_bytes()).is_err());
+
+        let mut model = Model::new(num_vars);
+        model.assign(renaming.new_var(Var::new(3)), Val::False);
+        let restored = renaming.restore(&model);
+        assert_eq!(restored.value(Var::new(3)), Val::False);
+        assert_eq!(restored.iter().filter(|&(_, val)| val != Val::Undef).count(), 1);
+    }
+}
@@ -0,0 +1,172 @@
+//! Property-testing generators for the core types
+//!
+//! This module is only available with the `proptest` feature enabled. It
+//! implements [`proptest::arbitrary::Arbitrary`] for [`Lit`], which
+//! automatically makes `any::<Clause>()` and `any::<Formula>()` available
+//! through proptest's `Vec` implementation, and adds shape-aware strategies
+//! for clauses and whole instances.
+//!
+//! Because [`Formula`] is a plain `Vec`, it does not know its own variable
+//! count. [`Instance`] bundles a formula with its `num_vars` so that
+//! generated problems can be handed to [`solve`](crate::solve) directly.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use proptest::test_runner::TestRunner;
+//! use putnam::arbitrary::{FormulaShape, Instance};
+//! use putnam::solver::{brute, dpll::SolveResult};
+//!
+//! let mut runner = TestRunner::default();
+//! runner.run(&any_with::<Instance>(FormulaShape::default()), |inst| {
+//!     let expected = matches!(brute::solve(&inst.formula, inst.num_vars), SolveResult::Sat(_));
+//!     let actual = matches!(putnam::solve(&inst.formula, inst.num_vars), SolveResult::Sat(_));
+//!     prop_assert_eq!(actual, expected);
+//!     Ok(())
+//! }).unwrap();
+//! ```
+
+use std::ops::RangeInclusive;
+
+use proptest::prelude::*;
+use proptest::sample::subsequence;
+
+use crate::types::*;
+
+/// Parameters for generating a single literal.
+#[derive(Clone, Debug)]
+pub struct LitParams {
+    /// Literals range over variables `0..num_vars` (must be non-zero)
+    pub num_vars: usize,
+}
+
+impl Default for LitParams {
+    fn default() -> Self {
+        Self { num_vars: 8 }
+    }
+}
+
+impl Arbitrary for Lit {
+    type Parameters = LitParams;
+    type Strategy = BoxedStrategy<Lit>;
+
+    fn arbitrary_with(params: Self::Parameters) -> Self::Strategy {
+        (0..params.num_vars, any::<bool>())
+            .prop_map(|(var, neg)| Lit::new(Var::new(var), neg))
+            .boxed()
+    }
+}
+
+/// Size and shape distribution of generated formulas.
+#[derive(Clone, Debug)]
+pub struct FormulaShape {
+    /// Number of variables (must be non-zero)
+    pub num_vars: usize,
+    /// Range of the number of clauses
+    pub num_clauses: RangeInclusive<usize>,
+    /// Range of clause lengths
+    pub clause_len: RangeInclusive<usize>,
+    /// Whether each clause mentions each variable at most once, ruling out
+    /// duplicate and complementary literals (as in random k-SAT)
+    pub distinct_vars: bool,
+}
+
+impl Default for FormulaShape {
+    /// Small 1–3 literal formulas, cheap enough for the brute-force oracle.
+    fn default() -> Self {
+        Self { num_vars: 8, num_clauses: 0..=30, clause_len: 1..=3, distinct_vars: false }
+    }
+}
+
+/// A generated formula together with its variable count.
+#[derive(Clone, Debug)]
+pub struct Instance {
+    /// The CNF formula
+    pub formula: Formula,
+    /// The number of variables the formula ranges over
+    pub num_vars: usize,
+}
+
+impl Arbitrary for Instance {
+    type Parameters = FormulaShape;
+    type Strategy = BoxedStrategy<Instance>;
+
+    fn arbitrary_with(shape: Self::Parameters) -> Self::Strategy {
+        let num_vars = shape.num_vars;
+        formula(shape)
+            .prop_map(move |formula| Instance { formula, num_vars })
+            .boxed()
+    }
+}
+
+/// Strategy generating clauses over `0..num_vars` with a length in `len`.
+///
+/// # Arguments
+///
+/// * `num_vars` - Number of variables (must be non-zero)
+/// * `len` - Range of clause lengths
+/// * `distinct_vars` - Whether each variable may appear at most once; the
+///   length is then capped at `num_vars`
+pub fn clause(num_vars: usize, len: RangeInclusive<usize>, distinct_vars: bool) -> BoxedStrategy<Clause> {
+    if distinct_vars {
+        let len = (*len.start()).min(num_vars)..=(*len.end()).min(num_vars);
+        subsequence((0..num_vars).collect::<Vec<_>>(), len)
+            .prop_flat_map(|vars| {
+                let n = vars.len();
+                (Just(vars), proptest::collection::vec(any::<bool>(), n))
+            })
+            .prop_map(|(vars, signs)| {
+                vars.into_iter()
+                    .zip(signs)
+                    .map(|(var, neg)| Lit::new(Var::new(var), neg))
+                    .collect()
+            })
+            .boxed()
+    } else {
+        proptest::collection::vec(any_with::<Lit>(LitParams { num_vars }), len).boxed()
+    }
+}
+
+/// Strategy generating formulas with the given shape.
+pub fn formula(shape: FormulaShape) -> BoxedStrategy<Formula> {
+    proptest::collection::vec(
+        clause(shape.num_vars, shape.clause_len, shape.distinct_vars),
+        shape.num_clauses,
+    )
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{brute, dpll::SolveResult};
+    use crate::testing::verify_model;
+
+    proptest! {
+        #[test]
+        fn shape_is_respected(inst in any_with::<Instance>(FormulaShape {
+            num_vars: 5,
+            num_clauses: 2..=4,
+            clause_len: 2..=3,
+            distinct_vars: true,
+        })) {
+            prop_assert!((2..=4).contains(&inst.formula.len()));
+            for c in &inst.formula {
+                prop_assert!((2..=3).contains(&c.len()));
+                prop_assert!(c.iter().all(|l| l.var.index() < 5));
+                for (i, a) in c.iter().enumerate() {
+                    prop_assert!(c[i + 1..].iter().all(|b| b.var != a.var));
+                }
+            }
+        }
+
+        #[test]
+        fn dpll_agrees_with_brute_force(inst in any::<Instance>()) {
+            match crate::solve(&inst.formula, inst.num_vars) {
+                SolveResult::Sat(model) => prop_assert!(verify_model(&inst.formula, &model)),
+                SolveResult::Unsat => prop_assert_eq!(brute::solve(&inst.formula, inst.num_vars), SolveResult::Unsat),
+            }
+        }
+    }
+}
@@ -1,22 +1,298 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
-use std::fs::File;
-use std::io::BufReader;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
 use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use putnam::solve;
-use putnam::parser::parse_and_convert;
+use putnam::anonymize::{anonymize, read_renaming, write_renaming};
+use putnam::cube::{aggregate, generate, read_result, write_icnf, write_job, JobResult};
+use putnam::features::features;
+use putnam::gen::{random_ksat, threshold_clauses};
+use putnam::parser::{parse_and_convert, parse_annotated, parse_order, write_dimacs, ParseMode, ParseOptions};
+use putnam::preprocess::symmetry::break_symmetries;
+use putnam::proof::tracecheck::{read_tracecheck, write_tracecheck};
+use putnam::proof::{refute, Proof};
+use putnam::proof::trim::trim;
+use putnam::solver::checkpoint::{read_checkpoint, write_checkpoint};
+use putnam::solver::config::read_config;
+use putnam::solver::count::{approx_count, count};
 use putnam::solver::dpll::SolveResult;
+use putnam::solver::events::{Event, EventFormat, EventWriter};
+use putnam::solver::replay::{read_decisions, write_decisions};
+use putnam::solver::special::{classify, FormulaClass};
+use putnam::solver::profile;
+use putnam::solver::stats::{peak_rss, SolveStats};
+use putnam::solver::{Solver, SolverConfig};
+use putnam::symbols::SymbolTable;
+use putnam::testing::witnesses;
+use putnam::types::{Formula, Lit, Model, Val, Var};
+use signal_hook::consts::SIGINT;
+use signal_hook::flag;
+
+/// Time between checkpoints written with `--checkpoint`
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Propagations between checkpoints with `--deterministic`, which never
+/// interrupts the search on a timer
+const CHECKPOINT_PROPAGATIONS: u64 = 100_000_000;
+
+/// Edge visits spent looking for symmetries with `--break-symmetries`
+const SYMMETRY_BUDGET: u64 = 100_000_000;
+
+/// Resolvents computed by variable elimination with `--eliminate`
+const ELIMINATION_BUDGET: u64 = 10_000_000;
+
+/// Propagations per slice of the search while reporting with `--progress`,
+/// `--events` or `--verbose`
+const PROGRESS_INTERVAL: u64 = 100_000;
+
+/// Decisions per cube of `putnam cube` without `--depth`
+const CUBE_DEPTH: usize = 8;
+
+/// Time between polls of the worker processes of `putnam cube --run`
+const WORKER_POLL: Duration = Duration::from_millis(10);
 
 fn main() {
     let args: Vec<String> = env::args().collect();
     
     if args.len() < 2 {
-        eprintln!("Usage: {} <file.cnf> [--model]", args[0]);
+        eprintln!("Usage: {} <file.cnf> [--model] [--model-format <dimacs|json|bitstring|names>] [--canonical-model] [--all-models [N]] [--count] [--approx] [--backbone] [--mus] [--mus-out <file.cnf>] [--verbose] [--quiet] [--strict] [--dry-run] [--deterministic] [--break-symmetries] [--eliminate] [--color <auto|always|never>] [--exit-codes <competition|normal>] [--mem-limit <MB>] [--stats] [--stats-format <text|csv>] [--stats-out <file>] [--config <file.toml>] [--order <file>] [--record <file>] [--replay <file>] [--checkpoint <file>] [--resume <file>] [--proof <file>] [--core <file>] [--certify] [--witness <file>] [--progress <file|->] [--events <file>] [--events-format <jsonl|binary>]", args[0]);
+        eprintln!("       {} gen <vars> <clauses|threshold> <k> [seed]", args[0]);
+        eprintln!("       {} compare <file.cnf> --configs <file.toml>... [--parallel]", args[0]);
+        eprintln!("       {} features <file.cnf>...", args[0]);
+        eprintln!("       {} cube <file.cnf> <dir> [--depth <n>] [--icnf] [--run <command>] [--jobs <n>] [--collect] [--exit-codes <competition|normal>]", args[0]);
+        eprintln!("       {} anonymize <file.cnf> <out.cnf> <map> [--seed <n>]", args[0]);
+        eprintln!("       {} anonymize --restore <map> [<solver output>]", args[0]);
         process::exit(1);
     }
+
+    if args[1] == "gen" {
+        run_gen(&args);
+    }
+    if args[1] == "compare" {
+        run_compare(&args);
+    }
+    if args[1] == "features" {
+        run_features(&args);
+    }
+    if args[1] == "cube" {
+        run_cube(&args);
+    }
+    if args[1] == "anonymize" {
+        run_anonymize(&args);
+    }
     
     let filename = &args[1];
-    let show_model = args.get(2).map_or(false, |arg| arg == "--model");
+    let mut show_model = false;
+    let mut show_stats = false;
+    let mut verbose = false;
+    let mut quiet = false;
+    let mut strict = false;
+    let mut dry_run = false;
+    let mut symmetries = false;
+    let mut deterministic = false;
+    let mut certify = false;
+    let mut canonical = false;
+    let mut color = None;
+    let mut convention = None;
+    let mut config_file = None;
+    let mut order_file = None;
+    let mut record_file = None;
+    let mut replay_file = None;
+    let mut checkpoint_file = None;
+    let mut resume_file = None;
+    let mut proof_file = None;
+    let mut core_file = None;
+    let mut witness_file = None;
+    let mut stats_format = None;
+    let mut model_format = None;
+    let mut stats_out = None;
+    let mut progress_file = None;
+    let mut events_file = None;
+    let mut events_format = None;
+    let mut mem_limit = None;
+    let mut all_models = None;
+    let mut count_models = false;
+    let mut approx = false;
+    let mut backbone = false;
+    let mut eliminate_vars = false;
+    let mut mus = false;
+    let mut mus_out = None;
+    let mut options = args[2..].iter().peekable();
+    while let Some(option) = options.next() {
+        let target = match option.as_str() {
+            "--model" => {
+                show_model = true;
+                continue;
+            }
+            "--stats" => {
+                show_stats = true;
+                continue;
+            }
+            "--verbose" | "-v" => {
+                verbose = true;
+                continue;
+            }
+            "--quiet" | "-q" => {
+                quiet = true;
+                continue;
+            }
+            "--strict" => {
+                strict = true;
+                continue;
+            }
+            "--dry-run" => {
+                dry_run = true;
+                continue;
+            }
+            "--break-symmetries" => {
+                symmetries = true;
+                continue;
+            }
+            "--deterministic" => {
+                deterministic = true;
+                continue;
+            }
+            "--certify" => {
+                certify = true;
+                continue;
+            }
+            "--canonical-model" => {
+                canonical = true;
+                continue;
+            }
+            "--all-models" => {
+                // The count is optional: all models without it
+                let limit = options.peek().and_then(|value| value.parse::<u64>().ok());
+                if limit.is_some() {
+                    options.next();
+                }
+                all_models = Some(limit);
+                continue;
+            }
+            "--count" => {
+                count_models = true;
+                continue;
+            }
+            "--approx" => {
+                count_models = true;
+                approx = true;
+                continue;
+            }
+            "--backbone" => {
+                backbone = true;
+                continue;
+            }
+            "--eliminate" => {
+                eliminate_vars = true;
+                continue;
+            }
+            "--mus" => {
+                mus = true;
+                continue;
+            }
+            "--color" => &mut color,
+            "--exit-codes" => &mut convention,
+            "--config" => &mut config_file,
+            "--order" => &mut order_file,
+            "--record" => &mut record_file,
+            "--replay" => &mut replay_file,
+            "--checkpoint" => &mut checkpoint_file,
+            "--resume" => &mut resume_file,
+            "--proof" => &mut proof_file,
+            "--core" => &mut core_file,
+            "--witness" => &mut witness_file,
+            "--stats-format" => &mut stats_format,
+            "--model-format" => &mut model_format,
+            "--stats-out" => &mut stats_out,
+            "--progress" => &mut progress_file,
+            "--events" => &mut events_file,
+            "--events-format" => &mut events_format,
+            "--mem-limit" => &mut mem_limit,
+            "--mus-out" => &mut mus_out,
+            other => {
+                eprintln!("Unknown option: {}", other);
+                process::exit(1);
+            }
+        };
+        match options.next() {
+            Some(value) => *target = Some(value),
+            None => {
+                eprintln!("{} requires an argument", option);
+                process::exit(1);
+            }
+        }
+    }
+
+    let report = StatsReport {
+        csv: match stats_format.map(String::as_str) {
+            None | Some("text") => false,
+            Some("csv") => true,
+            Some(other) => {
+                eprintln!("Unknown statistics format: {} (expected text or csv)", other);
+                process::exit(1);
+            }
+        },
+        out: stats_out,
+        instance: filename,
+        start: Instant::now(),
+        deterministic,
+    };
+    // Asking for a format or a file implies --stats
+    show_stats |= stats_format.is_some() || stats_out.is_some();
+    // and a model format or form implies --model
+    show_model |= model_format.is_some() || canonical;
+    let model_format = match model_format.map(String::as_str) {
+        None | Some("dimacs") => ModelFormat::Dimacs,
+        Some("json") => ModelFormat::Json,
+        Some("bitstring") => ModelFormat::Bitstring,
+        Some("names") => ModelFormat::Names,
+        Some(other) => {
+            eprintln!("Unknown model format: {} (expected dimacs, json, bitstring or names)", other);
+            process::exit(1);
+        }
+    };
+    let events_format = match events_format.map(String::as_str) {
+        None | Some("jsonl") => EventFormat::Jsonl,
+        Some("binary") => EventFormat::Binary,
+        Some(other) => {
+            eprintln!("Unknown event log format: {} (expected jsonl or binary)", other);
+            process::exit(1);
+        }
+    };
+    let color = match color.map(String::as_str) {
+        None | Some("auto") => io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+        Some("always") => true,
+        Some("never") => false,
+        Some(other) => {
+            eprintln!("Unknown color mode: {} (expected auto, always or never)", other);
+            process::exit(1);
+        }
+    };
+    let (sat_code, unsat_code, unknown_code) = exit_codes(convention.map(String::as_str));
+    // Megabytes of clauses and search state before giving up with UNKNOWN
+    let mem_limit: Option<u64> = mem_limit.map(|mb| match mb.parse::<u64>() {
+        Ok(mb) => mb.saturating_mul(1 << 20),
+        Err(_) => {
+            eprintln!("Invalid memory limit: {} (expected a number of megabytes)", mb);
+            process::exit(1);
+        }
+    });
+    // --quiet leaves only the exit code (and files asked for); errors are
+    // still reported on stderr
+    if quiet {
+        show_model = false;
+        verbose = false;
+        show_stats &= stats_out.is_some();
+    }
+
+    if filename.ends_with(".smt2") {
+        run_smtlib(filename);
+    }
     
     let file = match File::open(filename) {
         Ok(f) => f,
@@ -28,33 +304,1136 @@ fn main() {
     
     let reader = BufReader::new(file);
     
-    let (formula, num_vars) = match parse_and_convert(reader) {
-        Ok(result) => result,
+    let parse_options = ParseOptions {
+        mode: if strict { ParseMode::Strict } else { ParseMode::Lenient },
+        collect_warnings: verbose,
+    };
+    let (mut formula, num_vars, symbols) = match parse_annotated(reader, &parse_options) {
+        Ok((formula, num_vars, symbols, warnings)) => {
+            for warning in warnings {
+                println!("c warning: {}", warning);
+            }
+            (formula, num_vars, symbols)
+        }
         Err(e) => {
             eprintln!("Error parsing DIMACS file: {}", e);
             process::exit(1);
         }
     };
     
-    match solve(&formula, num_vars) {
-        SolveResult::Sat(model) => {
-            println!("SAT");
+    if dry_run {
+        print_instance_report(&formula, num_vars);
+        process::exit(0);
+    }
+    if all_models.is_some() && symmetries {
+        // Symmetry breaking removes models on purpose
+        eprintln!("--all-models cannot be combined with --break-symmetries");
+        process::exit(1);
+    }
+    if eliminate_vars && (all_models.is_some() || backbone) {
+        // Both report on variables that elimination removes
+        eprintln!("--eliminate cannot be combined with --all-models or --backbone");
+        process::exit(1);
+    }
+    if backbone && symmetries {
+        // Symmetry breaking removes models, and with them the freedom of
+        // variables
+        eprintln!("--backbone cannot be combined with --break-symmetries");
+        process::exit(1);
+    }
+    if mus || mus_out.is_some() {
+        // Clause handles number the clauses as read, that is as in the file
+        let Some(handles) = Solver::from_formula(formula.clone(), num_vars).mus() else {
+            if !quiet {
+                print_status("SAT", color);
+                println!("c satisfiable: no minimal unsatisfiable subset");
+            }
+            process::exit(sat_code);
+        };
+        if let Some(path) = mus_out {
+            let subset: Formula = handles.iter().map(|handle| formula[handle.index()].clone()).collect();
+            let written = File::create(path).and_then(|f| {
+                let mut writer = io::BufWriter::new(f);
+                write_dimacs(&mut writer, &subset, num_vars)?;
+                writer.flush()
+            });
+            if let Err(e) = written {
+                eprintln!("Error writing {}: {}", path, e);
+                process::exit(1);
+            }
+        }
+        if !quiet {
+            print_status("UNSAT", color);
+            println!("c mus: {} of {} clauses", handles.len(), formula.len());
+            if mus {
+                print!("m ");
+                for handle in handles {
+                    print!("{} ", handle.index() + 1);
+                }
+                println!("0");
+            }
+        }
+        process::exit(unsat_code);
+    }
+    if count_models {
+        if symmetries {
+            eprintln!("--count cannot be combined with --break-symmetries");
+            process::exit(1);
+        }
+        // A fixed seed: the same estimate on every run
+        let models = if approx { approx_count(&formula, num_vars, 0) } else { count(&formula, num_vars) };
+        if !quiet {
+            print_status(if models == Some(0) { "UNSAT" } else { "SAT" }, color);
+            match models {
+                Some(models) => println!("c models {}", models),
+                None => println!("c models 2^128 or more"),
+            }
+        }
+        process::exit(if models == Some(0) { unsat_code } else { sat_code });
+    }
+
+    // Proofs, cores and witnesses refer to the clauses as read, before unit
+    // simplification
+    let input = (proof_file.is_some() || core_file.is_some() || certify || witness_file.is_some()).then(|| formula.clone());
+    let mut solver_vars = num_vars;
+    if symmetries {
+        let stats = break_symmetries(&mut formula, num_vars, SYMMETRY_BUDGET);
+        if verbose {
+            println!(
+                "c symmetry: {} generators, {} clauses, {} auxiliary variables{}",
+                stats.generators,
+                stats.clauses_added,
+                stats.num_vars - num_vars,
+                if stats.incomplete { " (budget exhausted)" } else { "" }
+            );
+        }
+        solver_vars = stats.num_vars;
+    }
+    let mut solver = Solver::from_formula(formula, solver_vars);
+    if eliminate_vars {
+        // The solver completes its models to the eliminated variables
+        let stats = solver.eliminate(ELIMINATION_BUDGET);
+        if verbose {
+            println!(
+                "c elimination: {} substituted, {} eliminated; {} variables and {} clauses remain{}",
+                stats.substituted,
+                stats.eliminated,
+                stats.variables,
+                stats.clauses,
+                if stats.incomplete { " (budget exhausted)" } else { "" }
+            );
+        }
+    }
+    if let Some(config_file) = config_file {
+        match File::open(config_file).and_then(|f| read_config(BufReader::new(f))) {
+            Ok(config) => solver.set_config(config),
+            Err(e) => {
+                eprintln!("Error reading configuration {}: {}", config_file, e);
+                process::exit(1);
+            }
+        }
+    }
+    if deterministic {
+        solver.set_config(SolverConfig { deterministic, ..solver.config().clone() });
+    }
+    solver.set_memory_limit(mem_limit);
+    if let Some(resume_file) = resume_file {
+        if let Err(e) = File::open(resume_file).and_then(|f| solver.resume(&read_checkpoint(BufReader::new(f))?)) {
+            eprintln!("Error resuming from {}: {}", resume_file, e);
+            process::exit(1);
+        }
+    }
+    if let Some(order_file) = order_file {
+        match File::open(order_file).and_then(|f| parse_order(BufReader::new(f))) {
+            Ok(order) => solver.set_decision_order(order),
+            Err(e) => {
+                eprintln!("Error reading variable order {}: {}", order_file, e);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Some(replay_file) = replay_file {
+        match File::open(replay_file).and_then(|f| read_decisions(BufReader::new(f))) {
+            Ok(decisions) => solver.set_replay(decisions),
+            Err(e) => {
+                eprintln!("Error reading decisions {}: {}", replay_file, e);
+                process::exit(1);
+            }
+        }
+    }
+    solver.set_recording(record_file.is_some());
+
+    // Ctrl-C stops the search cleanly; a second Ctrl-C exits immediately
+    let interrupt = solver.interrupt_flag();
+    let sigint = Arc::new(AtomicBool::new(false));
+    if let Err(e) = flag::register_conditional_shutdown(SIGINT, 1, sigint.clone())
+        .and_then(|_| flag::register(SIGINT, sigint.clone()))
+        .and_then(|_| flag::register(SIGINT, interrupt.clone()))
+    {
+        eprintln!("Error installing the interrupt handler: {}", e);
+        process::exit(1);
+    }
+
+    if let Some(limit) = all_models {
+        // One thread keeps the order of the models the same on every run
+        let threads = if deterministic { 1 } else { thread::available_parallelism().map_or(1, |n| n.get()) };
+        let projection: Vec<Var> = Var::range(num_vars).collect();
+        let mut count = 0;
+        for projected in solver.models(&projection, threads).take(limit.map_or(usize::MAX, |limit| limit as usize)) {
+            if count == 0 && !quiet {
+                print_status("SAT", color);
+            }
+            count += 1;
+            if !quiet {
+                let model = Model::from(projected.iter().map(|lit| !lit.neg).collect::<Vec<bool>>());
+                print_model(&model, num_vars, model_format, symbols.clone());
+            }
+            if sigint.load(Ordering::Relaxed) {
+                break;
+            }
+        }
+        if !quiet {
+            if count == 0 {
+                print_status("UNSAT", color);
+            }
+            println!("c models: {}", count);
+        }
+        process::exit(if count == 0 { unsat_code } else { sat_code });
+    }
+    if backbone {
+        let Some(lits) = solver.backbone() else {
+            if !quiet {
+                print_status("UNSAT", color);
+            }
+            process::exit(unsat_code);
+        };
+        if !quiet {
+            print_status("SAT", color);
+            println!("c backbone: {} of {} variables", lits.len(), num_vars);
+            print!("b ");
+            for lit in lits {
+                print!("{} ", lit.to_dimacs());
+            }
+            println!("0");
+        }
+        process::exit(sat_code);
+    }
+
+    // With --checkpoint, the search is also interrupted periodically to save
+    // its position, and then continues from there. With --deterministic the
+    // period is counted in propagations instead, so checkpoints (and the
+    // search, which continues from them) do not depend on the clock
+    let checkpoint_due = Arc::new(AtomicBool::new(false));
+    let mut next_checkpoint = CHECKPOINT_PROPAGATIONS;
+    if checkpoint_file.is_some() && !deterministic {
+        let due = checkpoint_due.clone();
+        thread::spawn(move || loop {
+            thread::sleep(CHECKPOINT_INTERVAL);
+            due.store(true, Ordering::Relaxed);
+            interrupt.store(true, Ordering::Relaxed);
+        });
+    }
+    // With --progress, every restart is written as one JSON line; the search
+    // runs in slices of PROGRESS_INTERVAL propagations, after each of which
+    // the restarts of the slice are taken from the event log (and --verbose
+    // prints a comment line with the progress estimate)
+    let mut restarts = 0;
+    let mut progress: Option<Box<dyn Write>> = progress_file.map(|path| -> Box<dyn Write> {
+        if path == "-" {
+            return Box::new(io::stderr());
+        }
+        match File::create(path) {
+            Ok(f) => Box::new(io::LineWriter::new(f)),
+            Err(e) => {
+                eprintln!("Error creating progress file {}: {}", path, e);
+                process::exit(1);
+            }
+        }
+    });
+    // With --events, the events of each slice are written after it, so a
+    // killed run leaves them behind
+    if progress.is_some() {
+        solver.set_event_log(true);
+    }
+    let mut events = events_file.map(|path| {
+        solver.set_event_log(true);
+        match File::create(path).and_then(|f| EventWriter::new(io::BufWriter::new(f), events_format)) {
+            Ok(writer) => (path, writer),
+            Err(e) => {
+                eprintln!("Error creating event log {}: {}", path, e);
+                process::exit(1);
+            }
+        }
+    });
+    if progress.is_some() || events.is_some() || verbose || (deterministic && checkpoint_file.is_some()) {
+        solver.set_propagation_budget(Some(PROGRESS_INTERVAL));
+    }
+    let result = loop {
+        let result = solver.solve_limited(&[]);
+        let logged = solver.take_events();
+        if let Some((path, writer)) = &mut events {
+            let written = logged.iter().try_for_each(|event| writer.write(event)).and_then(|_| writer.flush());
+            if let Err(e) = written {
+                eprintln!("Error writing event log {}: {}", path, e);
+                process::exit(1);
+            }
+        }
+        if let Some(progress) = &mut progress {
+            for event in &logged {
+                let Event::Restart { kept, trail, .. } = event.event else { continue };
+                // Each slice counts its restarts from one
+                restarts += 1;
+                let line = format!(
+                    "{{\"time\":{},\"restarts\":{},\"conflicts\":{},\"kept\":{},\"trail\":{}}}",
+                    if deterministic { "null".to_string() } else { format!("{:.3}", event.time.as_secs_f64()) },
+                    restarts,
+                    event.conflicts,
+                    kept,
+                    trail
+                );
+                if let Err(e) = writeln!(progress, "{}", line) {
+                    eprintln!("Error writing progress: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        if verbose && result.is_none() {
+            let stats = solver.stats();
+            println!(
+                "c {:>9}  progress {:>7.3}%  decisions {}  conflicts {}",
+                report.elapsed().map_or(String::new(), |seconds| format!("{:.3}s", seconds)),
+                100.0 * solver.progress(),
+                stats.decisions,
+                stats.conflicts
+            );
+        }
+        // Searching again over the memory limit would stop at once
+        let stopped = sigint.load(Ordering::Relaxed) || solver.out_of_memory();
+        if let (None, Some(checkpoint_file)) = (&result, checkpoint_file) {
+            if deterministic && solver.stats().propagations >= next_checkpoint {
+                checkpoint_due.store(true, Ordering::Relaxed);
+                next_checkpoint = solver.stats().propagations + CHECKPOINT_PROPAGATIONS;
+            }
+            if checkpoint_due.swap(false, Ordering::Relaxed) || stopped {
+                save_checkpoint(&solver, checkpoint_file);
+            }
+        }
+        if result.is_some() || stopped {
+            break result;
+        }
+    };
+    if solver.replay_diverged() && !quiet {
+        eprintln!("Warning: the replayed decisions could not be followed");
+    }
+    if let Some(record_file) = record_file {
+        if let Err(e) = File::create(record_file).and_then(|f| write_decisions(io::BufWriter::new(f), solver.decisions())) {
+            eprintln!("Error writing decisions {}: {}", record_file, e);
+            process::exit(1);
+        }
+    }
+
+    match result {
+        Some(SolveResult::Sat(model)) => {
+            if !quiet {
+                print_status("SAT", color);
+            }
+            // Unassigned variables are printed as true, or as false in the
+            // canonical form
+            let model = if canonical { model.canonical() } else { model };
+            if let (Some(path), Some(input)) = (witness_file, &input) {
+                write_witnesses(path, input, &model);
+            }
             if show_model {
-                print!("v ");
-                for var in 0..num_vars {
-                    match model.value(var) {
-                        putnam::types::Val::True => print!("{} ", var + 1),
-                        putnam::types::Val::False => print!("-{} ", var + 1),
-                        putnam::types::Val::Undef => print!("{} ", var + 1), // default to true
+                print_model(&model, num_vars, model_format, symbols);
+            }
+            if show_stats {
+                print_stats(&solver, &report, "SAT");
+            }
+            process::exit(sat_code);
+        }
+        Some(SolveResult::Unsat) => {
+            if !quiet {
+                print_status("UNSAT", color);
+            }
+            if let Some(input) = &input {
+                let proof = write_proof(input, num_vars, proof_file, core_file);
+                if certify {
+                    certify_proof(input, proof, proof_file, &report, quiet);
+                }
+            }
+            if show_stats {
+                print_stats(&solver, &report, "UNSAT");
+            }
+            process::exit(unsat_code);
+        }
+        None => {
+            if verbose && solver.out_of_memory() {
+                println!("c memory limit of {} MB exceeded", mem_limit.unwrap_or(0) >> 20);
+            }
+            if !quiet {
+                print_status("s UNKNOWN", color);
+            }
+            if !quiet || stats_out.is_some() {
+                print_stats(&solver, &report, "UNKNOWN");
+            }
+            process::exit(unknown_code);
+        }
+    }
+}
+
+/// How `--model` prints a satisfying assignment.
+#[derive(Clone, Copy)]
+enum ModelFormat {
+    /// A `v` line of DIMACS literals, ended by `0`
+    Dimacs,
+    /// A JSON object from variable names (DIMACS numbers for unnamed
+    /// variables) to values
+    Json,
+    /// One `0` or `1` per variable, in order
+    Bitstring,
+    /// `name=value` pairs, `x<index>` for unnamed variables (0-based)
+    Names,
+}
+
+/// Prints the values of the first `num_vars` variables of `model` (leaving
+/// out the auxiliary variables of symmetry breaking), with the variables
+/// the search left unassigned set to true.
+fn print_model(model: &Model, num_vars: usize, format: ModelFormat, symbols: SymbolTable) {
+    let values: Vec<bool> = model.iter().take(num_vars).map(|(_, val)| val != Val::False).collect();
+    match format {
+        ModelFormat::Dimacs => {
+            print!("v ");
+            for (index, value) in values.iter().enumerate() {
+                print!("{}{} ", if *value { "" } else { "-" }, index + 1);
+            }
+            println!("0");
+        }
+        ModelFormat::Json => {
+            let entries: Vec<String> = values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    let key = match symbols.name(Var::new(index)) {
+                        Some(name) => json_escape(name),
+                        None => (index + 1).to_string(),
+                    };
+                    format!("\"{}\":{}", key, value)
+                })
+                .collect();
+            println!("{{{}}}", entries.join(","));
+        }
+        ModelFormat::Bitstring => {
+            println!("{}", values.iter().map(|&value| if value { '1' } else { '0' }).collect::<String>());
+        }
+        ModelFormat::Names => println!("{}", Model::from(values).with_symbols(Arc::new(symbols))),
+    }
+}
+
+/// Escapes `text` for a JSON string.
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Returns the exit codes of the SAT, UNSAT and UNKNOWN answers for a
+/// `--exit-codes` convention: the SAT competition's 10, 20 and 0, or for
+/// `normal` 0 for both answers and 2 for none, so that CI systems see a
+/// search given up (Ctrl-C, `--mem-limit`) as a failure.
+fn exit_codes(convention: Option<&str>) -> (i32, i32, i32) {
+    match convention {
+        None | Some("competition") => (10, 20, 0),
+        Some("normal") => (0, 0, 2),
+        Some(other) => {
+            eprintln!("Unknown exit code convention: {} (expected competition or normal)", other);
+            process::exit(1);
+        }
+    }
+}
+
+/// Prints the answer line, in bold green (SAT), red (UNSAT) or yellow
+/// (unknown) when colored.
+fn print_status(status: &str, color: bool) {
+    if !color {
+        println!("{}", status);
+        return;
+    }
+    let code = match status {
+        "SAT" => 32,
+        "UNSAT" => 31,
+        _ => 33,
+    };
+    println!("\x1b[1;{}m{}\x1b[0m", code, status);
+}
+
+/// Prints the size, clause length histogram and class of an instance as
+/// comment lines, for `--dry-run`.
+fn print_instance_report(formula: &Formula, num_vars: usize) {
+    let occurring = formula.iter().flatten().map(|lit| lit.var).collect::<BTreeSet<_>>().len();
+    let literals: usize = formula.iter().map(Vec::len).sum();
+    println!("c variables: {} ({} occurring)", num_vars, occurring);
+    println!("c clauses: {} ({} literals)", formula.len(), literals);
+    let mut lengths = BTreeMap::new();
+    for clause in formula {
+        *lengths.entry(clause.len()).or_insert(0usize) += 1;
+    }
+    for (length, count) in lengths {
+        println!("c   length {:>3}: {}", length, count);
+    }
+    let class = match classify(formula) {
+        FormulaClass::Trivial => "units only",
+        FormulaClass::TwoSat => "2-SAT",
+        FormulaClass::Horn => "Horn",
+        FormulaClass::Xor => "XOR",
+        FormulaClass::General => "general",
+    };
+    println!("c class: {}", class);
+}
+
+/// Writes a trimmed TraceCheck refutation of the input formula and/or the
+/// unsatisfiable core it uses (as DIMACS).
+fn write_proof(formula: &Formula, num_vars: usize, proof_file: Option<&String>, core_file: Option<&String>) -> Proof {
+    let Err(proof) = refute(formula, num_vars) else {
+        unreachable!("the proof search disagrees with the solver");
+    };
+    let trimmed = trim(&proof);
+    let write = |path: &str, what: &dyn Fn(&mut io::BufWriter<File>) -> io::Result<()>| {
+        let written = File::create(path).and_then(|f| {
+            let mut writer = io::BufWriter::new(f);
+            what(&mut writer)?;
+            writer.flush()
+        });
+        if let Err(e) = written {
+            eprintln!("Error writing {}: {}", path, e);
+            process::exit(1);
+        }
+    };
+    if let Some(path) = proof_file {
+        write(path, &|w| write_tracecheck(w, &trimmed.proof));
+    }
+    if let Some(path) = core_file {
+        let core: Vec<_> = trimmed.core.iter().map(|&i| formula[i].clone()).collect();
+        write(path, &|w| write_dimacs(w, &core, num_vars));
+    }
+    trimmed.proof
+}
+
+/// Writes the witness map of a SAT answer: a comment line, then one line per
+/// clause as read with its number and the literal the printed model makes
+/// true. A clause without such a literal is an error: the answer cannot be
+/// trusted.
+fn write_witnesses(path: &str, formula: &Formula, model: &Model) {
+    // The values as printed, with unassigned variables true
+    let printed = Model::from(model.iter().map(|(_, val)| val != Val::False).collect::<Vec<bool>>());
+    let map = witnesses(formula, &printed);
+    if let Some(index) = map.iter().position(Option::is_none) {
+        eprintln!("Error: the model violates clause {}", index + 1);
+        process::exit(1);
+    }
+    let written = File::create(path).and_then(|f| {
+        let mut writer = io::BufWriter::new(f);
+        writeln!(writer, "c putnam witnesses: clause number, satisfying literal")?;
+        for (index, lit) in map.into_iter().flatten().enumerate() {
+            writeln!(writer, "{} {}", index + 1, lit.to_dimacs())?;
+        }
+        writer.flush()
+    });
+    if let Err(e) = written {
+        eprintln!("Error writing {}: {}", path, e);
+        process::exit(1);
+    }
+}
+
+/// Checks the proof of an UNSAT answer against the formula as read, and
+/// prints the time the check took.
+///
+/// The proof file, if one was written, is read back and checked instead of
+/// the proof in memory, so that the file is what gets certified. A proof
+/// that does not check is an error: the answer cannot be trusted.
+fn certify_proof(formula: &Formula, proof: Proof, proof_file: Option<&String>, report: &StatsReport, quiet: bool) {
+    let start = Instant::now();
+    let proof = match proof_file {
+        Some(path) => match File::open(path).and_then(|f| read_tracecheck(BufReader::new(f))) {
+            Ok(proof) => proof,
+            Err(e) => {
+                eprintln!("Error reading back proof {}: {}", path, e);
+                process::exit(1);
+            }
+        },
+        None => proof,
+    };
+    if let Err(e) = proof.check_refutes(formula) {
+        eprintln!("Proof check failed: {}", e);
+        process::exit(1);
+    }
+    if !quiet {
+        match report.elapsed().map(|_| start.elapsed().as_secs_f64()) {
+            Some(seconds) => println!("c proof verified: {} steps checked in {:.3}s", proof.steps.len(), seconds),
+            None => println!("c proof verified: {} steps checked", proof.steps.len()),
+        }
+    }
+}
+
+/// Where and how the search statistics are reported.
+struct StatsReport<'a> {
+    /// CSV records instead of comment lines
+    csv: bool,
+    /// File to write to instead of standard output
+    out: Option<&'a String>,
+    /// Instance name for the CSV record
+    instance: &'a str,
+    /// Start of the run, for the CSV record
+    start: Instant,
+    /// Leave out the run time and peak memory, which vary between runs
+    deterministic: bool,
+}
+
+impl StatsReport<'_> {
+    /// Returns the seconds since the start, or `None` in deterministic mode.
+    fn elapsed(&self) -> Option<f64> {
+        (!self.deterministic).then(|| self.start.elapsed().as_secs_f64())
+    }
+
+    /// Returns the peak memory, or `None` in deterministic mode.
+    fn peak_rss(&self) -> Option<u64> {
+        peak_rss().filter(|_| !self.deterministic)
+    }
+}
+
+/// Prints the search statistics as comment lines, or as a CSV record with
+/// the instance, answer, run time and peak memory.
+///
+/// A statistics file is appended to, so batch runs can share one CSV file;
+/// the header is written only when the file is new or empty.
+fn print_stats(solver: &Solver, report: &StatsReport, answer: &str) {
+    let text = if report.csv {
+        let record = format!(
+            "{},{},{},{},{}\n",
+            report.instance,
+            answer,
+            report.elapsed().map_or(String::new(), |seconds| format!("{:.3}", seconds)),
+            solver.stats().to_csv(),
+            report.peak_rss().map_or(String::new(), |bytes| bytes.to_string())
+        );
+        let header = format!("instance,result,time_seconds,{},peak_rss_bytes\n", SolveStats::CSV_HEADER);
+        let empty = report.out.is_none_or(|path| std::fs::metadata(path).map_or(true, |m| m.len() == 0));
+        if empty { header + &record } else { record }
+    } else {
+        let mut text: String = solver.stats().to_string().lines().map(|line| format!("c {}\n", line)).collect();
+        if let Some(bytes) = report.peak_rss() {
+            text += &format!("c peak rss bytes: {}\n", bytes);
+        }
+        text += &format!("c duplicate clauses: {}\n", solver.duplicate_clauses());
+        if profile::ENABLED {
+            text.extend(solver.profile().to_string().lines().map(|line| format!("c profile {}\n", line)));
+        }
+        text
+    };
+    match report.out {
+        None => print!("{}", text),
+        Some(path) => {
+            let written = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut f| f.write_all(text.as_bytes()));
+            if let Err(e) = written {
+                eprintln!("Error writing statistics {}: {}", path, e);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// Writes the solver's checkpoint, replacing `path` only once it is complete.
+fn save_checkpoint(solver: &Solver, path: &str) {
+    let temp = format!("{}.tmp", path);
+    let written = File::create(&temp)
+        .and_then(|f| {
+            let mut writer = io::BufWriter::new(f);
+            write_checkpoint(&mut writer, &solver.checkpoint())?;
+            writer.flush()
+        })
+        .and_then(|_| std::fs::rename(&temp, path));
+    if let Err(e) = written {
+        eprintln!("Error writing checkpoint {}: {}", path, e);
+        process::exit(1);
+    }
+}
+
+/// Executes an SMT-LIB2 script, writing the responses to stdout.
+fn run_smtlib(filename: &str) -> ! {
+    let script = match std::fs::read_to_string(filename) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Error opening file {}: {}", filename, e);
+            process::exit(1);
+        }
+    };
+    if let Err(e) = putnam::smtlib::run(&script, io::stdout().lock()) {
+        eprintln!("Error writing response: {}", e);
+        process::exit(1);
+    }
+    process::exit(0);
+}
+
+/// `putnam gen`: writes a random k-SAT instance to stdout in DIMACS format.
+fn run_gen(args: &[String]) -> ! {
+    if args.len() < 5 {
+        eprintln!("Usage: {} gen <vars> <clauses|threshold> <k> [seed]", args[0]);
+        process::exit(1);
+    }
+
+    let parse = |arg: &str, what: &str| -> usize {
+        arg.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid {}: {}", what, arg);
+            process::exit(1);
+        })
+    };
+    let num_vars = parse(&args[2], "variable count");
+    let k = parse(&args[4], "clause length");
+    let num_clauses = match args[3].as_str() {
+        "threshold" => threshold_clauses(num_vars, k),
+        n => parse(n, "clause count"),
+    };
+    let seed = args.get(5).map_or(0, |s| parse(s, "seed") as u64);
+
+    if k > num_vars {
+        eprintln!("Clause length {} exceeds variable count {}", k, num_vars);
+        process::exit(1);
+    }
+
+    let (formula, num_vars) = random_ksat(num_vars, num_clauses, k, seed);
+    if let Err(e) = write_dimacs(io::stdout().lock(), &formula, num_vars) {
+        eprintln!("Error writing DIMACS: {}", e);
+        process::exit(1);
+    }
+    process::exit(0);
+}
+
+/// `putnam features`: prints the instance features of each file as one CSV
+/// record, after a header with the feature names.
+fn run_features(args: &[String]) -> ! {
+    if args.len() < 3 {
+        eprintln!("Usage: {} features <file.cnf>...", args[0]);
+        process::exit(1);
+    }
+    for (i, filename) in args[2..].iter().enumerate() {
+        let (formula, num_vars) = match File::open(filename).and_then(|f| parse_and_convert(BufReader::new(f))) {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("Error reading {}: {}", filename, e);
+                process::exit(1);
+            }
+        };
+        let pairs = features(&formula, num_vars).to_pairs();
+        if i == 0 {
+            let names: Vec<&str> = pairs.iter().map(|(name, _)| name.as_str()).collect();
+            println!("instance,{}", names.join(","));
+        }
+        let values: Vec<String> = pairs.iter().map(|(_, value)| value.to_string()).collect();
+        println!("{},{}", filename, values.join(","));
+    }
+    process::exit(0);
+}
+
+/// `putnam anonymize`: writes a renumbered and shuffled copy of an instance,
+/// without comments or names, and the renaming to a file only the user can
+/// read. With `--restore`, copies a solver's output on the anonymized copy,
+/// with its `v` lines translated back to the original variables.
+fn run_anonymize(args: &[String]) -> ! {
+    let usage = || -> ! {
+        eprintln!("Usage: {} anonymize <file.cnf> <out.cnf> <map> [--seed <n>]", args[0]);
+        eprintln!("       {} anonymize --restore <map> [<solver output>]", args[0]);
+        process::exit(1);
+    };
+    if args.get(2).is_some_and(|arg| arg == "--restore") {
+        let Some(map) = args.get(3) else { usage() };
+        restore_model(map, args.get(4));
+    }
+    let (Some(filename), Some(out), Some(map)) = (args.get(2), args.get(3), args.get(4)) else { usage() };
+    let mut seed = None;
+    let mut options = args[5..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--seed" => {
+                let Some(value) = options.next() else { usage() };
+                seed = Some(value.parse::<u64>().unwrap_or_else(|_| {
+                    eprintln!("Invalid seed: {}", value);
+                    process::exit(1);
+                }));
+            }
+            other => {
+                eprintln!("Unknown option: {}", other);
+                process::exit(1);
+            }
+        }
+    }
+    // Without --seed the renaming is unpredictable: knowing the seed is
+    // knowing the renaming
+    let seed = seed.unwrap_or_else(|| {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64);
+        nanos ^ u64::from(process::id()) << 32
+    });
+
+    let (formula, num_vars) = match File::open(filename).and_then(|f| parse_and_convert(BufReader::new(f))) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Error reading {}: {}", filename, e);
+            process::exit(1);
+        }
+    };
+    let (anonymized, renaming) = anonymize(&formula, num_vars, seed);
+    // The renaming first: an anonymized copy without it would be useless
+    let mut private = OpenOptions::new();
+    private.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut private, 0o600);
+    let written = private.open(map).and_then(|f| {
+        let mut writer = io::BufWriter::new(f);
+        write_renaming(&mut writer, &renaming)?;
+        writer.flush()
+    });
+    if let Err(e) = written {
+        eprintln!("Error writing {}: {}", map, e);
+        process::exit(1);
+    }
+    let written = File::create(out).and_then(|f| {
+        let mut writer = io::BufWriter::new(f);
+        write_dimacs(&mut writer, &anonymized, renaming.num_vars())?;
+        writer.flush()
+    });
+    if let Err(e) = written {
+        eprintln!("Error writing {}: {}", out, e);
+        process::exit(1);
+    }
+    process::exit(0);
+}
+
+/// `putnam anonymize --restore`: copies the solver output in `output` (or
+/// standard input) to standard output, with its `v` lines replaced by one
+/// `v` line of the assigned original variables at the end.
+fn restore_model(map: &str, output: Option<&String>) -> ! {
+    let renaming = match File::open(map).and_then(|f| read_renaming(BufReader::new(f))) {
+        Ok(renaming) => renaming,
+        Err(e) => {
+            eprintln!("Error reading {}: {}", map, e);
+            process::exit(1);
+        }
+    };
+    let reader: Box<dyn BufRead> = match output {
+        None => Box::new(io::stdin().lock()),
+        Some(path) => match File::open(path) {
+            Ok(f) => Box::new(BufReader::new(f)),
+            Err(e) => {
+                eprintln!("Error opening {}: {}", path, e);
+                process::exit(1);
+            }
+        },
+    };
+    let mut model = Model::new(renaming.num_vars());
+    let mut seen_model = false;
+    for line in reader.lines() {
+        let line = line.unwrap_or_else(|e| {
+            eprintln!("Error reading solver output: {}", e);
+            process::exit(1);
+        });
+        let Some(lits) = line.strip_prefix('v').filter(|rest| rest.is_empty() || rest.starts_with(' ')) else {
+            println!("{}", line);
+            continue;
+        };
+        seen_model = true;
+        for token in lits.split_whitespace() {
+            let lit = token.parse().ok().and_then(Lit::from_dimacs).filter(|lit| lit.var.index() < renaming.num_vars());
+            match lit {
+                Some(lit) => model.assign(lit.var, if lit.neg { Val::False } else { Val::True }),
+                None if token == "0" => {}
+                None => {
+                    eprintln!("Invalid literal in the model: {}", token);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+    if seen_model {
+        let restored = renaming.restore(&model);
+        print!("v ");
+        for (var, val) in restored.iter().filter(|&(_, val)| val != Val::Undef) {
+            print!("{} ", Lit::new(var, val == Val::False).to_dimacs());
+        }
+        println!("0");
+    }
+    process::exit(0);
+}
+
+/// Solves one instance under several configurations and prints a table of
+/// the answers, run times and search statistics.
+fn run_compare(args: &[String]) -> ! {
+    let usage = || -> ! {
+        eprintln!("Usage: {} compare <file.cnf> --configs <file.toml>... [--parallel]", args[0]);
+        process::exit(1);
+    };
+    let Some(filename) = args.get(2) else { usage() };
+    let mut config_files = Vec::new();
+    let mut parallel = false;
+    let mut in_configs = false;
+    for arg in &args[3..] {
+        match arg.as_str() {
+            "--configs" => in_configs = true,
+            "--parallel" => parallel = true,
+            file if in_configs && !file.starts_with("--") => config_files.push(file),
+            other => {
+                eprintln!("Unknown option: {}", other);
+                process::exit(1);
+            }
+        }
+    }
+    if config_files.is_empty() {
+        usage();
+    }
+
+    let configs: Vec<_> = config_files
+        .iter()
+        .map(|path| match File::open(path).and_then(|f| read_config(BufReader::new(f))) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Error reading configuration {}: {}", path, e);
+                process::exit(1);
+            }
+        })
+        .collect();
+    let (formula, num_vars) = match File::open(filename).and_then(|f| parse_and_convert(BufReader::new(f))) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Error reading {}: {}", filename, e);
+            process::exit(1);
+        }
+    };
+
+    // Deterministic configurations report no time, which varies between runs
+    let run = |config: SolverConfig| {
+        let deterministic = config.deterministic;
+        let mut solver = Solver::from_formula(formula.clone(), num_vars);
+        solver.set_config(config);
+        let start = Instant::now();
+        let answer = match solver.solve() {
+            SolveResult::Sat(_) => "SAT",
+            SolveResult::Unsat => "UNSAT",
+        };
+        (answer, (!deterministic).then(|| start.elapsed()), solver.stats())
+    };
+    let rows: Vec<(&str, Option<Duration>, SolveStats)> = if parallel {
+        thread::scope(|scope| {
+            let workers: Vec<_> = configs.into_iter().map(|config| scope.spawn(move || run(config))).collect();
+            workers.into_iter().map(|worker| worker.join().expect("solver thread panicked")).collect()
+        })
+    } else {
+        configs.into_iter().map(run).collect()
+    };
+
+    let width = config_files.iter().map(|f| f.len()).max().unwrap_or(0).max("config".len());
+    println!("{:<width$}  {:>6}  {:>10}  {:>12}  {:>12}  {:>14}", "config", "result", "time (s)", "conflicts", "decisions", "propagations");
+    for (file, (answer, time, stats)) in config_files.iter().zip(rows) {
+        println!(
+            "{:<width$}  {:>6}  {:>10}  {:>12}  {:>12}  {:>14}",
+            file,
+            answer,
+            time.map_or("-".to_string(), |time| format!("{:.3}", time.as_secs_f64())),
+            stats.conflicts,
+            stats.decisions,
+            stats.propagations
+        );
+    }
+    process::exit(0);
+}
+
+/// `putnam cube`: splits an instance into cube jobs written to a directory,
+/// optionally runs a solver command on each job and aggregates the answers.
+///
+/// Job `i` is `job-<i>.cnf`, and the output of its worker `job-<i>.out`.
+/// The command gets the job file in place of a `{}` argument, or as its
+/// last argument if it has none.
+/// With `--collect`, the answers of an earlier (or external) run are read
+/// back from the output files instead.
+fn run_cube(args: &[String]) -> ! {
+    let usage = || -> ! {
+        eprintln!("Usage: {} cube <file.cnf> <dir> [--depth <n>] [--icnf] [--run <command>] [--jobs <n>] [--collect] [--exit-codes <competition|normal>]", args[0]);
+        process::exit(1);
+    };
+    let (Some(filename), Some(dir)) = (args.get(2), args.get(3)) else { usage() };
+    let dir = std::path::Path::new(dir);
+    let parse = |arg: Option<&String>, what: &str| -> usize {
+        let Some(arg) = arg else { usage() };
+        arg.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid {}: {}", what, arg);
+            process::exit(1);
+        })
+    };
+    let mut depth = CUBE_DEPTH;
+    let mut icnf = false;
+    let mut command = None;
+    let mut jobs = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut collect = false;
+    let mut convention = None;
+    let mut options = args[4..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--depth" => depth = parse(options.next(), "depth"),
+            "--icnf" => icnf = true,
+            "--run" => command = Some(options.next().unwrap_or_else(|| usage())),
+            "--jobs" => jobs = parse(options.next(), "job count").max(1),
+            "--collect" => collect = true,
+            "--exit-codes" => convention = Some(options.next().unwrap_or_else(|| usage()).as_str()),
+            other => {
+                eprintln!("Unknown option: {}", other);
+                process::exit(1);
+            }
+        }
+    }
+    let (sat_code, unsat_code, unknown_code) = exit_codes(convention);
+    if icnf && (command.is_some() || collect) {
+        eprintln!("--icnf writes a single file; it cannot be combined with --run or --collect");
+        process::exit(1);
+    }
+    let job_path = |i: usize, extension: &str| dir.join(format!("job-{:05}.{}", i, extension));
+
+    let num_jobs = if collect {
+        // The jobs are numbered from 0 without gaps
+        (0..).take_while(|&i| job_path(i, "cnf").exists()).count()
+    } else {
+        let (formula, num_vars) = match File::open(filename).and_then(|f| parse_and_convert(BufReader::new(f))) {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("Error reading {}: {}", filename, e);
+                process::exit(1);
+            }
+        };
+        let cubes = generate(&formula, num_vars, depth);
+        let written = std::fs::create_dir_all(dir).and_then(|_| {
+            if icnf {
+                return write_icnf(io::BufWriter::new(File::create(dir.join("cubes.icnf"))?), &formula, &cubes);
+            }
+            for (i, cube) in cubes.iter().enumerate() {
+                write_job(io::BufWriter::new(File::create(job_path(i, "cnf"))?), &formula, num_vars, cube)?;
+            }
+            Ok(())
+        });
+        if let Err(e) = written {
+            eprintln!("Error writing jobs to {}: {}", dir.display(), e);
+            process::exit(1);
+        }
+        println!("c {} cubes of at most {} decisions written to {}", cubes.len(), depth, dir.display());
+        if command.is_none() {
+            process::exit(0);
+        }
+        cubes.len()
+    };
+
+    let mut results = vec![JobResult::Unknown; num_jobs];
+    if let Some(command) = command {
+        let mut words = command.split_whitespace();
+        let Some(program) = words.next() else { usage() };
+        let program_args: Vec<&str> = words.collect();
+        let mut pending = 0..num_jobs;
+        let mut running: Vec<(usize, process::Child)> = Vec::new();
+        loop {
+            while running.len() < jobs {
+                let Some(i) = pending.next() else { break };
+                let job = job_path(i, "cnf");
+                let mut worker = process::Command::new(program);
+                match program_args.contains(&"{}") {
+                    true => worker.args(program_args.iter().map(|&arg| if arg == "{}" { job.as_os_str() } else { arg.as_ref() })),
+                    false => worker.args(&program_args).arg(&job),
+                };
+                let child = File::create(job_path(i, "out")).and_then(|out| {
+                    worker
+                        .stdout(out)
+                        .stdin(process::Stdio::null())
+                        .spawn()
+                });
+                match child {
+                    Ok(child) => running.push((i, child)),
+                    Err(e) => {
+                        eprintln!("Error starting {} on job {}: {}", program, i, e);
+                        process::exit(1);
                     }
                 }
-                println!("0");
             }
-            process::exit(10);
+            if running.is_empty() {
+                break;
+            }
+            thread::sleep(WORKER_POLL);
+            let mut found = false;
+            running.retain_mut(|(i, child)| match child.try_wait() {
+                Ok(Some(status)) => {
+                    results[*i] = match JobResult::from_exit_code(status.code()) {
+                        JobResult::Unknown => read_job_output(&job_path(*i, "out")),
+                        result => result,
+                    };
+                    found |= results[*i] == JobResult::Sat;
+                    false
+                }
+                Ok(None) => true,
+                Err(_) => false,
+            });
+            // One model answers the instance: the other jobs are not needed
+            if found {
+                for (_, child) in &mut running {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+                break;
+            }
         }
-        SolveResult::Unsat => {
-            println!("UNSAT");
-            process::exit(20);
+    } else {
+        for (i, result) in results.iter_mut().enumerate() {
+            let out = job_path(i, "out");
+            if out.exists() {
+                *result = read_job_output(&out);
+            }
+        }
+    }
+
+    let count = |wanted: JobResult| results.iter().filter(|&&result| result == wanted).count();
+    println!(
+        "c {} jobs: {} satisfiable, {} unsatisfiable, {} unknown",
+        num_jobs,
+        count(JobResult::Sat),
+        count(JobResult::Unsat),
+        count(JobResult::Unknown)
+    );
+    let color = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+    match aggregate(results) {
+        JobResult::Sat => {
+            print_status("SAT", color);
+            process::exit(sat_code);
+        }
+        JobResult::Unsat => {
+            print_status("UNSAT", color);
+            process::exit(unsat_code);
+        }
+        JobResult::Unknown => {
+            print_status("s UNKNOWN", color);
+            process::exit(unknown_code);
+        }
+    }
+}
+
+/// Reads the answer of a finished job from its output file.
+fn read_job_output(path: &std::path::Path) -> JobResult {
+    match File::open(path).and_then(|f| read_result(BufReader::new(f))) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Error reading {}: {}", path.display(), e);
+            process::exit(1);
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,382 @@
+//! Bounded model checking
+//!
+//! This module provides a small transition-system representation and an
+//! unroller producing the classic BMC formula
+//!
+//! ```text
+//! Init(s₀) ∧ Trans(s₀, s₁) ∧ … ∧ Trans(sₖ₋₁, sₖ) ∧ Bad(sₖ)
+//! ```
+//!
+//! which is satisfiable exactly when a bad state is reachable in `k` steps.
+//! [`check`] decides this formula for increasing `k` and returns the first
+//! (and therefore shortest) counterexample trace. It runs an
+//! [`IncrementalBmc`]: a single [`Solver`] that grows by one transition per
+//! bound and checks each bad state under an assumption, rather than solving
+//! every [`unroll`]ed formula from scratch.
+//!
+//! A transition system works on *frames* of `frame_vars` boolean variables
+//! (state bits, inputs, and any auxiliary encoding variables). `init` and
+//! `bad` are CNF formulas over one frame (variables `0..frame_vars`), and
+//! `trans` is a CNF formula over two frames, where variable `v` refers to
+//! the current frame and `frame_vars + v` to the next one. In the unrolled
+//! formula, variable `v` of step `t` becomes `t * frame_vars + v`.
+//!
+//! # Example
+//!
+//! ```
+//! use putnam::bmc::{check, BmcResult, TransitionSystem};
+//! use putnam::types::{Lit, Var};
+//!
+//! // A 1-bit toggle starting at 0: is the state 1 reachable?
+//! let sys = TransitionSystem {
+//!     frame_vars: 1,
+//!     init: vec![vec![Lit::negative(Var::new(0))]],
+//!     // next ↔ ¬current
+//!     trans: vec![
+//!         vec![Lit::positive(Var::new(0)), Lit::positive(Var::new(1))],
+//!         vec![Lit::negative(Var::new(0)), Lit::negative(Var::new(1))],
+//!     ],
+//!     bad: vec![vec![Lit::positive(Var::new(0))]],
+//! };
+//!
+//! match check(&sys, 5) {
+//!     BmcResult::Counterexample { bound, trace } => {
+//!         assert_eq!(bound, 1);
+//!         assert_eq!(trace, vec![vec![false], vec![true]]);
+//!     }
+//!     BmcResult::Safe { .. } => unreachable!(),
+//! }
+//! ```
+
+use crate::solver::dpll::SolveResult;
+use crate::solver::Solver;
+use crate::types::*;
+
+/// A transition system over boolean frame variables.
+#[derive(Clone, Debug)]
+pub struct TransitionSystem {
+    /// Number of variables in one frame
+    pub frame_vars: usize,
+    /// Initial-state predicate over one frame
+    pub init: Formula,
+    /// Transition relation over two frames (current: `v`, next: `frame_vars + v`)
+    pub trans: Formula,
+    /// Bad-state predicate over one frame
+    pub bad: Formula,
+}
+
+/// Outcome of a bounded model checking run.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BmcResult {
+    /// A bad state is reachable in `bound` steps
+    Counterexample {
+        /// Number of transitions taken
+        bound: usize,
+        /// Values of the frame variables at each step `0..=bound`
+        trace: Vec<Vec<bool>>,
+    },
+    /// No bad state is reachable within `bound` steps
+    Safe {
+        /// Largest bound that was checked
+        bound: usize,
+    },
+}
+
+/// Shifts every literal of a formula by `offset` variables.
+fn shifted(formula: &Formula, offset: usize) -> impl Iterator<Item = Clause> + '_ {
+    formula.iter().map(move |clause| {
+        clause.iter().map(|lit| Lit { var: Var::new(lit.var.index() + offset), neg: lit.neg }).collect()
+    })
+}
+
+/// Unrolls a transition system for exactly `k` steps.
+///
+/// # Arguments
+///
+/// * `sys` - The transition system
+/// * `k` - Number of transitions
+///
+/// # Returns
+///
+/// A tuple containing:
+/// * `Formula` - `Init@0 ∧ Trans@0 ∧ … ∧ Trans@(k-1) ∧ Bad@k`
+/// * `usize` - The number of variables, `(k + 1) * frame_vars`
+pub fn unroll(sys: &TransitionSystem, k: usize) -> (Formula, usize) {
+    let n = sys.frame_vars;
+    let mut formula: Formula = shifted(&sys.init, 0).collect();
+    for step in 0..k {
+        formula.extend(shifted(&sys.trans, step * n));
+    }
+    formula.extend(shifted(&sys.bad, k * n));
+    (formula, (k + 1) * n)
+}
+
+/// Extracts the per-step frame values from a model of [`unroll`].
+///
+/// Unassigned variables are reported as `false`.
+pub fn trace(model: &Model, frame_vars: usize, k: usize) -> Vec<Vec<bool>> {
+    (0..=k)
+        .map(|step| {
+            (0..frame_vars)
+                .map(|v| model.value(Var::new(step * frame_vars + v)) == Val::True)
+                .collect()
+        })
+        .collect()
+}
+
+/// Searches for a reachable bad state with increasing bounds.
+///
+/// The bounds `k = 0, 1, …, max_bound` are checked on one
+/// [`IncrementalBmc`], stopping at the first bound with a bad state.
+///
+/// # Arguments
+///
+/// * `sys` - The transition system
+/// * `max_bound` - Largest number of transitions to consider
+///
+/// # Returns
+///
+/// * `BmcResult::Counterexample` - The shortest counterexample found
+/// * `BmcResult::Safe` - No bad state is reachable within `max_bound` steps
+pub fn check(sys: &TransitionSystem, max_bound: usize) -> BmcResult {
+    IncrementalBmc::new(sys.clone()).run(max_bound)
+}
+
+/// Bounded model checking on one incremental [`Solver`].
+///
+/// Frame `0` starts out constrained by `init`. Checking bound `k` adds the
+/// clauses of `bad` on frame `k`, each extended with the negation of a fresh
+/// activation variable, and solves under the assumption that this variable
+/// is true. If no bad state is reachable, the activation variable is fixed
+/// to false (disabling the bad clauses for good) and the next transition is
+/// appended.
+///
+/// The DPLL search learns no clauses, so what carries over between bounds
+/// is the formula built so far and the solver's configuration, which can be
+/// adjusted through [`solver_mut`](IncrementalBmc::solver_mut).
+///
+/// # Examples
+///
+/// ```
+/// use putnam::bmc::{BmcResult, IncrementalBmc, TransitionSystem};
+/// use putnam::types::{Lit, Var};
+///
+/// // A 1-bit toggle starting at 0 that is bad when 1
+/// let sys = TransitionSystem {
+///     frame_vars: 1,
+///     init: vec![vec![Lit::negative(Var::new(0))]],
+///     trans: vec![
+///         vec![Lit::positive(Var::new(0)), Lit::positive(Var::new(1))],
+///         vec![Lit::negative(Var::new(0)), Lit::negative(Var::new(1))],
+///     ],
+///     bad: vec![vec![Lit::positive(Var::new(0))]],
+/// };
+/// let mut bmc = IncrementalBmc::new(sys);
+///
+/// assert_eq!(bmc.step(), None);
+/// assert_eq!(bmc.bound(), 1);
+/// assert_eq!(bmc.step(), Some(vec![vec![false], vec![true]]));
+/// ```
+#[derive(Clone, Debug)]
+pub struct IncrementalBmc {
+    sys: TransitionSystem,
+    solver: Solver,
+    /// Index of the first solver variable of each frame
+    frames: Vec<usize>,
+    /// Activation variable of the bad clauses of the current bound
+    active: Option<Var>,
+}
+
+impl IncrementalBmc {
+    /// Creates a checker for `sys`, starting at bound 0.
+    pub fn new(sys: TransitionSystem) -> Self {
+        let mut bmc = IncrementalBmc { sys, solver: Solver::new(), frames: Vec::new(), active: None };
+        bmc.add_frame();
+        for clause in &bmc.sys.init {
+            let clause = clause.iter().map(|&lit| bmc.frame_lit(0, lit)).collect();
+            bmc.solver.add_clause(clause);
+        }
+        bmc
+    }
+
+    /// Returns the bound the next [`step`](IncrementalBmc::step) checks.
+    pub fn bound(&self) -> usize {
+        self.frames.len() - 1
+    }
+
+    /// Returns the underlying solver.
+    pub fn solver(&self) -> &Solver {
+        &self.solver
+    }
+
+    /// Returns the underlying solver, e.g. to configure it.
+    ///
+    /// Clauses added directly must only mention existing variables;
+    /// variables added later belong to the next frames.
+    pub fn solver_mut(&mut self) -> &mut Solver {
+        &mut self.solver
+    }
+
+    /// Checks whether a bad state is reachable in exactly
+    /// [`bound`](IncrementalBmc::bound) steps.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(trace)` - The values of the frame variables at each step
+    ///   `0..=bound`; the bound is not advanced
+    /// * `None` - No bad state at this bound; the bound was advanced by one
+    pub fn step(&mut self) -> Option<Vec<Vec<bool>>> {
+        let k = self.bound();
+        let active = match self.active {
+            Some(active) => active,
+            None => {
+                let active = self.solver.new_var();
+                for clause in &self.sys.bad {
+                    let mut guarded: Clause = clause.iter().map(|&lit| self.frame_lit(k, lit)).collect();
+                    guarded.push(Lit { var: active, neg: true });
+                    self.solver.add_clause(guarded);
+                }
+                *self.active.insert(active)
+            }
+        };
+
+        match self.solver.solve_with_assumptions(&[Lit { var: active, neg: false }]) {
+            SolveResult::Sat(model) => Some(
+                self.frames
+                    .iter()
+                    .map(|&first| (first..first + self.sys.frame_vars).map(|v| model.value(Var::new(v)) == Val::True).collect())
+                    .collect(),
+            ),
+            SolveResult::Unsat => {
+                self.solver.add_clause(vec![Lit { var: active, neg: true }]);
+                self.active = None;
+                self.add_frame();
+                for clause in &self.sys.trans {
+                    let clause = clause.iter().map(|&lit| self.frame_lit(k, lit)).collect();
+                    self.solver.add_clause(clause);
+                }
+                None
+            }
+        }
+    }
+
+    /// Checks bounds up to `max_bound`, continuing from the current bound.
+    ///
+    /// # Returns
+    ///
+    /// * `BmcResult::Counterexample` - The shortest counterexample from the
+    ///   current bound on
+    /// * `BmcResult::Safe` - No bad state is reachable within `max_bound` steps
+    pub fn run(&mut self, max_bound: usize) -> BmcResult {
+        while self.bound() <= max_bound {
+            if let Some(trace) = self.step() {
+                return BmcResult::Counterexample { bound: self.bound(), trace };
+            }
+        }
+        BmcResult::Safe { bound: max_bound }
+    }
+
+    /// Appends the variables of one frame.
+    fn add_frame(&mut self) {
+        self.frames.push(self.solver.num_vars());
+        for _ in 0..self.sys.frame_vars {
+            self.solver.new_var();
+        }
+    }
+
+    /// Maps a literal of a one- or two-frame formula, whose current frame is
+    /// `step`, to the solver.
+    fn frame_lit(&self, step: usize, lit: Lit) -> Lit {
+        let n = self.sys.frame_vars;
+        let (step, v) = match lit.var.index() {
+            v if v < n => (step, v),
+            v => (step + 1, v - n),
+        };
+        Lit { var: Var::new(self.frames[step] + v), neg: lit.neg }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(var: usize, neg: bool) -> Lit {
+        Lit::new(Var::new(var), neg)
+    }
+
+    /// 2-bit counter (b1 b0) starting at 00, incrementing modulo 4
+    fn counter(bad: Formula) -> TransitionSystem {
+        // next b0 = ¬b0, next b1 = b1 ⊕ b0
+        let (b0, b1, n0, n1) = (0, 1, 2, 3);
+        TransitionSystem {
+            frame_vars: 2,
+            init: vec![vec![lit(b0, true)], vec![lit(b1, true)]],
+            trans: vec![
+                vec![lit(b0, false), lit(n0, false)],
+                vec![lit(b0, true), lit(n0, true)],
+                vec![lit(b1, true), lit(b0, true), lit(n1, true)],
+                vec![lit(b1, false), lit(b0, false), lit(n1, true)],
+                vec![lit(b1, true), lit(b0, false), lit(n1, false)],
+                vec![lit(b1, false), lit(b0, true), lit(n1, false)],
+            ],
+            bad,
+        }
+    }
+
+    #[test]
+    fn counter_reaches_three_in_three_steps() {
+        let sys = counter(vec![vec![lit(0, false)], vec![lit(1, false)]]);
+
+        match check(&sys, 10) {
+            BmcResult::Counterexample { bound, trace } => {
+                assert_eq!(bound, 3);
+                assert_eq!(trace, vec![
+                    vec![false, false],
+                    vec![true, false],
+                    vec![false, true],
+                    vec![true, true],
+                ]);
+            }
+            BmcResult::Safe { .. } => panic!("Expected counterexample"),
+        }
+        assert_eq!(check(&sys, 2), BmcResult::Safe { bound: 2 });
+    }
+
+    #[test]
+    fn unreachable_bad_state_is_safe() {
+        // Bad: b0 ∧ ¬b0 can never hold
+        let sys = counter(vec![vec![lit(0, false)], vec![lit(0, true)]]);
+        assert_eq!(check(&sys, 6), BmcResult::Safe { bound: 6 });
+    }
+
+    #[test]
+    fn incremental_checks_agree_with_unrolling() {
+        let reach_three = counter(vec![vec![lit(0, false)], vec![lit(1, false)]]);
+        let mut bmc = IncrementalBmc::new(reach_three.clone());
+        assert_eq!(bmc.run(2), BmcResult::Safe { bound: 2 });
+        for k in 0..3 {
+            let (formula, num_vars) = unroll(&reach_three, k);
+            assert_eq!(crate::solve(&formula, num_vars), SolveResult::Unsat);
+        }
+        let (formula, num_vars) = unroll(&reach_three, 3);
+        let SolveResult::Sat(model) = crate::solve(&formula, num_vars) else { panic!("Expected SAT") };
+        let expected = BmcResult::Counterexample { bound: 3, trace: trace(&model, 2, 3) };
+        assert_eq!(bmc.run(10), expected);
+
+        let never = counter(vec![vec![lit(0, false)], vec![lit(0, true)]]);
+        let mut bmc = IncrementalBmc::new(never);
+        assert_eq!(bmc.run(6), BmcResult::Safe { bound: 6 });
+        assert_eq!(bmc.bound(), 7);
+    }
+
+    #[test]
+    fn unroll_shifts_frames() {
+        let sys = counter(vec![vec![lit(1, false)]]);
+        let (formula, num_vars) = unroll(&sys, 2);
+
+        assert_eq!(num_vars, 6);
+        assert_eq!(formula.len(), 2 + 2 * 6 + 1);
+        // Bad is asserted on the last frame
+        assert_eq!(formula.last().unwrap(), &vec![lit(5, false)]);
+    }
+}
@@ -0,0 +1,231 @@
+//! Cube-and-conquer job distribution
+//!
+//! *Cube and conquer* splits a hard formula into many independent
+//! subproblems, each the formula under a *cube* (a conjunction of literals),
+//! and solves them separately, possibly on other machines. The formula is
+//! satisfiable exactly when one of the subproblems is.
+//!
+//! [`generate`] builds the cubes by splitting on one variable at a time,
+//! with unit propagation after every split: cubes refuted by propagation
+//! are dropped (they contain no model), and splitting stops early where
+//! propagation satisfies the formula. The split variable is the unassigned
+//! variable occurring most often in the clauses not yet satisfied.
+//!
+//! The jobs are written as one DIMACS file per cube, with the cube as unit
+//! clauses ([`write_job`]), or all together as one iCNF file
+//! ([`write_icnf`]), the incremental format of cube-and-conquer tools, in
+//! which every `a` line lists the assumptions of one cube. Each worker's
+//! answer is read back from its exit code or output ([`JobResult`]), and
+//! the answers are combined with [`aggregate`].
+//!
+//! `putnam cube` drives the whole process from the command line.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::cube::{aggregate, generate, JobResult};
+//! use putnam::solve;
+//! use putnam::solver::dpll::SolveResult;
+//!
+//! let (formula, num_vars) = putnam::gen::random_ksat(30, 128, 3, 7);
+//! let cubes = generate(&formula, num_vars, 4);
+//! let results = cubes.iter().map(|cube| {
+//!     let mut job = formula.clone();
+//!     job.extend(cube.iter().map(|&lit| vec![lit]));
+//!     match solve(&job, num_vars) {
+//!         SolveResult::Sat(_) => JobResult::Sat,
+//!         SolveResult::Unsat => JobResult::Unsat,
+//!     }
+//! });
+//! let expected = match solve(&formula, num_vars) {
+//!     SolveResult::Sat(_) => JobResult::Sat,
+//!     SolveResult::Unsat => JobResult::Unsat,
+//! };
+//! assert_eq!(aggregate(results), expected);
+//! ```
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Write};
+
+use crate::parser::write_dimacs;
+use crate::solver::unit::unit_propagate;
+use crate::types::*;
+
+/// Splits `formula` with `num_vars` variables into cubes of at most
+/// `depth` decisions, see the [module documentation](self).
+///
+/// # Returns
+///
+/// Pairwise contradicting cubes, each with its decisions in order, whose
+/// models together are the models of the formula. An empty list means
+/// propagation refuted the formula.
+pub fn generate(formula: &Formula, num_vars: usize, depth: usize) -> Vec<Vec<Lit>> {
+    let mut cubes = Vec::new();
+    if formula.iter().any(|clause| clause.is_empty()) {
+        return cubes;
+    }
+    split(formula, Model::new(num_vars), depth, &mut Vec::new(), &mut VecDeque::new(), &mut cubes);
+    cubes
+}
+
+/// Propagates `model`, the assignment of `cube`, and adds the cubes
+/// below it with at most `depth` more decisions.
+fn split(
+    formula: &Formula,
+    mut model: Model,
+    depth: usize,
+    cube: &mut Vec<Lit>,
+    queue: &mut VecDeque<(Lit, ClauseId)>,
+    cubes: &mut Vec<Vec<Lit>>,
+) {
+    if unit_propagate(formula, &mut model, queue).is_err() {
+        return;
+    }
+    let var = if depth == 0 { None } else { split_variable(formula, &model) };
+    let Some(var) = var else {
+        cubes.push(cube.clone());
+        return;
+    };
+    for lit in [Lit::positive(var), Lit::negative(var)] {
+        let mut child = model.clone();
+        child.assign(var, if lit.neg { Val::False } else { Val::True });
+        cube.push(lit);
+        split(formula, child, depth - 1, cube, queue, cubes);
+        cube.pop();
+    }
+}
+
+/// Returns the unassigned variable with the most occurrences in clauses
+/// `model` does not satisfy, or `None` if it satisfies them all.
+fn split_variable(formula: &Formula, model: &Model) -> Option<Var> {
+    let mut occurrences = vec![0usize; model.num_vars()];
+    for clause in formula.iter().filter(|clause| !clause.iter().any(|&lit| model.is_true(lit))) {
+        for lit in clause.iter().filter(|lit| model.value(lit.var) == Val::Undef) {
+            occurrences[lit.var.index()] += 1;
+        }
+    }
+    // The lowest variable among the most frequent
+    let (index, &count) = occurrences.iter().enumerate().rev().max_by_key(|&(_, count)| count)?;
+    (count > 0).then(|| Var::new(index))
+}
+
+/// Writes the job of one cube: `formula` with `num_vars` variables and the
+/// literals of `cube` as unit clauses, in DIMACS format.
+pub fn write_job<W: Write>(writer: W, formula: &Formula, num_vars: usize, cube: &[Lit]) -> io::Result<()> {
+    let mut job = formula.clone();
+    job.extend(cube.iter().map(|&lit| vec![lit]));
+    write_dimacs(writer, &job, num_vars)
+}
+
+/// Writes `formula` and all `cubes` in iCNF format: a `p inccnf` header,
+/// the clauses, and one `a` line per cube.
+pub fn write_icnf<W: Write>(mut writer: W, formula: &Formula, cubes: &[Vec<Lit>]) -> io::Result<()> {
+    writeln!(writer, "p inccnf")?;
+    let line = |writer: &mut W, prefix: &str, lits: &[Lit]| -> io::Result<()> {
+        write!(writer, "{}", prefix)?;
+        for lit in lits {
+            write!(writer, "{} ", lit.to_dimacs())?;
+        }
+        writeln!(writer, "0")
+    };
+    for clause in formula {
+        line(&mut writer, "", clause)?;
+    }
+    for cube in cubes {
+        line(&mut writer, "a ", cube)?;
+    }
+    writer.flush()
+}
+
+/// Answer of one job.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobResult {
+    /// The formula has a model within the cube
+    Sat,
+    /// The cube contains no model
+    Unsat,
+    /// The worker gave no answer (it failed, or was stopped)
+    Unknown,
+}
+
+impl JobResult {
+    /// Interprets a worker's exit code by the SAT competition convention:
+    /// 10 for satisfiable, 20 for unsatisfiable.
+    pub fn from_exit_code(code: Option<i32>) -> Self {
+        match code {
+            Some(10) => JobResult::Sat,
+            Some(20) => JobResult::Unsat,
+            _ => JobResult::Unknown,
+        }
+    }
+}
+
+/// Reads a job's answer from the output of its worker: the first
+/// `s SATISFIABLE` or `s UNSATISFIABLE` status line of the competition
+/// format, or the `SAT` or `UNSAT` line of `putnam` itself.
+///
+/// # Returns
+///
+/// The answer, [`JobResult::Unknown`] if the output has none.
+pub fn read_result<R: BufRead>(reader: R) -> io::Result<JobResult> {
+    for line in reader.lines() {
+        match line?.trim() {
+            "s SATISFIABLE" | "SAT" => return Ok(JobResult::Sat),
+            "s UNSATISFIABLE" | "UNSAT" => return Ok(JobResult::Unsat),
+            _ => {}
+        }
+    }
+    Ok(JobResult::Unknown)
+}
+
+/// Combines the answers of the jobs of all cubes: satisfiable if any job
+/// is, unsatisfiable if all are (including when there are none), and
+/// unknown otherwise.
+pub fn aggregate(results: impl IntoIterator<Item = JobResult>) -> JobResult {
+    let mut combined = JobResult::Unsat;
+    for result in results {
+        match result {
+            JobResult::Sat => return JobResult::Sat,
+            JobResult::Unknown => combined = JobResult::Unknown,
+            JobResult::Unsat => {}
+        }
+    }
+    combined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cubes_partition_the_models() {
+        for seed in 0..8 {
+            let (formula, num_vars) = crate::gen::random_ksat(10, 42, 3, seed);
+            let cubes = generate(&formula, num_vars, 3);
+            assert!(cubes.iter().all(|cube| cube.len() <= 3));
+            let within = |bits: u32, cube: &[Lit]| cube.iter().all(|lit| (bits >> lit.var.index() & 1 == 1) != lit.neg);
+            // Every model lies in exactly one cube
+            for bits in 0..1u32 << num_vars {
+                let value = |lit: Lit| (bits >> lit.var.index() & 1 == 1) != lit.neg;
+                if formula.iter().all(|clause| clause.iter().any(|&lit| value(lit))) {
+                    assert_eq!(cubes.iter().filter(|cube| within(bits, cube)).count(), 1, "seed {}", seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn results_are_read_and_combined() {
+        let output = "c solving\ns UNSATISFIABLE\n";
+        assert_eq!(read_result(output.as_bytes()).unwrap(), JobResult::Unsat);
+        assert_eq!(read_result("SAT\nv 1 -2 0\n".as_bytes()).unwrap(), JobResult::Sat);
+        assert_eq!(read_result("".as_bytes()).unwrap(), JobResult::Unknown);
+        assert_eq!(JobResult::from_exit_code(Some(10)), JobResult::Sat);
+        assert_eq!(JobResult::from_exit_code(None), JobResult::Unknown);
+
+        use JobResult::*;
+        assert_eq!(aggregate([Unsat, Unknown, Sat]), Sat);
+        assert_eq!(aggregate([Unsat, Unknown]), Unknown);
+        assert_eq!(aggregate([]), Unsat);
+    }
+}
@@ -0,0 +1,128 @@
+//! Cardinality constraints with totalizers
+//!
+//! A cardinality constraint bounds how many of a set of literals are true.
+//! The *totalizer* (Bailleux and Boufkhad, 2003) encodes one as a balanced
+//! tree of unary adders: every node counts the true literals below it in
+//! unary, with output `k` (0-based) forced true as soon as more than `k`
+//! of them are. Asserting the negation of output `k` at the root then
+//! bounds the count by `k`.
+//!
+//! The outputs are only forced upwards, which is all an upper bound needs;
+//! lower bounds are upper bounds on the complemented literals. Since the
+//! bound is a single unit on an output, one totalizer serves every bound:
+//! assuming a stronger bound tightens it incrementally, as the
+//! [optimization](crate::solver::optimize) of a solver's models does.
+//!
+//! # Example
+//!
+//! ```
+//! use putnam::encode::cardinality::at_most;
+//! use putnam::solver::dpll::SolveResult;
+//! use putnam::types::{Lit, Var};
+//!
+//! let lits: Vec<Lit> = (0..4).map(|var| Lit::positive(Var::new(var))).collect();
+//! let (mut formula, num_vars) = at_most(&lits, 1, 4);
+//! formula.push(vec![lits[0]]);
+//! formula.push(vec![lits[2]]);
+//! assert_eq!(putnam::solve(&formula, num_vars), SolveResult::Unsat);
+//! ```
+
+use crate::types::*;
+
+/// Encodes a totalizer over `lits`, numbering its variables from
+/// `num_vars` on.
+///
+/// # Returns
+///
+/// A tuple `(formula, outputs, num_vars)`: the clauses, the outputs, where
+/// `outputs[k]` is forced true whenever more than `k` of `lits` are, and
+/// the number of variables including the new ones.
+pub fn totalizer(lits: &[Lit], num_vars: usize) -> (Formula, Vec<Lit>, usize) {
+    let mut formula = Vec::new();
+    let mut num_vars = num_vars;
+    let outputs = count(lits, &mut formula, &mut num_vars);
+    (formula, outputs, num_vars)
+}
+
+/// Encodes the node of the totalizer over `lits`, returning its outputs.
+fn count(lits: &[Lit], formula: &mut Formula, num_vars: &mut usize) -> Vec<Lit> {
+    if lits.len() <= 1 {
+        return lits.to_vec();
+    }
+    let (left, right) = lits.split_at(lits.len() / 2);
+    let left = count(left, formula, num_vars);
+    let right = count(right, formula, num_vars);
+    let outputs: Vec<Lit> = (*num_vars..*num_vars + lits.len()).map(|var| Lit::positive(Var::new(var))).collect();
+    *num_vars += lits.len();
+    // More than i on the left and more than j on the right make more than i + j
+    for i in 0..=left.len() {
+        for j in 0..=right.len() {
+            if i + j == 0 {
+                continue;
+            }
+            let mut clause = Vec::with_capacity(3);
+            if i > 0 {
+                clause.push(!left[i - 1]);
+            }
+            if j > 0 {
+                clause.push(!right[j - 1]);
+            }
+            clause.push(outputs[i + j - 1]);
+            formula.push(clause);
+        }
+    }
+    outputs
+}
+
+/// Encodes that at most `k` of `lits` are true.
+///
+/// # Arguments
+///
+/// * `lits` - The literals to count
+/// * `k` - The bound
+/// * `num_vars` - The number of variables in use; new variables follow them
+///
+/// # Returns
+///
+/// The clauses and the number of variables including the new ones.
+pub fn at_most(lits: &[Lit], k: usize, num_vars: usize) -> (Formula, usize) {
+    let (mut formula, outputs, num_vars) = totalizer(lits, num_vars);
+    if let Some(&output) = outputs.get(k) {
+        formula.push(vec![!output]);
+    }
+    (formula, num_vars)
+}
+
+/// Encodes that at least `k` of `lits` are true, as at most
+/// `lits.len() - k` of their complements.
+///
+/// A bound above `lits.len()` yields an empty clause.
+pub fn at_least(lits: &[Lit], k: usize, num_vars: usize) -> (Formula, usize) {
+    match lits.len().checked_sub(k) {
+        Some(rest) => at_most(&lits.iter().map(|&lit| !lit).collect::<Vec<_>>(), rest, num_vars),
+        None => (vec![Vec::new()], num_vars),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll::{solve, SolveResult};
+
+    #[test]
+    fn bounds_hold_for_every_input() {
+        let lits: Vec<Lit> = (0..5).map(|var| Lit::new(Var::new(var), var % 2 == 1)).collect();
+        for k in 0..=6 {
+            for inputs in 0..1u32 << lits.len() {
+                let units = lits.iter().enumerate().map(|(i, &lit)| vec![if inputs >> i & 1 == 1 { lit } else { !lit }]);
+                let (mut upper, num_vars) = at_most(&lits, k, 5);
+                upper.extend(units.clone());
+                let (mut lower, lower_vars) = at_least(&lits, k, 5);
+                lower.extend(units);
+                let ones = inputs.count_ones() as usize;
+                assert_eq!(solve(&upper, num_vars) != SolveResult::Unsat, ones <= k, "at most {} of {:b}", k, inputs);
+                assert_eq!(solve(&lower, lower_vars) != SolveResult::Unsat, ones >= k, "at least {} of {:b}", k, inputs);
+            }
+        }
+    }
+}
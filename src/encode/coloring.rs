@@ -0,0 +1,182 @@
+//! Graph coloring encoder
+//!
+//! This module reads graphs in the DIMACS `.col` format used by the graph
+//! coloring benchmarks, encodes "is this graph k-colorable?" as CNF, and
+//! decodes satisfying models back into vertex colorings.
+//!
+//! The `.col` format consists of:
+//! - Comment lines starting with 'c'
+//! - A problem line `p edge <vertices> <edges>` (`p col` is accepted as well)
+//! - Edge lines `e <u> <v>` with 1-based vertex numbers
+//!
+//! # Example
+//!
+//! ```
+//! use putnam::encode::coloring::{parse_col, encode, decode};
+//! use putnam::solver::dpll::SolveResult;
+//!
+//! // A 4-cycle is 2-colorable
+//! let graph = parse_col("p edge 4 4\ne 1 2\ne 2 3\ne 3 4\ne 4 1\n".as_bytes())?;
+//! let (formula, num_vars) = encode(&graph, 2);
+//!
+//! match putnam::solve(&formula, num_vars) {
+//!     SolveResult::Sat(model) => {
+//!         let colors = decode(&model, &graph, 2);
+//!         assert!(graph.edges.iter().all(|&(u, v)| colors[u] != colors[v]));
+//!     }
+//!     SolveResult::Unsat => unreachable!(),
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::io::{self, BufRead};
+
+use crate::gen::families::coloring_formula;
+use crate::types::*;
+
+/// An undirected graph with 0-based vertex numbers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Graph {
+    /// Number of vertices
+    pub num_vertices: usize,
+    /// Edge list (endpoints are `< num_vertices`)
+    pub edges: Vec<(usize, usize)>,
+}
+
+/// Parses a graph in DIMACS `.col` format.
+///
+/// Vertex numbers are converted from 1-based to 0-based. Lines other than
+/// comments, the problem line, and edge lines are rejected.
+///
+/// # Arguments
+///
+/// * `reader` - A reader implementing `BufRead` trait
+///
+/// # Returns
+///
+/// * `Ok(Graph)` - The parsed graph
+/// * `Err(io::Error)` - If reading fails, or with `ErrorKind::InvalidData`
+///   if the input is malformed (missing problem line, bad numbers, vertices
+///   out of range)
+pub fn parse_col<R: BufRead>(reader: R) -> io::Result<Graph> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let mut num_vertices = None;
+    let mut edges = Vec::new();
+
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line?;
+        let mut tokens = line.split_whitespace();
+        let number = |tok: Option<&str>| -> io::Result<usize> {
+            tok.and_then(|t| t.parse().ok())
+                .ok_or_else(|| invalid(format!("line {}: expected a number", lineno + 1)))
+        };
+
+        match tokens.next() {
+            None | Some("c") => continue,
+            Some("p") => {
+                tokens.next(); // "edge" or "col"
+                num_vertices = Some(number(tokens.next())?);
+            }
+            Some("e") => {
+                let n = num_vertices
+                    .ok_or_else(|| invalid(format!("line {}: edge before problem line", lineno + 1)))?;
+                let u = number(tokens.next())?;
+                let v = number(tokens.next())?;
+                if u == 0 || v == 0 || u > n || v > n {
+                    return Err(invalid(format!("line {}: vertex out of range 1..={}", lineno + 1, n)));
+                }
+                edges.push((u - 1, v - 1));
+            }
+            Some(other) => {
+                return Err(invalid(format!("line {}: unexpected token '{}'", lineno + 1, other)));
+            }
+        }
+    }
+
+    let num_vertices = num_vertices.ok_or_else(|| invalid("missing problem line".to_string()))?;
+    Ok(Graph { num_vertices, edges })
+}
+
+/// Encodes k-colorability of `graph` as CNF.
+///
+/// Variable `v * k + c` means "vertex `v` has color `c`"; see
+/// [`coloring_formula`] for the clauses.
+///
+/// # Returns
+///
+/// The formula and its `graph.num_vertices * k` variables
+pub fn encode(graph: &Graph, k: usize) -> (Formula, usize) {
+    coloring_formula(graph.num_vertices, &graph.edges, k)
+}
+
+/// Decodes a satisfying model of [`encode`] into a vertex coloring.
+///
+/// # Returns
+///
+/// The color (`0..k`) of each vertex. A vertex with no true color variable
+/// (only possible in a partial model) gets color 0.
+pub fn decode(model: &Model, graph: &Graph, k: usize) -> Vec<usize> {
+    (0..graph.num_vertices)
+        .map(|v| (0..k).find(|&c| model.value(Var::new(v * k + c)) == Val::True).unwrap_or(0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll::SolveResult;
+
+    const PETERSEN: &str = "\
+c Petersen graph: chromatic number 3
+p edge 10 15
+e 1 2
+e 2 3
+e 3 4
+e 4 5
+e 5 1
+e 1 6
+e 2 7
+e 3 8
+e 4 9
+e 5 10
+e 6 8
+e 8 10
+e 10 7
+e 7 9
+e 9 6
+";
+
+    #[test]
+    fn parse_petersen() {
+        let graph = parse_col(PETERSEN.as_bytes()).unwrap();
+        assert_eq!(graph.num_vertices, 10);
+        assert_eq!(graph.edges.len(), 15);
+        assert_eq!(graph.edges[0], (0, 1));
+    }
+
+    #[test]
+    fn petersen_chromatic_number() {
+        let graph = parse_col(PETERSEN.as_bytes()).unwrap();
+
+        let (formula, num_vars) = encode(&graph, 2);
+        assert_eq!(crate::solve(&formula, num_vars), SolveResult::Unsat);
+
+        let (formula, num_vars) = encode(&graph, 3);
+        match crate::solve(&formula, num_vars) {
+            SolveResult::Sat(model) => {
+                let colors = decode(&model, &graph, 3);
+                assert!(colors.iter().all(|&c| c < 3));
+                assert!(graph.edges.iter().all(|&(u, v)| colors[u] != colors[v]));
+            }
+            SolveResult::Unsat => panic!("Expected SAT"),
+        }
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert!(parse_col("e 1 2\n".as_bytes()).is_err());
+        assert!(parse_col("p edge 2 1\ne 1 3\n".as_bytes()).is_err());
+        assert!(parse_col("p edge 2 1\ne 1 x\n".as_bytes()).is_err());
+        assert!(parse_col("c only a comment\n".as_bytes()).is_err());
+    }
+}
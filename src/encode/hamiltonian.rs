@@ -0,0 +1,174 @@
+//! Hamiltonian cycle encoder
+//!
+//! This module encodes "does this graph have a Hamiltonian cycle?" as CNF
+//! using the classic position-based encoding: variable `v * n + p` means
+//! "vertex `v` is visited at position `p` of the cycle". The formula
+//! requires:
+//!
+//! - every position holds exactly one vertex,
+//! - every vertex is visited at exactly one position,
+//! - consecutive positions (including the wrap-around from `n - 1` to `0`)
+//!   hold vertices connected by an edge.
+//!
+//! The graph is given as a *directed* adjacency list: `adj[u]` contains `v`
+//! when the cycle may go from `u` to `v`. Undirected graphs list each edge
+//! in both directions.
+//!
+//! # Example
+//!
+//! ```
+//! use putnam::encode::hamiltonian::{encode, decode};
+//! use putnam::solver::dpll::SolveResult;
+//!
+//! // Undirected 4-cycle 0-1-2-3-0
+//! let adj = vec![vec![1, 3], vec![0, 2], vec![1, 3], vec![2, 0]];
+//! let (formula, num_vars) = encode(&adj);
+//!
+//! match putnam::solve(&formula, num_vars) {
+//!     SolveResult::Sat(model) => {
+//!         let cycle = decode(&model, adj.len());
+//!         assert_eq!(cycle.len(), 4);
+//!         assert_eq!(cycle[0], 0);
+//!     }
+//!     SolveResult::Unsat => unreachable!(),
+//! }
+//! ```
+
+use crate::types::*;
+
+/// Encodes the existence of a Hamiltonian cycle in a directed graph.
+///
+/// To break the rotational symmetry of cycles, vertex 0 is fixed at
+/// position 0.
+///
+/// # Arguments
+///
+/// * `adj` - Directed adjacency list; `adj.len()` is the number of vertices
+///
+/// # Returns
+///
+/// The formula and its `n * n` variables, where `n = adj.len()`
+pub fn encode(adj: &[Vec<usize>]) -> (Formula, usize) {
+    let n = adj.len();
+    let var = |vertex: usize, pos: usize| Var::new(vertex * n + pos);
+    let pos_lit = |vertex, pos| Lit { var: var(vertex, pos), neg: false };
+    let neg_lit = |vertex, pos| Lit { var: var(vertex, pos), neg: true };
+    let mut formula = Vec::new();
+
+    for p in 0..n {
+        // Each position holds at least one vertex ...
+        formula.push((0..n).map(|v| pos_lit(v, p)).collect());
+        // ... and at most one
+        for v1 in 0..n {
+            for v2 in (v1 + 1)..n {
+                formula.push(vec![neg_lit(v1, p), neg_lit(v2, p)]);
+            }
+        }
+    }
+
+    for v in 0..n {
+        // Each vertex is visited at least once ...
+        formula.push((0..n).map(|p| pos_lit(v, p)).collect());
+        // ... and at most once
+        for p1 in 0..n {
+            for p2 in (p1 + 1)..n {
+                formula.push(vec![neg_lit(v, p1), neg_lit(v, p2)]);
+            }
+        }
+    }
+
+    // Consecutive vertices must be connected by an edge
+    for (u, succ) in adj.iter().enumerate() {
+        for v in 0..n {
+            if succ.contains(&v) {
+                continue;
+            }
+            for p in 0..n {
+                formula.push(vec![neg_lit(u, p), neg_lit(v, (p + 1) % n)]);
+            }
+        }
+    }
+
+    // Symmetry breaking: the cycle starts at vertex 0
+    if n > 0 {
+        formula.push(vec![pos_lit(0, 0)]);
+    }
+
+    (formula, n * n)
+}
+
+/// Decodes a satisfying model of [`encode`] into the visiting order.
+///
+/// # Arguments
+///
+/// * `model` - A satisfying model of the encoded formula
+/// * `n` - Number of vertices of the graph
+///
+/// # Returns
+///
+/// The vertices in cycle order, starting with vertex 0. Positions without a
+/// true variable (only possible in a partial model) are skipped.
+pub fn decode(model: &Model, n: usize) -> Vec<usize> {
+    (0..n)
+        .filter_map(|p| (0..n).find(|&v| model.value(Var::new(v * n + p)) == Val::True))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll::SolveResult;
+
+    /// Converts an undirected edge list into a symmetric adjacency list
+    fn undirected(n: usize, edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+        let mut adj = vec![Vec::new(); n];
+        for &(u, v) in edges {
+            adj[u].push(v);
+            adj[v].push(u);
+        }
+        adj
+    }
+
+    fn is_hamiltonian_cycle(adj: &[Vec<usize>], cycle: &[usize]) -> bool {
+        let n = adj.len();
+        let mut seen = vec![false; n];
+        cycle.len() == n
+            && cycle.iter().all(|&v| !std::mem::replace(&mut seen[v], true))
+            && (0..n).all(|i| adj[cycle[i]].contains(&cycle[(i + 1) % n]))
+    }
+
+    #[test]
+    fn wheel_graph_has_cycle() {
+        // Hub 0 connected to the rim 1-2-3-4-5-1
+        let adj = undirected(6, &[(0, 1), (0, 2), (0, 3), (0, 4), (0, 5), (1, 2), (2, 3), (3, 4), (4, 5), (5, 1)]);
+        let (formula, num_vars) = encode(&adj);
+
+        match crate::solve(&formula, num_vars) {
+            SolveResult::Sat(model) => assert!(is_hamiltonian_cycle(&adj, &decode(&model, 6))),
+            SolveResult::Unsat => panic!("Expected SAT"),
+        }
+    }
+
+    #[test]
+    fn star_graph_has_no_cycle() {
+        let adj = undirected(4, &[(0, 1), (0, 2), (0, 3)]);
+        let (formula, num_vars) = encode(&adj);
+        assert_eq!(crate::solve(&formula, num_vars), SolveResult::Unsat);
+    }
+
+    #[test]
+    fn direction_matters() {
+        // Directed triangle 0 → 1 → 2 → 0 has a cycle, but only one way round
+        let adj = vec![vec![1], vec![2], vec![0]];
+        let (formula, num_vars) = encode(&adj);
+        match crate::solve(&formula, num_vars) {
+            SolveResult::Sat(model) => assert_eq!(decode(&model, 3), vec![0, 1, 2]),
+            SolveResult::Unsat => panic!("Expected SAT"),
+        }
+
+        // A directed path 0 → 1 → 2 has none
+        let adj = vec![vec![1], vec![2], vec![]];
+        let (formula, num_vars) = encode(&adj);
+        assert_eq!(crate::solve(&formula, num_vars), SolveResult::Unsat);
+    }
+}
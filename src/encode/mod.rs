@@ -0,0 +1,21 @@
+//! Encoders from application problems to CNF
+//!
+//! Each submodule translates one kind of problem into a [`Formula`](crate::types::Formula)
+//! and provides a decoder mapping satisfying models back to solutions of the
+//! original problem.
+//!
+//! # Modules
+//!
+//! - [`coloring`]: Graph k-coloring from DIMACS `.col` graph files
+//! - [`hamiltonian`]: Hamiltonian cycles with position-based variables
+//! - [`truth_table`]: Small constraints given as truth tables or predicates
+//! - [`miter`]: Combinational equivalence checking of two circuits
+//! - [`cardinality`]: Bounds on the number of true literals, with totalizers
+//! - [`three_cnf`]: Splitting long clauses into 3-CNF with chaining variables
+
+pub mod coloring;
+pub mod hamiltonian;
+pub mod truth_table;
+pub mod miter;
+pub mod cardinality;
+pub mod three_cnf;
@@ -0,0 +1,107 @@
+//! Conversion of k-CNF to 3-CNF
+//!
+//! Some tools (and many textbook reductions) accept only clauses of at most
+//! three literals. [`encode`] splits every longer clause into a chain of
+//! three-literal clauses linked by fresh variables:
+//!
+//! ```text
+//! (l1 ∨ l2 ∨ l3 ∨ l4 ∨ l5)  ↦  (l1 ∨ l2 ∨ y1) ∧ (¬y1 ∨ l3 ∨ y2) ∧ (¬y2 ∨ l4 ∨ l5)
+//! ```
+//!
+//! A model of the chain satisfies one of the `li`, since otherwise the
+//! chaining variables would have to be true all along and falsify the last
+//! clause. Conversely, every model of the clause extends to the chain by
+//! setting the `yi` before its first true literal. So the result is
+//! satisfiable exactly when the input is, and [`decode`] maps its models
+//! back by forgetting the chaining variables. Shorter clauses are kept as
+//! they are.
+//!
+//! # Example
+//!
+//! ```
+//! use putnam::encode::three_cnf::{decode, encode};
+//! use putnam::solver::dpll::SolveResult;
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! let formula = vec![vec![x(0), x(1), x(2), x(3), x(4)], vec![!x(0)], vec![!x(1), !x(2)]];
+//! let (cnf3, num_vars) = encode(&formula, 5);
+//! assert!(cnf3.iter().all(|clause| clause.len() <= 3));
+//! assert_eq!(num_vars, 7);
+//!
+//! let SolveResult::Sat(model) = putnam::solve(&cnf3, num_vars) else { unreachable!() };
+//! let model = decode(&model, 5);
+//! assert_eq!(model.num_vars(), 5);
+//! assert!(formula.iter().all(|clause| clause.iter().any(|&lit| model.is_true(lit))));
+//! ```
+
+use crate::types::*;
+
+/// Splits the clauses of `formula` with more than three literals into
+/// chains of three-literal clauses.
+///
+/// # Arguments
+///
+/// * `formula` - The formula to convert
+/// * `num_vars` - The number of variables of the formula; the chaining
+///   variables follow them
+///
+/// # Returns
+///
+/// The converted formula, with every clause of at most three literals, and
+/// its number of variables.
+pub fn encode(formula: &Formula, num_vars: usize) -> (Formula, usize) {
+    let mut num_vars = num_vars;
+    let mut converted = Vec::with_capacity(formula.len());
+    for clause in formula {
+        if clause.len() <= 3 {
+            converted.push(clause.clone());
+            continue;
+        }
+        let (first, rest) = clause.split_at(2);
+        let (middle, last) = rest.split_at(rest.len() - 2);
+        let mut link = Lit::positive(Var::new(num_vars));
+        num_vars += 1;
+        converted.push(vec![first[0], first[1], link]);
+        for &lit in middle {
+            let next = Lit::positive(Var::new(num_vars));
+            num_vars += 1;
+            converted.push(vec![!link, lit, next]);
+            link = next;
+        }
+        converted.push(vec![!link, last[0], last[1]]);
+    }
+    (converted, num_vars)
+}
+
+/// Maps a model of the converted formula back to the original formula
+/// with `num_vars` variables, dropping the chaining variables.
+pub fn decode(model: &Model, num_vars: usize) -> Model {
+    let mut values = model.to_vec();
+    values.resize(num_vars, None);
+    Model::from(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn models_correspond() {
+        for seed in 0..20 {
+            let (formula, num_vars) = crate::gen::random_ksat(7, 3, 6, seed);
+            let (converted, total) = encode(&formula, num_vars);
+            assert!(converted.iter().all(|clause| clause.len() <= 3));
+            let satisfies = |formula: &Formula, bits: u32| {
+                formula.iter().all(|clause| clause.iter().any(|l| (bits >> l.var.index() & 1 == 1) != l.neg))
+            };
+            // Each model of the original extends to one of the conversion,
+            // and each model of the conversion restricts to the original
+            let chains = 1u32 << (total - num_vars);
+            for bits in 0..1u32 << num_vars {
+                let extends = (0..chains).any(|chain| satisfies(&converted, bits | chain << num_vars));
+                assert_eq!(extends, satisfies(&formula, bits), "seed {}", seed);
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Truth table to CNF conversion
+//!
+//! Small constraints are often easiest to specify as a truth table or a
+//! Rust predicate over a handful of variables. This module expands such
+//! specifications into CNF:
+//!
+//! - [`from_fn`] / [`from_table`] produce the *maxterm expansion*: one
+//!   clause per falsifying row, forbidding exactly that row.
+//! - [`define_fn`] / [`define_table`] define an output variable equivalent
+//!   to the function, with one clause per row (minterms imply the output,
+//!   maxterms imply its negation).
+//!
+//! Row `r` of a table assigns bit `i` of `r` to `vars[i]`, so the table has
+//! `2^vars.len()` entries. The expansion is exponential in the number of
+//! variables and intended for small functions only.
+//!
+//! # Example
+//!
+//! ```
+//! use putnam::encode::truth_table::from_fn;
+//! use putnam::solver::dpll::SolveResult;
+//! use putnam::types::{Val, Var};
+//!
+//! // "exactly two of x0, x1, x2"
+//! let mut formula = from_fn(&[Var::new(0), Var::new(1), Var::new(2)], |v| v.iter().filter(|&&b| b).count() == 2);
+//! formula.push(vec![putnam::types::Lit::negative(Var::new(0))]); // and ¬x0
+//!
+//! match putnam::solve(&formula, 3) {
+//!     SolveResult::Sat(model) => {
+//!         assert_eq!(model.value(Var::new(1)), Val::True);
+//!         assert_eq!(model.value(Var::new(2)), Val::True);
+//!     }
+//!     SolveResult::Unsat => unreachable!(),
+//! }
+//! ```
+
+use crate::types::*;
+
+/// Largest number of input variables accepted by this module.
+pub const MAX_VARS: usize = 20;
+
+/// Returns the clause that is false exactly on row `row`.
+fn exclude_row(vars: &[Var], row: usize) -> Clause {
+    vars.iter()
+        .enumerate()
+        .map(|(i, &var)| Lit { var, neg: (row >> i) & 1 == 1 })
+        .collect()
+}
+
+/// Evaluates `f` on every row of the table over `vars.len()` variables.
+fn tabulate<F: Fn(&[bool]) -> bool>(n: usize, f: F) -> Vec<bool> {
+    assert!(n <= MAX_VARS, "truth tables support at most {} variables, got {}", MAX_VARS, n);
+    let mut values = vec![false; n];
+    (0..1usize << n)
+        .map(|row| {
+            for (i, v) in values.iter_mut().enumerate() {
+                *v = (row >> i) & 1 == 1;
+            }
+            f(&values)
+        })
+        .collect()
+}
+
+/// Converts a truth table into CNF by maxterm expansion.
+///
+/// # Arguments
+///
+/// * `vars` - The input variables; `vars[i]` is bit `i` of the row index
+/// * `table` - The function value of each row (`2^vars.len()` entries)
+///
+/// # Returns
+///
+/// A formula satisfied exactly by the assignments of `vars` whose row is
+/// `true` in the table.
+///
+/// # Panics
+///
+/// Panics if `table.len() != 2^vars.len()` or `vars.len() > MAX_VARS`.
+pub fn from_table(vars: &[Var], table: &[bool]) -> Formula {
+    assert!(vars.len() <= MAX_VARS, "truth tables support at most {} variables", MAX_VARS);
+    assert_eq!(table.len(), 1 << vars.len(), "table must have 2^{} rows", vars.len());
+    table
+        .iter()
+        .enumerate()
+        .filter(|(_, &value)| !value)
+        .map(|(row, _)| exclude_row(vars, row))
+        .collect()
+}
+
+/// Converts a predicate into CNF by maxterm expansion.
+///
+/// `f` receives the values of `vars` in order. See [`from_table`].
+pub fn from_fn<F: Fn(&[bool]) -> bool>(vars: &[Var], f: F) -> Formula {
+    from_table(vars, &tabulate(vars.len(), f))
+}
+
+/// Defines `out ↔ table(vars)` in CNF.
+///
+/// Every row contributes one clause: `row → out` for true rows (minterms)
+/// and `row → ¬out` for false rows (maxterms).
+///
+/// # Arguments
+///
+/// * `out` - The output variable (must not occur in `vars`)
+/// * `vars` - The input variables; `vars[i]` is bit `i` of the row index
+/// * `table` - The function value of each row (`2^vars.len()` entries)
+///
+/// # Panics
+///
+/// Panics if `table.len() != 2^vars.len()` or `vars.len() > MAX_VARS`.
+pub fn define_table(out: Var, vars: &[Var], table: &[bool]) -> Formula {
+    assert!(vars.len() <= MAX_VARS, "truth tables support at most {} variables", MAX_VARS);
+    assert_eq!(table.len(), 1 << vars.len(), "table must have 2^{} rows", vars.len());
+    table
+        .iter()
+        .enumerate()
+        .map(|(row, &value)| {
+            let mut clause = exclude_row(vars, row);
+            clause.push(Lit { var: out, neg: !value });
+            clause
+        })
+        .collect()
+}
+
+/// Defines `out ↔ f(vars)` in CNF. See [`define_table`].
+pub fn define_fn<F: Fn(&[bool]) -> bool>(out: Var, vars: &[Var], f: F) -> Formula {
+    define_table(out, vars, &tabulate(vars.len(), f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::brute;
+    use crate::solver::dpll::SolveResult;
+
+    /// Checks every assignment of the first `n` variables against `f`.
+    fn assert_equivalent(formula: &Formula, n: usize, f: impl Fn(&[bool]) -> bool) {
+        for row in 0..1usize << n {
+            let values: Vec<bool> = (0..n).map(|i| (row >> i) & 1 == 1).collect();
+            let mut fixed = formula.clone();
+            fixed.extend((0..n).map(|i| vec![Lit { var: Var::new(i), neg: !values[i] }]));
+            let sat = matches!(brute::solve(&fixed, n), SolveResult::Sat(_));
+            assert_eq!(sat, f(&values), "row {:b}", row);
+        }
+    }
+
+    #[test]
+    fn majority_function() {
+        let majority = |v: &[bool]| v.iter().filter(|&&b| b).count() >= 2;
+        let formula = from_fn(&[Var::new(0), Var::new(1), Var::new(2)], majority);
+        assert_eq!(formula.len(), 4); // four falsifying rows
+        assert_equivalent(&formula, 3, majority);
+    }
+
+    #[test]
+    fn constant_tables() {
+        assert!(from_table(&[Var::new(0), Var::new(1)], &[true; 4]).is_empty());
+        assert_eq!(from_table(&[Var::new(0)], &[false, false]).len(), 2);
+        // Zero variables: the empty row
+        assert_eq!(from_table(&[], &[false]), vec![Vec::<Lit>::new()]);
+    }
+
+    #[test]
+    fn definition_tracks_function() {
+        // out (var 2) ↔ x0 ⊕ x1
+        let formula = define_fn(Var::new(2), &[Var::new(0), Var::new(1)], |v| v[0] != v[1]);
+        assert_eq!(formula.len(), 4);
+        assert_equivalent(&formula, 3, |v| v[2] == (v[0] != v[1]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_table_size_panics() {
+        from_table(&[Var::new(0), Var::new(1)], &[true, false]);
+    }
+}
@@ -0,0 +1,379 @@
+//! Instance features
+//!
+//! Algorithm selection and per-family configuration presets need a numeric
+//! description of an instance. [`features`] computes SATzilla-style
+//! features in a few groups:
+//!
+//! - **Size**: variables, clauses and their ratio
+//! - **Graphs**: degree statistics of the variable-clause graph (each
+//!   variable joined to the clauses it occurs in) and of the variable graph
+//!   (variables joined when they share a clause)
+//! - **Balance**: how evenly positive and negative literals are spread over
+//!   each clause and each variable
+//! - **Clause kinds**: the fractions of unit, binary, ternary and Horn
+//!   clauses, and the occurrences of each variable in Horn clauses
+//! - **Probing**: the fraction of variables fixed by unit propagation after
+//!   random decisions at several depths, and the statistics of a short DPLL
+//!   run with a propagation budget
+//!
+//! Statistics over variables count only the variables that occur in the
+//! formula. Every feature is deterministic: the probes use a fixed seed.
+//! [`Features::to_pairs`] flattens the features into named numbers, e.g.
+//! for a CSV file or a learning library.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::features::features;
+//!
+//! let (formula, num_vars, _) = putnam::gen::families::pigeonhole(4);
+//! let features = features(&formula, num_vars);
+//!
+//! assert_eq!((features.num_vars, features.num_clauses), (20, 45));
+//! assert_eq!(features.binary_fraction, 40.0 / 45.0);
+//! assert_eq!(features.horn_fraction, 40.0 / 45.0);
+//! // Every variable occurs once positively and four times negatively
+//! assert_eq!(features.var_balance.min, features.var_balance.max);
+//! ```
+
+use crate::rng::Rng;
+use crate::solver::counters::Counters;
+use crate::solver::dpll::SolveResult;
+use crate::solver::Solver;
+use crate::types::*;
+
+/// Decisions made by the unit propagation probes, one probe per depth.
+pub const UP_PROBE_DEPTHS: [usize; 4] = [1, 4, 16, 64];
+
+/// Propagations granted to the DPLL probe.
+pub const SEARCH_PROBE_PROPAGATIONS: u64 = 10_000;
+
+/// Random runs averaged by each unit propagation probe.
+const UP_PROBE_RUNS: usize = 8;
+
+/// Seed of the probes' random decisions.
+const PROBE_SEED: u64 = 0x5A7F_EA70;
+
+/// Summary statistics of a list of values.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Distribution {
+    /// Arithmetic mean
+    pub mean: f64,
+    /// Coefficient of variation: standard deviation divided by the mean
+    /// (zero when the mean is zero)
+    pub variation: f64,
+    /// Smallest value
+    pub min: f64,
+    /// Largest value
+    pub max: f64,
+    /// Shannon entropy (in nats) of the frequencies of the distinct values
+    pub entropy: f64,
+}
+
+impl Distribution {
+    /// Summarizes `values`; all fields are zero for an empty list.
+    pub fn new(values: &[f64]) -> Self {
+        if values.is_empty() {
+            return Distribution::default();
+        }
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n;
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let mut entropy = 0.0;
+        for run in sorted.chunk_by(|a, b| a == b) {
+            let p = run.len() as f64 / n;
+            entropy -= p * p.ln();
+        }
+        Distribution {
+            mean,
+            variation: if mean == 0.0 { 0.0 } else { variance.sqrt() / mean },
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            entropy,
+        }
+    }
+}
+
+/// Features of a CNF instance; see the [module documentation](self).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Features {
+    /// Variables occurring in some clause
+    pub num_vars: usize,
+    /// Clauses
+    pub num_clauses: usize,
+    /// Clauses per occurring variable
+    pub clause_var_ratio: f64,
+    /// Occurrences of each variable (variable-clause graph)
+    pub var_degree: Distribution,
+    /// Length of each clause (variable-clause graph)
+    pub clause_degree: Distribution,
+    /// Number of other variables each variable shares a clause with
+    /// (variable graph)
+    pub var_graph_degree: Distribution,
+    /// Per clause, `|pos - neg| / len` over its literals (0 for balanced
+    /// clauses, 1 for pure ones)
+    pub clause_balance: Distribution,
+    /// Per variable, `|pos - neg| / occurrences` over its occurrences
+    pub var_balance: Distribution,
+    /// Fraction of unit clauses
+    pub unit_fraction: f64,
+    /// Fraction of binary clauses
+    pub binary_fraction: f64,
+    /// Fraction of ternary clauses
+    pub ternary_fraction: f64,
+    /// Fraction of Horn clauses (at most one positive literal)
+    pub horn_fraction: f64,
+    /// Occurrences of each variable in Horn clauses
+    pub horn_var_degree: Distribution,
+    /// Mean fraction of the variables assigned after the decisions of each
+    /// depth in [`UP_PROBE_DEPTHS`] and unit propagation (1 when
+    /// propagation alone refutes the formula)
+    pub up_probe: [f64; 4],
+    /// Whether the DPLL probe decided the formula within its budget
+    pub search_solved: bool,
+    /// Fraction of the search tree the DPLL probe covered (see
+    /// [`Solver::progress`])
+    pub search_progress: f64,
+    /// Decisions of the DPLL probe
+    pub search_decisions: u64,
+    /// Conflicts of the DPLL probe
+    pub search_conflicts: u64,
+}
+
+impl Features {
+    /// Returns the features as `(name, value)` pairs, in a fixed order.
+    ///
+    /// Distributions contribute `<name>_mean`, `<name>_variation`,
+    /// `<name>_min`, `<name>_max` and `<name>_entropy`; flags are 0 or 1.
+    pub fn to_pairs(&self) -> Vec<(String, f64)> {
+        let mut pairs = vec![
+            ("num_vars".to_string(), self.num_vars as f64),
+            ("num_clauses".to_string(), self.num_clauses as f64),
+            ("clause_var_ratio".to_string(), self.clause_var_ratio),
+        ];
+        let distributions = [
+            ("var_degree", self.var_degree),
+            ("clause_degree", self.clause_degree),
+            ("var_graph_degree", self.var_graph_degree),
+            ("clause_balance", self.clause_balance),
+            ("var_balance", self.var_balance),
+            ("horn_var_degree", self.horn_var_degree),
+        ];
+        for (name, d) in distributions {
+            for (statistic, value) in [("mean", d.mean), ("variation", d.variation), ("min", d.min), ("max", d.max), ("entropy", d.entropy)] {
+                pairs.push((format!("{}_{}", name, statistic), value));
+            }
+        }
+        for (name, value) in [
+            ("unit_fraction", self.unit_fraction),
+            ("binary_fraction", self.binary_fraction),
+            ("ternary_fraction", self.ternary_fraction),
+            ("horn_fraction", self.horn_fraction),
+        ] {
+            pairs.push((name.to_string(), value));
+        }
+        for (depth, value) in UP_PROBE_DEPTHS.iter().zip(self.up_probe) {
+            pairs.push((format!("up_probe_{}", depth), value));
+        }
+        pairs.push(("search_solved".to_string(), self.search_solved as u8 as f64));
+        pairs.push(("search_progress".to_string(), self.search_progress));
+        pairs.push(("search_decisions".to_string(), self.search_decisions as f64));
+        pairs.push(("search_conflicts".to_string(), self.search_conflicts as f64));
+        pairs
+    }
+}
+
+/// Computes the features of `formula`.
+///
+/// # Arguments
+///
+/// * `formula` - The clauses
+/// * `num_vars` - The number of variables (raised if the formula uses more)
+///
+/// # Returns
+///
+/// The features; see [`Features`] for their definitions
+pub fn features(formula: &Formula, num_vars: usize) -> Features {
+    let num_vars = formula.iter().flatten().map(|l| l.var.index() + 1).max().unwrap_or(0).max(num_vars);
+    let mut positive = vec![0usize; num_vars];
+    let mut negative = vec![0usize; num_vars];
+    let mut horn = vec![0usize; num_vars];
+    let mut clause_lengths = Vec::with_capacity(formula.len());
+    let mut clause_balance = Vec::with_capacity(formula.len());
+    let mut horn_clauses = 0;
+    for clause in formula {
+        let pos = clause.iter().filter(|l| !l.neg).count();
+        for lit in clause {
+            if lit.neg {
+                negative[lit.var.index()] += 1;
+            } else {
+                positive[lit.var.index()] += 1;
+            }
+        }
+        if pos <= 1 {
+            horn_clauses += 1;
+            for lit in clause {
+                horn[lit.var.index()] += 1;
+            }
+        }
+        clause_lengths.push(clause.len() as f64);
+        if !clause.is_empty() {
+            clause_balance.push((2.0 * pos as f64 - clause.len() as f64).abs() / clause.len() as f64);
+        }
+    }
+    let occurring: Vec<usize> = (0..num_vars).filter(|&var| positive[var] + negative[var] > 0).collect();
+    let per_var = |f: &dyn Fn(usize) -> f64| -> Vec<f64> { occurring.iter().map(|&var| f(var)).collect() };
+    let fraction = |count: usize| if formula.is_empty() { 0.0 } else { count as f64 / formula.len() as f64 };
+    let of_length = |len: usize| formula.iter().filter(|clause| clause.len() == len).count();
+    let (solved, progress, decisions, conflicts) = search_probe(formula, num_vars);
+
+    Features {
+        num_vars: occurring.len(),
+        num_clauses: formula.len(),
+        clause_var_ratio: if occurring.is_empty() { 0.0 } else { formula.len() as f64 / occurring.len() as f64 },
+        var_degree: Distribution::new(&per_var(&|var| (positive[var] + negative[var]) as f64)),
+        clause_degree: Distribution::new(&clause_lengths),
+        var_graph_degree: Distribution::new(&var_graph_degrees(formula, num_vars, &occurring)),
+        clause_balance: Distribution::new(&clause_balance),
+        var_balance: Distribution::new(&per_var(&|var| {
+            (positive[var] as f64 - negative[var] as f64).abs() / (positive[var] + negative[var]) as f64
+        })),
+        unit_fraction: fraction(of_length(1)),
+        binary_fraction: fraction(of_length(2)),
+        ternary_fraction: fraction(of_length(3)),
+        horn_fraction: fraction(horn_clauses),
+        horn_var_degree: Distribution::new(&per_var(&|var| horn[var] as f64)),
+        up_probe: up_probe(formula, num_vars, &occurring),
+        search_solved: solved,
+        search_progress: progress,
+        search_decisions: decisions,
+        search_conflicts: conflicts,
+    }
+}
+
+/// Returns the number of distinct other variables each occurring variable
+/// shares a clause with.
+fn var_graph_degrees(formula: &Formula, num_vars: usize, occurring: &[usize]) -> Vec<f64> {
+    let mut clauses_of = vec![Vec::new(); num_vars];
+    for (index, clause) in formula.iter().enumerate() {
+        for lit in clause {
+            clauses_of[lit.var.index()].push(index);
+        }
+    }
+    // Neighbors are counted once each, by stamping them with the variable
+    let mut stamp = vec![usize::MAX; num_vars];
+    occurring
+        .iter()
+        .map(|&var| {
+            stamp[var] = var;
+            let mut degree = 0;
+            for &index in &clauses_of[var] {
+                for lit in &formula[index] {
+                    if stamp[lit.var.index()] != var {
+                        stamp[lit.var.index()] = var;
+                        degree += 1;
+                    }
+                }
+            }
+            degree as f64
+        })
+        .collect()
+}
+
+/// Runs the unit propagation probes: for each depth, the mean fraction of
+/// the occurring variables assigned after that many random decisions (each
+/// followed by propagation, stopping early at a conflict).
+fn up_probe(formula: &Formula, num_vars: usize, occurring: &[usize]) -> [f64; 4] {
+    if formula.iter().any(Vec::is_empty) {
+        return [1.0; 4];
+    }
+    let mut counters = Counters::new(formula, num_vars);
+    let mut root = Model::new(num_vars);
+    if counters.propagate(formula, &mut root).is_err() {
+        return [1.0; 4];
+    }
+    let mark = counters.mark();
+    let mut rng = Rng::new(PROBE_SEED);
+    let assigned = |model: &Model| {
+        if occurring.is_empty() {
+            return 1.0;
+        }
+        occurring.iter().filter(|&&var| model.value(Var::new(var)) != Val::Undef).count() as f64 / occurring.len() as f64
+    };
+    UP_PROBE_DEPTHS.map(|depth| {
+        let mut total = 0.0;
+        for _ in 0..UP_PROBE_RUNS {
+            let mut model = root.clone();
+            counters.undo_to(mark);
+            for _ in 0..depth {
+                let open: Vec<usize> = occurring.iter().copied().filter(|&var| model.value(Var::new(var)) == Val::Undef).collect();
+                if open.is_empty() {
+                    break;
+                }
+                let var = Var::new(open[rng.below(open.len())]);
+                model.assign(var, if rng.coin() { Val::True } else { Val::False });
+                if counters.propagate(formula, &mut model).is_err() {
+                    break;
+                }
+            }
+            total += assigned(&model);
+        }
+        total / UP_PROBE_RUNS as f64
+    })
+}
+
+/// Runs DPLL for [`SEARCH_PROBE_PROPAGATIONS`] propagations and returns
+/// whether it finished, the covered fraction, decisions and conflicts.
+fn search_probe(formula: &Formula, num_vars: usize) -> (bool, f64, u64, u64) {
+    let mut solver = Solver::from_formula(formula.clone(), num_vars);
+    solver.set_propagation_budget(Some(SEARCH_PROBE_PROPAGATIONS));
+    let result = solver.solve_limited(&[]);
+    let stats = solver.stats();
+    let solved = matches!(result, Some(SolveResult::Sat(_) | SolveResult::Unsat));
+    (solved, solver.progress(), stats.decisions, stats.conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distributions() {
+        let d = Distribution::new(&[1.0, 1.0, 2.0, 4.0]);
+        assert_eq!((d.mean, d.min, d.max), (2.0, 1.0, 4.0));
+        assert!((d.variation - 1.5f64.sqrt() / 2.0).abs() < 1e-12);
+        assert!((d.entropy - 1.5 * 2f64.ln()).abs() < 1e-12);
+        assert_eq!(Distribution::new(&[]), Distribution::default());
+    }
+
+    #[test]
+    fn features_of_random_formulas() {
+        let (formula, num_vars) = crate::gen::random_ksat(30, 128, 3, 11);
+        let features = features(&formula, num_vars);
+        assert_eq!(features.clause_degree.min, 3.0);
+        assert_eq!(features.ternary_fraction, 1.0);
+        assert_eq!(features.var_degree.mean * features.num_vars as f64, 3.0 * 128.0);
+        assert!(features.up_probe.iter().all(|&p| (0.0..=1.0).contains(&p)));
+        assert!(features.search_solved);
+
+        // Equal inputs, equal features; every pair is finite
+        assert_eq!(super::features(&formula, num_vars), features);
+        let pairs = features.to_pairs();
+        assert!(pairs.iter().all(|(_, value)| value.is_finite()));
+        assert_eq!(pairs.len(), 3 + 6 * 5 + 4 + 4 + 4);
+    }
+
+    #[test]
+    fn degenerate_formulas() {
+        let empty = features(&Vec::new(), 3);
+        assert_eq!((empty.num_vars, empty.clause_var_ratio), (0, 0.0));
+        assert_eq!(empty.up_probe, [1.0; 4]);
+
+        let x = Lit::positive(Var::new(0));
+        let contradiction = features(&vec![vec![x], vec![!x]], 1);
+        assert_eq!(contradiction.up_probe, [1.0; 4]);
+        assert!(contradiction.search_solved);
+    }
+}
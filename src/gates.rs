@@ -0,0 +1,354 @@
+//! Gate extraction from CNF
+//!
+//! Most application instances are produced by Tseitin-style encodings of
+//! circuits, and the gate definitions survive in the CNF as characteristic
+//! clause patterns. This module recovers them:
+//!
+//! - **AND** `x ↔ l1 ∧ … ∧ lk`: the clauses `(¬x ∨ li)` for every `i` plus
+//!   `(x ∨ ¬l1 ∨ … ∨ ¬lk)`
+//! - **OR** `x ↔ l1 ∨ … ∨ lk`: the same pattern with `x` negated
+//! - **XOR** `x ↔ a ⊕ b`: the four ternary clauses over `{x, a, b}` that
+//!   forbid the assignments of the wrong parity
+//! - **ITE** `x ↔ (c ? t : e)`: the four ternary clauses `(¬c ∨ ¬t ∨ x)`,
+//!   `(¬c ∨ t ∨ ¬x)`, `(c ∨ ¬e ∨ x)`, `(c ∨ e ∨ ¬x)`
+//!
+//! Every variable is defined by at most one gate and every clause belongs
+//! to at most one gate. Each recovered gate is implied by the formula, but
+//! the gates need not form an acyclic circuit.
+//!
+//! # Example
+//!
+//! ```
+//! use putnam::gates::{extract_gates, GateKind};
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! // x2 ↔ x0 ∧ x1
+//! let formula = vec![vec![!x(2), x(0)], vec![!x(2), x(1)], vec![x(2), !x(0), !x(1)]];
+//!
+//! let gates = extract_gates(&formula);
+//! assert_eq!(gates.len(), 1);
+//! assert_eq!(gates[0].kind, GateKind::And);
+//! assert_eq!(gates[0].output, x(2));
+//! assert_eq!(gates[0].inputs, vec![x(0), x(1)]);
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use crate::types::*;
+
+/// The function computed by a [`Gate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GateKind {
+    /// Conjunction of all inputs
+    And,
+    /// Disjunction of all inputs
+    Or,
+    /// Exclusive or of the two inputs
+    Xor,
+    /// If-then-else; inputs are `[condition, then, else]`
+    Ite,
+}
+
+/// A gate definition `output ↔ kind(inputs)` recovered from the formula.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gate {
+    /// The function of the gate
+    pub kind: GateKind,
+    /// The defined literal; its variable is the gate's output variable
+    pub output: Lit,
+    /// The gate inputs (see [`GateKind`] for their order)
+    pub inputs: Vec<Lit>,
+    /// Indices of the clauses of the formula that encode this gate
+    pub clauses: Vec<usize>,
+}
+
+impl Gate {
+    /// Evaluates the gate function on the given input values.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value of each input literal (same order as [`inputs`](Gate::inputs))
+    ///
+    /// # Returns
+    ///
+    /// The value the output literal must take.
+    pub fn eval(&self, value: &[bool]) -> bool {
+        match self.kind {
+            GateKind::And => value.iter().all(|&v| v),
+            GateKind::Or => value.iter().any(|&v| v),
+            GateKind::Xor => value[0] != value[1],
+            GateKind::Ite => {
+                if value[0] {
+                    value[1]
+                } else {
+                    value[2]
+                }
+            }
+        }
+    }
+}
+
+/// Clause lookup used while matching gate patterns.
+struct Index {
+    /// Normalized (sorted, deduplicated) clause → index in the formula
+    clauses: HashMap<Vec<Lit>, usize>,
+    /// Ternary clauses containing each literal
+    ternary: HashMap<Lit, Vec<usize>>,
+    normalized: Vec<Option<Vec<Lit>>>,
+    /// Clauses already attributed to a gate
+    claimed: Vec<bool>,
+    /// Variables already defined by a gate
+    defined: HashSet<Var>,
+}
+
+impl Index {
+    fn new(formula: &Formula) -> Self {
+        let mut clauses = HashMap::new();
+        let mut ternary: HashMap<Lit, Vec<usize>> = HashMap::new();
+        let normalized: Vec<Option<Vec<Lit>>> = formula
+            .iter()
+            .map(|clause| {
+                let mut c = clause.clone();
+                c.sort();
+                c.dedup();
+                // Tautologies cannot be part of a gate definition
+                if c.windows(2).any(|w| w[0].var == w[1].var) {
+                    None
+                } else {
+                    Some(c)
+                }
+            })
+            .collect();
+        for (i, c) in normalized.iter().enumerate() {
+            if let Some(c) = c {
+                clauses.entry(c.clone()).or_insert(i);
+                if c.len() == 3 {
+                    for &lit in c {
+                        ternary.entry(lit).or_default().push(i);
+                    }
+                }
+            }
+        }
+        Index { clauses, ternary, claimed: vec![false; formula.len()], normalized, defined: HashSet::new() }
+    }
+
+    /// Finds an unclaimed clause consisting of exactly `lits`.
+    fn find(&self, lits: &[Lit]) -> Option<usize> {
+        let mut key = lits.to_vec();
+        key.sort();
+        key.dedup();
+        self.clauses.get(&key).copied().filter(|&i| !self.claimed[i])
+    }
+
+    /// Records a gate if its output is still undefined.
+    fn define(&mut self, gates: &mut Vec<Gate>, gate: Gate) -> bool {
+        if !self.defined.insert(gate.output.var) {
+            return false;
+        }
+        for &i in &gate.clauses {
+            self.claimed[i] = true;
+        }
+        gates.push(gate);
+        true
+    }
+}
+
+/// Detects AND, OR, XOR and ITE gate definitions in a formula.
+///
+/// XOR patterns are matched first, then AND/OR, then ITE, since an XOR can
+/// also be read as an ITE. For XOR gates the output is the variable with the
+/// highest index (auxiliary variables usually come last). AND and OR gates
+/// always have a positive output literal.
+///
+/// # Arguments
+///
+/// * `formula` - The CNF formula to analyze
+///
+/// # Returns
+///
+/// The recovered gates, in the order they were found.
+pub fn extract_gates(formula: &Formula) -> Vec<Gate> {
+    let mut index = Index::new(formula);
+    let mut gates = Vec::new();
+    extract_xor(&mut index, &mut gates);
+    extract_and_or(&mut index, &mut gates);
+    extract_ite(&mut index, &mut gates);
+    gates
+}
+
+fn extract_xor(index: &mut Index, gates: &mut Vec<Gate>) {
+    let mut seen = HashSet::new();
+    for i in 0..index.normalized.len() {
+        let c = match &index.normalized[i] {
+            Some(c) if c.len() == 3 && !index.claimed[i] => c.clone(),
+            _ => continue,
+        };
+        let vars = [c[0].var, c[1].var, c[2].var];
+        let parity = c.iter().filter(|l| l.neg).count() % 2;
+        if !seen.insert((vars, parity)) {
+            continue;
+        }
+
+        // The four sign patterns with the same parity
+        let clauses: Option<Vec<usize>> = (0..8u8)
+            .filter(|signs| signs.count_ones() as usize % 2 == parity)
+            .map(|signs| {
+                let lits: Vec<Lit> = (0..3).map(|k| Lit { var: vars[k], neg: (signs >> k) & 1 == 1 }).collect();
+                index.find(&lits)
+            })
+            .collect();
+        if let Some(clauses) = clauses {
+            // Odd-negation clauses forbid odd assignments: x0 ⊕ x1 ⊕ x2 = 0
+            let output = Lit { var: vars[2], neg: parity == 0 };
+            let inputs = vec![Lit { var: vars[0], neg: false }, Lit { var: vars[1], neg: false }];
+            index.define(gates, Gate { kind: GateKind::Xor, output, inputs, clauses });
+        }
+    }
+}
+
+fn extract_and_or(index: &mut Index, gates: &mut Vec<Gate>) {
+    for i in 0..index.normalized.len() {
+        let c = match &index.normalized[i] {
+            Some(c) if c.len() >= 3 && !index.claimed[i] => c.clone(),
+            _ => continue,
+        };
+        // (x ∨ ¬l1 ∨ … ∨ ¬lk) with (¬x ∨ li) for every i
+        for &x in &c {
+            if index.defined.contains(&x.var) {
+                continue;
+            }
+            let others: Vec<Lit> = c.iter().copied().filter(|&l| l != x).collect();
+            let binaries: Option<Vec<usize>> = others.iter().map(|&y| index.find(&[!x, !y])).collect();
+            if let Some(mut clauses) = binaries {
+                clauses.insert(0, i);
+                let gate = if x.neg {
+                    Gate { kind: GateKind::Or, output: !x, inputs: others, clauses }
+                } else {
+                    let inputs = others.iter().map(|&y| !y).collect();
+                    Gate { kind: GateKind::And, output: x, inputs, clauses }
+                };
+                index.define(gates, gate);
+                break;
+            }
+        }
+    }
+}
+
+fn extract_ite(index: &mut Index, gates: &mut Vec<Gate>) {
+    const ROLES: [[usize; 3]; 6] = [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+    for i in 0..index.normalized.len() {
+        let c = match &index.normalized[i] {
+            Some(c) if c.len() == 3 && !index.claimed[i] => c.clone(),
+            _ => continue,
+        };
+        // Read the clause as (¬cond ∨ ¬then ∨ out) under every role assignment
+        for [pc, pt, po] in ROLES {
+            let (cond, then, out) = (!c[pc], !c[pt], c[po]);
+            if index.defined.contains(&out.var) {
+                continue;
+            }
+            let Some(second) = index.find(&[!cond, then, !out]) else { continue };
+            let candidates = index.ternary.get(&cond).cloned().unwrap_or_default();
+            let found = candidates.into_iter().find_map(|j| {
+                let d = index.normalized[j].as_ref()?;
+                if index.claimed[j] || !d.contains(&!out) {
+                    return None;
+                }
+                let els = *d.iter().find(|&&l| l != cond && l != !out)?;
+                let vars = [cond.var, then.var, els.var, out.var];
+                if (1..4).any(|a| vars[..a].contains(&vars[a])) {
+                    return None;
+                }
+                let fourth = index.find(&[cond, !els, out])?;
+                Some((els, j, fourth))
+            });
+            if let Some((els, third, fourth)) = found {
+                // Normalize to a positive output: ¬x ↔ ite(c, ¬t, ¬e)
+                let (output, then, els) = if out.neg { (!out, !then, !els) } else { (out, then, els) };
+                let gate = Gate { kind: GateKind::Ite, output, inputs: vec![cond, then, els], clauses: vec![i, second, third, fourth] };
+                index.define(gates, gate);
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aig::Aig;
+    use crate::encode::truth_table::define_fn;
+
+    fn lit(var: usize) -> Lit {
+        Lit::positive(Var::new(var))
+    }
+
+    /// Checks that every gate holds in every model of the formula.
+    fn assert_gates_implied(formula: &Formula, num_vars: usize, gates: &[Gate]) {
+        for bits in 0..1u32 << num_vars {
+            let value = |l: Lit| ((bits >> l.var.index()) & 1 == 1) != l.neg;
+            if !formula.iter().all(|c| c.iter().any(|&l| value(l))) {
+                continue;
+            }
+            for gate in gates {
+                let inputs: Vec<bool> = gate.inputs.iter().map(|&l| value(l)).collect();
+                assert_eq!(value(gate.output), gate.eval(&inputs), "{:?}", gate);
+            }
+        }
+    }
+
+    #[test]
+    fn aig_tseitin_gates_are_recovered() {
+        let mut aig = Aig::default();
+        let a = aig.add_input();
+        let b = aig.add_input();
+        let c = aig.add_input();
+        let ab = aig.add_and(a, b);
+        let out = aig.add_and(ab, c ^ 1);
+        aig.outputs.push(out);
+
+        let (formula, num_vars) = aig.to_cnf();
+        let gates = extract_gates(&formula);
+        assert_eq!(gates.len(), 2);
+        assert!(gates.iter().all(|g| g.kind == GateKind::And));
+        assert!(gates.iter().any(|g| g.output.var == Var::new(out >> 1) && g.inputs.contains(&Lit::negative(Var::new(3)))));
+        assert_gates_implied(&formula, num_vars, &gates);
+    }
+
+    #[test]
+    fn or_and_xor_patterns() {
+        // x3 ↔ x0 ∨ x1 ∨ x2, x5 ↔ ¬(x3 ⊕ x4)
+        let mut formula = vec![vec![!lit(3), lit(0), lit(1), lit(2)]];
+        formula.extend((0..3).map(|i| vec![lit(3), !lit(i)]));
+        formula.extend(define_fn(Var::new(5), &[Var::new(3), Var::new(4)], |v| v[0] == v[1]));
+
+        let gates = extract_gates(&formula);
+        assert_eq!(gates.len(), 2);
+        let xor = gates.iter().find(|g| g.kind == GateKind::Xor).unwrap();
+        assert_eq!(xor.output, Lit::negative(Var::new(5)));
+        assert_eq!(xor.clauses.len(), 4);
+        let or = gates.iter().find(|g| g.kind == GateKind::Or).unwrap();
+        assert_eq!(or.output, lit(3));
+        assert_eq!(or.inputs, vec![lit(0), lit(1), lit(2)]);
+        assert_gates_implied(&formula, 6, &gates);
+    }
+
+    #[test]
+    fn ite_pattern() {
+        // x3 ↔ (x0 ? x1 : ¬x2)
+        let (c, t, e, x) = (lit(0), lit(1), !lit(2), lit(3));
+        let formula = vec![vec![!c, !t, x], vec![!c, t, !x], vec![c, !e, x], vec![c, e, !x]];
+        let gates = extract_gates(&formula);
+        assert_eq!(gates.len(), 1);
+        assert_eq!(gates[0].kind, GateKind::Ite);
+        assert_eq!(gates[0].output, x);
+        assert_gates_implied(&formula, 4, &gates);
+    }
+
+    #[test]
+    fn unstructured_formula_has_no_gates() {
+        let formula = vec![vec![lit(0), lit(1), lit(2)], vec![!lit(0), lit(1)], vec![lit(2), !lit(1), lit(0)]];
+        assert!(extract_gates(&formula).is_empty());
+    }
+}
@@ -0,0 +1,355 @@
+//! Classic hard-instance families
+//!
+//! This module generates structured benchmark families that are well known
+//! from the SAT literature:
+//!
+//! - [`pigeonhole`]: n+1 pigeons into n holes (UNSAT, exponential for resolution)
+//! - [`tseitin`]: parity constraints over the edges of a random regular graph
+//!   with odd total charge (UNSAT, hard for resolution on expanders)
+//! - [`coloring`]: k-coloring of a random graph (SAT or UNSAT)
+//!
+//! Every generator returns the formula, its variable count, and a
+//! [`FamilyInfo`] describing the instance.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::gen::families::pigeonhole;
+//! use putnam::solver::dpll::SolveResult;
+//!
+//! let (formula, num_vars, info) = pigeonhole(3);
+//! assert_eq!(info.name, "php-4-3");
+//! assert_eq!(info.expected, Some(false));
+//! assert_eq!(putnam::solve(&formula, num_vars), SolveResult::Unsat);
+//! ```
+
+use crate::rng::Rng;
+use crate::types::*;
+
+/// The family an instance belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Family {
+    /// Pigeonhole principle
+    Pigeonhole,
+    /// Tseitin parity formula over a graph
+    Tseitin,
+    /// Graph k-coloring
+    Coloring,
+}
+
+/// Metadata describing a generated instance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FamilyInfo {
+    /// The family the instance belongs to
+    pub family: Family,
+    /// Short conventional name, e.g. `php-4-3`
+    pub name: String,
+    /// Known satisfiability: `Some(true)` for SAT, `Some(false)` for UNSAT,
+    /// `None` when it depends on the random choices
+    pub expected: Option<bool>,
+}
+
+/// Generates the pigeonhole formula PHP(n+1, n).
+///
+/// Variable `p * n + h` means "pigeon `p` sits in hole `h`". The formula
+/// states that every pigeon sits in some hole and no hole holds two pigeons,
+/// which is unsatisfiable.
+///
+/// # Arguments
+///
+/// * `n` - Number of holes (there are `n + 1` pigeons)
+///
+/// # Returns
+///
+/// The formula, its `(n + 1) * n` variables, and its metadata
+pub fn pigeonhole(n: usize) -> (Formula, usize, FamilyInfo) {
+    let mut formula = Vec::new();
+    let num_vars = (n + 1) * n;
+    let var = |pigeon: usize, hole: usize| Var::new(pigeon * n + hole);
+
+    // Each pigeon must be in at least one hole
+    for pigeon in 0..=n {
+        formula.push((0..n).map(|hole| Lit { var: var(pigeon, hole), neg: false }).collect());
+    }
+
+    // No two pigeons in same hole
+    for hole in 0..n {
+        for p1 in 0..=n {
+            for p2 in (p1 + 1)..=n {
+                formula.push(vec![
+                    Lit { var: var(p1, hole), neg: true },
+                    Lit { var: var(p2, hole), neg: true },
+                ]);
+            }
+        }
+    }
+
+    let info = FamilyInfo {
+        family: Family::Pigeonhole,
+        name: format!("php-{}-{}", n + 1, n),
+        expected: Some(false),
+    };
+    (formula, num_vars, info)
+}
+
+/// Generates a Tseitin parity formula over a random `d`-regular graph.
+///
+/// Each edge of the graph becomes a variable, and each vertex requires the
+/// XOR of its incident edges to equal its charge. Vertex 0 has charge 1 and
+/// all other vertices charge 0; since the total charge is odd the formula is
+/// unsatisfiable. Random regular graphs are expanders with high probability,
+/// which makes these formulas exponentially hard for resolution.
+///
+/// # Arguments
+///
+/// * `n` - Number of vertices
+/// * `d` - Degree of every vertex (`n * d` must be even and `d < n`)
+/// * `seed` - Seed for the random graph
+///
+/// # Returns
+///
+/// The formula, its `n * d / 2` variables, and its metadata. Each vertex
+/// contributes `2^(d-1)` clauses.
+///
+/// # Panics
+///
+/// Panics if no `d`-regular graph on `n` vertices exists.
+pub fn tseitin(n: usize, d: usize, seed: u64) -> (Formula, usize, FamilyInfo) {
+    let edges = random_regular_graph(n, d, seed);
+    let mut charges = vec![false; n];
+    if n > 0 {
+        charges[0] = true;
+    }
+    let (formula, num_vars) = tseitin_formula(n, &edges, &charges);
+
+    let info = FamilyInfo {
+        family: Family::Tseitin,
+        name: format!("tseitin-{}-{}-{}", n, d, seed),
+        expected: Some(n == 0),
+    };
+    (formula, num_vars, info)
+}
+
+/// Encodes the Tseitin parity constraints of an arbitrary graph.
+///
+/// Edge `i` of `edges` becomes variable `i`. For every vertex `v`, the XOR
+/// of its incident edge variables must equal `charges[v]`.
+///
+/// # Arguments
+///
+/// * `num_vertices` - Number of vertices
+/// * `edges` - Edge list (endpoints must be `< num_vertices`)
+/// * `charges` - Required parity at each vertex
+///
+/// # Returns
+///
+/// The formula and its variable count (`edges.len()`)
+pub fn tseitin_formula(num_vertices: usize, edges: &[(usize, usize)], charges: &[bool]) -> (Formula, usize) {
+    let mut incident = vec![Vec::new(); num_vertices];
+    for (i, &(u, v)) in edges.iter().enumerate() {
+        incident[u].push(Var::new(i));
+        incident[v].push(Var::new(i));
+    }
+
+    let mut formula = Vec::new();
+    for (vertex, vars) in incident.iter().enumerate() {
+        formula.extend(xor_clauses(vars, charges[vertex]));
+    }
+    (formula, edges.len())
+}
+
+/// Returns the CNF clauses forcing the XOR of `vars` to equal `parity`.
+///
+/// Each clause forbids one assignment with the wrong parity, so `2^(len-1)`
+/// clauses are produced.
+fn xor_clauses(vars: &[Var], parity: bool) -> Formula {
+    let mut clauses = Vec::new();
+    for mask in 0u64..(1u64 << vars.len()) {
+        // `mask` is a forbidden assignment when its parity is wrong
+        if (mask.count_ones() % 2 == 1) != parity {
+            clauses.push(
+                vars.iter()
+                    .enumerate()
+                    .map(|(i, &var)| Lit { var, neg: (mask >> i) & 1 == 1 })
+                    .collect(),
+            );
+        }
+    }
+    clauses
+}
+
+/// Generates a k-coloring formula for a random graph with `n` vertices and
+/// `m` edges.
+///
+/// See [`coloring_formula`] for the encoding.
+///
+/// # Arguments
+///
+/// * `n` - Number of vertices
+/// * `m` - Number of edges (at most `n * (n - 1) / 2`)
+/// * `k` - Number of colors
+/// * `seed` - Seed for the random graph
+///
+/// # Returns
+///
+/// The formula, its `n * k` variables, and its metadata
+pub fn coloring(n: usize, m: usize, k: usize, seed: u64) -> (Formula, usize, FamilyInfo) {
+    let edges = random_graph(n, m, seed);
+    let (formula, num_vars) = coloring_formula(n, &edges, k);
+
+    let info = FamilyInfo {
+        family: Family::Coloring,
+        name: format!("color-{}-{}-{}-{}", n, m, k, seed),
+        expected: None,
+    };
+    (formula, num_vars, info)
+}
+
+/// Encodes graph k-coloring as CNF.
+///
+/// Variable `v * k + c` means "vertex `v` has color `c`". The formula
+/// requires every vertex to have exactly one color and adjacent vertices
+/// to have different colors.
+///
+/// # Arguments
+///
+/// * `num_vertices` - Number of vertices
+/// * `edges` - Edge list (endpoints must be `< num_vertices`)
+/// * `k` - Number of colors
+///
+/// # Returns
+///
+/// The formula and its `num_vertices * k` variables
+pub fn coloring_formula(num_vertices: usize, edges: &[(usize, usize)], k: usize) -> (Formula, usize) {
+    let var = |vertex: usize, color: usize| Var::new(vertex * k + color);
+    let mut formula = Vec::new();
+
+    for v in 0..num_vertices {
+        // At least one color
+        formula.push((0..k).map(|c| Lit { var: var(v, c), neg: false }).collect());
+        // At most one color
+        for c1 in 0..k {
+            for c2 in (c1 + 1)..k {
+                formula.push(vec![Lit { var: var(v, c1), neg: true }, Lit { var: var(v, c2), neg: true }]);
+            }
+        }
+    }
+
+    for &(u, v) in edges {
+        for c in 0..k {
+            formula.push(vec![Lit { var: var(u, c), neg: true }, Lit { var: var(v, c), neg: true }]);
+        }
+    }
+
+    (formula, num_vertices * k)
+}
+
+/// Generates a uniformly random simple graph with `n` vertices and `m` edges.
+///
+/// # Panics
+///
+/// Panics if `m` exceeds the number of possible edges `n * (n - 1) / 2`.
+pub fn random_graph(n: usize, m: usize, seed: u64) -> Vec<(usize, usize)> {
+    assert!(m <= n * n.saturating_sub(1) / 2, "a simple graph on {} vertices has fewer than {} edges", n, m);
+
+    let mut rng = Rng::new(seed);
+    let mut edges = Vec::with_capacity(m);
+    while edges.len() < m {
+        let u = rng.below(n);
+        let v = rng.below(n);
+        let edge = (u.min(v), u.max(v));
+        if u != v && !edges.contains(&edge) {
+            edges.push(edge);
+        }
+    }
+    edges
+}
+
+/// Generates a random simple `d`-regular graph on `n` vertices.
+///
+/// Uses the configuration model: vertex "stubs" are paired at random and
+/// pairings containing self-loops or parallel edges are rejected.
+///
+/// # Panics
+///
+/// Panics if `n * d` is odd or `d >= n` (with `n > 0`), since no such graph exists.
+pub fn random_regular_graph(n: usize, d: usize, seed: u64) -> Vec<(usize, usize)> {
+    assert!((n * d).is_multiple_of(2), "n * d must be even for a {}-regular graph on {} vertices", d, n);
+    assert!(n == 0 || d < n, "a simple {}-regular graph needs more than {} vertices", d, n);
+
+    let mut rng = Rng::new(seed);
+    'retry: loop {
+        let mut stubs: Vec<usize> = (0..n).flat_map(|v| std::iter::repeat_n(v, d)).collect();
+        let mut edges = Vec::with_capacity(n * d / 2);
+        while !stubs.is_empty() {
+            let a = stubs.swap_remove(rng.below(stubs.len()));
+            let b = stubs.swap_remove(rng.below(stubs.len()));
+            let edge = (a.min(b), a.max(b));
+            if a == b || edges.contains(&edge) {
+                continue 'retry;
+            }
+            edges.push(edge);
+        }
+        return edges;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{brute, dpll::SolveResult};
+
+    #[test]
+    fn pigeonhole_is_unsat() {
+        for n in 1..=3 {
+            let (formula, num_vars, info) = pigeonhole(n);
+            assert_eq!(num_vars, (n + 1) * n);
+            assert_eq!(info.family, Family::Pigeonhole);
+            assert_eq!(crate::solve(&formula, num_vars), SolveResult::Unsat);
+        }
+    }
+
+    #[test]
+    fn regular_graph_is_simple_and_regular() {
+        let edges = random_regular_graph(10, 3, 4);
+        assert_eq!(edges.len(), 15);
+
+        let mut degree = [0; 10];
+        for (i, &(u, v)) in edges.iter().enumerate() {
+            assert!(u < v);
+            assert!(!edges[i + 1..].contains(&(u, v)));
+            degree[u] += 1;
+            degree[v] += 1;
+        }
+        assert!(degree.iter().all(|&d| d == 3));
+    }
+
+    #[test]
+    fn tseitin_parity() {
+        let (formula, num_vars, info) = tseitin(6, 3, 1);
+        assert_eq!(num_vars, 9);
+        assert_eq!(formula.len(), 6 * 4);
+        assert_eq!(info.expected, Some(false));
+        assert_eq!(brute::solve(&formula, num_vars), SolveResult::Unsat);
+
+        // Even total charge on a connected graph (a triangle) is satisfiable
+        let edges = [(0, 1), (1, 2), (0, 2)];
+        let (formula, num_vars) = tseitin_formula(3, &edges, &[true, true, false]);
+        assert!(matches!(brute::solve(&formula, num_vars), SolveResult::Sat(_)));
+    }
+
+    #[test]
+    fn coloring_triangle() {
+        let triangle = [(0, 1), (1, 2), (0, 2)];
+        let (formula, num_vars) = coloring_formula(3, &triangle, 2);
+        assert_eq!(crate::solve(&formula, num_vars), SolveResult::Unsat);
+
+        let (formula, num_vars) = coloring_formula(3, &triangle, 3);
+        assert!(matches!(crate::solve(&formula, num_vars), SolveResult::Sat(_)));
+
+        let (formula, num_vars, info) = coloring(8, 10, 3, 2);
+        assert_eq!(num_vars, 24);
+        assert_eq!(info.name, "color-8-10-3-2");
+        assert!(!formula.is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+//! Instance generation
+//!
+//! This module generates random k-SAT instances in the uniform fixed-length
+//! clause model: every clause contains exactly `k` literals over distinct
+//! variables, each chosen uniformly at random with a random polarity.
+//! Classic structured families (pigeonhole, Tseitin, graph coloring) live
+//! in the [`families`] submodule.
+//!
+//! Random k-SAT instances are hardest around the *satisfiability phase
+//! transition*, the clause/variable ratio at which roughly half of the
+//! instances are satisfiable (≈ 4.27 for 3-SAT). [`phase_transition_ratio`]
+//! and [`threshold_clauses`] make it easy to sample right at that point.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::gen::{random_ksat, threshold_clauses};
+//!
+//! // 3-SAT with 20 variables at the phase transition
+//! let clauses = threshold_clauses(20, 3);
+//! let (formula, num_vars) = random_ksat(20, clauses, 3, 42);
+//!
+//! assert_eq!(num_vars, 20);
+//! assert_eq!(formula.len(), 85);
+//! assert!(formula.iter().all(|c| c.len() == 3));
+//! ```
+
+use crate::rng::Rng;
+use crate::types::*;
+
+pub mod families;
+
+/// Generates a uniform random k-SAT instance.
+///
+/// # Arguments
+///
+/// * `num_vars` - Number of variables
+/// * `num_clauses` - Number of clauses to generate
+/// * `k` - Number of literals per clause (over distinct variables)
+/// * `seed` - Seed for the pseudo-random generator
+///
+/// # Returns
+///
+/// A tuple containing:
+/// * `Formula` - The generated formula
+/// * `usize` - The number of variables (`num_vars`)
+///
+/// Equal arguments always produce the same instance on every platform.
+///
+/// # Panics
+///
+/// Panics if `k > num_vars`, since a clause cannot contain `k` distinct variables.
+pub fn random_ksat(num_vars: usize, num_clauses: usize, k: usize, seed: u64) -> (Formula, usize) {
+    assert!(k <= num_vars, "cannot draw {} distinct variables out of {}", k, num_vars);
+
+    let mut rng = Rng::new(seed);
+    let mut formula = Vec::with_capacity(num_clauses);
+    for _ in 0..num_clauses {
+        let mut clause: Clause = Vec::with_capacity(k);
+        while clause.len() < k {
+            let var = Var::new(rng.below(num_vars));
+            if clause.iter().all(|l| l.var != var) {
+                clause.push(Lit { var, neg: rng.coin() });
+            }
+        }
+        formula.push(clause);
+    }
+    (formula, num_vars)
+}
+
+/// Returns the empirical clause/variable ratio of the k-SAT phase transition.
+///
+/// For `k <= 7` experimentally measured values are used; for larger `k` the
+/// asymptotic estimate `2^k ln 2 - (1 + ln 2) / 2` is returned.
+///
+/// # Examples
+///
+/// ```
+/// use putnam::gen::phase_transition_ratio;
+///
+/// assert!((phase_transition_ratio(3) - 4.267).abs() < 1e-9);
+/// ```
+pub fn phase_transition_ratio(k: usize) -> f64 {
+    match k {
+        0 => 0.0,
+        1 => 0.5,
+        2 => 1.0,
+        3 => 4.267,
+        4 => 9.931,
+        5 => 21.117,
+        6 => 43.37,
+        7 => 87.79,
+        _ => {
+            let ln2 = std::f64::consts::LN_2;
+            2f64.powi(k as i32) * ln2 - (1.0 + ln2) / 2.0
+        }
+    }
+}
+
+/// Returns the number of clauses placing `num_vars` variables at the k-SAT
+/// phase transition, rounded to the nearest integer.
+pub fn threshold_clauses(num_vars: usize, k: usize) -> usize {
+    (num_vars as f64 * phase_transition_ratio(k)).round() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll::SolveResult;
+
+    #[test]
+    fn clauses_have_distinct_variables() {
+        let (formula, num_vars) = random_ksat(10, 50, 4, 7);
+
+        assert_eq!(num_vars, 10);
+        assert_eq!(formula.len(), 50);
+        for clause in &formula {
+            assert_eq!(clause.len(), 4);
+            for (i, a) in clause.iter().enumerate() {
+                assert!(a.var < Var::new(10));
+                assert!(clause[i + 1..].iter().all(|b| b.var != a.var));
+            }
+        }
+    }
+
+    #[test]
+    fn generation_is_deterministic() {
+        assert_eq!(random_ksat(8, 30, 3, 1), random_ksat(8, 30, 3, 1));
+        assert_ne!(random_ksat(8, 30, 3, 1), random_ksat(8, 30, 3, 2));
+    }
+
+    #[test]
+    fn phase_transition_mixes_sat_and_unsat() {
+        // Far below the threshold nearly everything is SAT, far above nearly
+        // everything is UNSAT.
+        let count_sat = |clauses: usize| {
+            (0..20)
+                .filter(|&seed| {
+                    let (f, n) = random_ksat(12, clauses, 3, seed);
+                    matches!(crate::solve(&f, n), SolveResult::Sat(_))
+                })
+                .count()
+        };
+        assert!(count_sat(12) >= 18);
+        assert!(count_sat(120) <= 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn k_larger_than_num_vars_panics() {
+        random_ksat(2, 1, 3, 0);
+    }
+}
@@ -9,11 +9,42 @@
 //!
 //! The solver is organized into four main layers:
 //!
-//! - **Data Types** ([`types`]): Core data structures for variables, literals, clauses, and models
+//! - **Data Types** ([`types`]): Core data structures for variables, literals, clauses, and models,
+//!   with optional variable names ([`symbols`]), readable rendering ([`pretty`]) and
+//!   alternative clause stores ([`store`])
 //! - **Parser** ([`parser`]): DIMACS CNF format parsing and conversion
-//! - **Solver** ([`solver`]): DPLL algorithm implementation with unit propagation
+//! - **Solver** ([`solver`]): DPLL algorithm implementation with unit propagation, and
+//!   polynomial algorithms for 2-SAT, Horn and XOR formulas
 //! - **CLI** (bin/putnam): Command-line interface for file-based solving
 //!
+//! Random k-SAT instances and classic benchmark families can be generated
+//! with [`gen`], and [`encode`] translates application problems (such as
+//! graph coloring) into CNF and decodes models back into solutions.
+//! [`bmc`] unrolls transition systems for bounded model checking, and
+//! [`aig`] reads AIGER and BENCH circuits and converts them to CNF, and
+//! [`smtlib`] executes propositional SMT-LIB2 scripts. [`gates`] recovers
+//! AND/OR/XOR/ITE gate definitions from Tseitin-encoded formulas, and
+//! [`preprocess`] simplifies formulas before or between searches.
+//! [`features`] describes instances numerically for algorithm selection,
+//! and [`cube`] splits hard instances into independent jobs.
+//! [`anonymize`] renumbers and shuffles proprietary instances so they can be
+//! shared in bug reports.
+//! [`proof`] produces resolution proofs of unsatisfiability, and [`mus`]
+//! enumerates the minimal unsatisfiable subsets of a formula.
+//!
+//! In addition, [`testing`] provides a differential testing harness that
+//! cross-checks solving engines on random formulas, and the `arbitrary`
+//! module (behind the `proptest` feature) provides property-testing
+//! generators for the core types.
+//!
+//! [`parser::parse_and_convert`] and [`solve`] accept untrusted input: any
+//! bytes either parse or give an `InvalidData` error, and any formula is
+//! decided, with the variable count raised to cover its literals. The
+//! solver allocates per variable, so the parser rejects variable counts
+//! above [`parser::MAX_VARS`]; formulas built in code with larger variables
+//! are decided only if memory allows. The fuzz targets in `fuzz/` (run with
+//! `cargo fuzz run parse` or `solve`) check this.
+//!
 //! ## Quick Start
 //!
 //! ```rust
@@ -21,8 +52,8 @@
 //!
 //! // Create a simple formula: (x0) ∧ (¬x1)
 //! let formula = vec![
-//!     vec![Lit { var: 0, neg: false }],  // (x0)
-//!     vec![Lit { var: 1, neg: true }],   // (¬x1)
+//!     vec![Lit::positive(Var::new(0))],  // (x0)
+//!     vec![Lit::negative(Var::new(1))],   // (¬x1)
 //! ];
 //!
 //! match solve(&formula, 2) {
@@ -44,8 +75,28 @@
 //! - **Performance benchmarks**: Criterion-based measurement suite
 
 pub mod types;
+pub mod store;
+pub mod symbols;
+pub mod pretty;
 pub mod parser;
 pub mod solver;
+pub mod testing;
+pub mod gen;
+pub mod encode;
+pub mod bmc;
+pub mod aig;
+pub mod smtlib;
+pub mod gates;
+pub mod preprocess;
+pub mod features;
+pub mod cube;
+pub mod anonymize;
+pub mod proof;
+pub mod mus;
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
+
+mod rng;
 
-pub use solver::dpll::solve;
+pub use solver::special::solve;
 
@@ -0,0 +1,93 @@
+//! Satisfiability checks of clause subsets
+//!
+//! The MUS algorithms check many subsets of the same formula. Rather than
+//! building a formula per subset, [`Checker`] loads the formula once with a
+//! fresh *selector* variable added negatively to every clause: assuming the
+//! selector of a clause enables it, and leaving it unassumed lets the
+//! search satisfy the clause through its selector.
+
+use crate::solver::dpll::SolveResult;
+use crate::solver::failed::CoreMinimization;
+use crate::solver::Solver;
+use crate::types::*;
+
+/// A formula whose clause subsets can be checked for satisfiability.
+pub(crate) struct Checker {
+    /// The formula with a selector on every clause
+    solver: Solver,
+    /// Selector variable of the first clause; clause `i` has `first + i`
+    first: usize,
+    /// The clauses
+    clauses: Formula,
+}
+
+impl Checker {
+    /// Loads `formula` with `num_vars` variables.
+    pub fn new(formula: &Formula, num_vars: usize) -> Self {
+        let mut solver = Solver::new();
+        solver.set_caching(false);
+        while solver.num_vars() < num_vars {
+            solver.new_var();
+        }
+        for clause in formula {
+            let selector = solver.new_var();
+            let mut guarded = clause.clone();
+            guarded.push(Lit::negative(selector));
+            solver.add_clause(guarded);
+        }
+        Checker { solver, first: num_vars, clauses: formula.clone() }
+    }
+
+    /// Returns the number of clauses.
+    pub fn len(&self) -> usize {
+        self.clauses.len()
+    }
+
+    /// Checks the clauses in `subset`.
+    pub fn check(&mut self, subset: &[usize]) -> SolveResult {
+        let selectors: Vec<Lit> = subset.iter().map(|&index| Lit::positive(Var::new(self.first + index))).collect();
+        self.solver.solve_with_assumptions(&selectors)
+    }
+
+    /// Checks the clauses in `subset`, returning a model of them or, if
+    /// they are unsatisfiable, a MUS among them, sorted.
+    pub fn check_or_shrink(&mut self, subset: &[usize]) -> Result<Model, Vec<usize>> {
+        self.solver.set_core_minimization(CoreMinimization::Deletion, u64::MAX);
+        let result = self.check(subset);
+        self.solver.set_core_minimization(CoreMinimization::None, 0);
+        match result {
+            SolveResult::Sat(model) => Ok(model),
+            SolveResult::Unsat => {
+                let mut mus: Vec<usize> =
+                    self.solver.failed_assumptions().iter().map(|lit| lit.var.index() - self.first).collect();
+                mus.sort_unstable();
+                Err(mus)
+            }
+        }
+    }
+
+    /// Grows the satisfiable `seed`, with a model of it, to a maximal
+    /// satisfiable subset, returned as a membership table.
+    pub fn grow(&mut self, seed: &[usize], mut model: Model) -> Vec<bool> {
+        let mut inside = vec![false; self.clauses.len()];
+        for &index in seed {
+            inside[index] = true;
+        }
+        for index in 0..self.clauses.len() {
+            if inside[index] {
+                continue;
+            }
+            // A clause the current model satisfies joins without a check
+            if !self.clauses[index].iter().any(|&lit| model.is_true(lit)) {
+                let mut subset: Vec<usize> = (0..self.clauses.len()).filter(|&other| inside[other]).collect();
+                subset.push(index);
+                match self.check(&subset) {
+                    SolveResult::Sat(grown) => model = grown,
+                    SolveResult::Unsat => continue,
+                }
+            }
+            inside[index] = true;
+        }
+        inside
+    }
+}
@@ -0,0 +1,169 @@
+//! Enumeration of all MUSes and MCSes (MARCO)
+//!
+//! MARCO (Liffiton et al., "Fast, flexible MUS enumeration", 2016) explores
+//! the subsets of the clauses through a *map*: a second formula over one
+//! variable per clause whose models are the subsets not yet explained.
+//! Each round takes a model of the map as a seed and checks it:
+//!
+//! - A satisfiable seed is grown to a maximal satisfiable subset, whose
+//!   complement is an MCS. No subset of it can contain a MUS, so the map
+//!   gets a clause requiring one of the MCS clauses.
+//! - An unsatisfiable seed is shrunk to a MUS. Every superset of it is
+//!   unsatisfiable too, so the map gets a clause excluding one of its
+//!   clauses.
+//!
+//! Once the map is unsatisfiable, every MUS and every MCS has been
+//! reported. The checks run on a copy of the formula in which every clause
+//! has a selector variable, enabled through assumptions; shrinking uses
+//! the failed assumptions with
+//! [deletion-based minimization](crate::solver::failed::CoreMinimization::Deletion).
+//! Seeds are biased towards large subsets, so MCSes tend to come first.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::mus::marco::{Marco, Subset};
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! // (x0) ∧ (¬x0) ∧ (x1) ∧ (¬x1 ∨ ¬x0)
+//! let formula = vec![vec![x(0)], vec![!x(0)], vec![x(1)], vec![!x(1), !x(0)]];
+//!
+//! let mut muses: Vec<Vec<usize>> = Marco::new(&formula, 2)
+//!     .filter_map(|subset| match subset {
+//!         Subset::Mus(clauses) => Some(clauses),
+//!         Subset::Mcs(_) => None,
+//!     })
+//!     .collect();
+//! muses.sort();
+//! assert_eq!(muses, vec![vec![0, 1], vec![0, 2, 3]]);
+//! ```
+
+use super::checker::Checker;
+use crate::solver::dpll::SolveResult;
+use crate::solver::Solver;
+use crate::types::*;
+
+/// A subset of the clauses found by [`Marco`], as sorted clause indices.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Subset {
+    /// A minimal unsatisfiable subset
+    Mus(Vec<usize>),
+    /// A minimal correction set: the complement of a maximal satisfiable
+    /// subset
+    Mcs(Vec<usize>),
+}
+
+/// Iterator over all MUSes and MCSes of a formula.
+///
+/// A satisfiable formula has no MUS and only the empty MCS. The number of
+/// MUSes can be exponential in the number of clauses; the iterator can be
+/// stopped at any time.
+pub struct Marco {
+    /// Checks of clause subsets
+    checker: Checker,
+    /// Subsets of the clauses not yet explained
+    map: Solver,
+    /// Whether the map has no models left
+    done: bool,
+}
+
+impl Marco {
+    /// Prepares the enumeration for `formula` with `num_vars` variables.
+    pub fn new(formula: &Formula, num_vars: usize) -> Self {
+        let mut map = Solver::new();
+        map.set_caching(false);
+        for _ in formula {
+            // Unexplored clauses are tried in, so seeds start large
+            let var = map.new_var();
+            map.set_phase(var, true);
+        }
+        Marco { checker: Checker::new(formula, num_vars), map, done: false }
+    }
+}
+
+impl Iterator for Marco {
+    type Item = Subset;
+
+    fn next(&mut self) -> Option<Subset> {
+        if self.done {
+            return None;
+        }
+        let SolveResult::Sat(map_model) = self.map.solve() else {
+            self.done = true;
+            return None;
+        };
+        let seed: Vec<usize> =
+            (0..self.checker.len()).filter(|&index| map_model.value(Var::new(index)) != Val::False).collect();
+        match self.checker.check_or_shrink(&seed) {
+            Ok(model) => {
+                let inside = self.checker.grow(&seed, model);
+                let mcs: Vec<usize> = (0..self.checker.len()).filter(|&index| !inside[index]).collect();
+                // Block down: later seeds must include a clause of the MCS.
+                // The empty MCS (a satisfiable formula) leaves nothing to explore
+                match mcs.is_empty() {
+                    true => self.done = true,
+                    false => {
+                        self.map.add_clause(mcs.iter().map(|&index| Lit::positive(Var::new(index))).collect());
+                    }
+                }
+                Some(Subset::Mcs(mcs))
+            }
+            Err(mus) => {
+                // Block up: later seeds must leave out a clause of the MUS
+                let block = mus.iter().map(|&index| Lit::negative(Var::new(index))).collect();
+                self.map.add_clause(block);
+                Some(Subset::Mus(mus))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll::{solve, SolveResult};
+
+    /// Returns `true` if the clauses of `formula` selected by `mask` are unsatisfiable.
+    fn unsat(formula: &Formula, num_vars: usize, mask: u32) -> bool {
+        let subset: Formula =
+            formula.iter().enumerate().filter(|(i, _)| mask >> i & 1 == 1).map(|(_, c)| c.clone()).collect();
+        solve(&subset, num_vars) == SolveResult::Unsat
+    }
+
+    #[test]
+    fn enumerates_exactly_the_muses_and_mcses() {
+        for seed in 0..6 {
+            let (formula, num_vars) = crate::gen::random_ksat(4, 10, 2, seed);
+            let m = formula.len();
+            let full = (1u32 << m) - 1;
+            let mut muses = Vec::new();
+            let mut mcses = Vec::new();
+            for mask in 0..=full {
+                let members = || (0..m).filter(move |i| mask >> i & 1 == 1);
+                let minimal_unsat = unsat(&formula, num_vars, mask)
+                    && members().all(|i| !unsat(&formula, num_vars, mask & !(1 << i)));
+                if minimal_unsat {
+                    muses.push(mask);
+                }
+                let correcting = !unsat(&formula, num_vars, full & !mask)
+                    && members().all(|i| unsat(&formula, num_vars, full & !(mask & !(1 << i))));
+                if correcting {
+                    mcses.push(mask);
+                }
+            }
+            let to_mask = |clauses: &[usize]| clauses.iter().fold(0u32, |mask, &i| mask | 1 << i);
+            let (mut found_muses, mut found_mcses) = (Vec::new(), Vec::new());
+            for subset in Marco::new(&formula, num_vars) {
+                match subset {
+                    Subset::Mus(clauses) => found_muses.push(to_mask(&clauses)),
+                    Subset::Mcs(clauses) => found_mcses.push(to_mask(&clauses)),
+                }
+            }
+            found_muses.sort_unstable();
+            found_mcses.sort_unstable();
+            assert_eq!(found_muses, muses, "seed {}", seed);
+            assert_eq!(found_mcses, mcses, "seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,23 @@
+//! Minimal unsatisfiable subsets
+//!
+//! An unsatisfiable formula usually has many reasons for being
+//! unsatisfiable. A *minimal unsatisfiable subset* (MUS) of its clauses is
+//! one such reason: unsatisfiable, but satisfiable as soon as any one of its
+//! clauses is dropped. Dually, a *minimal correction set* (MCS) is a set of
+//! clauses whose removal makes the formula satisfiable, none of which can be
+//! kept. Every MCS contains a clause of every MUS and vice versa, so the
+//! two families describe each other.
+//!
+//! Clauses are identified by their position in the formula.
+//! [`Solver::mus`](crate::solver::Solver::mus) names them by their handles
+//! in a solver instead, so that data attached to them can be reported.
+//!
+//! # Modules
+//!
+//! - [`checker`]: Satisfiability checks of clause subsets through selector variables
+//! - [`marco`]: Enumeration of all MUSes and MCSes with the MARCO algorithm
+//! - [`smus`]: Smallest MUS by implicit hitting sets
+
+pub(crate) mod checker;
+pub mod marco;
+pub mod smus;
@@ -0,0 +1,192 @@
+//! Smallest MUS by implicit hitting sets
+//!
+//! A MUS is only minimal by inclusion: no clause can be dropped from it,
+//! but another MUS of the same formula may be far smaller, and the smallest
+//! one is usually the most useful explanation. [`smallest_mus`] finds a MUS
+//! of minimum cardinality with the implicit hitting set method (Ignatiev
+//! et al., "Smallest MUS extraction with minimal hitting set dualization",
+//! 2015).
+//!
+//! Every unsatisfiable subset contains a clause of every MCS, so a smallest
+//! set of clauses meeting all the MCSes is a lower bound on the smallest
+//! MUS. The method keeps some of the MCSes, computes a minimum hitting set
+//! of them and checks it: if it is unsatisfiable, it is a smallest MUS;
+//! otherwise growing it to a maximal satisfiable subset yields a new MCS
+//! that the hitting set misses, and the method goes again.
+//!
+//! The hitting sets are computed exactly by branch and bound, so the method
+//! suits formulas with up to a few hundred clauses whose MUSes are small.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::mus::smus::smallest_mus;
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! // x0 and ¬x0 follow from a chain through x1 and x2, or directly
+//! let formula = vec![
+//!     vec![x(1)],
+//!     vec![!x(1), x(2)],
+//!     vec![!x(2), x(0)],
+//!     vec![!x(0)],
+//!     vec![x(0)],
+//! ];
+//! assert_eq!(smallest_mus(&formula, 3), Some(vec![3, 4]));
+//! ```
+
+use super::checker::Checker;
+use crate::solver::dpll::SolveResult;
+use crate::types::*;
+
+/// Returns a MUS of `formula` with `num_vars` variables with the fewest
+/// clauses, as sorted clause indices, or `None` if the formula is
+/// satisfiable.
+///
+/// # Arguments
+///
+/// * `formula` - The formula to explain
+/// * `num_vars` - The number of variables in the formula
+///
+/// # Returns
+///
+/// A smallest MUS, or `None` if `formula` is satisfiable
+pub fn smallest_mus(formula: &Formula, num_vars: usize) -> Option<Vec<usize>> {
+    let mut checker = Checker::new(formula, num_vars);
+    let mut correction_sets: Vec<Vec<usize>> = Vec::new();
+    loop {
+        let candidate = minimum_hitting_set(formula.len(), &correction_sets);
+        match checker.check(&candidate) {
+            SolveResult::Unsat => return Some(candidate),
+            SolveResult::Sat(model) => {
+                let inside = checker.grow(&candidate, model);
+                let mcs: Vec<usize> = (0..formula.len()).filter(|&index| !inside[index]).collect();
+                if mcs.is_empty() {
+                    return None;
+                }
+                correction_sets.push(mcs);
+            }
+        }
+    }
+}
+
+/// Returns a smallest set of elements below `universe` meeting every set
+/// of `family`, sorted.
+fn minimum_hitting_set(universe: usize, family: &[Vec<usize>]) -> Vec<usize> {
+    let mut search = HittingSearch {
+        family,
+        chosen: vec![false; universe],
+        excluded: vec![false; universe],
+        current: Vec::new(),
+        best: None,
+    };
+    search.extend();
+    let mut best = search.best.expect("every set of the family is non-empty");
+    best.sort_unstable();
+    best
+}
+
+/// Branch and bound over the elements of the sets not yet met.
+struct HittingSearch<'a> {
+    family: &'a [Vec<usize>],
+    /// Elements in the current partial hitting set
+    chosen: Vec<bool>,
+    /// Elements the current branch has decided to leave out
+    excluded: Vec<bool>,
+    /// The current partial hitting set
+    current: Vec<usize>,
+    /// The smallest hitting set found so far
+    best: Option<Vec<usize>>,
+}
+
+impl HittingSearch<'_> {
+    /// Whether `set` contains a chosen element.
+    fn is_met(&self, set: &[usize]) -> bool {
+        set.iter().any(|&element| self.chosen[element])
+    }
+
+    /// Returns the number of pairwise disjoint unmet sets found greedily,
+    /// each of which needs its own element, or `None` if an unmet set has
+    /// only excluded elements left.
+    fn lower_bound(&self) -> Option<usize> {
+        let mut taken = vec![false; self.chosen.len()];
+        let mut disjoint = 0;
+        for set in self.family.iter().filter(|set| !self.is_met(set)) {
+            let mut available = set.iter().filter(|&&element| !self.excluded[element]).peekable();
+            available.peek()?;
+            if available.clone().all(|&element| !taken[element]) {
+                for &element in available {
+                    taken[element] = true;
+                }
+                disjoint += 1;
+            }
+        }
+        Some(disjoint)
+    }
+
+    fn extend(&mut self) {
+        let Some(bound) = self.lower_bound() else { return };
+        if self.best.as_ref().is_some_and(|best| self.current.len() + bound >= best.len()) {
+            return;
+        }
+        // Branch on the elements of the unmet set with the fewest left
+        let branch = self
+            .family
+            .iter()
+            .filter(|set| !self.is_met(set))
+            .min_by_key(|set| set.iter().filter(|&&element| !self.excluded[element]).count());
+        let Some(set) = branch else {
+            self.best = Some(self.current.clone());
+            return;
+        };
+        let elements: Vec<usize> = set.iter().copied().filter(|&element| !self.excluded[element]).collect();
+        for &element in &elements {
+            self.chosen[element] = true;
+            self.current.push(element);
+            self.extend();
+            self.current.pop();
+            self.chosen[element] = false;
+            // Later branches leave this element out: the sets containing
+            // it have been covered by this branch already
+            self.excluded[element] = true;
+        }
+        for &element in &elements {
+            self.excluded[element] = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll::solve;
+
+    #[test]
+    fn hitting_sets_are_minimum() {
+        let family = vec![vec![0, 1], vec![1, 2], vec![2, 3], vec![3, 0], vec![4]];
+        let hitting = minimum_hitting_set(5, &family);
+        assert_eq!(hitting.len(), 3);
+        assert!(family.iter().all(|set| set.iter().any(|element| hitting.contains(element))));
+        assert_eq!(minimum_hitting_set(5, &[]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn finds_the_smallest_unsatisfiable_subset() {
+        for seed in 0..8 {
+            let (formula, num_vars) = crate::gen::random_ksat(4, 12, 2, seed);
+            let subset = |mask: u32| -> Formula {
+                formula.iter().enumerate().filter(|(i, _)| mask >> i & 1 == 1).map(|(_, c)| c.clone()).collect()
+            };
+            let smallest = (0..1u32 << formula.len())
+                .filter(|&mask| solve(&subset(mask), num_vars) == SolveResult::Unsat)
+                .map(u32::count_ones)
+                .min();
+            let found = smallest_mus(&formula, num_vars);
+            assert_eq!(found.as_ref().map(|mus| mus.len() as u32), smallest, "seed {}", seed);
+            if let Some(mus) = found {
+                let mask = mus.iter().fold(0, |mask, &i| mask | 1 << i);
+                assert_eq!(solve(&subset(mask), num_vars), SolveResult::Unsat);
+            }
+        }
+    }
+}
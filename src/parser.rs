@@ -9,27 +9,127 @@
 //! - A problem line starting with 'p cnf' followed by variable count and clause count
 //! - Clause lines containing space-separated literals, terminated by 0
 //!
+//! Variable names can be attached with the conventional comment annotation
+//! `c var <index> <name>` (1-based DIMACS index). [`parse_with_symbols`]
+//! collects them into a [`SymbolTable`] and [`write_dimacs_with_symbols`]
+//! emits them, so names survive a round trip through other tools.
+//!
+//! Files from different sources bend the format in different ways. The
+//! default parse is lenient; [`parse_with_options`] can instead enforce the
+//! format strictly, and report what a lenient parse had to tolerate (see
+//! [`ParseOptions`]).
+//!
 //! # Example DIMACS file
 //!
 //! ```text
 //! c This is a comment
+//! c var 1 start
 //! p cnf 3 2
 //! 1 -3 0
 //! 2 3 -1 0
 //! ```
 
-use std::io::{self, BufRead};
-use crate::types::{Lit, Formula};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use crate::symbols::SymbolTable;
+use crate::types::{Lit, Formula, Var};
+
+/// The largest variable count the parser accepts, declared or used.
+///
+/// The solver allocates about a hundred bytes per variable up front, so a
+/// count far beyond the clauses given would otherwise let a few bytes of
+/// input exhaust memory. Real instances stay well below the limit.
+pub const MAX_VARS: usize = 1 << 23;
 
 /// Internal representation of a DIMACS literal (with sign)
 #[derive(Debug, Copy, Clone)]
 struct DimacsLiteral(i32);
 
-/// Internal representation of a DIMACS clause
-type DimacsClause = Vec<DimacsLiteral>;
+/// Internal representation of a DIMACS formula: the literals of all
+/// clauses back to back, so that parsing allocates per file, not per clause
+#[derive(Debug, Default)]
+struct DimacsFormula {
+    lits: Vec<DimacsLiteral>,
+    /// End of each clause in `lits`
+    ends: Vec<usize>,
+}
+
+impl DimacsFormula {
+    /// Returns the number of clauses.
+    fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Returns `true` if there are no clauses.
+    fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Iterates over the clauses.
+    fn clauses(&self) -> impl Iterator<Item = &[DimacsLiteral]> + '_ {
+        (0..self.len()).map(|index| &self[index])
+    }
+}
+
+impl std::ops::Index<usize> for DimacsFormula {
+    type Output = [DimacsLiteral];
+
+    fn index(&self, index: usize) -> &[DimacsLiteral] {
+        let start = if index == 0 { 0 } else { self.ends[index - 1] };
+        &self.lits[start..self.ends[index]]
+    }
+}
+
+/// How strictly DIMACS input is checked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Tolerate the common deviations: a missing or malformed problem line,
+    /// a clause count that does not match it, `%` lines (as at the end of
+    /// the SATLIB benchmarks), unknown lines and clauses without the
+    /// terminating `0`
+    #[default]
+    Lenient,
+    /// Reject everything the lenient mode tolerates
+    Strict,
+}
+
+/// Options of [`parse_with_options`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// How strictly the input is checked
+    pub mode: ParseMode,
+    /// Whether to report the deviations a lenient parse tolerated
+    pub collect_warnings: bool,
+}
 
-/// Internal representation of a DIMACS formula
-type DimacsFormula = Vec<DimacsClause>;
+/// A deviation from the DIMACS format tolerated by a lenient parse.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseWarning {
+    /// Line number, starting at 1 (0 for the input as a whole)
+    pub line: usize,
+    /// Description of the deviation
+    pub message: String,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            0 => write!(f, "{}", self.message),
+            line => write!(f, "line {}: {}", line, self.message),
+        }
+    }
+}
+
+/// Result of parsing DIMACS text, before conversion.
+struct Parsed {
+    formula: DimacsFormula,
+    /// `c var` annotations in file order, named once the variable count is
+    /// known
+    annotations: Vec<(Var, String)>,
+    /// Variable count of the problem line
+    declared: Option<usize>,
+    warnings: Vec<ParseWarning>,
+}
 
 /// Parses a DIMACS CNF format from a reader.
 ///
@@ -42,13 +142,14 @@ type DimacsFormula = Vec<DimacsClause>;
 ///
 /// # Returns
 ///
-/// * `Ok(DimacsFormula)` - The parsed formula in DIMACS representation
+/// * `Ok((DimacsFormula, Option<usize>))` - The parsed formula in DIMACS
+///   representation and the variable count of the problem line, if any
 /// * `Err(io::Error)` - If reading fails
 ///
 /// # Format Details
 ///
 /// - Lines starting with 'c' or '%' are treated as comments
-/// - Lines starting with 'p' are problem declarations (ignored)
+/// - Lines starting with 'p' are problem declarations (only the variable count is used)
 /// - Other lines contain clauses: space-separated integers ending with 0
 /// - Positive integers represent positive literals
 /// - Negative integers represent negative literals
@@ -63,28 +164,182 @@ type DimacsFormula = Vec<DimacsClause>;
 /// let reader = Cursor::new(input);
 /// // let result = parse_dimacs(reader)?;
 /// ```
-fn parse_dimacs<R: BufRead>(r: R) -> io::Result<DimacsFormula> {
-    let mut formula = Vec::new();
+fn parse_dimacs<R: BufRead>(r: R) -> io::Result<(DimacsFormula, Option<usize>)> {
+    parse_dimacs_annotated(r, &ParseOptions::default()).map(|parsed| (parsed.formula, parsed.declared))
+}
 
-    for line in r.lines() {
-        let line = line?;
-        let line = line.trim();
+/// Parses DIMACS like [`parse_dimacs`], also collecting `c var` annotations,
+/// the variable count declared by the problem line and, if asked for, the
+/// deviations tolerated in lenient mode.
+///
+/// Malformed or conflicting annotations are ignored like any other comment.
+/// In lenient mode, so is a malformed problem line; in strict mode every
+/// deviation is an `InvalidData` error. Literals that are not integers are
+/// an error in both modes.
+fn parse_dimacs_annotated<R: BufRead>(mut r: R, options: &ParseOptions) -> io::Result<Parsed> {
+    let strict = options.mode == ParseMode::Strict;
+    let mut parsed = Parsed { formula: DimacsFormula::default(), annotations: Vec::new(), declared: None, warnings: Vec::new() };
+    let mut header = None;
+    // Reports a deviation: an error in strict mode, a warning otherwise
+    let deviation = |parsed: &mut Parsed, line: usize, message: String| {
+        if strict {
+            let at = if line == 0 { String::new() } else { format!("line {}: ", line) };
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}{}", at, message)));
+        }
+        if options.collect_warnings {
+            parsed.warnings.push(ParseWarning { line, message });
+        }
+        Ok(())
+    };
 
-        match line.chars().next() {
-            Some('c') | Some('%') |  None => continue, // コメント等
-            Some('p') => continue,                                // 問題行は今回は無視
-            _ => {
-                let lits = line
-                    .split_whitespace()
-                    .map(|tok| tok.parse::<i32>().unwrap())
-                    .take_while(|&n| n != 0)                     // 末尾 0 を捨てる
-                    .map(DimacsLiteral)
-                    .collect::<Vec<_>>();
-                formula.push(lits);
+    // One buffer for all lines; clause lines are scanned as bytes, without
+    // going through `str`
+    let mut buf = Vec::new();
+    let mut lineno = 0;
+    loop {
+        buf.clear();
+        if r.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        lineno += 1;
+        let line = buf.trim_ascii();
+
+        match line.first() {
+            None => continue,
+            Some(b'c') => {
+                if let Some((var, name)) = std::str::from_utf8(line).ok().and_then(parse_annotation) {
+                    parsed.annotations.push((var, name.to_string()));
+                }
+            }
+            Some(b'%') => deviation(&mut parsed, lineno, "'%' line skipped".to_string())?, // SATLIB の終端
+            Some(b'p') => {
+                if header.is_some() {
+                    deviation(&mut parsed, lineno, "second problem line".to_string())?;
+                }
+                match std::str::from_utf8(line).ok().and_then(parse_header) {
+                    Some((vars, clauses)) => {
+                        parsed.declared = Some(vars);
+                        header = Some(clauses);
+                    }
+                    None => {
+                        let message = format!("malformed problem line '{}'", String::from_utf8_lossy(line));
+                        deviation(&mut parsed, lineno, message)?;
+                    }
+                }
             }
+            Some(&c) if c == b'-' || c.is_ascii_digit() => {
+                if header.is_none() && parsed.formula.is_empty() {
+                    deviation(&mut parsed, lineno, "clause before the problem line".to_string())?;
+                }
+                let mut terminated = false;
+                let mut rest = line;
+                while !rest.is_empty() {
+                    let (n, len) = scan_literal(rest).ok_or_else(|| {
+                        let token = rest.split(|b| b.is_ascii_whitespace()).next().unwrap_or_default();
+                        let message = format!("line {}: invalid literal '{}'", lineno, String::from_utf8_lossy(token));
+                        io::Error::new(io::ErrorKind::InvalidData, message)
+                    })?;
+                    if terminated {
+                        deviation(&mut parsed, lineno, "literals after the terminating 0 ignored".to_string())?;
+                        break;
+                    }
+                    match n {
+                        0 => terminated = true, // 末尾 0 を捨てる
+                        n => parsed.formula.lits.push(DimacsLiteral(n)),
+                    }
+                    rest = rest[len..].trim_ascii_start();
+                }
+                if !terminated {
+                    deviation(&mut parsed, lineno, "clause not terminated by 0".to_string())?;
+                }
+                parsed.formula.ends.push(parsed.formula.lits.len());
+            }
+            Some(_) => {
+                let message = format!("unknown line '{}' skipped", String::from_utf8_lossy(line));
+                deviation(&mut parsed, lineno, message)?;
+            }
+        }
+    }
+
+    match header {
+        None => deviation(&mut parsed, 0, "no problem line".to_string())?,
+        Some(clauses) if clauses != parsed.formula.len() => {
+            let message = format!("problem line declares {} clauses, found {}", clauses, parsed.formula.len());
+            deviation(&mut parsed, 0, message)?;
+        }
+        Some(_) => {}
+    }
+    Ok(parsed)
+}
+
+/// Parses `p cnf <variables> <clauses>` into its two counts.
+fn parse_header(line: &str) -> Option<(usize, usize)> {
+    let mut tokens = line.split_whitespace();
+    if tokens.next() != Some("p") || tokens.next() != Some("cnf") {
+        return None;
+    }
+    let vars = tokens.next()?.parse().ok()?;
+    let clauses = tokens.next()?.parse().ok()?;
+    tokens.next().is_none().then_some((vars, clauses))
+}
+
+/// Scans the literal at the start of `bytes`: an optionally signed decimal
+/// integer that fits an `i32`, ended by whitespace or the end of the line.
+///
+/// Returns the literal and the number of bytes it takes, or `None` if the
+/// token is not such an integer. Hand-rolled rather than `str::parse` since
+/// literals are nearly all of a DIMACS file.
+fn scan_literal(bytes: &[u8]) -> Option<(i32, usize)> {
+    let (negative, start) = match bytes.first()? {
+        b'-' => (true, 1),
+        b'+' => (false, 1),
+        _ => (false, 0),
+    };
+    let mut magnitude: i64 = 0;
+    let mut end = start;
+    while let Some(&b) = bytes.get(end) {
+        if !b.is_ascii_digit() {
+            break;
+        }
+        magnitude = magnitude * 10 + i64::from(b - b'0');
+        if magnitude > 1 << 31 {
+            return None;
+        }
+        end += 1;
+    }
+    if end == start || bytes.get(end).is_some_and(|b| !b.is_ascii_whitespace()) {
+        return None;
+    }
+    let n = if negative { -magnitude } else { magnitude };
+    Some((i32::try_from(n).ok()?, end))
+}
+
+/// Parses `c var <index> <name>` into a 0-based variable and its name.
+fn parse_annotation(line: &str) -> Option<(Var, &str)> {
+    let mut tokens = line.split_whitespace();
+    if tokens.next() != Some("c") || tokens.next() != Some("var") {
+        return None;
+    }
+    let index: usize = tokens.next()?.parse().ok()?;
+    let name = tokens.next()?;
+    if index == 0 || tokens.next().is_some() {
+        return None;
+    }
+    Some((Var::try_new(index - 1)?, name))
+}
+
+/// Collects the annotations of the first `num_vars` variables into a symbol
+/// table, ignoring those that conflict with an earlier one. Annotations
+/// beyond the variable count are dropped, so a bogus index cannot make the
+/// table allocate for variables the formula does not have.
+fn bind_annotations(annotations: Vec<(Var, String)>, num_vars: usize) -> SymbolTable {
+    let mut symbols = SymbolTable::new();
+    for (var, name) in annotations {
+        if var.index() < num_vars {
+            symbols.bind(var, &name);
         }
     }
-    Ok(formula)
+    symbols
 }
 
 /// Converts DIMACS representation to internal solver representation.
@@ -92,48 +347,72 @@ fn parse_dimacs<R: BufRead>(r: R) -> io::Result<DimacsFormula> {
 /// This function performs several transformations:
 /// - Converts 1-based DIMACS variable numbering to 0-based internal numbering
 /// - Converts `DimacsLiteral` to internal `Lit` structures
-/// - Determines the maximum variable number for model initialization
+/// - Checks the variables against the count declared by the problem line,
+///   or determines the maximum variable number if there is none
 ///
 /// # Arguments
 ///
 /// * `dimacs_formula` - The formula in DIMACS representation
+/// * `declared` - The variable count of the problem line, if any
 ///
 /// # Returns
 ///
-/// A tuple containing:
-/// * `Formula` - The formula in internal representation
-/// * `usize` - The number of variables in the formula
+/// * `Ok((Formula, usize))` - The formula in internal representation and
+///   its number of variables (the declared count if there is one)
+/// * `Err(io::Error)` - `InvalidData` if a literal exceeds the declared count,
+///   or the declared count or a literal exceeds [`MAX_VARS`]
 ///
 /// # Examples
 ///
-/// ```no_run
-/// # use putnam::parser::*;
-/// # use putnam::types::*;
-/// // Assuming we have a DimacsFormula
-/// # let dimacs_formula = vec![];
-/// let (formula, num_vars) = convert_to_internal(dimacs_formula);
+/// The conversion is private; [`parse_and_convert`] runs it on the parsed
+/// clauses:
+///
+/// ```
+/// use putnam::parser::parse_and_convert;
+/// use putnam::types::{Lit, Var};
+///
+/// let (formula, num_vars) = parse_and_convert("p cnf 2 1\n1 -2 0\n".as_bytes())?;
+/// assert_eq!(num_vars, 2);
+/// assert_eq!(formula, vec![vec![Lit::positive(Var::new(0)), Lit::negative(Var::new(1))]]);
+/// # Ok::<(), std::io::Error>(())
 /// ```
-fn convert_to_internal(dimacs_formula: DimacsFormula) -> (Formula, usize) {
+fn convert_to_internal(dimacs_formula: DimacsFormula, declared: Option<usize>) -> io::Result<(Formula, usize)> {
+    if let Some(declared) = declared.filter(|&declared| declared > MAX_VARS) {
+        let message = format!("problem line declares {} variables, more than the supported {}", declared, MAX_VARS);
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    }
     let mut max_var = 0;
-    let mut formula = Vec::new();
+    let mut formula = Vec::with_capacity(dimacs_formula.len());
     
-    for dimacs_clause in dimacs_formula {
-        let mut clause = Vec::new();
-        for dimacs_lit in dimacs_clause {
-            let var_num = dimacs_lit.0.abs() as usize;
-            if var_num > 0 {
-                let var = var_num - 1; // Convert to 0-based
-                max_var = max_var.max(var);
-                clause.push(Lit {
-                    var,
-                    neg: dimacs_lit.0 < 0,
-                });
+    for (index, dimacs_clause) in dimacs_formula.clauses().enumerate() {
+        let mut clause = Vec::with_capacity(dimacs_clause.len());
+        for &dimacs_lit in dimacs_clause {
+            let var_num = dimacs_lit.0.unsigned_abs() as usize;
+            if declared.is_some_and(|declared| var_num > declared) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "clause {}: literal {} exceeds the declared variable count {}",
+                        index + 1,
+                        dimacs_lit.0,
+                        declared.unwrap_or_default()
+                    ),
+                ));
+            }
+            if var_num > MAX_VARS {
+                let message = format!("clause {}: literal {} exceeds the supported variable count {}", index + 1, dimacs_lit.0, MAX_VARS);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            }
+            // Converts to 0-based numbering
+            if let Some(lit) = Lit::from_dimacs(dimacs_lit.0.into()) {
+                max_var = max_var.max(lit.var.index());
+                clause.push(lit);
             }
         }
         formula.push(clause);
     }
     
-    (formula, max_var + 1)
+    Ok((formula, declared.unwrap_or(max_var + 1)))
 }
 
 /// Parses DIMACS CNF format and converts to internal representation.
@@ -148,7 +427,9 @@ fn convert_to_internal(dimacs_formula: DimacsFormula) -> (Formula, usize) {
 /// # Returns
 ///
 /// * `Ok((Formula, usize))` - The parsed formula and variable count
-/// * `Err(io::Error)` - If reading or parsing fails
+/// * `Err(io::Error)` - If reading or parsing fails, or with
+///   `ErrorKind::InvalidData` if a literal exceeds the variable count of the
+///   problem line, or a variable count exceeds [`MAX_VARS`]
 ///
 /// # Examples
 ///
@@ -178,9 +459,209 @@ fn convert_to_internal(dimacs_formula: DimacsFormula) -> (Formula, usize) {
 ///
 /// Where literals are non-zero integers (positive for variables, negative for negations).
 pub fn parse_and_convert<R: BufRead>(reader: R) -> io::Result<(Formula, usize)> {
-    let dimacs_formula = parse_dimacs(reader)?;
-    Ok(convert_to_internal(dimacs_formula))
+    let (dimacs_formula, declared) = parse_dimacs(reader)?;
+    convert_to_internal(dimacs_formula, declared)
 }
+
+/// Parses DIMACS CNF format together with its variable-name annotations.
+///
+/// Behaves like [`parse_and_convert`], and additionally collects comment
+/// lines of the form `c var <index> <name>` into a symbol table. Malformed
+/// annotations, annotations of variables beyond the variable count, and
+/// annotations that conflict with an earlier one (same variable or same
+/// name), are ignored.
+///
+/// # Returns
+///
+/// * `Ok((Formula, usize, SymbolTable))` - The formula, its variable count, and the names
+/// * `Err(io::Error)` - If reading or parsing fails
+///
+/// # Examples
+///
+/// ```
+/// use putnam::parser::parse_with_symbols;
+/// use putnam::types::Var;
+///
+/// let input = "c var 1 start\nc var 2 goal\np cnf 2 1\n1 -2 0\n";
+/// let (formula, num_vars, symbols) = parse_with_symbols(input.as_bytes())?;
+///
+/// assert_eq!(num_vars, 2);
+/// assert_eq!(symbols.lookup("goal"), Some(Var::new(1)));
+/// assert_eq!(symbols.name(Var::new(0)), Some("start"));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn parse_with_symbols<R: BufRead>(reader: R) -> io::Result<(Formula, usize, SymbolTable)> {
+    let parsed = parse_dimacs_annotated(reader, &ParseOptions::default())?;
+    let (formula, num_vars) = convert_to_internal(parsed.formula, parsed.declared)?;
+    Ok((formula, num_vars, bind_annotations(parsed.annotations, num_vars)))
+}
+
+/// Parses DIMACS CNF format as strictly as `options` ask.
+///
+/// With the default options this behaves like [`parse_and_convert`].
+///
+/// # Returns
+///
+/// * `Ok((Formula, usize, Vec<ParseWarning>))` - The formula, its variable
+///   count, and the tolerated deviations (empty unless
+///   [`collect_warnings`](ParseOptions::collect_warnings) is set)
+/// * `Err(io::Error)` - If reading or parsing fails, or with
+///   `ErrorKind::InvalidData` in strict mode at the first deviation
+///
+/// # Examples
+///
+/// ```
+/// use putnam::parser::{parse_with_options, ParseMode, ParseOptions};
+///
+/// let input = "p cnf 2 2\n1 -2 0\n2 0\n%\n0\n";
+/// let lenient = ParseOptions { collect_warnings: true, ..ParseOptions::default() };
+/// let (formula, _, warnings) = parse_with_options(input.as_bytes(), &lenient)?;
+/// assert_eq!(formula.len(), 3);
+/// assert_eq!(warnings.len(), 2); // the '%' line and the clause count
+///
+/// let strict = ParseOptions { mode: ParseMode::Strict, ..ParseOptions::default() };
+/// assert!(parse_with_options(input.as_bytes(), &strict).is_err());
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn parse_with_options<R: BufRead>(reader: R, options: &ParseOptions) -> io::Result<(Formula, usize, Vec<ParseWarning>)> {
+    let parsed = parse_dimacs_annotated(reader, options)?;
+    let (formula, num_vars) = convert_to_internal(parsed.formula, parsed.declared)?;
+    Ok((formula, num_vars, parsed.warnings))
+}
+
+/// Parses DIMACS CNF format as strictly as `options` ask, collecting the
+/// variable names as well: [`parse_with_options`] and
+/// [`parse_with_symbols`] in one pass.
+///
+/// # Returns
+///
+/// * `Ok((Formula, usize, SymbolTable, Vec<ParseWarning>))` - The formula,
+///   its variable count, the names and the tolerated deviations
+/// * `Err(io::Error)` - As for [`parse_with_options`]
+pub fn parse_annotated<R: BufRead>(
+    reader: R,
+    options: &ParseOptions,
+) -> io::Result<(Formula, usize, SymbolTable, Vec<ParseWarning>)> {
+    let parsed = parse_dimacs_annotated(reader, options)?;
+    let (formula, num_vars) = convert_to_internal(parsed.formula, parsed.declared)?;
+    Ok((formula, num_vars, bind_annotations(parsed.annotations, num_vars), parsed.warnings))
+}
+
+/// Parses a variable order file: one DIMACS variable (1-based) per line.
+///
+/// Blank lines and lines starting with `c` or `#` are ignored. The result
+/// is suitable for [`Solver::set_decision_order`](crate::solver::Solver::set_decision_order).
+///
+/// # Returns
+///
+/// * `Ok(Vec<Var>)` - The 0-based variables in file order
+/// * `Err(io::Error)` - If reading fails, or with `ErrorKind::InvalidData`
+///   if a line is not a positive integer
+///
+/// # Examples
+///
+/// ```
+/// use putnam::parser::parse_order;
+/// use putnam::types::Var;
+///
+/// let order = parse_order("c time step 0 first\n3\n1\n\n2\n".as_bytes())?;
+/// assert_eq!(order, vec![Var::new(2), Var::new(0), Var::new(1)]);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn parse_order<R: BufRead>(reader: R) -> io::Result<Vec<Var>> {
+    let mut order = Vec::new();
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('c') || line.starts_with('#') {
+            continue;
+        }
+        match line.parse::<usize>().ok().and_then(|n| Var::try_new(n.checked_sub(1)?)) {
+            Some(var) => order.push(var),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: expected a positive variable number, got '{}'", lineno + 1, line),
+                ))
+            }
+        }
+    }
+    Ok(order)
+}
+
+/// Writes a formula in DIMACS CNF format.
+///
+/// This is the inverse of [`parse_and_convert`]: variables are converted back
+/// to 1-based DIMACS numbering and each clause is written on its own line,
+/// terminated by `0`.
+///
+/// # Arguments
+///
+/// * `writer` - Destination for the DIMACS text
+/// * `formula` - The formula in internal representation
+/// * `num_vars` - The number of variables, written to the problem line
+///
+/// # Returns
+///
+/// * `Ok(())` - The formula was written completely
+/// * `Err(io::Error)` - If writing fails
+///
+/// # Examples
+///
+/// ```
+/// use putnam::parser::write_dimacs;
+/// use putnam::types::{Lit, Var};
+///
+/// let formula = vec![vec![Lit::positive(Var::new(0)), Lit::negative(Var::new(1))]];
+/// let mut out = Vec::new();
+/// write_dimacs(&mut out, &formula, 2)?;
+///
+/// assert_eq!(String::from_utf8(out).unwrap(), "p cnf 2 1\n1 -2 0\n");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn write_dimacs<W: Write>(writer: W, formula: &Formula, num_vars: usize) -> io::Result<()> {
+    write_dimacs_with_symbols(writer, formula, num_vars, &SymbolTable::new())
+}
+
+/// Writes a formula in DIMACS CNF format with variable-name annotations.
+///
+/// Every named variable gets a `c var <index> <name>` comment before the
+/// problem line; the rest of the output is identical to [`write_dimacs`].
+///
+/// # Examples
+///
+/// ```
+/// use putnam::parser::write_dimacs_with_symbols;
+/// use putnam::symbols::SymbolTable;
+/// use putnam::types::Lit;
+///
+/// let mut symbols = SymbolTable::new();
+/// let x = symbols.var("x");
+/// let mut out = Vec::new();
+/// write_dimacs_with_symbols(&mut out, &vec![vec![Lit { var: x, neg: false }]], 1, &symbols)?;
+///
+/// assert_eq!(String::from_utf8(out).unwrap(), "c var 1 x\np cnf 1 1\n1 0\n");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn write_dimacs_with_symbols<W: Write>(
+    mut writer: W,
+    formula: &Formula,
+    num_vars: usize,
+    symbols: &SymbolTable,
+) -> io::Result<()> {
+    for (var, name) in symbols.iter() {
+        writeln!(writer, "c var {} {}", var.index() + 1, name)?;
+    }
+    writeln!(writer, "p cnf {} {}", num_vars, formula.len())?;
+    for clause in formula {
+        for lit in clause {
+            write!(writer, "{} ", lit.to_dimacs())?;
+        }
+        writeln!(writer, "0")?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -190,7 +671,7 @@ mod tests {
 
     /// 文字列から直接パースするヘルパ
     fn parse_str(src: &str) -> DimacsFormula {
-        parse_dimacs(src.as_bytes()).expect("parse failed")
+        parse_dimacs(src.as_bytes()).expect("parse failed").0
     }
 
     /// (x1) だけの最小 SAT
@@ -244,8 +725,8 @@ p cnf 3 2
         
         match solve(&formula, num_vars) {
             SolveResult::Sat(model) => {
-                assert_eq!(model.value(0), Val::True);   // x1 = True
-                assert_eq!(model.value(1), Val::False);  // x2 = False
+                assert_eq!(model.value(Var::new(0)), Val::True);   // x1 = True
+                assert_eq!(model.value(Var::new(1)), Val::False);  // x2 = False
             }
             SolveResult::Unsat => panic!("Expected SAT")
         }
@@ -256,5 +737,124 @@ p cnf 3 2
         assert_eq!(num_vars, 1);
         assert_eq!(solve(&formula, num_vars), SolveResult::Unsat);
     }
-}
 
+    /// 書き出し → 再パースで元の式に戻る
+    #[test]
+    fn write_then_parse_roundtrip() {
+        let (formula, num_vars) = crate::gen::random_ksat(6, 10, 3, 5);
+        let mut out = Vec::new();
+        write_dimacs(&mut out, &formula, num_vars).unwrap();
+
+        let (parsed, parsed_vars) = parse_and_convert(out.as_slice()).unwrap();
+        assert_eq!(parsed, formula);
+        assert!(parsed_vars <= num_vars);
+    }
+
+    /// 変数名注釈: 不正・衝突する注釈は無視し、書き出し → 再パースで名前が保たれる
+    #[test]
+    fn variable_name_annotations() {
+        let dimacs = "\
+c var 1 alpha
+c var 2 beta
+c var 3 alpha
+c var 0 zero
+c var x bad
+c variable 3 gamma
+p cnf 3 1
+1 -2 3 0
+";
+        let (formula, num_vars, symbols) = parse_with_symbols(dimacs.as_bytes()).unwrap();
+        assert_eq!(symbols.iter().collect::<Vec<_>>(), vec![(Var::new(0), "alpha"), (Var::new(1), "beta")]);
+
+        let mut out = Vec::new();
+        write_dimacs_with_symbols(&mut out, &formula, num_vars, &symbols).unwrap();
+        let (parsed, _, reparsed) = parse_with_symbols(out.as_slice()).unwrap();
+        assert_eq!(parsed, formula);
+        assert_eq!(reparsed, symbols);
+
+        // 変数数を超える注釈は無視し、巨大な添字でも表を確保しない
+        let (_, num_vars, symbols) = parse_with_symbols("c var 2000000000 x\nc var 2 y\np cnf 1 1\n1 0\n".as_bytes()).unwrap();
+        assert_eq!(num_vars, 1);
+        assert!(symbols.is_empty());
+        let (_, _, symbols) = parse_with_symbols("c var 3 z\n1 -3 0\n".as_bytes()).unwrap();
+        assert_eq!(symbols.lookup("z"), Some(Var::new(2)));
+    }
+
+    /// 問題行の変数数: 超えるリテラルはエラー、未使用の変数も数える
+    #[test]
+    fn declared_variable_count() {
+        let error = parse_and_convert("p cnf 2 1\n1 -3 0\n".as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let (_, num_vars) = parse_and_convert("p cnf 5 1\n1 -3 0\n".as_bytes()).unwrap();
+        assert_eq!(num_vars, 5);
+        // 問題行がなければ最大の変数から求める
+        let (_, num_vars) = parse_and_convert("1 -3 0\n".as_bytes()).unwrap();
+        assert_eq!(num_vars, 3);
+        // 表せない変数数もエラー
+        let error = parse_and_convert("p cnf 99999999999 0\n".as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        // 上限を超える変数数は、問題行でもリテラルでもエラー
+        for input in ["p cnf 2000000000 1\n1 0\n", "1000000000 0\n", "p cnf 8388609 0\n"] {
+            assert_eq!(parse_and_convert(input.as_bytes()).unwrap_err().kind(), io::ErrorKind::InvalidData, "{}", input);
+        }
+        assert_eq!(parse_and_convert("p cnf 8388608 0\n".as_bytes()).unwrap().1, MAX_VARS);
+    }
+
+    /// 厳格モード: 寛容モードが見逃す逸脱はすべてエラー、寛容モードでは警告
+    #[test]
+    fn strict_and_lenient_modes() {
+        let strict = ParseOptions { mode: ParseMode::Strict, ..ParseOptions::default() };
+        let lenient = ParseOptions { collect_warnings: true, ..ParseOptions::default() };
+        let deviations = [
+            "1 -2 0\n",                       // 問題行なし
+            "p cnf 2 1\n1 -2\n",             // 末尾 0 なし
+            "p cnf 2 1\n1 0 2\n",            // 0 の後にリテラル
+            "p cnf 2 2\n1 -2 0\n",           // 節数の不一致
+            "p cnf 2 1\n1 -2 0\n%\n",        // SATLIB の終端
+            "p cnf 2 1\nx 1 0\n1 -2 0\n",    // 未知の行
+            "p cnf 2\n1 -2 0\n",             // 不正な問題行
+            "p cnf 2 1\np cnf 2 1\n1 -2 0\n", // 問題行が二つ
+        ];
+        for input in deviations {
+            let error = parse_with_options(input.as_bytes(), &strict).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{}", input);
+            let (_, _, warnings) = parse_with_options(input.as_bytes(), &lenient).unwrap();
+            assert!(!warnings.is_empty(), "{}", input);
+        }
+
+        let input = "c ok\np cnf 3 2\n1 -2 0\n\n-3 0\n";
+        assert_eq!(parse_with_options(input.as_bytes(), &strict).unwrap().0.len(), 2);
+        assert!(parse_with_options(input.as_bytes(), &lenient).unwrap().2.is_empty());
+        // 整数でないリテラルはどちらのモードでもエラー
+        assert!(parse_with_options("p cnf 1 1\n1 y 0\n".as_bytes(), &lenient).is_err());
+    }
+
+    /// 整数スキャナ: 符号・範囲・区切りを str::parse と同じく扱う
+    #[test]
+    fn literal_scanner_edge_cases() {
+        assert_eq!(scan_literal(b"-12 0"), Some((-12, 3)));
+        assert_eq!(scan_literal(b"+7\t"), Some((7, 2)));
+        assert_eq!(scan_literal(b"-2147483648"), Some((i32::MIN, 11)));
+        assert_eq!(scan_literal(b"2147483648"), None);  // i32 の範囲外
+        assert_eq!(scan_literal(b"99999999999999999999"), None);
+        assert_eq!(scan_literal(b"-"), None);
+        assert_eq!(scan_literal(b"1x"), None);
+
+        // CRLF 改行・行内の連続空白・エラーメッセージのトークン
+        let f = parse_str("p cnf 3 2\r\n1  -2\t3 0\r\n-1 0");
+        assert_eq!(f.len(), 2);
+        assert_eq!(f[0].iter().map(|l| l.0).collect::<Vec<_>>(), vec![1, -2, 3]);
+        let error = parse_and_convert("p cnf 2 1\n1 -2x 0\n".as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("line 2: invalid literal '-2x'"), "{}", error);
+    }
+
+    /// 変数順序ファイル: 不正な行はエラー
+    #[test]
+    fn order_file_errors() {
+        assert_eq!(parse_order("# comment\n 2 \n".as_bytes()).unwrap(), vec![Var::new(1)]);
+        assert!(parse_order("0\n".as_bytes()).is_err());
+        assert!(parse_order("-3\n".as_bytes()).is_err());
+        assert!(parse_order("1 2\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,254 @@
+//! Clause distillation
+//!
+//! Distillation (also called asymmetric branching) takes a clause
+//! `l1 ∨ … ∨ lk` and assigns `¬l1, ¬l2, …` in turn, propagating the *other*
+//! clauses after each step:
+//!
+//! - a conflict after `¬l1 … ¬li` means the other clauses imply the prefix
+//!   `l1 ∨ … ∨ li`, which replaces the clause;
+//! - a later literal `lj` that became true means they imply
+//!   `l1 ∨ … ∨ li ∨ lj`, which replaces the clause;
+//! - a later literal that became false can be dropped from the clause.
+//!
+//! When the implied clause is the whole clause, it is redundant and removed.
+//! Every step keeps the formula logically equivalent, so models are
+//! unaffected.
+//!
+//! Propagation starts from the negated prefix only; unit clauses of the
+//! formula are not propagated first, so it pays to remove them beforehand.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::preprocess::distill::distill;
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! // x0 → x1 → x2 already implies (¬x0 ∨ x2)
+//! let mut formula = vec![vec![!x(0), x(1)], vec![!x(1), x(2)], vec![!x(0), x(2)]];
+//! let stats = distill(&mut formula, 3, u64::MAX);
+//!
+//! assert_eq!(formula, vec![vec![!x(0), x(1)], vec![!x(1), x(2)]]);
+//! assert_eq!(stats.clauses_removed, 1);
+//! ```
+
+use crate::types::*;
+
+/// What a distillation run changed and the work it did.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DistillStats {
+    /// Clauses replaced by a proper subset of their literals
+    pub clauses_strengthened: u64,
+    /// Literals removed from strengthened clauses
+    pub literals_removed: u64,
+    /// Clauses removed because the other clauses imply them
+    pub clauses_removed: u64,
+    /// Clause visits during propagation, the unit of the work budget
+    pub steps: u64,
+    /// Whether the budget ran out before every clause was tried
+    pub incomplete: bool,
+}
+
+/// Index of a literal in the occurrence lists.
+fn code(lit: Lit) -> usize {
+    2 * lit.var.index() + lit.neg as usize
+}
+
+/// Distills the clauses of `formula` in place.
+///
+/// Clauses are tried in order until about `budget` propagation steps (clause
+/// visits) are spent; the remaining clauses are left as they are. Removed
+/// clauses are dropped from `formula`, and the relative order of the other
+/// clauses is kept.
+///
+/// # Arguments
+///
+/// * `formula` - The clauses to simplify
+/// * `num_vars` - The number of variables (raised if the formula uses more)
+/// * `budget` - Maximal number of propagation steps
+///
+/// # Returns
+///
+/// The changes made and the work spent
+pub fn distill(formula: &mut Formula, num_vars: usize, budget: u64) -> DistillStats {
+    let num_vars = formula.iter().flatten().map(|l| l.var.index() + 1).max().unwrap_or(0).max(num_vars);
+    let mut occurs = vec![Vec::new(); 2 * num_vars];
+    for (index, clause) in formula.iter().enumerate() {
+        for &lit in clause {
+            occurs[code(lit)].push(index);
+        }
+    }
+    let mut distiller = Distiller {
+        formula,
+        occurs,
+        active: Vec::new(),
+        vals: vec![Val::Undef; num_vars],
+        trail: Vec::new(),
+        stats: DistillStats::default(),
+    };
+    distiller.active = vec![true; distiller.formula.len()];
+
+    for index in 0..distiller.formula.len() {
+        if distiller.stats.steps >= budget {
+            distiller.stats.incomplete = true;
+            break;
+        }
+        distiller.distill_clause(index);
+    }
+
+    let Distiller { formula, active, stats, .. } = distiller;
+    let mut keep = active.into_iter();
+    formula.retain(|_| keep.next().unwrap_or(true));
+    stats
+}
+
+/// State of one distillation run.
+struct Distiller<'a> {
+    formula: &'a mut Formula,
+    /// Clauses containing each literal; entries may be stale after strengthening
+    occurs: Vec<Vec<usize>>,
+    /// Whether each clause is still part of the formula
+    active: Vec<bool>,
+    /// Current assignment
+    vals: Vec<Val>,
+    /// Literals made true, in order
+    trail: Vec<Lit>,
+    stats: DistillStats,
+}
+
+impl Distiller<'_> {
+    fn value(&self, lit: Lit) -> Val {
+        match (self.vals[lit.var.index()], lit.neg) {
+            (Val::Undef, _) => Val::Undef,
+            (Val::True, false) | (Val::False, true) => Val::True,
+            _ => Val::False,
+        }
+    }
+
+    fn assign(&mut self, lit: Lit) {
+        self.vals[lit.var.index()] = if lit.neg { Val::False } else { Val::True };
+        self.trail.push(lit);
+    }
+
+    /// Propagates the trail from `start`, ignoring clause `skip`.
+    ///
+    /// Returns `false` on a conflict.
+    fn propagate(&mut self, mut start: usize, skip: usize) -> bool {
+        while start < self.trail.len() {
+            let lit = self.trail[start];
+            start += 1;
+            for position in 0..self.occurs[code(!lit)].len() {
+                let index = self.occurs[code(!lit)][position];
+                if index == skip || !self.active[index] {
+                    continue;
+                }
+                self.stats.steps += 1;
+                let mut unit = None;
+                let mut open = 0;
+                for &other in &self.formula[index] {
+                    match self.value(other) {
+                        Val::True => {
+                            open = usize::MAX;
+                            break;
+                        }
+                        Val::Undef => {
+                            open += 1;
+                            unit = Some(other);
+                        }
+                        Val::False => {}
+                    }
+                }
+                match (open, unit) {
+                    (0, _) => return false,
+                    (1, Some(unit)) => self.assign(unit),
+                    _ => {}
+                }
+            }
+        }
+        true
+    }
+
+    fn distill_clause(&mut self, index: usize) {
+        let clause = self.formula[index].clone();
+        let mut kept = Vec::with_capacity(clause.len());
+        let mut implied = false;
+        for &lit in &clause {
+            match self.value(lit) {
+                Val::True => {
+                    kept.push(lit);
+                    implied = true;
+                    break;
+                }
+                Val::False => continue,
+                Val::Undef => {
+                    kept.push(lit);
+                    let start = self.trail.len();
+                    self.assign(!lit);
+                    if !self.propagate(start, index) {
+                        implied = true;
+                        break;
+                    }
+                }
+            }
+        }
+        for lit in self.trail.drain(..) {
+            self.vals[lit.var.index()] = Val::Undef;
+        }
+
+        if implied && kept.len() == clause.len() {
+            self.active[index] = false;
+            self.stats.clauses_removed += 1;
+        } else if kept.len() < clause.len() {
+            self.stats.clauses_strengthened += 1;
+            self.stats.literals_removed += (clause.len() - kept.len()) as u64;
+            self.formula[index] = kept;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(var: usize, neg: bool) -> Lit {
+        Lit::new(Var::new(var), neg)
+    }
+
+    #[test]
+    fn strengthens_by_conflict_and_false_literals() {
+        // ¬x0 → x1 and ¬x0 → ¬x1, so (x0 ∨ x2) shrinks to (x0)
+        let mut formula = vec![
+            vec![lit(0, false), lit(1, false)],
+            vec![lit(0, false), lit(1, true)],
+            vec![lit(0, false), lit(2, false)],
+        ];
+        let stats = distill(&mut formula, 3, u64::MAX);
+        assert!(formula.contains(&vec![lit(0, false)]));
+        assert!(stats.clauses_strengthened >= 1);
+        assert!(!stats.incomplete);
+    }
+
+    #[test]
+    fn preserves_models() {
+        let satisfies = |formula: &Formula, bits: usize| {
+            formula.iter().all(|clause| clause.iter().any(|l| (bits >> l.var.index() & 1 == 1) != l.neg))
+        };
+        for seed in 0..30 {
+            let (original, num_vars) = crate::gen::random_ksat(8, 30, 3, seed);
+            let mut formula = original.clone();
+            distill(&mut formula, num_vars, u64::MAX);
+            for bits in 0..1 << num_vars {
+                assert_eq!(satisfies(&formula, bits), satisfies(&original, bits), "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn budget_stops_early() {
+        let (mut formula, num_vars) = crate::gen::random_ksat(30, 120, 3, 1);
+        let stats = distill(&mut formula, num_vars, 0);
+        assert!(stats.incomplete);
+        assert_eq!(stats.steps, 0);
+        assert_eq!(formula.len(), 120);
+    }
+}
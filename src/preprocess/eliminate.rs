@@ -0,0 +1,361 @@
+//! Variable elimination and substitution, with model reconstruction
+//!
+//! Unlike the other passes, [`eliminate`] removes variables from the
+//! formula, so it keeps satisfiability but not the models:
+//!
+//! - *Substitution*: binary clauses `(¬a ∨ b) ∧ (a ∨ ¬b)` make `a` and `b`
+//!   equivalent, and every variable of such a class of equivalent literals
+//!   is replaced by one representative.
+//! - *Bounded variable elimination*: a variable `x` is replaced by all
+//!   non-tautological resolvents of its clauses on `x`, as in the original
+//!   Davis-Putnam procedure, whenever there are no more of them than
+//!   clauses removed. Pure literals, which have no resolvents, are
+//!   eliminated this way too.
+//!
+//! A model of the simplified formula leaves the removed variables open, and
+//! may even violate clauses of the original formula through them. The
+//! [`Reconstruction`] returned with the formula completes it to a model of
+//! the original: it keeps the removed clauses, each with the literal of the
+//! removed variable, and going through them in reverse order of removal
+//! makes that literal true wherever the clause is violated.
+//!
+//! Frozen variables, which later clauses or assumptions may mention (see
+//! [`Solver::freeze`](crate::solver::Solver::freeze)), are never removed,
+//! though other variables may be substituted by them.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::preprocess::eliminate::eliminate;
+//! use putnam::solve;
+//! use putnam::solver::dpll::SolveResult;
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! // x0 ↔ x1, x1 ∨ x2, ¬x2 ∨ x3
+//! let formula = vec![vec![!x(0), x(1)], vec![x(0), !x(1)], vec![x(1), x(2)], vec![!x(2), x(3)]];
+//! let mut simplified = formula.clone();
+//! let (stats, reconstruction) = eliminate(&mut simplified, 4, &[Var::new(3)], 1000);
+//! assert_eq!(stats.substituted, 1);
+//! assert!(stats.variables < 4);
+//!
+//! let SolveResult::Sat(model) = solve(&simplified, 4) else { unreachable!() };
+//! let model = reconstruction.reconstruct(&model);
+//! assert!(formula.iter().all(|clause| clause.iter().any(|&lit| model.is_true(lit))));
+//! ```
+
+use std::collections::HashSet;
+
+use crate::types::*;
+
+/// Longest resolvent added; variables that would need longer ones are kept.
+const MAX_RESOLVENT: usize = 16;
+
+/// What an elimination run removed and the size of the result.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EliminationStats {
+    /// Variables replaced by an equivalent literal
+    pub substituted: u64,
+    /// Variables replaced by the resolvents of their clauses
+    pub eliminated: u64,
+    /// Variables occurring in the simplified formula
+    pub variables: u64,
+    /// Clauses of the simplified formula
+    pub clauses: u64,
+    /// Resolvents computed, the unit of the work budget
+    pub steps: u64,
+    /// Whether the budget ran out before every variable was tried
+    pub incomplete: bool,
+}
+
+/// Completes models of a simplified formula to models of the original, see
+/// the [module documentation](self).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Reconstruction {
+    /// Number of variables of the original formula
+    num_vars: usize,
+    /// Removed clauses, each with the literal to make true if it is violated
+    stack: Vec<(Lit, Clause)>,
+}
+
+impl Reconstruction {
+    /// Returns the number of variables of the original formula.
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// Returns the variables removed from the formula, in order of removal.
+    pub(crate) fn removed(&self) -> impl Iterator<Item = Var> + '_ {
+        self.stack.iter().map(|(witness, _)| witness.var)
+    }
+
+    /// Extends the reconstruction by that of a later elimination run on the
+    /// simplified formula; the later run is undone first.
+    pub(crate) fn append(&mut self, later: Reconstruction) {
+        self.num_vars = self.num_vars.max(later.num_vars);
+        self.stack.extend(later.stack);
+    }
+
+    /// Completes `model`, a model of the simplified formula, to a model of
+    /// the original.
+    ///
+    /// Variables `model` leaves unassigned (or does not cover) are set to
+    /// false first, then the removed variables are set as needed; the
+    /// result assigns every variable of the original formula.
+    pub fn reconstruct(&self, model: &Model) -> Model {
+        let mut values: Vec<bool> = Var::range(self.num_vars)
+            .map(|var| var.index() < model.num_vars() && model.value(var) == Val::True)
+            .collect();
+        for (witness, clause) in self.stack.iter().rev() {
+            if !clause.iter().any(|lit| values[lit.var.index()] != lit.neg) {
+                values[witness.var.index()] = !witness.neg;
+            }
+        }
+        Model::from(values)
+    }
+}
+
+/// Index of a literal in the occurrence lists.
+fn code(lit: Lit) -> usize {
+    2 * lit.var.index() + lit.neg as usize
+}
+
+/// Sorts `clause` and drops repeated literals; returns `false` if it is a
+/// tautology.
+fn normalize(clause: &mut Clause) -> bool {
+    clause.sort();
+    clause.dedup();
+    !clause.windows(2).any(|pair| pair[0].var == pair[1].var)
+}
+
+/// Substitutes equivalent literals and eliminates variables in `formula`.
+///
+/// Tautologies are dropped and repeated literals merged first.
+/// Substitution runs to completion; elimination tries the variables in
+/// order of increasing number of resolution pairs until about `budget`
+/// resolvents are computed.
+///
+/// # Arguments
+///
+/// * `formula` - The clauses to simplify
+/// * `num_vars` - The number of variables (raised if the formula uses more)
+/// * `frozen` - Variables to keep
+/// * `budget` - Maximal number of resolvents to compute
+///
+/// # Returns
+///
+/// The changes made with the size of the simplified formula, and the
+/// reconstruction of models of the original
+pub fn eliminate(formula: &mut Formula, num_vars: usize, frozen: &[Var], budget: u64) -> (EliminationStats, Reconstruction) {
+    let num_vars = formula.iter().flatten().map(|l| l.var.index() + 1).max().unwrap_or(0).max(num_vars);
+    let mut is_frozen = vec![false; num_vars];
+    for var in frozen.iter().filter(|var| var.index() < num_vars) {
+        is_frozen[var.index()] = true;
+    }
+    formula.retain_mut(normalize);
+    let mut stats = EliminationStats::default();
+    let mut stack = Vec::new();
+    substitute(formula, num_vars, &is_frozen, &mut stack, &mut stats);
+    resolve_away(formula, num_vars, &is_frozen, budget, &mut stack, &mut stats);
+
+    let mut occurs = vec![false; num_vars];
+    for lit in formula.iter().flatten() {
+        occurs[lit.var.index()] = true;
+    }
+    stats.variables = occurs.iter().filter(|&&occurs| occurs).count() as u64;
+    stats.clauses = formula.len() as u64;
+    (stats, Reconstruction { num_vars, stack })
+}
+
+/// Classes of equivalent literals, as a union-find over the variables where
+/// each variable equals its parent's value exclusive-or its parity.
+struct Classes {
+    parent: Vec<usize>,
+    parity: Vec<bool>,
+}
+
+impl Classes {
+    /// Returns the root of `var`'s class and the parity of `var` to it.
+    fn find(&mut self, var: usize) -> (usize, bool) {
+        let parent = self.parent[var];
+        if parent == var {
+            return (var, false);
+        }
+        let (root, parity) = self.find(parent);
+        self.parent[var] = root;
+        self.parity[var] ^= parity;
+        (root, self.parity[var])
+    }
+
+    /// Records that `a` equals `b` exclusive-or `parity`, keeping frozen
+    /// roots; returns `false` if this contradicts the classes.
+    fn union(&mut self, a: usize, b: usize, parity: bool, frozen: &[bool]) -> bool {
+        let (root_a, parity_a) = self.find(a);
+        let (root_b, parity_b) = self.find(b);
+        let relative = parity_a ^ parity_b ^ parity;
+        if root_a == root_b {
+            return !relative;
+        }
+        let (child, root) = if frozen[root_a] { (root_b, root_a) } else { (root_a, root_b) };
+        self.parent[child] = root;
+        self.parity[child] = relative;
+        true
+    }
+}
+
+/// Replaces every unfrozen variable by the root of its class of equivalent
+/// literals. A variable equivalent to its own negation makes the formula
+/// the empty clause.
+fn substitute(formula: &mut Formula, num_vars: usize, frozen: &[bool], stack: &mut Vec<(Lit, Clause)>, stats: &mut EliminationStats) {
+    let binaries: HashSet<(Lit, Lit)> = formula
+        .iter()
+        .filter_map(|clause| match clause[..] {
+            [a, b] => Some((a, b)),
+            _ => None,
+        })
+        .collect();
+    let mut classes = Classes { parent: (0..num_vars).collect(), parity: vec![false; num_vars] };
+    for &(a, b) in &binaries {
+        // (a ∨ b) ∧ (¬a ∨ ¬b): b is the negation of a. The clauses are
+        // normalized, so the pair is sorted the same way
+        let (first, second) = if !a < !b { (!a, !b) } else { (!b, !a) };
+        if binaries.contains(&(first, second)) && !classes.union(a.var.index(), b.var.index(), !(a.neg ^ b.neg), frozen) {
+            *formula = vec![Vec::new()];
+            return;
+        }
+    }
+    // The literal each substituted variable equals
+    let mut replacement = vec![None; num_vars];
+    for var in Var::range(num_vars).filter(|var| !frozen[var.index()]) {
+        let (root, parity) = classes.find(var.index());
+        if root != var.index() {
+            let equal = Lit::new(Var::new(root), parity);
+            let x = Lit::positive(var);
+            stack.push((x, vec![x, !equal]));
+            stack.push((!x, vec![!x, equal]));
+            replacement[var.index()] = Some(equal);
+            stats.substituted += 1;
+        }
+    }
+    if stats.substituted == 0 {
+        return;
+    }
+    for clause in formula.iter_mut() {
+        for lit in clause.iter_mut() {
+            if let Some(equal) = replacement[lit.var.index()] {
+                *lit = if lit.neg { !equal } else { equal };
+            }
+        }
+    }
+    formula.retain_mut(normalize);
+}
+
+/// Resolves the clauses of `a` and `b` on `var`.
+///
+/// # Returns
+///
+/// The resolvent, or `None` if it is a tautology.
+fn resolvent(a: &Clause, b: &Clause, var: Var) -> Option<Clause> {
+    let mut resolvent: Clause = a.iter().chain(b).copied().filter(|lit| lit.var != var).collect();
+    normalize(&mut resolvent).then_some(resolvent)
+}
+
+/// Eliminates unfrozen variables by resolution while the formula does not
+/// grow, see [`eliminate`].
+fn resolve_away(
+    formula: &mut Formula,
+    num_vars: usize,
+    frozen: &[bool],
+    budget: u64,
+    stack: &mut Vec<(Lit, Clause)>,
+    stats: &mut EliminationStats,
+) {
+    let mut clauses: Vec<Option<Clause>> = std::mem::take(formula).into_iter().map(Some).collect();
+    let mut occurs = vec![Vec::new(); 2 * num_vars];
+    for (index, clause) in clauses.iter().enumerate() {
+        for &lit in clause.iter().flatten() {
+            occurs[code(lit)].push(index);
+        }
+    }
+    let mut order: Vec<Var> = Var::range(num_vars).filter(|var| !frozen[var.index()]).collect();
+    order.sort_by_key(|&var| occurs[code(Lit::positive(var))].len() * occurs[code(Lit::negative(var))].len());
+
+    for var in order {
+        if stats.steps >= budget {
+            stats.incomplete = true;
+            break;
+        }
+        // Occurrence lists keep removed clauses; clauses never change
+        let live = |lit: Lit| -> Vec<usize> { occurs[code(lit)].iter().copied().filter(|&index| clauses[index].is_some()).collect() };
+        let (positive, negative) = (live(Lit::positive(var)), live(Lit::negative(var)));
+        if positive.is_empty() && negative.is_empty() {
+            continue;
+        }
+        let mut resolvents = Vec::new();
+        let mut bounded = true;
+        'pairs: for &p in &positive {
+            for &n in &negative {
+                stats.steps += 1;
+                let (Some(a), Some(b)) = (&clauses[p], &clauses[n]) else { continue };
+                if let Some(resolvent) = resolvent(a, b, var) {
+                    resolvents.push(resolvent);
+                    if resolvents.len() > positive.len() + negative.len() || resolvents.last().is_some_and(|r| r.len() > MAX_RESOLVENT) {
+                        bounded = false;
+                        break 'pairs;
+                    }
+                }
+            }
+        }
+        if !bounded {
+            continue;
+        }
+        for &index in positive.iter().chain(&negative) {
+            let clause = clauses[index].take().unwrap_or_default();
+            let witness = clause.iter().copied().find(|lit| lit.var == var).unwrap_or(Lit::positive(var));
+            stack.push((witness, clause));
+        }
+        for resolvent in resolvents {
+            for &lit in &resolvent {
+                occurs[code(lit)].push(clauses.len());
+            }
+            clauses.push(Some(resolvent));
+        }
+        stats.eliminated += 1;
+    }
+    *formula = clauses.into_iter().flatten().collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll::SolveResult;
+
+    #[test]
+    fn reconstructed_models_satisfy_the_original() {
+        let x = |var| Lit::positive(Var::new(var));
+        let mut eliminated = 0;
+        for seed in 0..40 {
+            let (mut formula, num_vars) = crate::gen::random_ksat(10, 20 + seed as usize % 30, 3, seed);
+            // Equivalences to substitute
+            formula.extend([vec![!x(0), x(1)], vec![x(0), !x(1)], vec![x(2), x(3)], vec![!x(2), !x(3)], vec![x(4)]]);
+            let frozen = [Var::new(1), Var::new(7)];
+            let mut simplified = formula.clone();
+            let (stats, reconstruction) = eliminate(&mut simplified, num_vars, &frozen, u64::MAX);
+            assert!(stats.substituted >= 2, "seed {}", seed);
+            eliminated += stats.eliminated;
+            assert_eq!(stats.clauses, simplified.len() as u64);
+
+            let SolveResult::Sat(model) = crate::solve(&simplified, num_vars) else {
+                assert_eq!(crate::solver::brute::solve(&formula, num_vars), SolveResult::Unsat, "seed {}", seed);
+                continue;
+            };
+            let complete = reconstruction.reconstruct(&model);
+            assert!(formula.iter().all(|clause| clause.iter().any(|&lit| complete.is_true(lit))), "seed {}", seed);
+            // Frozen variables keep the values of the model
+            for var in frozen {
+                assert_eq!(complete.value(var) == Val::True, model.value(var) == Val::True, "seed {}", seed);
+            }
+        }
+        assert!(eliminated > 0);
+    }
+}
@@ -0,0 +1,22 @@
+//! Formula simplification passes
+//!
+//! Each pass rewrites a [`Formula`](crate::types::Formula) into a logically
+//! equivalent one that is smaller or easier to search. Passes can run before
+//! solving or between the queries of a [`Solver`](crate::solver::Solver).
+//! Symmetry breaking and variable elimination are the exceptions: they only
+//! preserve satisfiability, so they belong before solving, and elimination
+//! comes with the means to complete the models.
+//!
+//! # Modules
+//!
+//! - [`distill`]: Clause distillation (asymmetric branching) under a work budget
+//! - [`eliminate`]: Variable substitution and elimination, with model reconstruction
+//! - [`probe`]: Failed literal probing that adds hyper-binary resolvents
+//! - [`symmetry`]: Lex-leader symmetry breaking (equisatisfiable, adds variables)
+//! - [`units`]: Propagation of top-level unit clauses
+
+pub mod distill;
+pub mod eliminate;
+pub mod probe;
+pub mod symmetry;
+pub mod units;
@@ -0,0 +1,297 @@
+//! Failed literal probing with hyper-binary resolution
+//!
+//! Probing assigns a literal `l` on its own and propagates the formula:
+//!
+//! - a conflict means `l` is a *failed literal*: no model makes it true, so
+//!   the unit clause `¬l` is added and propagated at the top level;
+//! - otherwise every literal `m` that propagation made true is implied by
+//!   `l`, and the binary clause `¬l ∨ m` may be added.
+//!
+//! Implications that follow from binary clauses alone are already found by
+//! propagating binary clauses, so only the literals forced by a longer
+//! clause are recorded: the *hyper-binary resolvents* of that clause with
+//! the binary clauses that falsified its other literals. They let later
+//! propagation reach `m` from `l` through one binary clause instead of a
+//! chain of longer ones. New binary clauses take part in the propagation of
+//! the probes that follow.
+//!
+//! Every added clause is implied by the formula, so models are unaffected.
+//! The work is bounded by a budget of propagation steps (clause visits);
+//! probing stops once it is spent.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::preprocess::probe::probe;
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! // x0 implies x1 and x2, which together imply x3
+//! let mut formula = vec![vec![!x(0), x(1)], vec![!x(0), x(2)], vec![!x(1), !x(2), x(3)]];
+//! let stats = probe(&mut formula, 4, u64::MAX);
+//!
+//! assert!(formula.contains(&vec![!x(0), x(3)]));
+//! assert_eq!((stats.binaries_added, stats.failed_literals), (1, 0));
+//! ```
+
+use std::collections::HashSet;
+
+use crate::types::*;
+
+/// What a probing run changed and the work it did.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProbeStats {
+    /// Literals assigned and propagated on their own
+    pub probes: u64,
+    /// Probes that ended in a conflict, whose complements were added as units
+    pub failed_literals: u64,
+    /// Binary clauses added from the implications of probes
+    pub binaries_added: u64,
+    /// Clause visits during propagation, the unit of the work budget
+    pub steps: u64,
+    /// Whether the budget ran out before every literal was probed
+    pub incomplete: bool,
+}
+
+/// Index of a literal in the occurrence lists.
+fn code(lit: Lit) -> usize {
+    2 * lit.var.index() + lit.neg as usize
+}
+
+/// Probes both literals of every variable of `formula`, adding the binary
+/// clauses and units found to it.
+///
+/// Variables are probed in increasing order, positive literal first, until
+/// about `budget` propagation steps are spent. The clauses of the formula
+/// keep their order and the new clauses are appended; if the units make
+/// the formula unsatisfiable, the empty clause is appended.
+///
+/// # Arguments
+///
+/// * `formula` - The clauses to extend
+/// * `num_vars` - The number of variables (raised if the formula uses more)
+/// * `budget` - Maximal number of propagation steps
+///
+/// # Returns
+///
+/// The changes made and the work spent
+pub fn probe(formula: &mut Formula, num_vars: usize, budget: u64) -> ProbeStats {
+    let num_vars = formula.iter().flatten().map(|l| l.var.index() + 1).max().unwrap_or(0).max(num_vars);
+    let mut prober = Prober {
+        occurs: vec![Vec::new(); 2 * num_vars],
+        binaries: HashSet::new(),
+        formula,
+        vals: vec![Val::Undef; num_vars],
+        trail: Vec::new(),
+        forced_by_long: Vec::new(),
+        stats: ProbeStats::default(),
+    };
+    for index in 0..prober.formula.len() {
+        prober.index(index);
+    }
+    if prober.formula.iter().any(|clause| clause.is_empty()) {
+        return prober.stats;
+    }
+    // Top-level units, so that probes start from them
+    let units: Vec<Lit> = prober
+        .formula
+        .iter()
+        .filter_map(|clause| match clause[..] {
+            [unit] => Some(unit),
+            _ => None,
+        })
+        .collect();
+    for unit in units {
+        if !prober.fix(unit) {
+            return prober.stats;
+        }
+    }
+
+    'probing: for var in Var::range(num_vars) {
+        for lit in [Lit::positive(var), Lit::negative(var)] {
+            if prober.stats.steps >= budget {
+                prober.stats.incomplete = true;
+                break 'probing;
+            }
+            if prober.vals[var.index()] != Val::Undef || prober.occurs[code(!lit)].is_empty() {
+                continue;
+            }
+            if !prober.probe(lit) {
+                break 'probing;
+            }
+        }
+    }
+    prober.stats
+}
+
+/// State of one probing run.
+struct Prober<'a> {
+    formula: &'a mut Formula,
+    /// Clauses containing each literal
+    occurs: Vec<Vec<usize>>,
+    /// Binary clauses of the formula, with their literals sorted
+    binaries: HashSet<(Lit, Lit)>,
+    /// Current assignment: top-level units, then the probe
+    vals: Vec<Val>,
+    /// Literals made true, in order
+    trail: Vec<Lit>,
+    /// Whether each literal of the trail was forced by a clause of more
+    /// than two literals
+    forced_by_long: Vec<bool>,
+    stats: ProbeStats,
+}
+
+impl Prober<'_> {
+    fn value(&self, lit: Lit) -> Val {
+        match (self.vals[lit.var.index()], lit.neg) {
+            (Val::Undef, _) => Val::Undef,
+            (Val::True, false) | (Val::False, true) => Val::True,
+            _ => Val::False,
+        }
+    }
+
+    fn assign(&mut self, lit: Lit, forced_by_long: bool) {
+        self.vals[lit.var.index()] = if lit.neg { Val::False } else { Val::True };
+        self.trail.push(lit);
+        self.forced_by_long.push(forced_by_long);
+    }
+
+    /// Records the clause at `index` in the occurrence lists.
+    fn index(&mut self, index: usize) {
+        let clause = &self.formula[index];
+        for &lit in clause {
+            self.occurs[code(lit)].push(index);
+        }
+        if let [a, b] = clause[..] {
+            self.binaries.insert((a.min(b), a.max(b)));
+        }
+    }
+
+    /// Appends `clause` to the formula.
+    fn add(&mut self, clause: Clause) {
+        self.formula.push(clause);
+        self.index(self.formula.len() - 1);
+    }
+
+    /// Propagates the trail from `start`; returns `false` on a conflict.
+    fn propagate(&mut self, mut start: usize) -> bool {
+        while start < self.trail.len() {
+            let lit = self.trail[start];
+            start += 1;
+            for position in 0..self.occurs[code(!lit)].len() {
+                let index = self.occurs[code(!lit)][position];
+                self.stats.steps += 1;
+                let mut unit = None;
+                let mut open = 0;
+                for &other in &self.formula[index] {
+                    match self.value(other) {
+                        Val::True => {
+                            open = usize::MAX;
+                            break;
+                        }
+                        Val::Undef => {
+                            open += 1;
+                            unit = Some(other);
+                        }
+                        Val::False => {}
+                    }
+                }
+                match (open, unit) {
+                    (0, _) => return false,
+                    (1, Some(unit)) => self.assign(unit, self.formula[index].len() > 2),
+                    _ => {}
+                }
+            }
+        }
+        true
+    }
+
+    /// Adds and propagates the top-level unit `lit`; returns `false` (after
+    /// adding the empty clause) if the formula turns out unsatisfiable.
+    fn fix(&mut self, lit: Lit) -> bool {
+        match self.value(lit) {
+            Val::True => return true,
+            Val::False => {
+                self.formula.push(Vec::new());
+                return false;
+            }
+            Val::Undef => {}
+        }
+        let start = self.trail.len();
+        self.assign(lit, false);
+        if !self.propagate(start) {
+            self.formula.push(Vec::new());
+            return false;
+        }
+        true
+    }
+
+    /// Probes `lit`; returns `false` if the formula turned out
+    /// unsatisfiable.
+    fn probe(&mut self, lit: Lit) -> bool {
+        self.stats.probes += 1;
+        let start = self.trail.len();
+        self.assign(lit, false);
+        let consistent = self.propagate(start);
+        let implied: Vec<Lit> = (start + 1..self.trail.len())
+            .filter(|&position| self.forced_by_long[position])
+            .map(|position| self.trail[position])
+            .collect();
+        for undone in self.trail.drain(start..) {
+            self.vals[undone.var.index()] = Val::Undef;
+        }
+        self.forced_by_long.truncate(start);
+
+        if !consistent {
+            self.stats.failed_literals += 1;
+            self.add(vec![!lit]);
+            return self.fix(!lit);
+        }
+        for implied in implied {
+            let (a, b) = (!lit, implied);
+            if self.binaries.contains(&(a.min(b), a.max(b))) {
+                continue;
+            }
+            self.stats.binaries_added += 1;
+            self.add(vec![a, b]);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preserves_models() {
+        let satisfies = |formula: &Formula, bits: usize| {
+            formula.iter().all(|clause| clause.iter().any(|l| (bits >> l.var.index() & 1 == 1) != l.neg))
+        };
+        for seed in 0..30 {
+            let (original, num_vars) = crate::gen::random_ksat(8, 34, 3, seed);
+            let mut formula = original.clone();
+            let stats = probe(&mut formula, num_vars, u64::MAX);
+            assert!(!stats.incomplete);
+            assert!(formula.iter().skip(original.len()).all(|clause| clause.len() <= 2));
+            for bits in 0..1 << num_vars {
+                assert_eq!(satisfies(&formula, bits), satisfies(&original, bits), "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn failed_literals_become_units() {
+        let x = |var| Lit::positive(Var::new(var));
+        // x0 implies both x1 and ¬x1
+        let mut formula = vec![vec![!x(0), x(1), x(2)], vec![!x(0), !x(2)], vec![!x(0), !x(1)], vec![x(0), x(3)]];
+        let stats = probe(&mut formula, 4, u64::MAX);
+        assert_eq!(stats.failed_literals, 1);
+        assert!(formula.contains(&vec![!x(0)]));
+
+        let (mut formula, num_vars) = crate::gen::random_ksat(20, 90, 3, 3);
+        let stats = probe(&mut formula, num_vars, 0);
+        assert!(stats.incomplete);
+        assert_eq!((stats.probes, formula.len()), (0, 90));
+    }
+}
@@ -0,0 +1,495 @@
+//! Symmetry breaking
+//!
+//! A *symmetry* of a formula is a permutation of its literals that maps
+//! complementary literals to complementary literals and the clause set onto
+//! itself. Symmetric assignments are equally (un)satisfying, so a search
+//! that explores one of them wastes its effort on all the others: the
+//! pigeonhole formulas, whose pigeons and holes can be renamed at will, are
+//! hopeless for plain DPLL.
+//!
+//! [`find_symmetries`] detects symmetries syntactically, BreakID-style: the
+//! formula becomes a colored graph (one vertex per literal and per clause,
+//! literals joined to their complement and to the clauses containing them)
+//! whose automorphisms are the symmetries, and a small individualization
+//! and refinement search collects generators of its automorphism group.
+//! [`break_symmetries`] then appends, for each generator `σ`, *lex-leader*
+//! clauses requiring the assignment to be no greater than its image under
+//! `σ`, the variables compared in index order. Every class of symmetric
+//! assignments keeps its least member, so satisfiability is preserved.
+//!
+//! Unlike the other passes, this one removes models: the result is
+//! equisatisfiable, not equivalent. The lex-leader encoding also introduces
+//! auxiliary variables numbered after the original ones; models of the
+//! result restricted to the original variables satisfy the original
+//! formula.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::preprocess::symmetry::break_symmetries;
+//! use putnam::solver::dpll::SolveResult;
+//!
+//! let (mut formula, num_vars, _) = putnam::gen::families::pigeonhole(4);
+//! let stats = break_symmetries(&mut formula, num_vars, u64::MAX);
+//!
+//! assert!(stats.generators > 0);
+//! assert!(stats.num_vars > num_vars);
+//! assert_eq!(putnam::solve(&formula, stats.num_vars), SolveResult::Unsat);
+//! ```
+
+use crate::types::*;
+
+/// Largest number of variables compared by the lex-leader clauses of one
+/// generator; longer comparisons are cut to their prefix, which is implied.
+pub const MAX_LEX_LENGTH: usize = 50;
+
+/// What a symmetry breaking pass found and added.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SymmetryStats {
+    /// Generators of the symmetry group that were found
+    pub generators: u64,
+    /// Lex-leader clauses appended to the formula
+    pub clauses_added: u64,
+    /// The number of variables including the auxiliary ones
+    pub num_vars: usize,
+    /// Edge visits during graph refinement, the unit of the work budget
+    pub steps: u64,
+    /// Whether the budget ran out before the search finished
+    pub incomplete: bool,
+}
+
+/// A symmetry of a formula: a permutation of its literals.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symmetry {
+    /// Image of the positive literal of each variable
+    images: Vec<Lit>,
+}
+
+impl Symmetry {
+    /// Returns the image of `lit`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the variable of `lit` is beyond the formula the symmetry
+    /// was found in.
+    pub fn image(&self, lit: Lit) -> Lit {
+        let image = self.images[lit.var.index()];
+        if lit.neg { !image } else { image }
+    }
+
+    /// Returns the variables the symmetry moves (including those mapped to
+    /// their own complement), in index order.
+    pub fn support(&self) -> Vec<Var> {
+        self.images
+            .iter()
+            .enumerate()
+            .map(|(index, &image)| (Var::new(index), image))
+            .filter(|&(var, image)| image != Lit::positive(var))
+            .map(|(var, _)| var)
+            .collect()
+    }
+}
+
+/// Index of a literal vertex.
+fn code(lit: Lit) -> usize {
+    2 * lit.var.index() + lit.neg as usize
+}
+
+/// Literal of a literal vertex.
+fn literal(vertex: usize) -> Lit {
+    Lit::new(Var::new(vertex / 2), vertex % 2 == 1)
+}
+
+/// A partition of the vertices on the leftmost path of the search.
+struct Level {
+    /// Color of each vertex
+    colors: Vec<usize>,
+    /// Number of vertices of each color
+    histogram: Vec<usize>,
+    /// The cell split at this level, `None` once the partition is discrete
+    cell: Option<usize>,
+    /// The vertex individualized at this level
+    vertex: usize,
+}
+
+/// The colored graph of a formula and the state of the automorphism search.
+struct Search {
+    /// Neighbors of each vertex: literals `0..2 * num_vars`, then clauses
+    adjacency: Vec<Vec<usize>>,
+    /// Number of literal vertices
+    literals: usize,
+    /// The normalized clauses, sorted and without duplicates, for checking
+    /// candidate automorphisms
+    clauses: Vec<Clause>,
+    steps: u64,
+    budget: u64,
+}
+
+impl Search {
+    fn new(formula: &Formula, num_vars: usize, budget: u64) -> Self {
+        let mut clauses: Vec<Clause> = formula
+            .iter()
+            .map(|clause| {
+                let mut clause = clause.clone();
+                clause.sort();
+                clause.dedup();
+                clause
+            })
+            .collect();
+        clauses.sort();
+        clauses.dedup();
+        let literals = 2 * num_vars;
+        let mut adjacency = vec![Vec::new(); literals + clauses.len()];
+        for var in 0..num_vars {
+            adjacency[2 * var].push(2 * var + 1);
+            adjacency[2 * var + 1].push(2 * var);
+        }
+        for (index, clause) in clauses.iter().enumerate() {
+            for &lit in clause {
+                adjacency[literals + index].push(code(lit));
+                adjacency[code(lit)].push(literals + index);
+            }
+        }
+        Search { adjacency, literals, clauses, steps: 0, budget }
+    }
+
+    /// The initial coloring: literals and clauses apart, and the literals
+    /// of variables without occurrences fixed, so that no generator merely
+    /// swaps unused variables.
+    fn initial_colors(&self) -> Vec<usize> {
+        let mut next = 2;
+        (0..self.adjacency.len())
+            .map(|vertex| {
+                if vertex >= self.literals {
+                    1
+                } else if self.adjacency[vertex].len() > 1 || self.adjacency[vertex ^ 1].len() > 1 {
+                    0
+                } else {
+                    next += 1;
+                    next - 1
+                }
+            })
+            .collect()
+    }
+
+    /// Refines `colors` to an equitable partition: vertices of the same
+    /// color end up with the same number of neighbors of each color.
+    ///
+    /// Colors are renumbered canonically (by sorted signatures), so
+    /// isomorphic inputs get corresponding colors. Returns the number of
+    /// colors.
+    fn refine(&mut self, colors: &mut [usize]) -> usize {
+        let mut count = {
+            let mut distinct = colors.to_vec();
+            distinct.sort_unstable();
+            distinct.dedup();
+            distinct.len()
+        };
+        let mut order: Vec<usize> = (0..colors.len()).collect();
+        loop {
+            let signatures: Vec<(usize, Vec<usize>)> = self
+                .adjacency
+                .iter()
+                .enumerate()
+                .map(|(vertex, neighbors)| {
+                    let mut around: Vec<usize> = neighbors.iter().map(|&n| colors[n]).collect();
+                    around.sort_unstable();
+                    (colors[vertex], around)
+                })
+                .collect();
+            self.steps += self.adjacency.len() as u64 + signatures.iter().map(|(_, around)| around.len() as u64).sum::<u64>();
+            order.sort_by(|&a, &b| signatures[a].cmp(&signatures[b]));
+            let mut color = 0;
+            for (i, &vertex) in order.iter().enumerate() {
+                if i > 0 && signatures[vertex] != signatures[order[i - 1]] {
+                    color += 1;
+                }
+                colors[vertex] = color;
+            }
+            if color + 1 == count {
+                return count;
+            }
+            count = color + 1;
+        }
+    }
+
+    /// Gives `vertex` a color of its own and refines.
+    fn individualize(&mut self, colors: &[usize], vertex: usize) -> Vec<usize> {
+        let mut colors = colors.to_vec();
+        colors[vertex] = colors.len();
+        self.refine(&mut colors);
+        colors
+    }
+
+    /// Returns the histogram of `colors` and the first color with several
+    /// literal vertices.
+    fn cells(&self, colors: &[usize]) -> (Vec<usize>, Option<usize>) {
+        let mut histogram = vec![0; colors.len()];
+        for &color in colors {
+            histogram[color] += 1;
+        }
+        let cell = (0..self.literals).map(|vertex| colors[vertex]).filter(|&color| histogram[color] > 1).min();
+        (histogram, cell)
+    }
+
+    /// Builds the leftmost path: individualizes the first vertex of the
+    /// first non-singleton literal cell until the partition is discrete.
+    fn leftmost_path(&mut self) -> Option<Vec<Level>> {
+        let mut colors = self.initial_colors();
+        self.refine(&mut colors);
+        let mut path = Vec::new();
+        loop {
+            if self.steps >= self.budget {
+                return None;
+            }
+            let (histogram, cell) = self.cells(&colors);
+            let vertex = cell.map_or(0, |cell| colors.iter().position(|&color| color == cell).expect("cell is not empty"));
+            let next = cell.map(|_| self.individualize(&colors, vertex));
+            path.push(Level { colors, histogram, cell, vertex });
+            match next {
+                Some(next) => colors = next,
+                None => return Some(path),
+            }
+        }
+    }
+
+    /// Looks for an automorphism mapping the partition of level `depth` of
+    /// the leftmost path to `colors`, individualizing the vertices of the
+    /// corresponding cells in turn.
+    fn extend(&mut self, path: &[Level], depth: usize, colors: Vec<usize>) -> Option<Vec<usize>> {
+        let level = &path[depth];
+        if self.steps >= self.budget || self.cells(&colors).0 != level.histogram {
+            return None;
+        }
+        let Some(cell) = level.cell else {
+            let mut vertex_of = vec![0; colors.len()];
+            for (vertex, &color) in colors.iter().enumerate() {
+                vertex_of[color] = vertex;
+            }
+            let permutation: Vec<usize> = level.colors.iter().map(|&color| vertex_of[color]).collect();
+            return self.is_automorphism(&permutation).then_some(permutation);
+        };
+        let candidates: Vec<usize> = (0..colors.len()).filter(|&vertex| colors[vertex] == cell).collect();
+        for vertex in candidates {
+            let next = self.individualize(&colors, vertex);
+            if let Some(permutation) = self.extend(path, depth + 1, next) {
+                return Some(permutation);
+            }
+        }
+        None
+    }
+
+    /// Checks that a vertex permutation maps the clause set onto itself.
+    fn is_automorphism(&self, permutation: &[usize]) -> bool {
+        (0..self.literals).all(|vertex| permutation[vertex] < self.literals && permutation[vertex ^ 1] == permutation[vertex] ^ 1)
+            && self.clauses.iter().all(|clause| {
+                let mut image: Clause = clause.iter().map(|&lit| literal(permutation[code(lit)])).collect();
+                image.sort();
+                self.clauses.binary_search(&image).is_ok()
+            })
+    }
+}
+
+/// Finds generators of the symmetry group of `formula`.
+///
+/// The generators come from the stabilizer chain of the leftmost path of
+/// the search, deepest level first, so the first ones tend to move few
+/// variables. Without the budget the generators found generate the whole
+/// group of syntactic symmetries in most practical cases; the search gives
+/// up on a level after about `budget` edge visits in total.
+///
+/// # Arguments
+///
+/// * `formula` - The clauses
+/// * `num_vars` - The number of variables (raised if the formula uses more)
+/// * `budget` - Maximal number of edge visits during refinement
+///
+/// # Returns
+///
+/// The generators found and the work spent (with `incomplete` set if the
+/// budget ran out)
+pub fn find_symmetries(formula: &Formula, num_vars: usize, budget: u64) -> (Vec<Symmetry>, SymmetryStats) {
+    let num_vars = formula.iter().flatten().map(|l| l.var.index() + 1).max().unwrap_or(0).max(num_vars);
+    let mut search = Search::new(formula, num_vars, budget);
+    let mut stats = SymmetryStats { num_vars, ..Default::default() };
+    let mut generators = Vec::new();
+    let Some(path) = search.leftmost_path() else {
+        stats.steps = search.steps;
+        stats.incomplete = true;
+        return (generators, stats);
+    };
+    // Orbits of the literal vertices under the generators found so far,
+    // which all fix the vertices individualized above the current level
+    let mut orbit: Vec<usize> = (0..search.literals).collect();
+    fn find(orbit: &mut [usize], mut vertex: usize) -> usize {
+        while orbit[vertex] != vertex {
+            orbit[vertex] = orbit[orbit[vertex]];
+            vertex = orbit[vertex];
+        }
+        vertex
+    }
+    'levels: for depth in (0..path.len()).rev() {
+        let Some(cell) = path[depth].cell else { continue };
+        let target = path[depth].vertex;
+        let candidates: Vec<usize> = (0..search.literals).filter(|&vertex| path[depth].colors[vertex] == cell).collect();
+        for vertex in candidates {
+            if find(&mut orbit, vertex) == find(&mut orbit, target) {
+                continue;
+            }
+            if search.steps >= search.budget {
+                stats.incomplete = true;
+                break 'levels;
+            }
+            let colors = search.individualize(&path[depth].colors, vertex);
+            if let Some(permutation) = search.extend(&path, depth + 1, colors) {
+                for (from, &to) in permutation[..search.literals].iter().enumerate() {
+                    let (a, b) = (find(&mut orbit, from), find(&mut orbit, to));
+                    orbit[a] = b;
+                }
+                generators.push(Symmetry { images: (0..num_vars).map(|var| literal(permutation[2 * var])).collect() });
+            }
+        }
+    }
+    stats.incomplete |= search.steps >= search.budget;
+    stats.steps = search.steps;
+    stats.generators = generators.len() as u64;
+    (generators, stats)
+}
+
+/// Returns the lex-leader clauses of `symmetry`, which require the
+/// assignment of its support to be lexicographically no greater than its
+/// image. Auxiliary variables are numbered from `*num_vars`, which is
+/// advanced past them.
+///
+/// The auxiliary variable `p_i` is forced true while the first `i` compared
+/// variables equal their images; each comparison `x_i ≤ σ(x_i)` is then
+/// only required under `p_{i-1}`.
+fn lex_leader(symmetry: &Symmetry, num_vars: &mut usize) -> Formula {
+    let mut clauses = Vec::new();
+    let mut equal: Option<Lit> = None;
+    let support = symmetry.support();
+    let compared = &support[..support.len().min(MAX_LEX_LENGTH)];
+    for (i, &var) in compared.iter().enumerate() {
+        let (x, y) = (Lit::positive(var), symmetry.image(Lit::positive(var)));
+        let guard: Vec<Lit> = equal.map(|p| !p).into_iter().collect();
+        if y == !x {
+            // x ≤ ¬x forces x false, and the prefix can no longer be equal
+            clauses.push([guard.as_slice(), &[!x]].concat());
+            break;
+        }
+        clauses.push([guard.as_slice(), &[!x, y]].concat());
+        if i + 1 < compared.len() {
+            let p = Lit::positive(Var::new(*num_vars));
+            *num_vars += 1;
+            clauses.push([guard.as_slice(), &[!x, !y, p]].concat());
+            clauses.push([guard.as_slice(), &[x, y, p]].concat());
+            equal = Some(p);
+        }
+    }
+    clauses
+}
+
+/// Appends lex-leader symmetry breaking clauses to `formula`.
+///
+/// Finds generators with [`find_symmetries`] and adds the lex-leader
+/// clauses of each, comparing at most [`MAX_LEX_LENGTH`] variables. The
+/// result is equisatisfiable with the input, and every model of it is a
+/// model of the input on the original variables.
+///
+/// # Arguments
+///
+/// * `formula` - The clauses to extend
+/// * `num_vars` - The number of variables (raised if the formula uses more)
+/// * `budget` - Maximal number of edge visits during the symmetry search
+///
+/// # Returns
+///
+/// The symmetries found, the clauses added and the new number of variables
+pub fn break_symmetries(formula: &mut Formula, num_vars: usize, budget: u64) -> SymmetryStats {
+    let (generators, mut stats) = find_symmetries(formula, num_vars, budget);
+    for symmetry in &generators {
+        let clauses = lex_leader(symmetry, &mut stats.num_vars);
+        stats.clauses_added += clauses.len() as u64;
+        formula.extend(clauses);
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::brute;
+    use crate::solver::dpll::SolveResult;
+
+    fn x(var: usize) -> Lit {
+        Lit::positive(Var::new(var))
+    }
+
+    #[test]
+    fn finds_swaps_and_phase_flips() {
+        // x0 and x1 are interchangeable; x2 and ¬x2 play the same role
+        let formula = vec![vec![x(0), x(1)], vec![x(2), x(3)], vec![!x(2), x(3)]];
+        let (generators, stats) = find_symmetries(&formula, 4, u64::MAX);
+        assert!(!stats.incomplete);
+        let mut supports: Vec<Vec<Var>> = generators.iter().map(Symmetry::support).collect();
+        supports.sort();
+        assert_eq!(supports, vec![vec![Var::new(0), Var::new(1)], vec![Var::new(2)]]);
+        for symmetry in &generators {
+            for clause in &formula {
+                let mut image: Clause = clause.iter().map(|&lit| symmetry.image(lit)).collect();
+                image.sort();
+                assert!(formula.iter().any(|c| {
+                    let mut c = c.clone();
+                    c.sort();
+                    c == image
+                }));
+            }
+        }
+
+        // Breaking leaves one member of each class of models
+        let mut broken = formula.clone();
+        let stats = break_symmetries(&mut broken, 4, u64::MAX);
+        let count = |formula: &Formula, num_vars| {
+            (0..1usize << 4)
+                .filter(|row| {
+                    let mut fixed = formula.clone();
+                    fixed.extend((0..4).map(|i| vec![Lit::new(Var::new(i), (row >> i) & 1 == 0)]));
+                    matches!(brute::solve(&fixed, num_vars), SolveResult::Sat(_))
+                })
+                .count()
+        };
+        assert_eq!(count(&formula, 4), 6);
+        assert_eq!(count(&broken, stats.num_vars), 2);
+    }
+
+    #[test]
+    fn breaking_preserves_answers() {
+        let (mut formula, num_vars, _) = crate::gen::families::pigeonhole(5);
+        let stats = break_symmetries(&mut formula, num_vars, u64::MAX);
+        assert!(stats.generators >= 2);
+        assert_eq!(crate::solve(&formula, stats.num_vars), SolveResult::Unsat);
+
+        // Five pigeons in five holes: still satisfiable, by a model of the
+        // original formula
+        let (original, num_vars) = {
+            let (mut formula, _, _) = crate::gen::families::pigeonhole(5);
+            formula.retain(|clause| !clause.iter().any(|lit| lit.var.index() >= 25));
+            (formula, 25)
+        };
+        let mut formula = original.clone();
+        let stats = break_symmetries(&mut formula, num_vars, u64::MAX);
+        assert!(stats.generators > 0);
+        match crate::solve(&formula, stats.num_vars) {
+            SolveResult::Sat(model) => assert!(original.iter().all(|clause| clause.iter().any(|&lit| model.is_true(lit)))),
+            SolveResult::Unsat => panic!("symmetry breaking lost every model"),
+        }
+    }
+
+    #[test]
+    fn budget_stops_early() {
+        let (mut formula, num_vars, _) = crate::gen::families::pigeonhole(5);
+        let stats = break_symmetries(&mut formula, num_vars, 10);
+        assert!(stats.incomplete);
+        assert_eq!(stats.clauses_added, 0);
+    }
+}
@@ -0,0 +1,179 @@
+//! Top-level unit propagation
+//!
+//! A unit clause fixes its literal in every model. [`simplify_units`]
+//! propagates the unit clauses of a formula to a fixpoint, removes the
+//! clauses they satisfy and the literals they falsify, and keeps one unit
+//! clause per fixed variable so that models still assign it. A formula
+//! whose units contradict each other is replaced by the empty clause.
+//!
+//! Structured instances often start with many units (fixed inputs, asserted
+//! outputs); removing them once up front spares the search from carrying
+//! them through every branch.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::preprocess::units::simplify_units;
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! // x0 forces x1, which satisfies the last clause and shortens the third
+//! let mut formula = vec![vec![x(0)], vec![!x(0), x(1)], vec![!x(1), x(2), x(3)], vec![x(1), x(2)]];
+//! let stats = simplify_units(&mut formula, 4);
+//!
+//! assert_eq!(formula, vec![vec![x(0)], vec![x(1)], vec![x(2), x(3)]]);
+//! assert_eq!((stats.units, stats.clauses_removed, stats.literals_removed), (2, 2, 1));
+//! ```
+
+use crate::types::*;
+
+/// What a unit propagation pass changed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UnitStats {
+    /// Variables fixed by unit clauses
+    pub units: u64,
+    /// Clauses removed because a fixed literal satisfies them
+    pub clauses_removed: u64,
+    /// Falsified literals removed from the remaining clauses
+    pub literals_removed: u64,
+    /// Whether the units contradict each other (or the formula already
+    /// contained the empty clause)
+    pub conflict: bool,
+}
+
+/// Index of a literal in the occurrence lists.
+fn code(lit: Lit) -> usize {
+    2 * lit.var.index() + lit.neg as usize
+}
+
+/// Value of `lit` under the fixed variables.
+fn value(values: &[Val], lit: Lit) -> Val {
+    match (values[lit.var.index()], lit.neg) {
+        (Val::Undef, _) => Val::Undef,
+        (Val::True, false) | (Val::False, true) => Val::True,
+        _ => Val::False,
+    }
+}
+
+/// Fixes `lit`; returns `false` if it is already false.
+fn fix(values: &mut [Val], trail: &mut Vec<Lit>, lit: Lit) -> bool {
+    match value(values, lit) {
+        Val::True => true,
+        Val::False => false,
+        Val::Undef => {
+            values[lit.var.index()] = if lit.neg { Val::False } else { Val::True };
+            trail.push(lit);
+            true
+        }
+    }
+}
+
+/// Propagates the unit clauses of `formula` in place.
+///
+/// Afterwards the formula starts with one unit clause per fixed variable,
+/// in the order they were derived, followed by the other clauses in their
+/// original order without falsified literals. On a conflict it is just the
+/// empty clause. Either way it is logically equivalent to the input.
+///
+/// # Arguments
+///
+/// * `formula` - The clauses to simplify
+/// * `num_vars` - The number of variables (raised if the formula uses more)
+///
+/// # Returns
+///
+/// The changes made
+pub fn simplify_units(formula: &mut Formula, num_vars: usize) -> UnitStats {
+    let num_vars = formula.iter().flatten().map(|lit| lit.var.index() + 1).fold(num_vars, usize::max);
+    let mut values = vec![Val::Undef; num_vars];
+    let mut trail = Vec::new();
+    let mut stats = UnitStats { conflict: formula.iter().any(|clause| clause.is_empty()), ..UnitStats::default() };
+
+    // Occurrence lists and the number of literals not yet false per clause
+    let mut occurs = vec![Vec::new(); 2 * num_vars];
+    for (index, clause) in formula.iter().enumerate() {
+        for &lit in clause {
+            occurs[code(lit)].push(index);
+        }
+        if let [lit] = clause[..] {
+            stats.conflict |= !fix(&mut values, &mut trail, lit);
+        }
+    }
+    let mut open: Vec<usize> = formula.iter().map(|clause| clause.len()).collect();
+
+    let mut head = 0;
+    while head < trail.len() && !stats.conflict {
+        let falsified = !trail[head];
+        head += 1;
+        for &index in &occurs[code(falsified)] {
+            open[index] -= 1;
+            let clause = &formula[index];
+            if open[index] > 1 || clause.iter().any(|&l| value(&values, l) == Val::True) {
+                continue;
+            }
+            match clause.iter().find(|&&l| value(&values, l) == Val::Undef) {
+                Some(&unit) => {
+                    fix(&mut values, &mut trail, unit);
+                }
+                None => stats.conflict = true,
+            }
+        }
+    }
+
+    if stats.conflict {
+        stats.clauses_removed = formula.len() as u64;
+        *formula = vec![Vec::new()];
+        return stats;
+    }
+    stats.units = trail.len() as u64;
+    let clauses = std::mem::take(formula);
+    formula.extend(trail.iter().map(|&lit| vec![lit]));
+    for mut clause in clauses {
+        if clause.iter().any(|&l| value(&values, l) == Val::True) {
+            if clause.len() > 1 {
+                stats.clauses_removed += 1;
+            }
+            continue;
+        }
+        let length = clause.len();
+        clause.retain(|&l| value(&values, l) == Val::Undef);
+        stats.literals_removed += (length - clause.len()) as u64;
+        formula.push(clause);
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::brute;
+    use crate::solver::dpll::SolveResult;
+
+    #[test]
+    fn equivalent_to_the_input() {
+        let x = |var| Lit::positive(Var::new(var));
+        for seed in 0..40 {
+            let (mut formula, num_vars) = crate::gen::random_ksat(8, 20, 3, seed);
+            formula.push(vec![x(seed as usize % 8)]);
+            formula.push(vec![!x((seed as usize + 3) % 8)]);
+            let mut simplified = formula.clone();
+            simplify_units(&mut simplified, num_vars);
+            assert!(simplified.len() <= formula.len());
+            // Same models: compare every assignment through unit clauses
+            for bits in 0..1u32 << num_vars {
+                let cube: Formula = (0..num_vars).map(|v| vec![Lit { var: Var::new(v), neg: bits >> v & 1 == 0 }]).collect();
+                let with = |f: &Formula| matches!(brute::solve(&[f.clone(), cube.clone()].concat(), num_vars), SolveResult::Sat(_));
+                assert_eq!(with(&formula), with(&simplified), "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn contradicting_units() {
+        let x = |var| Lit::positive(Var::new(var));
+        let mut formula = vec![vec![x(0)], vec![!x(0), x(1)], vec![!x(1), x(2)], vec![x(3), x(4)], vec![!x(2)]];
+        let stats = simplify_units(&mut formula, 5);
+        assert!(stats.conflict);
+        assert_eq!(formula, vec![Vec::<Lit>::new()]);
+    }
+}
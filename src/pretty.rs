@@ -0,0 +1,265 @@
+//! Human-readable rendering of formulas
+//!
+//! [`Pretty`] is a `Display` adapter that renders a CNF formula as infix
+//! logic, e.g. `(x0 ∨ ¬x1) ∧ x2`. Variables are printed as `x<var>` (the
+//! same 0-based naming used by [`Model`](crate::types::Model)'s `Display`)
+//! unless a [`SymbolTable`] provides a name. Unit clauses are printed
+//! without parentheses, the empty clause as `⊥` and the empty formula as `⊤`.
+//!
+//! [`Latex`] renders the same structure as LaTeX math for papers and
+//! teaching material.
+//!
+//! # Example
+//!
+//! ```
+//! use putnam::pretty::Pretty;
+//! use putnam::symbols::SymbolTable;
+//! use putnam::types::{Lit, Var};
+//!
+//! let formula = vec![
+//!     vec![Lit::positive(Var::new(0)), Lit::negative(Var::new(1))],
+//!     vec![Lit::positive(Var::new(2))],
+//! ];
+//! assert_eq!(Pretty::new(&formula).to_string(), "(x0 ∨ ¬x1) ∧ x2");
+//!
+//! let mut symbols = SymbolTable::new();
+//! symbols.bind(Var::new(2), "done");
+//! let text = Pretty::new(&formula).symbols(&symbols).width(12).to_string();
+//! assert_eq!(text, "(x0 ∨ ¬x1)\n∧ done");
+//! ```
+
+use std::fmt;
+
+use crate::symbols::SymbolTable;
+use crate::types::*;
+
+/// `Display` adapter rendering a formula as infix logic.
+///
+/// Created with [`Pretty::new`] and configured with [`symbols`](Pretty::symbols)
+/// and [`width`](Pretty::width).
+#[derive(Clone, Copy, Debug)]
+pub struct Pretty<'a> {
+    formula: &'a Formula,
+    symbols: Option<&'a SymbolTable>,
+    width: Option<usize>,
+}
+
+impl<'a> Pretty<'a> {
+    /// Creates an adapter printing `formula` on a single line with `x<var>` names.
+    pub fn new(formula: &'a Formula) -> Self {
+        Pretty { formula, symbols: None, width: None }
+    }
+
+    /// Uses the names of `symbols` for the variables that have one.
+    pub fn symbols(mut self, symbols: &'a SymbolTable) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+
+    /// Wraps lines at `width` characters.
+    ///
+    /// Lines are only broken between clauses, and continuation lines start
+    /// with `∧ `; a single clause longer than `width` is not split.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Renders a literal, e.g. `¬x3`.
+    fn lit(&self, lit: Lit) -> String {
+        let sign = if lit.neg { "¬" } else { "" };
+        match self.symbols.and_then(|s| s.name(lit.var)) {
+            Some(name) => format!("{}{}", sign, name),
+            None => format!("{}x{}", sign, lit.var),
+        }
+    }
+
+    /// Renders a clause, parenthesized unless it has a single literal.
+    fn clause(&self, clause: &Clause) -> String {
+        match clause.as_slice() {
+            [] => "⊥".to_string(),
+            [lit] => self.lit(*lit),
+            lits => {
+                let lits: Vec<String> = lits.iter().map(|&l| self.lit(l)).collect();
+                format!("({})", lits.join(" ∨ "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Pretty<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.formula.is_empty() {
+            return f.write_str("⊤");
+        }
+        let mut line_len = 0;
+        for (i, clause) in self.formula.iter().enumerate() {
+            let text = self.clause(clause);
+            let len = text.chars().count();
+            if i > 0 {
+                match self.width {
+                    Some(width) if line_len + 3 + len > width => {
+                        f.write_str("\n∧ ")?;
+                        line_len = 2;
+                    }
+                    _ => {
+                        f.write_str(" ∧ ")?;
+                        line_len += 3;
+                    }
+                }
+            }
+            f.write_str(&text)?;
+            line_len += len;
+        }
+        Ok(())
+    }
+}
+
+/// `Display` adapter rendering a formula as LaTeX math.
+///
+/// The output uses `\land`, `\lor` and `\lnot`, prints unnamed variables as
+/// `x_{<var>}` and named ones as `\mathit{<name>}` (with LaTeX special
+/// characters escaped), and is meant to be placed in a math environment.
+/// With [`per_line`](Latex::per_line), lines are broken with `\\` for use
+/// in multi-line environments such as `gathered` or `align*`.
+///
+/// # Examples
+///
+/// ```
+/// use putnam::pretty::Latex;
+/// use putnam::symbols::SymbolTable;
+/// use putnam::types::{Lit, Var};
+///
+/// let formula = vec![vec![Lit::positive(Var::new(0)), Lit::negative(Var::new(1))]];
+/// let mut symbols = SymbolTable::new();
+/// symbols.bind(Var::new(1), "slot_2");
+///
+/// assert_eq!(
+///     Latex::new(&formula).symbols(&symbols).to_string(),
+///     r"(x_{0} \lor \lnot \mathit{slot\_2})"
+/// );
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Latex<'a> {
+    formula: &'a Formula,
+    symbols: Option<&'a SymbolTable>,
+    per_line: Option<usize>,
+}
+
+impl<'a> Latex<'a> {
+    /// Creates an adapter printing `formula` on a single line with `x_{<var>}` names.
+    pub fn new(formula: &'a Formula) -> Self {
+        Latex { formula, symbols: None, per_line: None }
+    }
+
+    /// Uses the names of `symbols` for the variables that have one.
+    pub fn symbols(mut self, symbols: &'a SymbolTable) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+
+    /// Breaks the line (with `\\`) after every `clauses` clauses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `clauses` is zero.
+    pub fn per_line(mut self, clauses: usize) -> Self {
+        assert!(clauses > 0, "at least one clause per line is required");
+        self.per_line = Some(clauses);
+        self
+    }
+
+    fn lit(&self, f: &mut fmt::Formatter<'_>, lit: Lit) -> fmt::Result {
+        if lit.neg {
+            f.write_str("\\lnot ")?;
+        }
+        match self.symbols.and_then(|s| s.name(lit.var)) {
+            Some(name) => {
+                f.write_str("\\mathit{")?;
+                for c in name.chars() {
+                    match c {
+                        '_' | '&' | '%' | '$' | '#' | '{' | '}' => write!(f, "\\{}", c)?,
+                        '~' => f.write_str("\\sim{}")?,
+                        '^' => f.write_str("\\hat{}")?,
+                        '\\' => f.write_str("\\backslash{}")?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                f.write_str("}")
+            }
+            None => write!(f, "x_{{{}}}", lit.var),
+        }
+    }
+}
+
+impl fmt::Display for Latex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.formula.is_empty() {
+            return f.write_str("\\top");
+        }
+        for (i, clause) in self.formula.iter().enumerate() {
+            if i > 0 {
+                match self.per_line {
+                    Some(n) if i % n == 0 => f.write_str(" \\\\\n\\land ")?,
+                    _ => f.write_str(" \\land ")?,
+                }
+            }
+            match clause.as_slice() {
+                [] => f.write_str("\\bot")?,
+                [lit] => self.lit(f, *lit)?,
+                lits => {
+                    f.write_str("(")?;
+                    for (j, &lit) in lits.iter().enumerate() {
+                        if j > 0 {
+                            f.write_str(" \\lor ")?;
+                        }
+                        self.lit(f, lit)?;
+                    }
+                    f.write_str(")")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(var: usize, neg: bool) -> Lit {
+        Lit::new(Var::new(var), neg)
+    }
+
+    #[test]
+    fn special_cases() {
+        assert_eq!(Pretty::new(&vec![]).to_string(), "⊤");
+        assert_eq!(Pretty::new(&vec![vec![]]).to_string(), "⊥");
+        assert_eq!(Pretty::new(&vec![vec![lit(4, true)], vec![]]).to_string(), "¬x4 ∧ ⊥");
+    }
+
+    #[test]
+    fn wrapping_breaks_between_clauses() {
+        let formula: Formula = (0..4).map(|v| vec![lit(v, false), lit(v + 1, true)]).collect();
+        let text = Pretty::new(&formula).width(24).to_string();
+        assert_eq!(text, "(x0 ∨ ¬x1) ∧ (x1 ∨ ¬x2)\n∧ (x2 ∨ ¬x3)\n∧ (x3 ∨ ¬x4)");
+        assert!(text.lines().all(|l| l.chars().count() <= 24));
+
+        // Overlong clauses stay intact
+        assert_eq!(Pretty::new(&formula).width(1).to_string().lines().count(), 4);
+    }
+
+    #[test]
+    fn latex_lines_and_escaping() {
+        let formula = vec![vec![lit(0, false)], vec![lit(1, true), lit(2, false)], vec![]];
+        assert_eq!(
+            Latex::new(&formula).per_line(2).to_string(),
+            "x_{0} \\land (\\lnot x_{1} \\lor x_{2}) \\\\\n\\land \\bot"
+        );
+        assert_eq!(Latex::new(&vec![]).to_string(), "\\top");
+
+        let mut symbols = SymbolTable::new();
+        symbols.bind(Var::new(0), "a&b^{c}");
+        assert_eq!(Latex::new(&formula[..1].to_vec()).symbols(&symbols).to_string(), "\\mathit{a\\&b\\hat{}\\{c\\}}");
+    }
+}
@@ -0,0 +1,215 @@
+//! Craig interpolants from resolution proofs
+//!
+//! For an unsatisfiable conjunction `A ∧ B`, an interpolant `I` is a formula
+//! over the variables shared by `A` and `B` such that `A ⇒ I` and `I ∧ B` is
+//! unsatisfiable. Interpolants over-approximate the states reachable from
+//! `A` without leaving the shared vocabulary, which is the basis of
+//! interpolation-based model checking.
+//!
+//! [`interpolant`] builds one from a refutation with McMillan's system,
+//! labelling each proof step with a partial interpolant:
+//!
+//! - an input clause of `A` gets the disjunction of its shared literals,
+//! - an input clause of `B` gets TRUE,
+//! - a resolvent on a variable occurring only in `A` gets the disjunction of
+//!   the labels of its premises, any other resolvent their conjunction.
+//!
+//! The label of the empty clause is the interpolant. It is returned as an
+//! [`Aig`] circuit whose inputs are the shared variables.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::proof::interpolant::interpolate;
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! // A: x0 ∧ (x0 → x1) ∧ (x1 → x2)    B: ¬x2
+//! let a = vec![vec![x(0)], vec![!x(0), x(1)], vec![!x(1), x(2)]];
+//! let b = vec![vec![!x(2)]];
+//! let itp = interpolate(&a, &b, 3).unwrap();
+//!
+//! // The only shared variable is x2, and the interpolant is x2 itself
+//! assert_eq!(itp.shared, vec![Var::new(2)]);
+//! assert!(itp.evaluate(&[false, false, true]));
+//! assert!(!itp.evaluate(&[true, true, false]));
+//! ```
+
+use crate::aig::{self, Aig, AigLit};
+use crate::types::*;
+
+use super::{normalized, refute, Proof};
+
+/// An interpolant circuit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Interpolant {
+    /// Combinational circuit with one input per shared variable
+    pub aig: Aig,
+    /// Literal of the interpolant in `aig`
+    pub output: AigLit,
+    /// `shared[i]` is the formula variable of input `aig.inputs[i]`, in
+    /// increasing order
+    pub shared: Vec<Var>,
+}
+
+impl Interpolant {
+    /// Evaluates the interpolant on an assignment of the formula variables.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The value of every formula variable, indexed by variable
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` does not cover the shared variables.
+    pub fn evaluate(&self, values: &[bool]) -> bool {
+        let inputs: Vec<bool> = self.shared.iter().map(|&var| values[var.index()]).collect();
+        let circuit = self.aig.simulate(&inputs, &[]);
+        circuit[self.output >> 1] != (self.output & 1 == 1)
+    }
+
+    /// Encodes the interpolant as CNF over the formula variables.
+    ///
+    /// Shared variables keep their numbers; the gates of the circuit get
+    /// fresh variables from `num_vars` on.
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing:
+    /// * `Formula` - The Tseitin clauses of the circuit
+    /// * `Lit` - A literal equivalent to the interpolant
+    /// * `usize` - The number of variables of the encoding
+    pub fn to_cnf(&self, num_vars: usize) -> (Formula, Lit, usize) {
+        let mut var_of: Vec<Var> = (0..self.aig.num_vars()).map(|v| Var::new(num_vars + v)).collect();
+        for (&input, &var) in self.aig.inputs.iter().zip(&self.shared) {
+            var_of[input >> 1] = var;
+        }
+        let rename = |lit: Lit| Lit { var: var_of[lit.var.index()], neg: lit.neg };
+        let formula = self.aig.tseitin().iter().map(|clause| clause.iter().map(|&l| rename(l)).collect()).collect();
+        (formula, rename(aig::to_lit(self.output)), num_vars + self.aig.num_vars())
+    }
+}
+
+/// Computes an interpolant from a refutation of `A ∧ B`.
+///
+/// # Arguments
+///
+/// * `proof` - A refutation of `A ∧ B`
+/// * `in_a` - For each input step of the proof, in order, whether its clause
+///   belongs to `A`; for proofs from [`refute`] this is indexed by clause
+///
+/// # Panics
+///
+/// Panics if `in_a` does not have one entry per input step.
+pub fn interpolant(proof: &Proof, in_a: &[bool]) -> Interpolant {
+    let inputs: Vec<_> = proof.steps.iter().filter(|step| step.antecedents.is_empty()).collect();
+    assert_eq!(inputs.len(), in_a.len(), "one partition entry is needed per input clause");
+
+    // Bit 0: occurs in A, bit 1: occurs in B
+    let num_vars = proof.steps.iter().flat_map(|s| &s.clause).map(|l| l.var.index() + 1).max().unwrap_or(0);
+    let mut occurs = vec![0u8; num_vars];
+    for (step, &a) in inputs.iter().zip(in_a) {
+        for lit in &step.clause {
+            occurs[lit.var.index()] |= if a { 1 } else { 2 };
+        }
+    }
+
+    let mut aig = Aig::default();
+    let shared: Vec<Var> = Var::range(num_vars).filter(|var| occurs[var.index()] == 3).collect();
+    let mut input_of = vec![None; num_vars];
+    for &var in &shared {
+        input_of[var.index()] = Some(aig.add_input());
+    }
+
+    let mut labels = Vec::with_capacity(proof.steps.len());
+    let mut in_a = in_a.iter();
+    for step in &proof.steps {
+        let Some((&first, rest)) = step.antecedents.split_first() else {
+            let label = if *in_a.next().unwrap() {
+                step.clause.iter().fold(aig::FALSE, |label, lit| match input_of[lit.var.index()] {
+                    Some(input) => aig.add_or(label, input ^ lit.neg as usize),
+                    None => label,
+                })
+            } else {
+                aig::TRUE
+            };
+            labels.push(label);
+            continue;
+        };
+        let mut resolvent = normalized(&proof.step(first).clause);
+        let mut label = labels[first - 1];
+        for &antecedent in rest {
+            let other = normalized(&proof.step(antecedent).clause);
+            let pivot = *resolvent.iter().find(|l| other.contains(&!**l)).expect("a checked proof");
+            label = if occurs[pivot.var.index()] == 1 {
+                aig.add_or(label, labels[antecedent - 1])
+            } else {
+                aig.add_and(label, labels[antecedent - 1])
+            };
+            resolvent.retain(|&l| l != pivot);
+            resolvent.extend(other.into_iter().filter(|&l| l != !pivot));
+            resolvent = normalized(&resolvent);
+        }
+        labels.push(label);
+    }
+
+    let output = *labels.last().expect("a proof has at least one step");
+    Interpolant { aig, output, shared }
+}
+
+/// Refutes `A ∧ B` and computes an interpolant.
+///
+/// # Returns
+///
+/// * `Some(interpolant)` - An interpolant of `A` and `B`
+/// * `None` - `A ∧ B` is satisfiable
+pub fn interpolate(a: &Formula, b: &Formula, num_vars: usize) -> Option<Interpolant> {
+    let formula: Formula = a.iter().chain(b).cloned().collect();
+    let in_a: Vec<bool> = (0..formula.len()).map(|i| i < a.len()).collect();
+    refute(&formula, num_vars).err().map(|proof| interpolant(&proof, &in_a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll::{solve, SolveResult};
+
+    #[test]
+    fn interpolants_separate_a_from_b() {
+        let mut checked = 0;
+        for seed in 0..30 {
+            let (formula, num_vars) = crate::gen::random_ksat(12, 70, 3, seed);
+            let (a, b) = formula.split_at(35);
+            let Some(itp) = interpolate(&a.to_vec(), &b.to_vec(), num_vars) else { continue };
+            checked += 1;
+
+            let vars = |part: &[Clause]| part.iter().flatten().map(|l| l.var).collect::<Vec<_>>();
+            assert!(itp.shared.iter().all(|v| vars(a).contains(v) && vars(b).contains(v)));
+
+            // A ∧ ¬I and I ∧ B are both unsatisfiable
+            let (gates, output, total) = itp.to_cnf(num_vars);
+            let mut a_not_i: Formula = a.iter().chain(&gates).cloned().collect();
+            a_not_i.push(vec![!output]);
+            assert_eq!(solve(&a_not_i, total), SolveResult::Unsat, "seed {}", seed);
+            let mut i_b: Formula = b.iter().chain(&gates).cloned().collect();
+            i_b.push(vec![output]);
+            assert_eq!(solve(&i_b, total), SolveResult::Unsat, "seed {}", seed);
+        }
+        assert!(checked > 0);
+    }
+
+    #[test]
+    fn trimmed_proofs_give_interpolants_too() {
+        let x = |var| Lit::positive(Var::new(var));
+        let a = [vec![x(0)], vec![!x(0), x(1)], vec![x(3), x(4)]];
+        let b = [vec![!x(1), x(2)], vec![!x(2)]];
+        let formula: Formula = a.iter().chain(&b).cloned().collect();
+        let trimmed = super::super::trim::trim(&refute(&formula, 5).unwrap_err());
+        let in_a: Vec<bool> = trimmed.core.iter().map(|&i| i < a.len()).collect();
+
+        let itp = interpolant(&trimmed.proof, &in_a);
+        assert_eq!(itp.shared, vec![Var::new(1)]);
+        assert!(itp.evaluate(&[false, true, false, false, false]));
+        assert!(!itp.evaluate(&[true, false, true, true, true]));
+    }
+}
@@ -0,0 +1,363 @@
+//! Resolution proofs of unsatisfiability
+//!
+//! [`refute`] runs a DPLL search that keeps the reason clause of every
+//! propagated literal. Each refuted branch yields a clause over the
+//! decisions above it, derived by resolution: a conflict clause is resolved
+//! with the reasons of its propagated literals, and the clauses of the two
+//! branches of a decision are resolved on its variable. When a branch's
+//! clause does not mention the decision, it already refutes the parent and
+//! the other branch is skipped. The clause derived at the root is empty.
+//!
+//! The result is a [`Proof`] with explicit antecedents for every step, as
+//! needed by interpolation or proof visualization, which can be written in
+//! TraceCheck format with [`tracecheck`].
+//!
+//! # Modules
+//!
+//! - [`tracecheck`]: Reading and writing proofs in TraceCheck format
+//! - [`interpolant`]: Craig interpolants of partitioned formulas
+//! - [`trim`]: Removing unneeded steps and extracting an unsatisfiable core
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::proof::refute;
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! let formula = vec![vec![x(0), x(1)], vec![x(0), !x(1)], vec![!x(0), x(1)], vec![!x(0), !x(1)]];
+//! let proof = refute(&formula, 2).unwrap_err();
+//!
+//! assert_eq!(proof.check(), Ok(()));
+//! assert!(proof.conclusion().clause.is_empty());
+//! ```
+
+pub mod interpolant;
+pub mod tracecheck;
+pub mod trim;
+
+use std::collections::HashSet;
+
+use crate::types::*;
+
+/// One clause of a proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofStep {
+    /// Identifier, starting at 1; input clauses come first
+    pub id: usize,
+    /// The clause
+    pub clause: Clause,
+    /// Clauses resolved (in this order) to derive the clause; empty for
+    /// input clauses
+    pub antecedents: Vec<usize>,
+}
+
+/// A resolution refutation.
+///
+/// Steps are ordered by identifier, which equals their position plus one;
+/// antecedents always precede the steps using them. The last step is the
+/// empty clause.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof {
+    /// The input clauses followed by the derived clauses
+    pub steps: Vec<ProofStep>,
+}
+
+impl Proof {
+    /// Returns the step with identifier `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such step.
+    pub fn step(&self, id: usize) -> &ProofStep {
+        &self.steps[id - 1]
+    }
+
+    /// Returns the final step, the empty clause.
+    pub fn conclusion(&self) -> &ProofStep {
+        self.steps.last().expect("a proof has at least one step")
+    }
+
+    /// Checks every derived step by replaying its resolution chain.
+    ///
+    /// Each antecedent after the first must clash with the resolvent so far
+    /// on exactly one variable; the final resolvent must contain the same
+    /// literals as the step's clause.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Every step follows from its antecedents
+    /// * `Err(message)` - Describing the first faulty step
+    pub fn check(&self) -> Result<(), String> {
+        for (position, step) in self.steps.iter().enumerate() {
+            if step.id != position + 1 {
+                return Err(format!("step {} has identifier {}", position + 1, step.id));
+            }
+            let Some((&first, rest)) = step.antecedents.split_first() else { continue };
+            if step.antecedents.iter().any(|&a| a == 0 || a >= step.id) {
+                return Err(format!("step {} uses a later or missing antecedent", step.id));
+            }
+            let mut resolvent = normalized(&self.step(first).clause);
+            for &antecedent in rest {
+                let other = normalized(&self.step(antecedent).clause);
+                let pivots: Vec<Lit> = resolvent.iter().copied().filter(|l| other.contains(&!*l)).collect();
+                let [pivot] = pivots[..] else {
+                    return Err(format!("step {}: antecedent {} clashes on {} variables", step.id, antecedent, pivots.len()));
+                };
+                resolvent.retain(|&l| l != pivot);
+                resolvent.extend(other.into_iter().filter(|&l| l != !pivot));
+                resolvent = normalized(&resolvent);
+            }
+            if resolvent != normalized(&step.clause) {
+                return Err(format!("step {}: resolution yields {:?}", step.id, resolvent));
+            }
+        }
+        match self.steps.last() {
+            Some(last) if last.clause.is_empty() => Ok(()),
+            _ => Err("the proof does not end with the empty clause".to_string()),
+        }
+    }
+
+    /// Checks that the proof refutes `formula`: every input step is a
+    /// clause of `formula` (up to the order and repetition of literals) and
+    /// every derived step passes [`check`](Proof::check).
+    ///
+    /// A proof read from a file is only evidence of unsatisfiability once
+    /// its inputs are tied to the formula; `check` alone accepts a proof of
+    /// any formula.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The proof is a refutation of `formula`
+    /// * `Err(message)` - Describing the first input step not in `formula`
+    ///   or the first faulty step
+    pub fn check_refutes(&self, formula: &Formula) -> Result<(), String> {
+        let clauses: HashSet<Vec<Lit>> = formula.iter().map(|clause| normalized(clause)).collect();
+        let foreign = self.steps.iter().find(|step| step.antecedents.is_empty() && !clauses.contains(&normalized(&step.clause)));
+        if let Some(step) = foreign {
+            return Err(format!("input step {} is not a clause of the formula", step.id));
+        }
+        self.check()
+    }
+}
+
+/// Sorts and deduplicates the literals of a clause.
+fn normalized(clause: &[Lit]) -> Vec<Lit> {
+    let mut lits = clause.to_vec();
+    lits.sort();
+    lits.dedup();
+    lits
+}
+
+/// Solves `formula`, returning a model or a resolution proof of
+/// unsatisfiability.
+///
+/// The search is a plain DPLL with scanning propagation; it is meant for
+/// producing proofs, not for speed.
+///
+/// # Returns
+///
+/// * `Ok(model)` - A satisfying (partial) assignment
+/// * `Err(proof)` - A refutation whose first steps are the clauses of `formula`
+pub fn refute(formula: &Formula, num_vars: usize) -> Result<Model, Proof> {
+    let steps = formula
+        .iter()
+        .enumerate()
+        .map(|(index, clause)| ProofStep { id: index + 1, clause: clause.clone(), antecedents: Vec::new() })
+        .collect();
+    let mut search = Search {
+        proof: Proof { steps },
+        num_inputs: formula.len(),
+        vals: vec![Val::Undef; num_vars],
+        reasons: vec![None; num_vars],
+        trail: Vec::new(),
+    };
+    match search.refute() {
+        None => {
+            let mut model = Model::new(num_vars);
+            for lit in &search.trail {
+                model.assign(lit.var, if lit.neg { Val::False } else { Val::True });
+            }
+            Ok(model)
+        }
+        Some(id) => {
+            // An empty input clause ends the proof as it is
+            if id != search.proof.steps.len() {
+                search.derive(Vec::new(), vec![id]);
+            }
+            Err(search.proof)
+        }
+    }
+}
+
+/// State of a proof-producing search.
+struct Search {
+    proof: Proof,
+    /// Number of input clauses (the clauses propagated over)
+    num_inputs: usize,
+    /// Current assignment
+    vals: Vec<Val>,
+    /// Identifier of the clause that propagated each variable
+    reasons: Vec<Option<usize>>,
+    /// Assigned literals, in order
+    trail: Vec<Lit>,
+}
+
+impl Search {
+    fn value(&self, lit: Lit) -> Val {
+        match (self.vals[lit.var.index()], lit.neg) {
+            (Val::Undef, _) => Val::Undef,
+            (Val::True, false) | (Val::False, true) => Val::True,
+            _ => Val::False,
+        }
+    }
+
+    fn satisfied(&self, clause: &[Lit]) -> bool {
+        clause.iter().any(|&l| self.value(l) == Val::True)
+    }
+
+    fn assign(&mut self, lit: Lit, reason: Option<usize>) {
+        self.vals[lit.var.index()] = if lit.neg { Val::False } else { Val::True };
+        self.reasons[lit.var.index()] = reason;
+        self.trail.push(lit);
+    }
+
+    fn backtrack(&mut self, length: usize) {
+        for lit in self.trail.drain(length..) {
+            self.vals[lit.var.index()] = Val::Undef;
+            self.reasons[lit.var.index()] = None;
+        }
+    }
+
+    /// Propagates unit clauses; returns the identifier of a falsified clause.
+    fn propagate(&mut self) -> Option<usize> {
+        loop {
+            let mut changed = false;
+            for index in 0..self.num_inputs {
+                let clause = &self.proof.steps[index].clause;
+                if self.satisfied(clause) {
+                    continue;
+                }
+                let mut open = clause.iter().copied().filter(|&l| self.value(l) == Val::Undef);
+                let (first, second) = (open.next(), open.next());
+                match (first, second) {
+                    (None, _) => return Some(index + 1),
+                    (Some(unit), None) => {
+                        self.assign(unit, Some(index + 1));
+                        changed = true;
+                    }
+                    _ => {}
+                }
+            }
+            if !changed {
+                return None;
+            }
+        }
+    }
+
+    /// Adds a derived clause and returns its identifier.
+    fn derive(&mut self, clause: Clause, antecedents: Vec<usize>) -> usize {
+        let id = self.proof.steps.len() + 1;
+        self.proof.steps.push(ProofStep { id, clause, antecedents });
+        id
+    }
+
+    /// Resolves the propagated literals out of the falsified clause
+    /// `conflict`, leaving a clause over decisions only.
+    fn analyze(&mut self, conflict: usize) -> usize {
+        let mut clause = normalized(&self.proof.step(conflict).clause);
+        let mut antecedents = vec![conflict];
+        for position in (0..self.trail.len()).rev() {
+            let lit = self.trail[position];
+            let Some(reason) = self.reasons[lit.var.index()] else { continue };
+            if !clause.contains(&!lit) {
+                continue;
+            }
+            clause.retain(|&l| l != !lit);
+            clause.extend(self.proof.step(reason).clause.iter().filter(|&&l| l != lit));
+            clause = normalized(&clause);
+            antecedents.push(reason);
+        }
+        match antecedents.len() {
+            1 => conflict,
+            _ => self.derive(clause, antecedents),
+        }
+    }
+
+    /// Searches below the current assignment. Returns `None` when a model
+    /// is found, and otherwise the identifier of a clause over the current
+    /// decisions that the current assignment falsifies.
+    fn refute(&mut self) -> Option<usize> {
+        let start = self.trail.len();
+        if let Some(conflict) = self.propagate() {
+            let learned = self.analyze(conflict);
+            self.backtrack(start);
+            return Some(learned);
+        }
+        let inputs = &self.proof.steps[..self.num_inputs];
+        let open = inputs.iter().find(|step| !self.satisfied(&step.clause))?;
+        let decision = *open.clause.iter().find(|&&l| self.value(l) == Val::Undef)?;
+
+        let length = self.trail.len();
+        self.assign(decision, None);
+        let left = self.refute()?;
+        self.backtrack(length);
+        if !self.proof.step(left).clause.contains(&!decision) {
+            // The decision played no part: the clause refutes this node too
+            self.backtrack(start);
+            return Some(left);
+        }
+        self.assign(!decision, None);
+        let right = self.refute()?;
+        self.backtrack(start);
+        if !self.proof.step(right).clause.contains(&decision) {
+            return Some(right);
+        }
+        let mut clause: Clause = self.proof.step(left).clause.iter().copied().filter(|&l| l != !decision).collect();
+        clause.extend(self.proof.step(right).clause.iter().filter(|&&l| l != decision));
+        Some(self.derive(normalized(&clause), vec![left, right]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll::{solve, SolveResult};
+
+    #[test]
+    fn proofs_check_and_models_satisfy() {
+        for seed in 0..40 {
+            let (formula, num_vars) = crate::gen::random_ksat(10, 50, 3, seed);
+            match (refute(&formula, num_vars), solve(&formula, num_vars)) {
+                (Ok(model), SolveResult::Sat(_)) => {
+                    assert!(formula.iter().all(|c| c.iter().any(|&l| model.is_true(l))), "seed {}", seed)
+                }
+                (Err(proof), SolveResult::Unsat) => {
+                    assert_eq!(proof.check(), Ok(()), "seed {}", seed);
+                    assert_eq!(&proof.steps[..formula.len()].iter().map(|s| s.clause.clone()).collect::<Vec<_>>(), &formula);
+                }
+                (result, _) => panic!("seed {}: answers disagree ({:?})", seed, result.is_ok()),
+            }
+        }
+    }
+
+    #[test]
+    fn pigeonhole_and_empty_clause() {
+        let (formula, num_vars, _) = crate::gen::families::pigeonhole(3);
+        assert_eq!(refute(&formula, num_vars).unwrap_err().check(), Ok(()));
+
+        let proof = refute(&vec![vec![Lit::positive(Var::new(0))], vec![]], 1).unwrap_err();
+        assert_eq!(proof.check(), Ok(()));
+        assert_eq!(proof.conclusion().id, 2);
+    }
+
+    #[test]
+    fn faulty_steps_are_reported() {
+        let x = Lit::positive(Var::new(0));
+        let mut proof = refute(&vec![vec![x], vec![!x]], 1).unwrap_err();
+        assert_eq!(proof.check_refutes(&vec![vec![!x], vec![x, x]]), Ok(()));
+        assert!(proof.check_refutes(&vec![vec![x], vec![!x, Lit::positive(Var::new(1))]]).is_err());
+        proof.steps.last_mut().unwrap().antecedents = vec![1, 1];
+        assert!(proof.check().is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! TraceCheck proof files
+//!
+//! TraceCheck lists one clause per line: its identifier, its literals in
+//! DIMACS notation terminated by `0`, and the identifiers of the clauses
+//! it was resolved from, again terminated by `0`. Input clauses have no
+//! antecedents.
+//!
+//! ```text
+//! 1 1 2 0 0
+//! 2 -1 0 0
+//! 3 -2 0 0
+//! 4 0 1 2 3 0
+//! ```
+
+use std::io::{self, BufRead, Write};
+
+use crate::types::*;
+
+use super::{Proof, ProofStep};
+
+/// Writes a proof in TraceCheck format.
+///
+/// # Examples
+///
+/// ```
+/// use putnam::proof::refute;
+/// use putnam::proof::tracecheck::write_tracecheck;
+/// use putnam::types::{Lit, Var};
+///
+/// let x = Lit::positive(Var::new(0));
+/// let proof = refute(&vec![vec![x], vec![!x]], 1).unwrap_err();
+///
+/// let mut out = Vec::new();
+/// write_tracecheck(&mut out, &proof)?;
+/// assert_eq!(String::from_utf8(out).unwrap(), "1 1 0 0\n2 -1 0 0\n3 0 2 1 0\n");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn write_tracecheck<W: Write>(mut writer: W, proof: &Proof) -> io::Result<()> {
+    for step in &proof.steps {
+        write!(writer, "{}", step.id)?;
+        for lit in &step.clause {
+            write!(writer, " {}", lit.to_dimacs())?;
+        }
+        write!(writer, " 0")?;
+        for antecedent in &step.antecedents {
+            write!(writer, " {}", antecedent)?;
+        }
+        writeln!(writer, " 0")?;
+    }
+    Ok(())
+}
+
+/// Reads a proof in TraceCheck format.
+///
+/// Steps must appear with consecutive identifiers starting at 1 (as
+/// written by [`write_tracecheck`]). The proof is not checked; use
+/// [`Proof::check`] for that.
+///
+/// # Returns
+///
+/// * `Ok(Proof)` - The proof
+/// * `Err(io::Error)` - If reading fails, or with `ErrorKind::InvalidData`
+///   if a line is malformed or out of order
+pub fn read_tracecheck<R: BufRead>(reader: R) -> io::Result<Proof> {
+    let mut steps = Vec::new();
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line?;
+        let invalid = |what: &str| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", lineno + 1, what));
+        let numbers = line
+            .split_whitespace()
+            .map(|t| t.parse::<i64>())
+            .collect::<Result<Vec<i64>, _>>()
+            .map_err(|_| invalid("expected integers"))?;
+        let Some((&id, rest)) = numbers.split_first() else { continue };
+        if id != steps.len() as i64 + 1 {
+            return Err(invalid("expected consecutive identifiers starting at 1"));
+        }
+        let mut parts = rest.splitn(2, |&n| n == 0);
+        let (Some(lits), Some(tail)) = (parts.next(), parts.next()) else {
+            return Err(invalid("missing 0 after the literals"));
+        };
+        let Some((&0, antecedents)) = tail.split_last() else {
+            return Err(invalid("missing 0 after the antecedents"));
+        };
+        if antecedents.iter().any(|&a| a <= 0) {
+            return Err(invalid("antecedents must be positive"));
+        }
+        let Some(clause) = lits.iter().map(|&n| Lit::from_dimacs(n)).collect() else {
+            return Err(invalid("literal out of range"));
+        };
+        steps.push(ProofStep {
+            id: id as usize,
+            clause,
+            antecedents: antecedents.iter().map(|&a| a as usize).collect(),
+        });
+    }
+    Ok(Proof { steps })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let (formula, num_vars, _) = crate::gen::families::pigeonhole(3);
+        let proof = crate::proof::refute(&formula, num_vars).unwrap_err();
+        let mut out = Vec::new();
+        write_tracecheck(&mut out, &proof).unwrap();
+        assert_eq!(read_tracecheck(out.as_slice()).unwrap(), proof);
+
+        assert!(read_tracecheck("2 1 0 0\n".as_bytes()).is_err());
+        assert!(read_tracecheck("1 1 0\n".as_bytes()).is_err());
+        assert!(read_tracecheck("1 1 0 -1 0\n".as_bytes()).is_err());
+    }
+}
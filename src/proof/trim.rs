@@ -0,0 +1,95 @@
+//! Proof trimming
+//!
+//! A proof found by search usually contains steps that do not contribute to
+//! the empty clause, for instance the refutation of a branch that a later
+//! backjump made irrelevant. [`trim`] keeps only the steps the conclusion
+//! depends on, renumbers them, and reports the input clauses that remain:
+//! an unsatisfiable core of the formula.
+
+use super::{Proof, ProofStep};
+
+/// A trimmed proof and the unsatisfiable core it uses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trimmed {
+    /// The steps needed for the empty clause, with consecutive identifiers
+    pub proof: Proof,
+    /// Positions (0-based) of the used input clauses in the original proof,
+    /// which for proofs from [`refute`](super::refute) are clause indices of
+    /// the refuted formula
+    pub core: Vec<usize>,
+}
+
+/// Removes the steps the conclusion does not depend on.
+///
+/// Input steps (those without antecedents) are kept only if used, and the
+/// relative order of the kept steps is preserved.
+///
+/// # Examples
+///
+/// ```
+/// use putnam::proof::refute;
+/// use putnam::proof::trim::trim;
+/// use putnam::types::{Lit, Var};
+///
+/// let x = |var| Lit::positive(Var::new(var));
+/// // Only the last two clauses are needed
+/// let formula = vec![vec![x(1), x(2)], vec![x(0)], vec![!x(0)]];
+/// let trimmed = trim(&refute(&formula, 3).unwrap_err());
+///
+/// assert_eq!(trimmed.core, vec![1, 2]);
+/// assert_eq!(trimmed.proof.check(), Ok(()));
+/// assert_eq!(trimmed.proof.steps.len(), 3);
+/// ```
+pub fn trim(proof: &Proof) -> Trimmed {
+    let mut used = vec![false; proof.steps.len()];
+    if let Some(last) = used.last_mut() {
+        *last = true;
+    }
+    // Antecedents precede their steps, so one backward sweep suffices
+    for position in (0..proof.steps.len()).rev() {
+        if used[position] {
+            for &antecedent in &proof.steps[position].antecedents {
+                used[antecedent - 1] = true;
+            }
+        }
+    }
+
+    let mut renumbered = vec![0; proof.steps.len()];
+    let mut steps = Vec::new();
+    let mut core = Vec::new();
+    for (position, step) in proof.steps.iter().enumerate().filter(|(p, _)| used[*p]) {
+        if step.antecedents.is_empty() {
+            core.push(position);
+        }
+        renumbered[position] = steps.len() + 1;
+        steps.push(ProofStep {
+            id: steps.len() + 1,
+            clause: step.clause.clone(),
+            antecedents: step.antecedents.iter().map(|&a| renumbered[a - 1]).collect(),
+        });
+    }
+    Trimmed { proof: Proof { steps }, core }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll::{solve, SolveResult};
+
+    #[test]
+    fn trimmed_proofs_check_and_cores_are_unsat() {
+        let mut trimmed_any = false;
+        for seed in 0..40 {
+            let (formula, num_vars) = crate::gen::random_ksat(10, 60, 3, seed);
+            let Err(proof) = crate::proof::refute(&formula, num_vars) else { continue };
+            let trimmed = trim(&proof);
+            assert_eq!(trimmed.proof.check(), Ok(()), "seed {}", seed);
+            assert!(trimmed.proof.steps.len() <= proof.steps.len());
+            trimmed_any |= trimmed.core.len() < formula.len();
+
+            let core: Vec<_> = trimmed.core.iter().map(|&i| formula[i].clone()).collect();
+            assert_eq!(solve(&core, num_vars), SolveResult::Unsat, "seed {}", seed);
+        }
+        assert!(trimmed_any);
+    }
+}
@@ -0,0 +1,425 @@
+//! Propositional SMT-LIB2 frontend
+//!
+//! This module executes SMT-LIB2 scripts restricted to pure propositional
+//! logic, the fragment emitted by many verification and synthesis tools:
+//!
+//! - `(declare-const x Bool)` and `(declare-fun x () Bool)`
+//! - `(assert <term>)` with `true`, `false`, `not`, `and`, `or`, `xor`,
+//!   `=>`, `=`, `distinct`, and `ite` over Bool terms
+//! - `(check-sat)`, `(get-model)`, `(get-value (<term>*))`
+//! - `(set-logic …)`, `(set-option …)`, `(set-info …)`, `(exit)`
+//!
+//! Asserted terms are built as an And-Inverter Graph ([`crate::aig`]) and
+//! converted to CNF with the Tseitin transformation before solving.
+//! Responses follow SMT-LIB conventions: `sat`/`unsat`, a `(define-fun …)`
+//! model, and `(error "…")` for unsupported or malformed commands, after
+//! which execution continues.
+//!
+//! # Example
+//!
+//! ```
+//! use putnam::smtlib::run;
+//!
+//! let script = "
+//!     (declare-const p Bool)
+//!     (declare-const q Bool)
+//!     (assert (and (=> p q) p))
+//!     (check-sat)
+//!     (get-value (q))
+//! ";
+//! let mut out = Vec::new();
+//! run(script, &mut out)?;
+//! assert_eq!(String::from_utf8(out).unwrap(), "sat\n((q true))\n");
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::aig::{self, Aig, AigLit};
+use crate::solver::dpll::SolveResult;
+use crate::types::*;
+
+/// A parsed S-expression.
+#[derive(Clone, Debug, PartialEq)]
+enum Sexp {
+    Atom(String),
+    List(Vec<Sexp>),
+}
+
+/// Splits the script into S-expressions.
+fn parse_sexps(src: &str) -> Result<Vec<Sexp>, String> {
+    let mut stack: Vec<Vec<Sexp>> = vec![Vec::new()];
+    let mut chars = src.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '(' => stack.push(Vec::new()),
+            ')' => {
+                let list = stack.pop().filter(|_| !stack.is_empty()).ok_or("unbalanced ')'")?;
+                stack.last_mut().unwrap().push(Sexp::List(list));
+            }
+            '|' => {
+                let symbol: String = chars.by_ref().take_while(|&c| c != '|').collect();
+                stack.last_mut().unwrap().push(Sexp::Atom(symbol));
+            }
+            '"' => {
+                let mut text = String::from('"');
+                while let Some(c) = chars.next() {
+                    text.push(c);
+                    if c == '"' {
+                        // "" is an escaped quote inside a string literal
+                        if chars.peek() == Some(&'"') {
+                            text.push(chars.next().unwrap());
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                stack.last_mut().unwrap().push(Sexp::Atom(text));
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut atom = String::from(c);
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "();|\"".contains(c) {
+                        break;
+                    }
+                    atom.push(c);
+                    chars.next();
+                }
+                stack.last_mut().unwrap().push(Sexp::Atom(atom));
+            }
+        }
+    }
+
+    if stack.len() != 1 {
+        return Err("unbalanced '('".to_string());
+    }
+    Ok(stack.pop().unwrap())
+}
+
+/// State of an SMT-LIB session.
+#[derive(Default)]
+struct Session {
+    aig: Aig,
+    /// Declared constants in declaration order
+    decls: Vec<(String, AigLit)>,
+    symbols: HashMap<String, AigLit>,
+    assertions: Vec<AigLit>,
+    /// Model of the last satisfiable `check-sat`, indexed by AIG variable
+    model: Option<Model>,
+}
+
+/// Formats a symbol, quoting it when it is not a simple symbol.
+fn symbol(name: &str) -> String {
+    let simple = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || "~!@$%^&*_-+=<>.?/".contains(c));
+    if simple {
+        name.to_string()
+    } else {
+        format!("|{}|", name)
+    }
+}
+
+impl Session {
+    /// Executes one command, returning its response (if any) and whether
+    /// the script should stop.
+    fn command(&mut self, cmd: &Sexp) -> Result<(Option<String>, bool), String> {
+        let items = match cmd {
+            Sexp::List(items) => items,
+            Sexp::Atom(a) => return Err(format!("expected a command, got '{}'", a)),
+        };
+        let name = match items.first() {
+            Some(Sexp::Atom(name)) => name.as_str(),
+            _ => return Err("expected a command name".to_string()),
+        };
+
+        match (name, &items[1..]) {
+            ("set-logic" | "set-option" | "set-info", _) => Ok((None, false)),
+            ("exit", []) => Ok((None, true)),
+            ("declare-const", [Sexp::Atom(x), Sexp::Atom(sort)]) if sort == "Bool" => self.declare(x),
+            ("declare-fun", [Sexp::Atom(x), Sexp::List(args), Sexp::Atom(sort)]) if args.is_empty() && sort == "Bool" => {
+                self.declare(x)
+            }
+            ("declare-const" | "declare-fun", _) => Err("only Bool constants are supported".to_string()),
+            ("assert", [term]) => {
+                self.model = None;
+                let lit = self.term(term)?;
+                self.assertions.push(lit);
+                Ok((None, false))
+            }
+            ("check-sat", []) => Ok((Some(self.check_sat().to_string()), false)),
+            ("get-model", []) => {
+                let model = self.model.as_ref().ok_or("model is not available")?;
+                let mut out = String::from("(\n");
+                for (name, lit) in &self.decls {
+                    let value = lit_value(model, *lit);
+                    out.push_str(&format!("  (define-fun {} () Bool {})\n", symbol(name), value));
+                }
+                out.push(')');
+                Ok((Some(out), false))
+            }
+            ("get-value", [Sexp::List(terms)]) => {
+                if self.model.is_none() {
+                    return Err("model is not available".to_string());
+                }
+                let mut pairs = Vec::new();
+                for term in terms {
+                    // The term may create new gates, so evaluate by simulation
+                    // from the model's input values rather than reading gates.
+                    let lit = self.term(term)?;
+                    let values = self.evaluate();
+                    pairs.push(format!("({} {})", show(term), values[lit >> 1] != (lit & 1 == 1)));
+                }
+                Ok((Some(format!("({})", pairs.join(" "))), false))
+            }
+            (other, _) => Err(format!("unsupported command '{}'", other)),
+        }
+    }
+
+    fn declare(&mut self, name: &str) -> Result<(Option<String>, bool), String> {
+        if self.symbols.contains_key(name) {
+            return Err(format!("'{}' is already declared", name));
+        }
+        self.model = None;
+        let lit = self.aig.add_input();
+        self.symbols.insert(name.to_string(), lit);
+        self.decls.push((name.to_string(), lit));
+        Ok((None, false))
+    }
+
+    /// Builds the AIG node of a Bool term.
+    fn term(&mut self, term: &Sexp) -> Result<AigLit, String> {
+        match term {
+            Sexp::Atom(a) => match a.as_str() {
+                "true" => Ok(aig::TRUE),
+                "false" => Ok(aig::FALSE),
+                x => self.symbols.get(x).copied().ok_or_else(|| format!("unknown constant '{}'", x)),
+            },
+            Sexp::List(items) => {
+                let (op, args) = match items.split_first() {
+                    Some((Sexp::Atom(op), args)) => (op.as_str(), args),
+                    _ => return Err(format!("unsupported term {}", show(term))),
+                };
+                let args = args.iter().map(|a| self.term(a)).collect::<Result<Vec<_>, _>>()?;
+                let aig = &mut self.aig;
+                let arity = |ok: bool| if ok { Ok(()) } else { Err(format!("wrong number of arguments to '{}'", op)) };
+
+                match op {
+                    "not" => {
+                        arity(args.len() == 1)?;
+                        Ok(args[0] ^ 1)
+                    }
+                    "and" => Ok(args.iter().fold(aig::TRUE, |acc, &x| aig.add_and(acc, x))),
+                    "or" => Ok(args.iter().fold(aig::FALSE, |acc, &x| aig.add_or(acc, x))),
+                    "xor" => {
+                        arity(args.len() >= 2)?;
+                        Ok(args[1..].iter().fold(args[0], |acc, &x| aig.add_xor(acc, x)))
+                    }
+                    "=>" => {
+                        // Right-associative: a => (b => c)
+                        arity(args.len() >= 2)?;
+                        let last = args[args.len() - 1];
+                        Ok(args[..args.len() - 1].iter().rev().fold(last, |acc, &x| aig.add_or(x ^ 1, acc)))
+                    }
+                    "=" => {
+                        // Chainable: (= a b c) means a = b ∧ b = c
+                        arity(args.len() >= 2)?;
+                        let mut result = aig::TRUE;
+                        for pair in args.windows(2) {
+                            let eq = aig.add_xor(pair[0], pair[1]) ^ 1;
+                            result = aig.add_and(result, eq);
+                        }
+                        Ok(result)
+                    }
+                    "distinct" => {
+                        // Pairwise distinct Bool values: at most two arguments can be
+                        arity(args.len() >= 2)?;
+                        let mut result = aig::TRUE;
+                        for i in 0..args.len() {
+                            for j in (i + 1)..args.len() {
+                                let ne = aig.add_xor(args[i], args[j]);
+                                result = aig.add_and(result, ne);
+                            }
+                        }
+                        Ok(result)
+                    }
+                    "ite" => {
+                        arity(args.len() == 3)?;
+                        let then = aig.add_and(args[0], args[1]);
+                        let other = aig.add_and(args[0] ^ 1, args[2]);
+                        Ok(aig.add_or(then, other))
+                    }
+                    _ => Err(format!("unsupported operator '{}'", op)),
+                }
+            }
+        }
+    }
+
+    /// Solves the conjunction of all assertions.
+    fn check_sat(&mut self) -> &'static str {
+        let mut formula = self.aig.tseitin();
+        formula.extend(self.assertions.iter().map(|&lit| vec![aig::to_lit(lit)]));
+        match crate::solve(&formula, self.aig.num_vars()) {
+            SolveResult::Sat(model) => {
+                self.model = Some(model);
+                "sat"
+            }
+            SolveResult::Unsat => {
+                self.model = None;
+                "unsat"
+            }
+        }
+    }
+
+    /// Simulates the AIG on the current model's input values.
+    fn evaluate(&self) -> Vec<bool> {
+        let model = self.model.as_ref().expect("evaluate requires a model");
+        let inputs: Vec<bool> = self.aig.inputs.iter().map(|&lit| lit_value(model, lit)).collect();
+        self.aig.simulate(&inputs, &[])
+    }
+}
+
+/// Value of an AIG literal in a model (unassigned inputs count as false).
+fn lit_value(model: &Model, lit: AigLit) -> bool {
+    (model.value(Var::new(lit >> 1)) == Val::True) != (lit & 1 == 1)
+}
+
+/// Prints an S-expression back in SMT-LIB syntax.
+fn show(sexp: &Sexp) -> String {
+    match sexp {
+        Sexp::Atom(a) => symbol(a),
+        Sexp::List(items) => format!("({})", items.iter().map(show).collect::<Vec<_>>().join(" ")),
+    }
+}
+
+/// Executes an SMT-LIB2 script and writes the responses.
+///
+/// Each response is written on its own line(s). Malformed or unsupported
+/// commands produce an `(error "…")` response and execution continues with
+/// the next command; an unbalanced script is reported as a single error.
+///
+/// # Arguments
+///
+/// * `script` - The SMT-LIB2 source text
+/// * `out` - Destination for the responses
+///
+/// # Returns
+///
+/// * `Ok(())` - The script was executed (possibly with reported errors)
+/// * `Err(io::Error)` - If writing a response fails
+pub fn run<W: Write>(script: &str, mut out: W) -> io::Result<()> {
+    let commands = match parse_sexps(script) {
+        Ok(commands) => commands,
+        Err(msg) => return writeln!(out, "(error \"{}\")", msg),
+    };
+
+    let mut session = Session::default();
+    for cmd in &commands {
+        match session.command(cmd) {
+            Ok((response, stop)) => {
+                if let Some(response) = response {
+                    writeln!(out, "{}", response)?;
+                }
+                if stop {
+                    break;
+                }
+            }
+            Err(msg) => writeln!(out, "(error \"{}\")", msg.replace('"', "\"\""))?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_str(script: &str) -> String {
+        let mut out = Vec::new();
+        run(script, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn sat_with_model() {
+        let out = run_str(
+            "(set-logic QF_UF)
+             (declare-const a Bool)
+             (declare-fun b () Bool)
+             (assert (xor a b))
+             (assert (not a))
+             (check-sat)
+             (get-model)",
+        );
+        assert_eq!(out, "sat\n(\n  (define-fun a () Bool false)\n  (define-fun b () Bool true)\n)\n");
+    }
+
+    #[test]
+    fn unsat() {
+        let out = run_str(
+            "(declare-const p Bool)
+             (assert (ite p false (not true)))
+             (check-sat)
+             (get-model)
+             (exit)
+             (check-sat)",
+        );
+        assert_eq!(out, "unsat\n(error \"model is not available\")\n");
+    }
+
+    #[test]
+    fn operators_agree_with_truth_tables() {
+        type BinOp = fn(bool, bool) -> bool;
+        let ops: [(&str, BinOp); 5] = [
+            ("and", |a, b| a && b),
+            ("or", |a, b| a || b),
+            ("xor", |a, b| a != b),
+            ("=>", |a, b| !a || b),
+            ("=", |a, b| a == b),
+        ];
+        for (op, f) in ops {
+            for a in [false, true] {
+                for b in [false, true] {
+                    let script = format!(
+                        "(declare-const x Bool)(declare-const y Bool)
+                         (assert (= x {a}))(assert (= y {b}))
+                         (check-sat)(get-value (({op} x y)))"
+                    );
+                    let expected = format!("sat\n((({} x y) {}))\n", op, f(a, b));
+                    assert_eq!(run_str(&script), expected, "{} {} {}", op, a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn errors_are_reported() {
+        let out = run_str(
+            "(declare-const x Int)
+             (assert (+ x 1))
+             (push 1)
+             (declare-const |weird name| Bool)
+             (assert |weird name|)
+             (check-sat)
+             (get-model)",
+        );
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines[0].starts_with("(error"));
+        assert!(lines[1].starts_with("(error"));
+        assert!(lines[2].starts_with("(error"));
+        assert_eq!(lines[3], "sat");
+        assert_eq!(lines[5], "  (define-fun |weird name| () Bool true)");
+
+        assert!(run_str("(assert (and true)").starts_with("(error"));
+    }
+}
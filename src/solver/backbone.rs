@@ -0,0 +1,85 @@
+//! Backbones: the literals true in every model
+//!
+//! The backbone of a satisfiable formula is the set of literals that every
+//! model makes true. Backbone literals are the decisions a configurator or
+//! planner cannot avoid, and fixing them simplifies the formula for later
+//! queries without losing models.
+//!
+//! [`Solver::backbone`] computes it by the classic iterative test: a first
+//! model proposes its literals as candidates, and each candidate `l` is
+//! checked by solving under the assumption `¬l`. An unsatisfiable query
+//! confirms `l`; a model drops `l` together with every other candidate it
+//! disagrees with, which usually removes many candidates per query. A
+//! variable the search leaves unassigned can take either value, so it has
+//! no backbone literal.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::solver::Solver;
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! // x0 ∧ (¬x0 ∨ ¬x1) ∧ (x2 ∨ x3): x0 and ¬x1 are forced, x2 and x3 are not
+//! let mut solver = Solver::from_formula(vec![vec![x(0)], vec![!x(0), !x(1)], vec![x(2), x(3)]], 4);
+//! assert_eq!(solver.backbone(), Some(vec![x(0), !x(1)]));
+//! ```
+
+use super::dpll::SolveResult;
+use super::Solver;
+use crate::types::*;
+
+impl Solver {
+    /// Computes the backbone of the current formula over all variables of
+    /// the solver, see the [module documentation](self).
+    ///
+    /// The assumption queries go through the query cache, so repeating the
+    /// call on an unchanged solver is cheap.
+    ///
+    /// # Returns
+    ///
+    /// The backbone literals in increasing order of their variables, or
+    /// `None` if the formula is unsatisfiable.
+    pub fn backbone(&mut self) -> Option<Vec<Lit>> {
+        let SolveResult::Sat(model) = self.solve() else { return None };
+        let mut candidates: Vec<Lit> = Var::range(self.num_vars())
+            .filter_map(|var| match model.value(var) {
+                Val::True => Some(Lit::positive(var)),
+                Val::False => Some(Lit::negative(var)),
+                Val::Undef => None,
+            })
+            .collect();
+        let mut backbone = Vec::new();
+        while let Some(lit) = candidates.pop() {
+            match self.solve_with_assumptions(&[!lit]) {
+                SolveResult::Unsat => backbone.push(lit),
+                SolveResult::Sat(model) => candidates.retain(|&candidate| model.is_true(candidate)),
+            }
+        }
+        backbone.sort_by_key(|lit| lit.var);
+        Some(backbone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backbone_matches_exhaustive_search() {
+        for seed in 0..20 {
+            let (formula, num_vars) = crate::gen::random_ksat(8, 20 + seed as usize % 15, 3, seed);
+            let models: Vec<Model> = crate::solver::brute::models(&formula, num_vars).collect();
+            let expected = (!models.is_empty()).then(|| {
+                Var::range(num_vars)
+                    .filter_map(|var| {
+                        let first = models[0].value(var);
+                        models.iter().all(|model| model.value(var) == first).then(|| Lit::new(var, first == Val::False))
+                    })
+                    .collect::<Vec<Lit>>()
+            });
+            let mut solver = Solver::from_formula(formula, num_vars);
+            assert_eq!(solver.backbone(), expected, "seed {}", seed);
+        }
+    }
+}
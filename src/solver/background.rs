@@ -0,0 +1,153 @@
+//! Asynchronous solving on a background thread
+//!
+//! [`Solver::solve_async`] runs a search on its own thread and returns a
+//! [`SolveFuture`] that completes with the answer. It works with any
+//! executor and needs no async runtime: the worker thread wakes the task
+//! when the answer is ready. Dropping the future cancels the search, which
+//! stops at its next branching point.
+//!
+//! # Example
+//!
+//! ```
+//! use std::future::Future;
+//! use std::pin::pin;
+//! use std::sync::Arc;
+//! use std::task::{Context, Poll, Wake, Waker};
+//! use std::thread::{self, Thread};
+//!
+//! use putnam::solver::Solver;
+//! use putnam::solver::dpll::SolveResult;
+//! use putnam::types::{Lit, Var};
+//!
+//! // A minimal executor; real applications would simply `.await`
+//! struct Unpark(Thread);
+//! impl Wake for Unpark {
+//!     fn wake(self: Arc<Self>) {
+//!         self.0.unpark();
+//!     }
+//! }
+//! fn block_on<F: Future>(future: F) -> F::Output {
+//!     let mut future = pin!(future);
+//!     let waker = Waker::from(Arc::new(Unpark(thread::current())));
+//!     loop {
+//!         match future.as_mut().poll(&mut Context::from_waker(&waker)) {
+//!             Poll::Ready(output) => return output,
+//!             Poll::Pending => thread::park(),
+//!         }
+//!     }
+//! }
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! let solver = Solver::from_formula(vec![vec![x(0)], vec![!x(0), x(1)]], 2);
+//! assert!(matches!(block_on(solver.solve_async(&[])), SolveResult::Sat(_)));
+//! assert_eq!(block_on(solver.solve_async(&[!x(1)])), SolveResult::Unsat);
+//! ```
+//!
+//! [`Solver::solve_async`]: super::Solver::solve_async
+
+use std::any::Any;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use crate::types::*;
+
+use super::dpll::SolveResult;
+use super::Solver;
+
+/// Outcome of the worker thread: the answer, or the payload of its panic.
+type Outcome = Result<SolveResult, Box<dyn Any + Send>>;
+
+/// State shared between a [`SolveFuture`] and its worker thread.
+struct Shared {
+    /// The outcome (once available) and the waker of the waiting task
+    state: Mutex<(Option<Outcome>, Option<Waker>)>,
+    /// Set when the future is dropped
+    cancel: AtomicBool,
+}
+
+/// A future resolving to the answer of a background search.
+///
+/// Created by [`Solver::solve_async`](super::Solver::solve_async). Dropping
+/// it before completion cancels the search.
+pub struct SolveFuture {
+    shared: Arc<Shared>,
+}
+
+/// Starts `solver` on a background thread.
+pub(crate) fn spawn(mut solver: Solver, assumptions: Vec<Lit>) -> SolveFuture {
+    let shared = Arc::new(Shared { state: Mutex::new((None, None)), cancel: AtomicBool::new(false) });
+    let worker = Arc::clone(&shared);
+    thread::spawn(move || {
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            solver.run_interruptible(&assumptions, Some(&worker.cancel), None)
+        }));
+        let outcome = match outcome {
+            Ok(Some(result)) => Ok(result),
+            Ok(None) => return, // Cancelled: nobody is waiting
+            Err(payload) => Err(payload),
+        };
+        let mut state = worker.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.0 = Some(outcome);
+        if let Some(waker) = state.1.take() {
+            waker.wake();
+        }
+    });
+    SolveFuture { shared }
+}
+
+impl Future for SolveFuture {
+    type Output = SolveResult;
+
+    /// Polls for the answer.
+    ///
+    /// # Panics
+    ///
+    /// Resumes the panic of the worker thread if the search panicked.
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<SolveResult> {
+        let mut state = self.shared.state.lock().unwrap_or_else(|e| e.into_inner());
+        match state.0.take() {
+            Some(Ok(result)) => Poll::Ready(result),
+            Some(Err(payload)) => {
+                drop(state);
+                panic::resume_unwind(payload)
+            }
+            None => {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for SolveFuture {
+    fn drop(&mut self) {
+        self.shared.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn dropping_the_future_cancels_the_search() {
+        // Far too hard for plain DPLL to finish during the test
+        let (formula, num_vars, _) = crate::gen::families::pigeonhole(12);
+        let future = Solver::from_formula(formula, num_vars).solve_async(&[]);
+        let worker = Arc::downgrade(&future.shared);
+        drop(future);
+
+        // The worker notices the cancellation and releases the shared state
+        let start = Instant::now();
+        while worker.upgrade().is_some() {
+            assert!(start.elapsed() < Duration::from_secs(10), "search was not cancelled");
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
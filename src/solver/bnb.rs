@@ -0,0 +1,156 @@
+//! Branch and bound over a linear objective
+//!
+//! Iterative tightening (see [`optimize`](super::optimize)) finds an
+//! optimal model through a sequence of complete searches, each under a
+//! stronger bound. Branch and bound instead runs a single search that does
+//! not stop at the first model: it records the model, and from then on
+//! abandons every node whose assignment already costs at least as much as
+//! the best model found, until the whole tree is refuted. The last model
+//! recorded is optimal.
+//!
+//! The objective is a sum of non-negative weights of literals, to be
+//! minimized. A node's cost counts the weights of its true literals, a
+//! lower bound on the cost of every model below it; literals the search
+//! leaves unassigned in a model are made false. Pure literal elimination
+//! is off during the search, since a pure literal may carry a weight.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::solver::bnb::minimize;
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! // (x0 ∨ x1) ∧ (x1 ∨ x2): x1 alone costs 5, x0 and x2 together 4
+//! let formula = vec![vec![x(0), x(1)], vec![x(1), x(2)]];
+//! let (model, cost) = minimize(&formula, 3, &[(x(0), 2), (x(1), 5), (x(2), 2)]).unwrap();
+//! assert_eq!(cost, 4);
+//! assert!(model.is_true(x(0)) && model.is_true(x(2)) && !model.is_true(x(1)));
+//! ```
+
+use super::dpll::{search, Branching, Hints};
+use crate::types::*;
+
+/// An objective and the best model found for it so far.
+pub(crate) struct Objective {
+    /// Literal with a positive weight of each variable in the objective
+    terms: Vec<(Lit, u64)>,
+    /// Cost every model has: the smaller weight of variables occurring in
+    /// both phases
+    offset: u64,
+    /// The cheapest model found and its cost
+    pub best: Option<(Model, u64)>,
+}
+
+impl Objective {
+    /// Prepares `terms`, merging the terms of each variable into one.
+    pub fn new(terms: &[(Lit, u64)]) -> Self {
+        // Weights of the positive and the negative literal of each variable
+        let mut sorted = terms.to_vec();
+        sorted.sort_by_key(|&(lit, _)| lit.var);
+        let mut weights: Vec<(Var, u64, u64)> = Vec::new();
+        for (lit, weight) in sorted {
+            if weights.last().is_none_or(|&(var, _, _)| var != lit.var) {
+                weights.push((lit.var, 0, 0));
+            }
+            let last = weights.last_mut().expect("pushed above");
+            match lit.neg {
+                false => last.1 += weight,
+                true => last.2 += weight,
+            }
+        }
+        // a·x + b·¬x is min(a, b) plus the difference on the heavier literal
+        let offset = weights.iter().map(|&(_, a, b)| a.min(b)).sum();
+        let terms = weights.into_iter().filter(|&(_, a, b)| a != b).map(|(var, a, b)| (Lit::new(var, b > a), a.abs_diff(b))).collect();
+        Objective { terms, offset, best: None }
+    }
+
+    /// Returns the weight of the true literals of `model`, a lower bound on
+    /// the cost of the models extending it.
+    fn cost(&self, model: &Model) -> u64 {
+        self.offset + self.terms.iter().filter(|&&(lit, _)| model.is_true(lit)).map(|&(_, weight)| weight).sum::<u64>()
+    }
+
+    /// Returns `true` if no model extending `model` beats the best so far.
+    pub fn prunes(&self, model: &Model) -> bool {
+        self.best.as_ref().is_some_and(|&(_, best)| self.cost(model) >= best)
+    }
+
+    /// Records the model `model`, with its unassigned objective literals
+    /// made false, as the best so far, returning `true` if no model can
+    /// cost less.
+    pub fn record(&mut self, model: &Model) -> bool {
+        let mut model = model.clone();
+        for &(lit, _) in &self.terms {
+            if model.value(lit.var) == Val::Undef {
+                model.assign(lit.var, if lit.neg { Val::True } else { Val::False });
+            }
+        }
+        let cost = self.cost(&model);
+        self.best = Some((model, cost));
+        cost == self.offset
+    }
+
+    /// Fills in the phases of `hints` that make the objective literals
+    /// false, where `hints` has none.
+    pub fn suggest_phases(&self, hints: &mut Hints) {
+        for &(lit, _) in &self.terms {
+            let index = lit.var.index();
+            if hints.phases.len() <= index {
+                hints.phases.resize(index + 1, None);
+            }
+            hints.phases[index].get_or_insert(lit.neg);
+        }
+    }
+}
+
+/// Finds a model of `formula` with `num_vars` variables that minimizes the
+/// total weight of its true literals among `terms`.
+///
+/// # Arguments
+///
+/// * `formula` - The formula to solve
+/// * `num_vars` - The number of variables in the formula
+/// * `terms` - The objective: literals with their weights
+///
+/// # Returns
+///
+/// An optimal model with its cost, or `None` if the formula is
+/// unsatisfiable.
+///
+/// # Panics
+///
+/// Panics if a term refers to a variable outside the formula's range.
+pub fn minimize(formula: &Formula, num_vars: usize, terms: &[(Lit, u64)]) -> Option<(Model, u64)> {
+    let objective = Objective::new(terms);
+    let mut hints = Hints::default();
+    objective.suggest_phases(&mut hints);
+    let mut branching = Branching::new(&hints);
+    branching.objective = Some(objective);
+    search(formula, num_vars, &[], &mut branching).expect("search without an interrupt flag completes");
+    branching.objective.and_then(|objective| objective.best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_cheapest_model() {
+        for seed in 0..10 {
+            let (formula, num_vars) = crate::gen::random_ksat(8, 20, 3, seed);
+            let mut rng = crate::rng::Rng::new(seed);
+            let terms: Vec<(Lit, u64)> = (0..12)
+                .map(|_| (Lit::new(Var::new(rng.below(num_vars)), rng.coin()), rng.below(10) as u64))
+                .collect();
+            let cost = |model: &Model| -> u64 { terms.iter().filter(|&&(lit, _)| model.is_true(lit)).map(|&(_, weight)| weight).sum() };
+            let cheapest = crate::solver::brute::models(&formula, num_vars).map(|model| cost(&model)).min();
+            let found = minimize(&formula, num_vars, &terms);
+            assert_eq!(found.as_ref().map(|&(_, cost)| cost), cheapest, "seed {}", seed);
+            if let Some((model, found_cost)) = found {
+                assert_eq!(found_cost, cost(&model));
+                assert!(crate::testing::verify_model(&formula, &model));
+            }
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Brute-force reference solver
+//!
+//! This module provides an exhaustive solver that simply enumerates all
+//! 2^n assignments of the variables. It is hopelessly slow for anything
+//! but tiny formulas, which is exactly the point: it is so simple that it
+//! can serve as a trusted correctness oracle when testing the real solver.
+//!
+//! Typical use is differential testing: generate a small random formula,
+//! solve it with both [`dpll::solve`](super::dpll::solve) and
+//! [`brute::solve`](solve), and check that the answers agree. [`models`]
+//! lists every model, the reference for counting, enumeration and
+//! optimization.
+
+use crate::types::*;
+use super::dpll::SolveResult;
+
+/// Largest number of variables the brute-force solver accepts.
+///
+/// 2^25 assignments already take a few seconds to enumerate; anything
+/// beyond that is not a reasonable oracle anymore.
+pub const MAX_VARS: usize = 25;
+
+/// Solves a SAT problem by enumerating every possible assignment.
+///
+/// Assignments are visited in increasing binary order where bit `i` is the
+/// value of variable `i`, so the returned model is the first satisfying
+/// assignment in that order. Every variable of a returned model is assigned
+/// (no `Val::Undef`).
+///
+/// # Arguments
+///
+/// * `formula` - The CNF formula to solve
+/// * `num_vars` - The total number of variables in the problem (raised if
+///   the formula uses more)
+///
+/// # Returns
+///
+/// * `SolveResult::Sat(model)` - If satisfiable, with a complete satisfying assignment
+/// * `SolveResult::Unsat` - If no assignment satisfies the formula
+///
+/// # Panics
+///
+/// Panics if the number of variables exceeds [`MAX_VARS`].
+///
+/// # Examples
+///
+/// ```
+/// use putnam::solver::{brute, dpll::SolveResult};
+/// use putnam::types::{Lit, Val, Var};
+///
+/// // (x0 ∨ x1) ∧ (¬x0)
+/// let formula = vec![
+///     vec![Lit::positive(Var::new(0)), Lit::positive(Var::new(1))],
+///     vec![Lit::negative(Var::new(0))],
+/// ];
+///
+/// match brute::solve(&formula, 2) {
+///     SolveResult::Sat(model) => {
+///         assert_eq!(model.value(Var::new(0)), Val::False);
+///         assert_eq!(model.value(Var::new(1)), Val::True);
+///     }
+///     SolveResult::Unsat => unreachable!(),
+/// }
+/// ```
+pub fn solve(formula: &Formula, num_vars: usize) -> SolveResult {
+    match models(formula, num_vars).next() {
+        Some(model) => SolveResult::Sat(model),
+        None => SolveResult::Unsat,
+    }
+}
+
+/// Enumerates every model of a formula by trying every assignment.
+///
+/// # Arguments
+///
+/// * `formula` - The CNF formula
+/// * `num_vars` - The total number of variables in the problem (raised if
+///   the formula uses more)
+///
+/// # Returns
+///
+/// The complete satisfying assignments, in the order of [`solve`]
+///
+/// # Panics
+///
+/// Panics if the number of variables exceeds [`MAX_VARS`].
+///
+/// # Examples
+///
+/// ```
+/// use putnam::solver::brute;
+/// use putnam::types::{Lit, Var};
+///
+/// // x0 ∨ x1 has three models over two variables
+/// let formula = vec![vec![Lit::positive(Var::new(0)), Lit::positive(Var::new(1))]];
+/// assert_eq!(brute::models(&formula, 2).count(), 3);
+/// ```
+pub fn models(formula: &Formula, num_vars: usize) -> impl Iterator<Item = Model> + '_ {
+    // Every literal must fall within the enumerated bits
+    let num_vars = formula.iter().flatten().map(|l| l.var.index() + 1).max().unwrap_or(0).max(num_vars);
+    assert!(
+        num_vars <= MAX_VARS,
+        "brute-force solver supports at most {} variables, got {}",
+        MAX_VARS,
+        num_vars
+    );
+    (0u64..(1u64 << num_vars))
+        .filter(|&bits| formula.iter().all(|clause| clause_holds(clause, bits)))
+        .map(move |bits| model_from_bits(bits, num_vars))
+}
+
+/// Checks whether a clause is satisfied by the assignment encoded in `bits`.
+fn clause_holds(clause: &Clause, bits: u64) -> bool {
+    clause.iter().any(|lit| {
+        let value = (bits >> lit.var.index()) & 1 == 1;
+        value != lit.neg
+    })
+}
+
+/// Builds a complete model from the assignment encoded in `bits`.
+fn model_from_bits(bits: u64, num_vars: usize) -> Model {
+    let mut model = Model::new(num_vars);
+    for var in Var::range(num_vars) {
+        let val = if (bits >> var.index()) & 1 == 1 { Val::True } else { Val::False };
+        model.assign(var, val);
+    }
+    model
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(var: usize, neg: bool) -> Lit {
+        Lit::new(Var::new(var), neg)
+    }
+
+    #[test]
+    fn test_simple_sat() {
+        // Formula: (x0) ∧ (¬x1)
+        let formula = vec![vec![lit(0, false)], vec![lit(1, true)]];
+
+        match solve(&formula, 2) {
+            SolveResult::Sat(model) => {
+                assert_eq!(model.value(Var::new(0)), Val::True);
+                assert_eq!(model.value(Var::new(1)), Val::False);
+            }
+            SolveResult::Unsat => panic!("Expected SAT"),
+        }
+    }
+
+    #[test]
+    fn test_simple_unsat() {
+        // Formula: (x0 ∨ x1) ∧ (¬x0) ∧ (¬x1)
+        let formula = vec![
+            vec![lit(0, false), lit(1, false)],
+            vec![lit(0, true)],
+            vec![lit(1, true)],
+        ];
+        assert_eq!(solve(&formula, 2), SolveResult::Unsat);
+    }
+
+    #[test]
+    fn test_empty_formula_and_clause() {
+        assert!(matches!(solve(&vec![], 0), SolveResult::Sat(_)));
+        assert_eq!(solve(&vec![vec![]], 0), SolveResult::Unsat);
+    }
+
+    #[test]
+    fn test_agrees_with_dpll() {
+        // Every 2-clause formula over x0, x1 built from the four literals
+        let lits = [lit(0, false), lit(0, true), lit(1, false), lit(1, true)];
+        for a in 0..4 {
+            for b in 0..4 {
+                for c in 0..4 {
+                    let formula = vec![
+                        vec![lits[a], lits[b]],
+                        vec![lits[c]],
+                        vec![lits[(a + c) % 4], lits[(b + 1) % 4]],
+                    ];
+                    let expected = matches!(super::super::dpll::solve(&formula, 2), SolveResult::Sat(_));
+                    assert_eq!(matches!(solve(&formula, 2), SolveResult::Sat(_)), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_too_many_vars() {
+        solve(&vec![], MAX_VARS + 1);
+    }
+
+    #[test]
+    fn test_variables_beyond_the_count() {
+        // x3 needs four variables even if the caller says one
+        let SolveResult::Sat(model) = solve(&vec![vec![lit(3, false)]], 1) else { panic!("Expected SAT") };
+        assert_eq!(model.num_vars(), 4);
+        assert_eq!(model.value(Var::new(3)), Val::True);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_variable_beyond_max_vars() {
+        solve(&vec![vec![lit(64, false)]], 1);
+    }
+}
@@ -0,0 +1,353 @@
+//! Checkpoint files for long searches
+//!
+//! An interrupted search (see [`Solver::solve_limited`]) remembers its
+//! position: the decisions leading to the node it stopped at, each marked
+//! once its first branch has been refuted. [`Solver::checkpoint`] captures
+//! that position together with the branching hints and statistics, and
+//! [`Solver::resume`] loads it into a solver for the same formula, whose
+//! next search of the same query skips every refuted subtree. The DPLL
+//! search learns no clauses, so the position is the whole of its progress
+//! (a restart only moves it back to a shallower node). The checkpoint also
+//! keeps the variable scores of the branching heuristic, the phases it
+//! computed and the restart counters, so that the resumed search decides
+//! and restarts as the interrupted one would have.
+//!
+//! The format is line based; the first token of a line gives its kind:
+//!
+//! ```text
+//! c putnam checkpoint
+//! p 3 2 5d0c3a9e1f2b4c67
+//! a -2 0
+//! h 1 -3 0
+//! o 3 1 0
+//! s 2 14 40 6 96 48
+//! k lrb jw
+//! r 6 1 4
+//! b 3.99994e-1 1e0
+//! w 1 2.5e-1 0 4
+//! w 2 0e0 1 6
+//! w 3 1.2e-1 0 0
+//! i 1 -2 3 0
+//! f 1
+//! d -3
+//! ```
+//!
+//! * `p` - variable count, clause count and fingerprint of the formula
+//! * `a` - the assumptions of the query (DIMACS literals, 0-terminated)
+//! * `h` - phase hints as DIMACS literals, positive when `true` is preferred
+//! * `o` - the static decision order (1-based variables)
+//! * `s` - the statistics so far: searches, decisions, propagations,
+//!   conflicts, clause bytes and search bytes
+//! * `k` - the branching heuristic and polarity, named as in
+//!   [`config`](super::config) files
+//! * `r` - the conflicts of the interrupted search, its restarts (the
+//!   position in the Luby schedule) and the conflict count at the last one
+//! * `b` - the step size of the learning-rate heuristics and the next
+//!   activity bump
+//! * `w` - the scores of a variable: activity (or learning-rate
+//!   estimate), conflicts participated in, and the conflict count at the
+//!   last of them
+//! * `i` - the phases the polarity computed, as DIMACS literals
+//! * `d` / `f` - a decision on the current path (the literal tried first),
+//!   `f` once its first branch is refuted
+//!
+//! The fingerprint is only stable between builds of the same version of
+//! the solver; a mismatch makes [`Solver::resume`] reject the checkpoint,
+//! as does a solver configured with another heuristic or polarity.
+//!
+//! [`Solver::solve_limited`]: super::Solver::solve_limited
+//! [`Solver::checkpoint`]: super::Solver::checkpoint
+//! [`Solver::resume`]: super::Solver::resume
+
+use std::io::{self, BufRead, Write};
+
+use crate::types::*;
+
+use super::dpll::{Heuristic, HeuristicState, Polarity};
+use super::stats::SolveStats;
+
+/// The saved state of a [`Solver`](super::Solver) and its search position.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Checkpoint {
+    /// Number of variables of the formula
+    pub(crate) num_vars: usize,
+    /// Number of clauses of the formula
+    pub(crate) num_clauses: usize,
+    /// Fingerprint of the clause sequence
+    pub(crate) fingerprint: u64,
+    /// Assumptions of the interrupted query, sorted
+    pub(crate) assumptions: Vec<Lit>,
+    /// Phase hints
+    pub(crate) phases: Vec<Option<bool>>,
+    /// Static decision order
+    pub(crate) order: Vec<Var>,
+    /// Statistics accumulated so far
+    pub(crate) stats: SolveStats,
+    /// Decisions leading to the interrupted node, flagged once refuted
+    pub(crate) path: Vec<(Lit, bool)>,
+    /// Scores, phases and restart counters of the branching heuristic
+    pub(crate) heuristic: HeuristicState,
+}
+
+impl Checkpoint {
+    /// Returns the number of decisions on the saved search path.
+    ///
+    /// Zero means the next search starts from the beginning.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Returns the statistics at the time of the checkpoint.
+    pub fn stats(&self) -> SolveStats {
+        self.stats
+    }
+}
+
+/// Writes a checkpoint.
+///
+/// # Examples
+///
+/// ```
+/// use putnam::solver::checkpoint::{read_checkpoint, write_checkpoint};
+/// use putnam::solver::Solver;
+/// use putnam::types::{Lit, Var};
+///
+/// let x = |var| Lit::positive(Var::new(var));
+/// let mut solver = Solver::from_formula(vec![vec![x(0), x(1)]], 2);
+/// solver.set_phase(Var::new(1), false);
+///
+/// let mut out = Vec::new();
+/// write_checkpoint(&mut out, &solver.checkpoint())?;
+/// assert_eq!(read_checkpoint(out.as_slice())?, solver.checkpoint());
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn write_checkpoint<W: Write>(mut writer: W, checkpoint: &Checkpoint) -> io::Result<()> {
+    writeln!(writer, "c putnam checkpoint")?;
+    writeln!(writer, "p {} {} {:016x}", checkpoint.num_vars, checkpoint.num_clauses, checkpoint.fingerprint)?;
+    write!(writer, "a")?;
+    for &lit in &checkpoint.assumptions {
+        write!(writer, " {}", lit.to_dimacs())?;
+    }
+    writeln!(writer, " 0")?;
+    write!(writer, "h")?;
+    for (var, phase) in Var::range(checkpoint.phases.len()).zip(&checkpoint.phases) {
+        if let Some(value) = phase {
+            write!(writer, " {}", Lit { var, neg: !value }.to_dimacs())?;
+        }
+    }
+    writeln!(writer, " 0")?;
+    write!(writer, "o")?;
+    for var in &checkpoint.order {
+        write!(writer, " {}", var.index() + 1)?;
+    }
+    writeln!(writer, " 0")?;
+    let stats = &checkpoint.stats;
+    writeln!(
+        writer,
+        "s {} {} {} {} {} {}",
+        stats.searches, stats.decisions, stats.propagations, stats.conflicts, stats.clause_bytes, stats.search_bytes
+    )?;
+    let state = &checkpoint.heuristic;
+    writeln!(writer, "k {} {}", heuristic_name(state.heuristic), polarity_name(state.polarity))?;
+    writeln!(writer, "r {} {} {}", state.conflicts, state.restarts, state.restart_conflicts)?;
+    writeln!(writer, "b {:e} {:e}", state.step, state.bump)?;
+    for (var, activity) in Var::range(state.activity.len()).zip(&state.activity) {
+        let participated = state.participated.get(var.index()).copied().unwrap_or(0);
+        let last_conflict = state.last_conflict.get(var.index()).copied().unwrap_or(0);
+        writeln!(writer, "w {} {:e} {} {}", var.index() + 1, activity, participated, last_conflict)?;
+    }
+    if let Some(phases) = &state.phases {
+        write!(writer, "i")?;
+        for (var, &value) in Var::range(phases.len()).zip(phases) {
+            write!(writer, " {}", Lit { var, neg: !value }.to_dimacs())?;
+        }
+        writeln!(writer, " 0")?;
+    }
+    for &(lit, refuted) in &checkpoint.path {
+        writeln!(writer, "{} {}", if refuted { 'f' } else { 'd' }, lit.to_dimacs())?;
+    }
+    Ok(())
+}
+
+/// Reads a checkpoint written by [`write_checkpoint`].
+///
+/// # Returns
+///
+/// * `Ok(Checkpoint)` - The checkpoint
+/// * `Err(io::Error)` - If reading fails, or with `ErrorKind::InvalidData`
+///   if a line is malformed or the `p` line is missing
+pub fn read_checkpoint<R: BufRead>(reader: R) -> io::Result<Checkpoint> {
+    let mut checkpoint = Checkpoint::default();
+    let mut header = false;
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line?;
+        let invalid = |what: &str| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}: '{}'", lineno + 1, what, line.trim()))
+        };
+        let mut tokens = line.split_whitespace();
+        let kind = match tokens.next() {
+            None => continue,
+            Some(kind) if kind.starts_with('c') => continue,
+            Some(kind) => kind,
+        };
+        let numbers: Vec<&str> = tokens.collect();
+        let parse_lit = |token: &str| {
+            token.parse::<i64>().ok().and_then(Lit::from_dimacs).ok_or_else(|| invalid("expected a non-zero literal"))
+        };
+        // Literal lists end with a 0
+        let list = || match numbers.split_last() {
+            Some((&"0", lits)) => lits.iter().map(|t| parse_lit(t)).collect::<io::Result<Vec<Lit>>>(),
+            _ => Err(invalid("expected a 0-terminated list")),
+        };
+        match kind {
+            "p" => {
+                let [vars, clauses, fingerprint] = numbers[..] else {
+                    return Err(invalid("expected variable count, clause count and fingerprint"));
+                };
+                checkpoint.num_vars = vars.parse().map_err(|_| invalid("invalid variable count"))?;
+                checkpoint.num_clauses = clauses.parse().map_err(|_| invalid("invalid clause count"))?;
+                checkpoint.fingerprint =
+                    u64::from_str_radix(fingerprint, 16).map_err(|_| invalid("invalid fingerprint"))?;
+                header = true;
+            }
+            "a" => checkpoint.assumptions = list()?,
+            "h" => {
+                for lit in list()? {
+                    if lit.var.index() >= checkpoint.phases.len() {
+                        checkpoint.phases.resize(lit.var.index() + 1, None);
+                    }
+                    checkpoint.phases[lit.var.index()] = Some(!lit.neg);
+                }
+            }
+            "o" => {
+                let lits = list()?;
+                if lits.iter().any(|l| l.neg) {
+                    return Err(invalid("expected positive variables"));
+                }
+                checkpoint.order = lits.iter().map(|l| l.var).collect();
+            }
+            "s" => {
+                let counts = numbers.iter().map(|t| t.parse::<u64>()).collect::<Result<Vec<u64>, _>>();
+                let Ok(&[searches, decisions, propagations, conflicts, clause_bytes, search_bytes]) = counts.as_deref()
+                else {
+                    return Err(invalid("expected six counters"));
+                };
+                checkpoint.stats =
+                    SolveStats { searches, decisions, propagations, conflicts, clause_bytes, search_bytes };
+            }
+            "k" => {
+                let [heuristic, polarity] = numbers[..] else {
+                    return Err(invalid("expected a heuristic and a polarity"));
+                };
+                let state = &mut checkpoint.heuristic;
+                state.heuristic = parse_heuristic(heuristic).ok_or_else(|| invalid("unknown heuristic"))?;
+                state.polarity = parse_polarity(polarity).ok_or_else(|| invalid("unknown polarity"))?;
+            }
+            "r" => {
+                let counts = numbers.iter().map(|t| t.parse::<u64>()).collect::<Result<Vec<u64>, _>>();
+                let Ok(&[conflicts, restarts, restart_conflicts]) = counts.as_deref() else {
+                    return Err(invalid("expected three counters"));
+                };
+                let state = &mut checkpoint.heuristic;
+                (state.conflicts, state.restarts, state.restart_conflicts) = (conflicts, restarts, restart_conflicts);
+            }
+            "b" => {
+                let values = numbers.iter().map(|t| t.parse::<f64>()).collect::<Result<Vec<f64>, _>>();
+                let Ok(&[step, bump]) = values.as_deref() else {
+                    return Err(invalid("expected a step size and a bump"));
+                };
+                (checkpoint.heuristic.step, checkpoint.heuristic.bump) = (step, bump);
+            }
+            "w" => {
+                let [var, activity, participated, last_conflict] = numbers[..] else {
+                    return Err(invalid("expected a variable and its three scores"));
+                };
+                let var = var
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|var| var.checked_sub(1))
+                    .and_then(Var::try_new)
+                    .ok_or_else(|| invalid("invalid variable"))?;
+                let state = &mut checkpoint.heuristic;
+                if var.index() >= state.activity.len() {
+                    state.activity.resize(var.index() + 1, 0.0);
+                    state.participated.resize(var.index() + 1, 0);
+                    state.last_conflict.resize(var.index() + 1, 0);
+                }
+                state.activity[var.index()] = activity.parse().map_err(|_| invalid("invalid activity"))?;
+                state.participated[var.index()] = participated.parse().map_err(|_| invalid("invalid count"))?;
+                state.last_conflict[var.index()] = last_conflict.parse().map_err(|_| invalid("invalid count"))?;
+            }
+            "i" => {
+                let lits = list()?;
+                if lits.iter().enumerate().any(|(index, lit)| lit.var.index() != index) {
+                    return Err(invalid("expected one phase per variable, in order"));
+                }
+                checkpoint.heuristic.phases = Some(lits.iter().map(|lit| !lit.neg).collect());
+            }
+            "d" | "f" => match numbers[..] {
+                [lit] => checkpoint.path.push((parse_lit(lit)?, kind == "f")),
+                _ => return Err(invalid("expected one literal")),
+            },
+            _ => return Err(invalid("unknown line")),
+        }
+    }
+    if !header {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "missing 'p' line"));
+    }
+    Ok(checkpoint)
+}
+
+/// Returns the configuration file name of `heuristic`.
+fn heuristic_name(heuristic: Heuristic) -> &'static str {
+    match heuristic {
+        Heuristic::Activity => "activity",
+        Heuristic::Lrb => "lrb",
+        Heuristic::Chb => "chb",
+    }
+}
+
+/// Parses a heuristic named by [`heuristic_name`].
+fn parse_heuristic(name: &str) -> Option<Heuristic> {
+    [Heuristic::Activity, Heuristic::Lrb, Heuristic::Chb].into_iter().find(|&h| heuristic_name(h) == name)
+}
+
+/// Returns the configuration file name of `polarity`.
+fn polarity_name(polarity: Polarity) -> &'static str {
+    match polarity {
+        Polarity::True => "true",
+        Polarity::False => "false",
+        Polarity::JeroslowWang => "jw",
+        Polarity::Occurrence => "occurrence",
+    }
+}
+
+/// Parses a polarity named by [`polarity_name`].
+fn parse_polarity(name: &str) -> Option<Polarity> {
+    [Polarity::True, Polarity::False, Polarity::JeroslowWang, Polarity::Occurrence]
+        .into_iter()
+        .find(|&p| polarity_name(p) == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn malformed_lines_are_rejected() {
+        assert!(read_checkpoint("c putnam checkpoint\n".as_bytes()).is_err());
+        assert!(read_checkpoint("p 2 1 ff\na 1\n".as_bytes()).is_err());
+        assert!(read_checkpoint("p 2 1 ff\no -1 0\n".as_bytes()).is_err());
+        assert!(read_checkpoint("p 2 1 ff\ns 1 2 3\n".as_bytes()).is_err());
+        assert!(read_checkpoint("p 2 1 ff\nd 0\n".as_bytes()).is_err());
+        assert!(read_checkpoint("p 2 1 ff\nx 1\n".as_bytes()).is_err());
+        assert!(read_checkpoint("p 2 1 ff\nk vsids true\n".as_bytes()).is_err());
+        assert!(read_checkpoint("p 2 1 ff\nw 0 1e0 0 0\n".as_bytes()).is_err());
+        assert!(read_checkpoint("p 2 1 ff\ni -2 0\n".as_bytes()).is_err());
+
+        let checkpoint = read_checkpoint("p 2 1 ff\nh -2 0\nf 1\nd -2\n".as_bytes()).unwrap();
+        assert_eq!(checkpoint.phases, vec![None, Some(false)]);
+        assert_eq!(checkpoint.depth(), 2);
+        assert_eq!(checkpoint.path[0], (Lit::positive(Var::new(0)), true));
+    }
+}
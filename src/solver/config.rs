@@ -0,0 +1,265 @@
+//! Tunable search parameters
+//!
+//! [`SolverConfig`] collects the knobs of the DPLL search that are worth
+//! tuning per instance family. Every setting only changes the order in
+//! which the search explores assignments, never the answer.
+//!
+//! The search learns no clauses, so there are no clause-activity or
+//! learned-clause parameters.
+//!
+//! # File format
+//!
+//! [`read_config`] reads configurations from flat TOML files with one
+//! `key = value` line per field; missing keys keep their defaults:
+//!
+//! ```toml
+//! # Activity-based branching with some noise
+//! propagation = "counters"    # or "scan"
+//! heuristic = "activity"      # or "lrb", "chb"
+//! polarity = "jw"             # or "true", "false", "occurrence"
+//! restart_interval = 100      # omit to never restart
+//! var_decay = 0.95            # omit for the static heuristic
+//! random_decision_freq = 0.02
+//! seed = 7
+//! special_classes = false
+//! deterministic = true        # reproducible statistics on every platform
+//! pure_literals = true
+//! ```
+
+use std::io::{self, BufRead};
+
+use super::dpll::{Heuristic, Polarity, Propagation};
+
+/// Parameters of the search, applied with [`Solver::set_config`](super::Solver::set_config).
+///
+/// The default reproduces the plain search (scanning propagation, the
+/// static first-open-clause heuristic and no random decisions), with
+/// special formula classes handed to their polynomial algorithms.
+///
+/// # Examples
+///
+/// ```
+/// use putnam::solver::{Solver, SolverConfig};
+/// use putnam::solver::dpll::{Heuristic, Polarity, Propagation, SolveResult};
+///
+/// let (formula, num_vars, _) = putnam::gen::families::pigeonhole(4);
+/// let mut solver = Solver::from_formula(formula, num_vars);
+/// solver.set_config(SolverConfig {
+///     propagation: Propagation::Counters,
+///     heuristic: Heuristic::Activity,
+///     polarity: Polarity::JeroslowWang,
+///     restart_interval: Some(50),
+///     var_decay: Some(0.95),
+///     random_decision_freq: 0.02,
+///     seed: 7,
+///     special_classes: false,
+///     deterministic: false,
+///     pure_literals: true,
+/// });
+/// assert_eq!(solver.solve(), SolveResult::Unsat);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolverConfig {
+    /// Unit propagation engine
+    pub propagation: Propagation,
+    /// Dynamic branching heuristic
+    pub heuristic: Heuristic,
+    /// Value tried first for variables without a phase hint
+    pub polarity: Polarity,
+    /// Conflicts per unit of the Luby restart schedule, or `None` to never
+    /// restart. Restarts keep the decisions the heuristic would repeat
+    /// (partial restarts with trail reuse); they only pay off with a
+    /// dynamic heuristic or random decisions. The schedule grows without
+    /// bound, so the search stays complete.
+    pub restart_interval: Option<u64>,
+    /// Variable activity decay in `(0, 1]`, or `None` for the static
+    /// heuristic. With a decay, every conflict bumps the variables of the
+    /// falsified clause, older bumps fade by this factor, and the search
+    /// branches on the most active variable of the open clauses. Activities
+    /// start from zero in every search, except one continuing an
+    /// interrupted search. Only used by [`Heuristic::Activity`].
+    pub var_decay: Option<f64>,
+    /// Probability in `[0, 1]` that a decision picks a random unassigned
+    /// variable instead of consulting the heuristic
+    pub random_decision_freq: f64,
+    /// Seed of the random decisions; equal seeds give equal searches
+    pub seed: u64,
+    /// Whether 2-SAT, Horn and XOR formulas (including the assumptions) are
+    /// decided by the algorithms of [`special`](super::special) instead of
+    /// the search. They are never used while phase hints, a decision order,
+    /// recording, replay or a resumed position steer the search.
+    pub special_classes: bool,
+    /// Whether the reported figures leave out everything that depends on
+    /// the platform or the clock, so that equal inputs give bit-identical
+    /// statistics, checkpoints and proofs everywhere. The byte counters of
+    /// [`Solver::stats`](super::Solver::stats) (which depend on pointer
+    /// width and allocator growth) and the phase times of
+    /// [`Solver::profile`](super::Solver::profile) are reported as zero.
+    /// The search itself never reads the clock, iterates over hash maps or
+    /// depends on thread scheduling; its random decisions follow `seed`.
+    pub deterministic: bool,
+    /// Whether every node of the search assigns the pure literals: those
+    /// occurring in open clauses while their complements do not. They are
+    /// found from per-literal occurrence counts kept along the trail.
+    /// Assigning them never loses a model of the formula (though it may
+    /// skip models of the pure literals' complements), and they appear as
+    /// assignments without a reason in [`graph`](super::graph).
+    pub pure_literals: bool,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        SolverConfig {
+            propagation: Propagation::Scan,
+            heuristic: Heuristic::Activity,
+            polarity: Polarity::True,
+            restart_interval: None,
+            var_decay: None,
+            random_decision_freq: 0.0,
+            seed: 0,
+            special_classes: true,
+            deterministic: false,
+            pure_literals: false,
+        }
+    }
+}
+
+impl SolverConfig {
+    /// Checks that the parameters are within their ranges.
+    ///
+    /// # Returns
+    ///
+    /// A description of the first invalid parameter, if any
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(decay) = self.var_decay {
+            if !(decay > 0.0 && decay <= 1.0) {
+                return Err(format!("var_decay must be in (0, 1], got {}", decay));
+            }
+        }
+        if self.restart_interval == Some(0) {
+            return Err("restart_interval must be positive".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.random_decision_freq) {
+            return Err(format!("random_decision_freq must be in [0, 1], got {}", self.random_decision_freq));
+        }
+        Ok(())
+    }
+}
+
+/// Reads a configuration in the flat TOML format described in the
+/// [module documentation](self).
+///
+/// # Returns
+///
+/// * `Ok(config)` - The configuration, which passes [`SolverConfig::validate`]
+/// * `Err(error)` - `InvalidData` for unknown keys, malformed values or
+///   parameters out of range
+///
+/// # Examples
+///
+/// ```
+/// use putnam::solver::config::read_config;
+/// use putnam::solver::dpll::Propagation;
+///
+/// let config = read_config("propagation = \"counters\"\nvar_decay = 0.9 # fast decay\n".as_bytes()).unwrap();
+/// assert_eq!(config.propagation, Propagation::Counters);
+/// assert_eq!(config.var_decay, Some(0.9));
+/// assert!(config.special_classes);
+/// ```
+pub fn read_config<R: BufRead>(reader: R) -> io::Result<SolverConfig> {
+    let mut config = SolverConfig::default();
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line?;
+        let invalid = |what: &str| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}: '{}'", lineno + 1, what, line.trim()))
+        };
+        // Comments cannot contain '#' inside strings, as no value needs one
+        let content = line.split('#').next().unwrap_or_default().trim();
+        if content.is_empty() {
+            continue;
+        }
+        let Some((key, value)) = content.split_once('=') else {
+            return Err(invalid("expected key = value"));
+        };
+        let value = value.trim();
+        let number = || value.parse::<f64>().map_err(|_| invalid("expected a number"));
+        match key.trim() {
+            "propagation" => {
+                config.propagation = match value {
+                    "\"scan\"" => Propagation::Scan,
+                    "\"counters\"" => Propagation::Counters,
+                    _ => return Err(invalid("expected \"scan\" or \"counters\"")),
+                }
+            }
+            "heuristic" => {
+                config.heuristic = match value {
+                    "\"activity\"" => Heuristic::Activity,
+                    "\"lrb\"" => Heuristic::Lrb,
+                    "\"chb\"" => Heuristic::Chb,
+                    _ => return Err(invalid("expected \"activity\", \"lrb\" or \"chb\"")),
+                }
+            }
+            "polarity" => {
+                config.polarity = match value {
+                    "\"true\"" => Polarity::True,
+                    "\"false\"" => Polarity::False,
+                    "\"jw\"" => Polarity::JeroslowWang,
+                    "\"occurrence\"" => Polarity::Occurrence,
+                    _ => return Err(invalid("expected \"true\", \"false\", \"jw\" or \"occurrence\"")),
+                }
+            }
+            "restart_interval" => {
+                config.restart_interval = Some(value.parse().map_err(|_| invalid("expected an unsigned integer"))?)
+            }
+            "var_decay" => config.var_decay = Some(number()?),
+            "random_decision_freq" => config.random_decision_freq = number()?,
+            "seed" => config.seed = value.parse().map_err(|_| invalid("expected an unsigned integer"))?,
+            "special_classes" => {
+                config.special_classes = value.parse().map_err(|_| invalid("expected true or false"))?
+            }
+            "deterministic" => config.deterministic = value.parse().map_err(|_| invalid("expected true or false"))?,
+            "pure_literals" => config.pure_literals = value.parse().map_err(|_| invalid("expected true or false"))?,
+            _ => return Err(invalid("unknown key")),
+        }
+    }
+    config.validate().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_are_checked() {
+        assert_eq!(SolverConfig::default().validate(), Ok(()));
+        assert!(SolverConfig { var_decay: Some(0.0), ..SolverConfig::default() }.validate().is_err());
+        assert!(SolverConfig { var_decay: Some(f64::NAN), ..SolverConfig::default() }.validate().is_err());
+        assert!(SolverConfig { random_decision_freq: 1.5, ..SolverConfig::default() }.validate().is_err());
+        assert!(SolverConfig { restart_interval: Some(0), ..SolverConfig::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn config_files() {
+        let text = "# tuned\n\npropagation = \"scan\"\nheuristic = \"chb\"\npolarity = \"occurrence\"\nrandom_decision_freq = 0.5\nseed = 42\nspecial_classes = false\ndeterministic = true\npure_literals = true\n";
+        let config = read_config(text.as_bytes()).unwrap();
+        assert_eq!(
+            config,
+            SolverConfig {
+                heuristic: Heuristic::Chb,
+                polarity: Polarity::Occurrence,
+                random_decision_freq: 0.5,
+                seed: 42,
+                special_classes: false,
+                deterministic: true,
+                pure_literals: true,
+                ..SolverConfig::default()
+            }
+        );
+
+        for bad in ["seed = -1", "restarts = 100", "restart_interval = -5", "var_decay = 2", "propagation = counters", "heuristic = \"vsids\"", "special_classes"] {
+            let error = read_config(bad.as_bytes()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{}", bad);
+        }
+    }
+}
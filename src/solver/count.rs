@@ -0,0 +1,360 @@
+//! Model counting, exact and approximate
+//!
+//! [`count`] computes the number of models of a formula (#SAT) exactly, by
+//! a DPLL search that does not stop at the first model: a branch whose
+//! clauses are all satisfied contributes `2^k` models for its `k`
+//! unassigned variables, and a refuted branch none. Like the search for a
+//! single model it is exponential in the worst case, but free variables are
+//! counted at once rather than enumerated.
+//!
+//! [`approx_count`] estimates large counts with random hashing, after
+//! ApproxMC: random parity (XOR) constraints split the models into cells of
+//! roughly equal size, and enough constraints are added for the cell
+//! containing the remaining models to be small enough to enumerate. Its
+//! size times the number of cells is an estimate, and the median over
+//! several rounds is reported. Formulas with few models are counted
+//! exactly. The constraints are not encoded as clauses: they are kept in
+//! reduced row echelon form beside them, and the count of a cell branches
+//! on the variables that are not pivots, whose values fix the rest.
+//!
+//! Counts are `u128`; `None` stands for a count of `2^128` or more.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::solver::count::{approx_count, count};
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! // x0 ∨ x1 over four variables: 3 · 2^2 models
+//! let formula = vec![vec![x(0), x(1)]];
+//! assert_eq!(count(&formula, 4), Some(12));
+//! assert_eq!(approx_count(&formula, 4, 0), Some(12));
+//!
+//! // 2^100 models, far too many to enumerate
+//! assert_eq!(count(&Vec::new(), 100), Some(1 << 100));
+//! ```
+
+use crate::rng::Rng;
+use crate::types::*;
+
+/// Models a cell may have to count as small, and the most counted per
+/// cell; ApproxMC's value for a tolerance of 0.8
+const THRESHOLD: usize = 73;
+
+/// Rounds of hashing whose median is reported
+const ROUNDS: usize = 9;
+
+/// Counts the models of `formula` exactly.
+///
+/// # Arguments
+///
+/// * `formula` - The formula to count the models of
+/// * `num_vars` - The number of variables (raised if the formula uses
+///   more); unused variables double the count
+///
+/// # Returns
+///
+/// The number of complete assignments satisfying the formula, or `None` if
+/// it is `2^128` or more.
+pub fn count(formula: &Formula, num_vars: usize) -> Option<u128> {
+    let num_vars = formula.iter().flatten().map(|l| l.var.index() + 1).max().unwrap_or(0).max(num_vars);
+    Counter::new(formula, num_vars, Vec::new(), u128::MAX).count()
+}
+
+/// A parity constraint: the sum modulo 2 of the variables equals the
+/// parity.
+type Xor = (Vec<Var>, bool);
+
+/// State of an exact count.
+struct Counter<'a> {
+    formula: &'a Formula,
+    /// Parity constraints that hold besides the clauses, in reduced row
+    /// echelon form
+    xors: Vec<Xor>,
+    /// Whether each variable is the pivot of a parity constraint, and thus
+    /// set by propagation once the others are
+    pivot: Vec<bool>,
+    vals: Vec<Val>,
+    /// Assigned variables, in order
+    trail: Vec<Var>,
+    /// Count after which the search may stop early, returning a count of
+    /// at least `limit`
+    limit: u128,
+}
+
+impl<'a> Counter<'a> {
+    fn new(formula: &'a Formula, num_vars: usize, xors: Vec<Xor>, limit: u128) -> Self {
+        let mut pivot = vec![false; num_vars];
+        for (vars, _) in &xors {
+            pivot[vars[0].index()] = true;
+        }
+        Counter { formula, xors, pivot, vals: vec![Val::Undef; num_vars], trail: Vec::new(), limit }
+    }
+
+    fn value(&self, lit: Lit) -> Val {
+        match (self.vals[lit.var.index()], lit.neg) {
+            (Val::Undef, _) => Val::Undef,
+            (Val::True, false) | (Val::False, true) => Val::True,
+            _ => Val::False,
+        }
+    }
+
+    fn assign(&mut self, lit: Lit) {
+        self.vals[lit.var.index()] = if lit.neg { Val::False } else { Val::True };
+        self.trail.push(lit.var);
+    }
+
+    fn undo(&mut self, mark: usize) {
+        for var in self.trail.drain(mark..) {
+            self.vals[var.index()] = Val::Undef;
+        }
+    }
+
+    /// Assigns the literals of unit clauses and the last variables of
+    /// parity constraints until none is left; returns `false` on a
+    /// falsified clause or constraint.
+    fn propagate(&mut self) -> bool {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for clause in self.formula {
+                let mut open = None;
+                let mut satisfied = false;
+                let mut unassigned = 0;
+                for &lit in clause {
+                    match self.value(lit) {
+                        Val::True => {
+                            satisfied = true;
+                            break;
+                        }
+                        Val::Undef => {
+                            unassigned += 1;
+                            open = Some(lit);
+                        }
+                        Val::False => {}
+                    }
+                }
+                match (satisfied, unassigned, open) {
+                    (true, _, _) => {}
+                    (false, 0, _) => return false,
+                    (false, 1, Some(lit)) => {
+                        self.assign(lit);
+                        changed = true;
+                    }
+                    _ => {}
+                }
+            }
+            for index in 0..self.xors.len() {
+                let (vars, parity) = &self.xors[index];
+                // The parity still missing from the unassigned variables
+                let mut missing = *parity;
+                let mut open = None;
+                let mut unassigned = 0;
+                for &var in vars {
+                    match self.vals[var.index()] {
+                        Val::True => missing = !missing,
+                        Val::False => {}
+                        Val::Undef => {
+                            unassigned += 1;
+                            open = Some(var);
+                        }
+                    }
+                }
+                match (unassigned, open) {
+                    (0, _) if missing => return false,
+                    (1, Some(var)) => {
+                        self.assign(Lit::new(var, !missing));
+                        changed = true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        true
+    }
+
+    /// Returns the unassigned variable occurring most often in clauses not
+    /// yet satisfied and in open parity constraints, preferring those that
+    /// are not pivots, or `None` if nothing is left open.
+    fn branch_var(&self) -> Option<Var> {
+        let mut occurrences = vec![0usize; self.vals.len()];
+        for clause in self.formula {
+            if clause.iter().any(|&lit| self.value(lit) == Val::True) {
+                continue;
+            }
+            for lit in clause.iter().filter(|&&lit| self.value(lit) == Val::Undef) {
+                occurrences[lit.var.index()] += 1;
+            }
+        }
+        for (vars, _) in &self.xors {
+            for var in vars.iter().filter(|var| self.vals[var.index()] == Val::Undef) {
+                occurrences[var.index()] += 1;
+            }
+        }
+        let (index, &most) = occurrences
+            .iter()
+            .enumerate()
+            .max_by_key(|&(index, &count)| (count > 0 && !self.pivot[index], count, std::cmp::Reverse(index)))?;
+        (most > 0).then(|| Var::new(index))
+    }
+
+    /// Counts the models extending the current assignment.
+    fn count(&mut self) -> Option<u128> {
+        let mark = self.trail.len();
+        let result = if !self.propagate() {
+            Some(0)
+        } else {
+            match self.branch_var() {
+                None => {
+                    let free = self.vals.iter().filter(|&&val| val == Val::Undef).count();
+                    (free < 128).then(|| 1 << free)
+                }
+                Some(var) => {
+                    let inner = self.trail.len();
+                    self.assign(Lit::positive(var));
+                    let positive = self.count();
+                    if positive.is_some_and(|positive| positive >= self.limit) {
+                        self.undo(mark);
+                        return positive;
+                    }
+                    self.undo(inner);
+                    self.assign(Lit::negative(var));
+                    // Only the models still missing to reach the limit
+                    let limit = self.limit;
+                    self.limit = limit - positive.unwrap_or(0);
+                    let negative = self.count();
+                    self.limit = limit;
+                    positive.zip(negative).and_then(|(positive, negative)| positive.checked_add(negative))
+                }
+            }
+        };
+        self.undo(mark);
+        result
+    }
+}
+
+/// Estimates the number of models of `formula` by hashing, see the
+/// [module documentation](self).
+///
+/// Formulas with fewer than 73 models are counted exactly. Larger counts
+/// are usually within a factor of 1.8 of the true count; the estimate is
+/// a random variable, fixed by `seed`.
+///
+/// # Arguments
+///
+/// * `formula` - The formula to count the models of
+/// * `num_vars` - The number of variables (raised if the formula uses more)
+/// * `seed` - Seed of the random parity constraints
+///
+/// # Returns
+///
+/// The estimated number of models, or `None` if it is `2^128` or more.
+pub fn approx_count(formula: &Formula, num_vars: usize, seed: u64) -> Option<u128> {
+    let num_vars = formula.iter().flatten().map(|l| l.var.index() + 1).max().unwrap_or(0).max(num_vars);
+    let small = cell_size(formula, num_vars, &[]);
+    if small < THRESHOLD {
+        return Some(small as u128);
+    }
+    let mut rng = Rng::new(seed);
+    let mut estimates: Vec<Option<u128>> = (0..ROUNDS).map(|_| round(formula, num_vars, &mut rng)).collect();
+    // `None` (too large) sorts last
+    estimates.sort_by_key(|estimate| estimate.map_or((1, 0), |count| (0, count)));
+    estimates[ROUNDS / 2]
+}
+
+/// Adds random parity constraints until the cell of the remaining models
+/// is small, and returns its size times the number of cells.
+fn round(formula: &Formula, num_vars: usize, rng: &mut Rng) -> Option<u128> {
+    let mut rows = Vec::new();
+    for constraints in 1..=num_vars {
+        rows.push((Var::range(num_vars).map(|_| rng.coin()).collect(), rng.coin()));
+        let cell = cell_size(formula, num_vars, &rows);
+        if cell == 0 {
+            return Some(0);
+        }
+        if cell < THRESHOLD {
+            let cells = 1u128.checked_shl(constraints as u32)?;
+            return cells.checked_mul(cell as u128);
+        }
+    }
+    // Every variable is hashed; the cells cannot shrink further
+    Some(cell_size(formula, num_vars, &rows) as u128)
+}
+
+/// Counts the models of `formula` satisfying the parity constraints
+/// `rows`, each given by the variables it sums (as a vector of
+/// `num_vars` flags) and its parity, stopping at [`THRESHOLD`].
+fn cell_size(formula: &Formula, num_vars: usize, rows: &[(Vec<bool>, bool)]) -> usize {
+    let Some(xors) = eliminate(rows, num_vars) else {
+        return 0;
+    };
+    let mut counter = Counter::new(formula, num_vars, xors, THRESHOLD as u128);
+    counter.count().map_or(THRESHOLD, |count| count.min(THRESHOLD as u128) as usize)
+}
+
+/// Brings parity constraints into reduced row echelon form by Gauss-Jordan
+/// elimination, so that the first variable of each is its pivot and occurs
+/// in no other.
+///
+/// # Returns
+///
+/// The independent constraints, or `None` if they contradict each other.
+fn eliminate(rows: &[(Vec<bool>, bool)], num_vars: usize) -> Option<Vec<Xor>> {
+    let mut rows = rows.to_vec();
+    let mut pivots = Vec::new();
+    for column in 0..num_vars {
+        let rank = pivots.len();
+        let Some(found) = (rank..rows.len()).find(|&row| rows[row].0[column]) else {
+            continue;
+        };
+        rows.swap(rank, found);
+        let (pivot, parity) = rows[rank].clone();
+        for (index, (vars, other)) in rows.iter_mut().enumerate() {
+            if index != rank && vars[column] {
+                vars.iter_mut().zip(&pivot).for_each(|(var, &bit)| *var ^= bit);
+                *other ^= parity;
+            }
+        }
+        pivots.push(column);
+    }
+    // The remaining rows are empty sums, 0 = parity
+    if rows[pivots.len()..].iter().any(|&(_, parity)| parity) {
+        return None;
+    }
+    let xors = rows
+        .into_iter()
+        .zip(pivots)
+        .map(|((vars, parity), pivot)| {
+            // The pivot first, as `Counter::new` expects
+            let others = Var::range(num_vars).filter(|&var| vars[var.index()] && var.index() != pivot);
+            (std::iter::once(Var::new(pivot)).chain(others).collect(), parity)
+        })
+        .collect();
+    Some(xors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_counts_agree_with_enumeration() {
+        for seed in 0..20 {
+            let (formula, num_vars) = crate::gen::random_ksat(10, 20 + seed as usize % 25, 3, seed);
+            let expected = crate::solver::brute::models(&formula, num_vars).count();
+            assert_eq!(count(&formula, num_vars), Some(expected as u128), "seed {}", seed);
+        }
+        assert_eq!(count(&vec![vec![]], 3), Some(0));
+        assert_eq!(count(&Vec::new(), 128), None);
+    }
+
+    #[test]
+    fn approximate_counts_are_close() {
+        let (formula, num_vars) = crate::gen::random_ksat(16, 24, 3, 1);
+        let exact = count(&formula, num_vars).unwrap() as f64;
+        let estimate = approx_count(&formula, num_vars, 7).unwrap() as f64;
+        assert!(exact > THRESHOLD as f64);
+        assert!(estimate >= exact / 1.8 && estimate <= exact * 1.8, "{} for {}", estimate, exact);
+    }
+}
@@ -0,0 +1,194 @@
+//! Counter-based unit propagation
+//!
+//! The classic alternative to scanning the formula after every assignment:
+//! each clause keeps a count of its true and false literals, and every
+//! literal has an occurrence list of the clauses containing it. Assigning a
+//! literal only touches the clauses in which it or its complement occurs;
+//! a clause becomes unit when all but one of its literals are false and
+//! none is true, and conflicting when all of them are false.
+//!
+//! The counters follow the model's assignment trail and are rolled back to
+//! a mark when the search backtracks, so one instance serves a whole
+//! search. Selected with [`Propagation::Counters`](super::dpll::Propagation).
+
+use crate::store::FormulaSource;
+use crate::types::*;
+
+/// Type alias for contradiction errors, containing the clause the conflict falsified
+type Contradict = ClauseId;
+
+/// Index of a literal in the occurrence lists.
+fn code(lit: Lit) -> usize {
+    2 * lit.var.index() + lit.neg as usize
+}
+
+/// Per-clause true/false literal counts maintained along the trail.
+pub(crate) struct Counters {
+    /// Clauses containing each literal (see [`code`])
+    occurs: Vec<Vec<usize>>,
+    /// Number of true literals of each clause
+    satisfied: Vec<usize>,
+    /// Number of false literals of each clause
+    falsified: Vec<usize>,
+    /// Literals made true so far, in trail order
+    applied: Vec<Lit>,
+    /// Literals of the unit clauses, which no assignment makes unit, with
+    /// their clauses
+    units: Vec<(Lit, ClauseId)>,
+}
+
+impl Counters {
+    /// Builds the occurrence lists of `formula` for `num_vars` variables.
+    pub fn new<F: FormulaSource + ?Sized>(formula: &F, num_vars: usize) -> Self {
+        let mut counters = Counters {
+            occurs: Vec::new(),
+            satisfied: Vec::new(),
+            falsified: Vec::new(),
+            applied: Vec::new(),
+            units: Vec::new(),
+        };
+        counters.reset(formula, num_vars);
+        counters
+    }
+
+    /// Rebuilds the counters for another formula (or the same formula
+    /// after changes), reusing the allocations.
+    pub fn reset<F: FormulaSource + ?Sized>(&mut self, formula: &F, num_vars: usize) {
+        for list in &mut self.occurs {
+            list.clear();
+        }
+        self.occurs.resize_with(2 * num_vars, Vec::new);
+        for (index, clause) in formula.clauses().enumerate() {
+            for &lit in clause {
+                self.occurs[code(lit)].push(index);
+            }
+        }
+        self.satisfied.clear();
+        self.satisfied.resize(formula.num_clauses(), 0);
+        self.falsified.clear();
+        self.falsified.resize(formula.num_clauses(), 0);
+        self.applied.clear();
+        self.units.clear();
+        self.units.extend(
+            formula
+                .clauses()
+                .enumerate()
+                .filter(|(_, clause)| clause.len() == 1)
+                .map(|(index, clause)| (clause[0], ClauseId::new(index))),
+        );
+    }
+
+    /// Returns the number of trail entries reflected in the counters.
+    pub fn mark(&self) -> usize {
+        self.applied.len()
+    }
+
+    /// Rolls the counters back to an earlier [`mark`](Counters::mark).
+    pub fn undo_to(&mut self, mark: usize) {
+        while self.applied.len() > mark {
+            let lit = self.applied.pop().expect("applied is longer than mark");
+            for &index in &self.occurs[code(lit)] {
+                self.satisfied[index] -= 1;
+            }
+            for &index in &self.occurs[code(!lit)] {
+                self.falsified[index] -= 1;
+            }
+        }
+    }
+
+    /// Brings the counters up to date with `model` and propagates units.
+    ///
+    /// Forced literals are assigned in `model`, with their clauses as
+    /// reasons. The model's trail must extend
+    /// the trail the counters were last synchronized with.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Propagation completed successfully without conflicts
+    /// * `Err(clause)` - The model falsifies `clause`
+    pub fn propagate<F: FormulaSource + ?Sized>(&mut self, formula: &F, model: &mut Model) -> Result<(), Contradict> {
+        for &(unit, reason) in &self.units {
+            if model.value(unit.var) == Val::Undef {
+                model.imply(unit, reason);
+            }
+        }
+        while self.applied.len() < model.trail_len() {
+            let var = model.trail_var(self.applied.len());
+            let lit = Lit { var, neg: model.value(var) == Val::False };
+            self.applied.push(lit);
+            for &index in &self.occurs[code(lit)] {
+                self.satisfied[index] += 1;
+            }
+            for &index in &self.occurs[code(!lit)] {
+                self.falsified[index] += 1;
+            }
+            // Counts are complete before a conflict returns, so undo_to stays exact
+            for &index in &self.occurs[code(!lit)] {
+                let clause = formula.clause(index);
+                if self.satisfied[index] > 0 || self.falsified[index] + 1 < clause.len() {
+                    continue;
+                }
+                if self.falsified[index] == clause.len() {
+                    return Err(ClauseId::new(index));
+                }
+                // Unit in the counters; the model may already be ahead of them
+                if let Some(&unit) = clause.iter().find(|l| model.value(l.var) == Val::Undef) {
+                    model.imply(unit, ClauseId::new(index));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    use crate::solver::unit::unit_propagate;
+
+    fn lit(var: usize, neg: bool) -> Lit {
+        Lit::new(Var::new(var), neg)
+    }
+
+    #[test]
+    fn agrees_with_scanning_and_rolls_back() {
+        // x0 → x1 → x2, and (¬x2 ∨ ¬x3 ∨ x4)
+        let formula = vec![
+            vec![lit(0, true), lit(1, false)],
+            vec![lit(1, true), lit(2, false)],
+            vec![lit(2, true), lit(3, true), lit(4, false)],
+        ];
+        let mut counters = Counters::new(&formula, 5);
+        let mut model = Model::new(5);
+        assert_eq!(counters.propagate(&formula, &mut model), Ok(()));
+        let mark = counters.mark();
+
+        let mut branch = model.clone();
+        branch.assign(Var::new(0), Val::True);
+        branch.assign(Var::new(3), Val::True);
+        let mut scanned = branch.clone();
+        assert_eq!(counters.propagate(&formula, &mut branch), Ok(()));
+        assert_eq!(unit_propagate(&formula, &mut scanned, &mut VecDeque::new()), Ok(()));
+        assert!(branch.diff(&scanned).is_empty());
+        assert_eq!(branch.value(Var::new(4)), Val::True);
+
+        // Both record the clause that forced each literal
+        for model in [&branch, &scanned] {
+            assert_eq!(model.reason(Var::new(0)), None);
+            assert_eq!(model.reason(Var::new(1)), Some(ClauseId::new(0)));
+            assert_eq!(model.reason(Var::new(2)), Some(ClauseId::new(1)));
+            assert_eq!(model.reason(Var::new(4)), Some(ClauseId::new(2)));
+        }
+
+        // After rolling back, the opposite branch conflicts: the last clause
+        // forces ¬x2, which falsifies x1 → x2
+        counters.undo_to(mark);
+        let mut branch = model.clone();
+        branch.assign(Var::new(4), Val::False);
+        branch.assign(Var::new(3), Val::True);
+        branch.assign(Var::new(0), Val::True);
+        assert_eq!(counters.propagate(&formula, &mut branch), Err(ClauseId::new(1)));
+    }
+}
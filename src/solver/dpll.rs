@@ -8,8 +8,32 @@
 //! - Systematic variable selection
 //! - Backtracking search with branch pruning
 //! - Early termination on satisfiability or unsatisfiability
+//! - Optional branching [`Hints`] (phases and a decision order), e.g. for
+//!   warm-starting from a previous model with [`solve_from`]
+//! - A choice of unit propagation engine ([`Propagation`])
+//! - Satisfied clauses tracked incrementally along the trail, so that
+//!   checking for a model and choosing a variable do not rescan the formula
+//! - Optional pure literal elimination at every node, from per-literal
+//!   counts of open occurrences
+//! - Branch and bound over a linear objective (see [`bnb`](super::bnb))
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 
 use crate::types::*;
+use crate::rng::Rng;
+use crate::store::FormulaSource;
+
+use super::bnb::Objective;
+use super::config::SolverConfig;
+use super::counters::Counters;
+use super::events::Event;
+use super::heap::VarHeap;
+use super::profile::{Profile, Span};
+use super::satisfied::Satisfaction;
+use super::stats::SolveStats;
 use super::unit::unit_propagate;
 
 /// Result of a SAT solving attempt.
@@ -22,12 +46,12 @@ use super::unit::unit_propagate;
 ///
 /// ```
 /// use putnam::solver::dpll::{SolveResult, solve};
-/// use putnam::types::{Lit, Val};
+/// use putnam::types::{Lit, Val, Var};
 ///
-/// let formula = vec![vec![Lit { var: 0, neg: false }]];
+/// let formula = vec![vec![Lit::positive(Var::new(0))]];
 /// match solve(&formula, 1) {
 ///     SolveResult::Sat(model) => {
-///         assert_eq!(model.value(0), Val::True);
+///         assert_eq!(model.value(Var::new(0)), Val::True);
 ///         println!("Satisfiable!");
 ///     }
 ///     SolveResult::Unsat => println!("Unsatisfiable"),
@@ -41,6 +65,152 @@ pub enum SolveResult {
     Unsat,
 }
 
+/// Unit propagation engine used by the search.
+///
+/// Both engines derive the same assignments; they differ only in how they
+/// find unit clauses, which makes them useful for comparing propagation
+/// data structures (see `benches/solver.rs`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Propagation {
+    /// Rescan the whole formula after every assignment
+    #[default]
+    Scan,
+    /// Keep true/false literal counts per clause, updated through
+    /// occurrence lists and rolled back on backtracking
+    Counters,
+}
+
+/// Dynamic branching heuristic of the search.
+///
+/// All heuristics branch on the highest-scoring unassigned variable of the
+/// open clauses (the lowest-numbered on ties), kept at the top of a heap
+/// of the unassigned variables; they differ in how conflicts score the variables. The
+/// search learns no clauses, so a variable "participates" in a conflict
+/// when it occurs in the clause the conflict falsified.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Heuristic {
+    /// Decaying activities bumped at every conflict (VSIDS style), if a
+    /// `var_decay` is configured; the static first-open-clause order
+    /// otherwise
+    #[default]
+    Activity,
+    /// Learning-rate branching: a variable is rewarded, when it becomes
+    /// unassigned, with the fraction of the conflicts during its assignment
+    /// in which it participated
+    Lrb,
+    /// Conflict history-based branching: every propagation rewards the
+    /// variables it assigned by how recently they participated in a
+    /// conflict
+    Chb,
+}
+
+/// Value tried first for variables without a phase hint.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Polarity {
+    /// Always `true`
+    #[default]
+    True,
+    /// Always `false`
+    False,
+    /// The literal with the larger Jeroslow-Wang weight (the sum of
+    /// `2^-len` over the clauses containing it), `true` on ties
+    JeroslowWang,
+    /// The literal occurring in more clauses, `true` on ties
+    Occurrence,
+}
+
+impl Polarity {
+    /// Computes the initial phase of every variable of `formula`, or `None`
+    /// for the constant polarities.
+    fn phases<F: FormulaSource + ?Sized>(self, formula: &F, num_vars: usize) -> Option<Vec<bool>> {
+        let weight = match self {
+            Polarity::True | Polarity::False => return None,
+            Polarity::JeroslowWang => |len: usize| 0.5f64.powi(len.min(1000) as i32),
+            Polarity::Occurrence => |_: usize| 1.0,
+        };
+        // Weight of the positive and the negative literal of each variable
+        let mut weights = vec![(0.0, 0.0); num_vars];
+        for clause in formula.clauses() {
+            for lit in clause.iter().filter(|lit| lit.var.index() < num_vars) {
+                let entry = &mut weights[lit.var.index()];
+                if lit.neg { entry.1 += weight(clause.len()) } else { entry.0 += weight(clause.len()) }
+            }
+        }
+        Some(weights.into_iter().map(|(pos, neg)| pos >= neg).collect())
+    }
+}
+
+/// Initial step size of the learning-rate heuristics.
+const STEP: f64 = 0.4;
+/// Final step size of the learning-rate heuristics.
+const MIN_STEP: f64 = 0.06;
+/// Decrease of the step size per conflict.
+const STEP_DECAY: f64 = 1e-6;
+
+/// Branching guidance for the DPLL search.
+///
+/// Hints never affect the answer, only the order in which the search space
+/// is explored (and therefore which model is found, and how fast).
+///
+/// # Examples
+///
+/// ```
+/// use putnam::solver::dpll::{solve_with_hints, Hints, SolveResult};
+/// use putnam::types::{Lit, Val, Var};
+///
+/// // (x0 ∨ x1): prefer x0 = false
+/// let formula = vec![vec![Lit::positive(Var::new(0)), Lit::positive(Var::new(1))]];
+/// let hints = Hints { phases: vec![Some(false)], ..Hints::default() };
+///
+/// match solve_with_hints(&formula, 2, &hints) {
+///     SolveResult::Sat(model) => {
+///         assert_eq!(model.value(Var::new(0)), Val::False);
+///         assert_eq!(model.value(Var::new(1)), Val::True);
+///     }
+///     SolveResult::Unsat => unreachable!(),
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Hints {
+    /// Value to try first for each variable (`None` or missing: the
+    /// configured [`Polarity`], `true` by default)
+    pub phases: Vec<Option<bool>>,
+    /// Variables to branch on first, in this order; once all of them are
+    /// assigned, the default heuristic takes over. Variables outside the
+    /// formula's range are ignored.
+    pub order: Vec<Var>,
+}
+
+impl Hints {
+    /// Derives hints from a (partial or full) model: every assigned variable
+    /// is branched on first, in variable order, with its value in the model.
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - A previous model or partial assignment
+    ///
+    /// # Returns
+    ///
+    /// Hints that steer the search straight towards `model`
+    pub fn from_model(model: &Model) -> Self {
+        let phases: Vec<Option<bool>> = model
+            .iter()
+            .map(|(_, val)| match val {
+                Val::True => Some(true),
+                Val::False => Some(false),
+                Val::Undef => None,
+            })
+            .collect();
+        let order = Var::range(phases.len()).filter(|var| phases[var.index()].is_some()).collect();
+        Hints { phases, order }
+    }
+
+    /// Returns the value to try first for `var`, if hinted.
+    fn phase(&self, var: Var) -> Option<bool> {
+        self.phases.get(var.index()).copied().flatten()
+    }
+}
+
 /// Solves a SAT problem using the DPLL algorithm.
 ///
 /// This is the main entry point for solving Boolean satisfiability problems.
@@ -49,7 +219,8 @@ pub enum SolveResult {
 /// # Arguments
 ///
 /// * `formula` - The CNF formula to solve
-/// * `num_vars` - The total number of variables in the problem
+/// * `num_vars` - The total number of variables in the problem (raised if
+///   the formula uses more, so the model covers every variable)
 ///
 /// # Returns
 ///
@@ -60,12 +231,12 @@ pub enum SolveResult {
 ///
 /// ```
 /// use putnam::solver::dpll::{solve, SolveResult};
-/// use putnam::types::{Lit, Val};
+/// use putnam::types::{Lit, Val, Var};
 ///
 /// // Formula: (x₁ ∨ x₂) ∧ (¬x₁ ∨ x₃)
 /// let formula = vec![
-///     vec![Lit { var: 0, neg: false }, Lit { var: 1, neg: false }],
-///     vec![Lit { var: 0, neg: true }, Lit { var: 2, neg: false }],
+///     vec![Lit::positive(Var::new(0)), Lit::positive(Var::new(1))],
+///     vec![Lit::negative(Var::new(0)), Lit::positive(Var::new(2))],
 /// ];
 ///
 /// match solve(&formula, 3) {
@@ -92,11 +263,601 @@ pub enum SolveResult {
 /// - **Space Complexity**: O(n) for the recursion stack
 /// - **Practical Performance**: Often much better due to unit propagation and pruning
 pub fn solve(formula: &Formula, num_vars: usize) -> SolveResult {
+    solve_with_hints(formula, num_vars, &Hints::default())
+}
+
+/// Solves a formula held in any clause store (see [`store`](crate::store)).
+///
+/// # Returns
+///
+/// The same answer as [`solve`] on the same clauses. Like [`solve`], the
+/// variable count is raised to cover every literal.
+pub fn solve_source<F: FormulaSource + ?Sized>(formula: &F, num_vars: usize) -> SolveResult {
+    let num_vars = formula.clauses().flatten().map(|l| l.var.index() + 1).max().unwrap_or(0).max(num_vars);
+    search(formula, num_vars, &[], &mut Branching::new(&Hints::default())).expect("search without an interrupt flag completes")
+}
+
+/// Solves a SAT problem using the DPLL algorithm, guided by branching hints.
+///
+/// # Arguments
+///
+/// * `formula` - The CNF formula to solve
+/// * `num_vars` - The total number of variables in the problem
+/// * `hints` - Preferred phases and decision order (see [`Hints`])
+///
+/// # Returns
+///
+/// The same answer as [`solve`]; only the model found may differ.
+pub fn solve_with_hints(formula: &Formula, num_vars: usize, hints: &Hints) -> SolveResult {
+    let num_vars = formula.iter().flatten().map(|l| l.var.index() + 1).max().unwrap_or(0).max(num_vars);
+    search(formula, num_vars, &[], &mut Branching::new(hints)).expect("search without an interrupt flag completes")
+}
+
+/// Buffers of the search that are reused from one search to the next, so
+/// that many small queries do not allocate them again: the propagation
+/// queue, the counter-based engine, the satisfied-clause counts and the
+/// heuristics' variable heap and per-variable tables.
+///
+/// The contents are meaningless between searches; clones start empty.
+#[derive(Default)]
+pub(crate) struct Scratch {
+    queue: VecDeque<(Lit, ClauseId)>,
+    counters: Option<Counters>,
+    satisfaction: Satisfaction,
+    heap: VarHeap,
+    activity: Vec<f64>,
+    assigned_at: Vec<Option<u64>>,
+    participated: Vec<u64>,
+    last_conflict: Vec<u64>,
+}
+
+impl Clone for Scratch {
+    fn clone(&self) -> Self {
+        Scratch::default()
+    }
+}
+
+impl fmt::Debug for Scratch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scratch").finish_non_exhaustive()
+    }
+}
+
+/// Scores, phases and restart counters of an interrupted search, with
+/// which a search resuming its position continues.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct HeuristicState {
+    /// Heuristic the scores were computed by
+    pub heuristic: Heuristic,
+    /// Polarity the phases were computed by
+    pub polarity: Polarity,
+    /// Conflicts so far, the clock of `last_conflict` and `restart_conflicts`
+    pub conflicts: u64,
+    /// Restarts so far, which give the position in the Luby schedule
+    pub restarts: u64,
+    /// Conflict count at the last restart
+    pub restart_conflicts: u64,
+    /// Step size of the learning-rate heuristics
+    pub step: f64,
+    /// Amount added to an activity by the next bump
+    pub bump: f64,
+    /// Activity (or learning-rate estimate) of each variable
+    pub activity: Vec<f64>,
+    /// Conflicts each variable participated in
+    pub participated: Vec<u64>,
+    /// Conflict count at the last conflict each variable participated in
+    pub last_conflict: Vec<u64>,
+    /// Phases of the variables without a hint, for the computed polarities
+    pub phases: Option<Vec<bool>>,
+}
+
+/// Branching state of one search: hints plus decision recording and replay.
+///
+/// A decision is recorded as the literal tried first at a branching point;
+/// the opposite branch is implied. Branching points are logged in the
+/// order the depth-first search reaches them.
+pub(crate) struct Branching<'a> {
+    /// Phases and static order
+    pub hints: &'a Hints,
+    /// Decisions made so far, if recording
+    pub record: Option<Vec<Lit>>,
+    /// Decisions to replay, in order
+    pub replay: &'a [Lit],
+    /// Number of replayed decisions consumed
+    pub replayed: usize,
+    /// Whether a replayed decision could not be followed
+    pub diverged: bool,
+    /// Flag polled at every branching point; the search stops once it is set
+    pub interrupt: Option<&'a AtomicBool>,
+    /// Whether the search was stopped by `interrupt`, `budget` or
+    /// `memory_limit`
+    pub interrupted: bool,
+    /// Number of propagations after which the search stops, once it has
+    /// made a decision of its own (so that resumed searches make progress)
+    pub budget: Option<u64>,
+    /// Number of bytes of clauses and partial assignments after which the
+    /// search stops
+    pub memory_limit: Option<u64>,
+    /// Whether the search was stopped by `memory_limit`
+    pub out_of_memory: bool,
+    /// Whether a decision not taken from `resume` was made
+    fresh: bool,
+    /// Work done so far
+    pub stats: SolveStats,
+    /// Memory currently held by partial assignments, in bytes
+    pub live_bytes: usize,
+    /// Propagation engine to use
+    pub propagation: Propagation,
+    /// State of the counter-based engine, built when the search starts
+    counters: Option<Counters>,
+    /// Queue of the scanning engine
+    queue: VecDeque<(Lit, ClauseId)>,
+    /// Clauses satisfied by the model, rebuilt when the search starts
+    satisfaction: Satisfaction,
+    /// Unassigned variables by activity, if decisions follow activities
+    heap: VarHeap,
+    /// Dynamic branching heuristic
+    heuristic: Heuristic,
+    /// Phase of variables without a hint
+    polarity: Polarity,
+    /// Conflicts per unit of the Luby restart schedule, if restarting
+    restart_interval: Option<u64>,
+    /// Restarts so far
+    pub restarts: u64,
+    /// Conflict count at the last restart
+    restart_conflicts: u64,
+    /// Conflicts of the interrupted searches this one continues
+    clock: u64,
+    /// Depth of the first decision to drop, while a restart unwinds the search
+    restart: Option<usize>,
+    /// Restarts with their time and the conflicts of the search before
+    /// them, if logging events
+    pub events: Option<Vec<(Instant, u64, Event)>>,
+    /// Phases computed by `polarity` when the search starts
+    initial_phases: Option<Vec<bool>>,
+    /// Activity decay, if decisions follow variable activities
+    var_decay: Option<f64>,
+    /// Activity (or learning-rate estimate) of each variable
+    activity: Vec<f64>,
+    /// Step size of the learning-rate heuristics
+    step: f64,
+    /// Conflict count at which each variable was assigned, while assigned
+    /// (learning-rate branching)
+    assigned_at: Vec<Option<u64>>,
+    /// Conflicts each assigned variable participated in (learning-rate
+    /// branching)
+    participated: Vec<u64>,
+    /// Conflict count at the last conflict each variable participated in
+    /// (conflict history-based branching)
+    last_conflict: Vec<u64>,
+    /// Amount added to an activity by the next bump
+    bump: f64,
+    /// Whether pure literals are assigned at every node
+    pure_literals: bool,
+    /// Objective to minimize by branch and bound, if any; the search then
+    /// refutes every node after recording its models
+    pub objective: Option<Objective>,
+    /// Probability of a random decision
+    random_freq: f64,
+    /// Source of random decisions
+    rng: Rng,
+    /// Decisions on the way to the current node, each flagged once its
+    /// first branch is exhausted; kept as the position of an interrupted search
+    pub path: Vec<(Lit, bool)>,
+    /// Position of an earlier interrupted search to continue from
+    pub resume: &'a [(Lit, bool)],
+    /// Time spent in each phase (with the `profiling` feature)
+    pub profile: Profile,
+    /// Number of resumed decisions consumed
+    pub resumed: usize,
+}
+
+impl<'a> Branching<'a> {
+    /// Plain branching guided by `hints`, without recording or replay.
+    pub fn new(hints: &'a Hints) -> Self {
+        Branching {
+            hints,
+            record: None,
+            replay: &[],
+            replayed: 0,
+            diverged: false,
+            interrupt: None,
+            interrupted: false,
+            budget: None,
+            memory_limit: None,
+            out_of_memory: false,
+            fresh: false,
+            stats: SolveStats::default(),
+            live_bytes: 0,
+            propagation: Propagation::Scan,
+            counters: None,
+            queue: VecDeque::new(),
+            satisfaction: Satisfaction::default(),
+            heap: VarHeap::default(),
+            heuristic: Heuristic::Activity,
+            polarity: Polarity::True,
+            restart_interval: None,
+            restarts: 0,
+            restart_conflicts: 0,
+            clock: 0,
+            restart: None,
+            events: None,
+            initial_phases: None,
+            var_decay: None,
+            activity: Vec::new(),
+            step: STEP,
+            assigned_at: Vec::new(),
+            participated: Vec::new(),
+            last_conflict: Vec::new(),
+            bump: 1.0,
+            pure_literals: false,
+            objective: None,
+            random_freq: 0.0,
+            rng: Rng::new(0),
+            path: Vec::new(),
+            resume: &[],
+            resumed: 0,
+            profile: Profile::default(),
+        }
+    }
+
+    /// Takes over the buffers of an earlier search.
+    pub fn reuse(&mut self, scratch: Scratch) {
+        self.queue = scratch.queue;
+        self.counters = scratch.counters;
+        self.satisfaction = scratch.satisfaction;
+        self.heap = scratch.heap;
+        self.activity = scratch.activity;
+        self.assigned_at = scratch.assigned_at;
+        self.participated = scratch.participated;
+        self.last_conflict = scratch.last_conflict;
+    }
+
+    /// Hands the buffers on to the next search, with the tables emptied.
+    pub fn scratch(&mut self) -> Scratch {
+        self.activity.clear();
+        self.assigned_at.clear();
+        self.participated.clear();
+        self.last_conflict.clear();
+        Scratch {
+            queue: std::mem::take(&mut self.queue),
+            counters: self.counters.take(),
+            satisfaction: std::mem::take(&mut self.satisfaction),
+            heap: std::mem::take(&mut self.heap),
+            activity: std::mem::take(&mut self.activity),
+            assigned_at: std::mem::take(&mut self.assigned_at),
+            participated: std::mem::take(&mut self.participated),
+            last_conflict: std::mem::take(&mut self.last_conflict),
+        }
+    }
+
+    /// Applies the heuristic parameters of `config`.
+    pub fn configure(&mut self, config: &SolverConfig) {
+        self.propagation = config.propagation;
+        self.heuristic = config.heuristic;
+        self.polarity = config.polarity;
+        self.restart_interval = config.restart_interval;
+        self.var_decay = config.var_decay;
+        self.random_freq = config.random_decision_freq;
+        self.pure_literals = config.pure_literals;
+        self.rng = Rng::new(config.seed);
+    }
+
+    /// Continues the scores, phases and restart counters of an interrupted
+    /// search, if it used the configured heuristic and polarity.
+    pub fn restore(&mut self, state: &HeuristicState) {
+        if (state.heuristic, state.polarity) != (self.heuristic, self.polarity) {
+            return;
+        }
+        self.clock = state.conflicts;
+        self.restarts = state.restarts;
+        self.restart_conflicts = state.restart_conflicts;
+        self.step = state.step;
+        self.bump = state.bump;
+        self.activity.clone_from(&state.activity);
+        self.participated.clone_from(&state.participated);
+        self.last_conflict.clone_from(&state.last_conflict);
+        // The activities may be the only table an activity search sized
+        let len = self.activity.len();
+        self.participated.resize(len, 0);
+        self.last_conflict.resize(len, 0);
+        self.assigned_at.clear();
+        self.assigned_at.resize(len, None);
+        self.initial_phases.clone_from(&state.phases);
+    }
+
+    /// Returns the scores, phases and restart counters reached, for a later
+    /// search to continue with.
+    pub fn heuristic_state(&self) -> HeuristicState {
+        HeuristicState {
+            heuristic: self.heuristic,
+            polarity: self.polarity,
+            conflicts: self.conflicts(),
+            restarts: self.restarts,
+            restart_conflicts: self.restart_conflicts,
+            step: self.step,
+            bump: self.bump,
+            activity: self.activity.clone(),
+            participated: self.participated.clone(),
+            last_conflict: self.last_conflict.clone(),
+            phases: self.initial_phases.clone(),
+        }
+    }
+
+    /// Returns the conflicts so far, counting those of the continued searches.
+    fn conflicts(&self) -> u64 {
+        self.clock + self.stats.conflicts
+    }
+
+    /// Scores the variables of the clause `falsified` by the propagation of
+    /// `model`: bumps their activities and decays the older bumps, or
+    /// records their participation for the learning-rate heuristics.
+    fn on_conflict(&mut self, falsified: &[Lit], model: &Model) {
+        if self.heuristic != Heuristic::Activity {
+            self.reserve(model.num_vars());
+            for lit in falsified {
+                self.participated[lit.var.index()] += 1;
+                self.last_conflict[lit.var.index()] = self.conflicts();
+            }
+            self.step = (self.step - STEP_DECAY).max(MIN_STEP);
+            return;
+        }
+        let Some(decay) = self.var_decay else { return };
+        if self.activity.len() < model.num_vars() {
+            self.activity.resize(model.num_vars(), 0.0);
+        }
+        for lit in falsified {
+            self.activity[lit.var.index()] += self.bump;
+            self.heap.update(lit.var, &self.activity);
+        }
+        self.bump /= decay;
+        if self.bump > 1e100 {
+            for activity in &mut self.activity {
+                *activity *= 1e-100;
+            }
+            self.bump *= 1e-100;
+        }
+    }
+
+    /// Starts a partial restart once the conflicts since the last restart
+    /// reach the Luby schedule.
+    ///
+    /// The restart keeps the leading decisions the heuristic would make
+    /// again anyway: those on variables scoring higher than the best
+    /// unassigned variable of `model` (the variable a full restart would
+    /// decide next), and those whose first branch is already refuted.
+    /// The search then unwinds to the first dropped decision and decides
+    /// again there, without re-propagating the kept levels. Restarts are
+    /// off while decisions are recorded, replayed or resumed, since those
+    /// follow the depth-first order.
+    fn schedule_restart(&mut self, model: &Model) {
+        let Some(interval) = self.restart_interval else { return };
+        if self.record.is_some() || !self.replay.is_empty() || self.resumed < self.resume.len() {
+            return;
+        }
+        let limit = interval.saturating_mul(luby(self.restarts + 1));
+        if self.conflicts() - self.restart_conflicts < limit {
+            return;
+        }
+        self.restarts += 1;
+        self.restart_conflicts = self.conflicts();
+        let score = |var: Var| self.activity.get(var.index()).copied().unwrap_or(0.0);
+        let next = model.unassigned().map(score).fold(f64::NEG_INFINITY, f64::max);
+        let mut level = self.path.iter().take_while(|(lit, _)| score(lit.var) > next).count();
+        // Only a node still in its first branch can decide again
+        while self.path.get(level).is_some_and(|&(_, exhausted)| exhausted) {
+            level += 1;
+        }
+        if level < self.path.len() {
+            self.restart = Some(level);
+        }
+        if let Some(events) = &mut self.events {
+            let restart = Event::Restart {
+                restarts: self.restarts,
+                kept: level.min(self.path.len()) as u64,
+                trail: model.assigned().count() as u64,
+            };
+            events.push((Instant::now(), self.stats.conflicts, restart));
+        }
+    }
+
+    /// Returns `true` if decisions follow the activities, and so come from
+    /// the heap.
+    fn uses_heap(&self) -> bool {
+        self.heuristic != Heuristic::Activity || self.var_decay.is_some()
+    }
+
+    /// Sizes the per-variable tables of the heuristics for `num_vars` variables.
+    fn reserve(&mut self, num_vars: usize) {
+        if self.activity.len() < num_vars {
+            self.activity.resize(num_vars, 0.0);
+            self.assigned_at.resize(num_vars, None);
+            self.participated.resize(num_vars, 0);
+            self.last_conflict.resize(num_vars, 0);
+        }
+    }
+
+    /// Updates the learning-rate heuristics after propagating the trail of
+    /// `model` from position `from` on (the decision of the node and the
+    /// literals it implied), which ended in a conflict if `conflict`.
+    fn on_assigned(&mut self, model: &Model, from: usize, conflict: bool) {
+        if self.heuristic == Heuristic::Activity {
+            return;
+        }
+        self.reserve(model.num_vars());
+        let conflicts = self.conflicts();
+        for position in from..model.trail_len() {
+            let var = model.trail_var(position).index();
+            match self.heuristic {
+                Heuristic::Lrb => {
+                    if self.assigned_at[var].is_none() {
+                        self.assigned_at[var] = Some(conflicts);
+                        self.participated[var] = 0;
+                    }
+                }
+                _ => {
+                    // Propagations that ended in a conflict earn the full reward
+                    let multiplier = if conflict { 1.0 } else { 0.9 };
+                    let reward = multiplier / (conflicts - self.last_conflict[var] + 1) as f64;
+                    self.activity[var] = (1.0 - self.step) * self.activity[var] + self.step * reward;
+                    self.heap.update(Var::new(var), &self.activity);
+                }
+            }
+        }
+    }
+
+    /// Rewards the variables assigned from trail position `from` on as the
+    /// search backtracks over them (learning-rate branching).
+    fn on_unassigned(&mut self, model: &Model, from: usize) {
+        if self.heuristic != Heuristic::Lrb {
+            return;
+        }
+        let conflicts = self.conflicts();
+        for position in from..model.trail_len() {
+            let var = model.trail_var(position).index();
+            let Some(since) = self.assigned_at.get_mut(var).and_then(Option::take) else { continue };
+            if conflicts > since {
+                let rate = self.participated[var] as f64 / (conflicts - since) as f64;
+                self.activity[var] = (1.0 - self.step) * self.activity[var] + self.step * rate;
+                self.heap.update(Var::new(var), &self.activity);
+            }
+        }
+    }
+
+    /// Accounts for `bytes` more (or, negative, fewer) bytes of assignments.
+    fn track(&mut self, bytes: isize) {
+        self.live_bytes = self.live_bytes.saturating_add_signed(bytes);
+        self.stats.search_bytes = self.stats.search_bytes.max(self.live_bytes as u64);
+    }
+
+    /// Takes the next decision of the resumed position, if any, with whether
+    /// its first branch is already exhausted.
+    fn resume_step(&mut self, model: &Model) -> Option<(Lit, bool)> {
+        let &(lit, exhausted) = self.resume.get(self.resumed)?;
+        self.resumed += 1;
+        if lit.var.index() < model.num_vars() && model.value(lit.var) == Val::Undef {
+            return Some((lit, exhausted));
+        }
+        // Not the recorded node: search normally from here
+        self.resumed = self.resume.len();
+        None
+    }
+
+    /// Chooses the decision literal at a branching point.
+    fn decide<F: FormulaSource + ?Sized>(&mut self, formula: &F, model: &Model) -> Option<Lit> {
+        if let Some(&lit) = self.replay.get(self.replayed) {
+            self.replayed += 1;
+            if lit.var.index() < model.num_vars() && model.value(lit.var) == Val::Undef {
+                return Some(lit);
+            }
+            // The recorded path cannot be followed: continue with the heuristic
+            self.diverged = true;
+            self.replayed = self.replay.len();
+        }
+        let hints = self.hints;
+        let hinted = hints.order.iter().copied().find(|&v| v.index() < model.num_vars() && model.value(v) == Val::Undef);
+        let var = hinted
+            .or_else(|| self.random_variable(model))
+            .or_else(|| match (self.heuristic, self.var_decay) {
+                (Heuristic::Activity, None) => choose_variable(formula, model, &mut self.satisfaction),
+                _ => self.heap.top(&self.activity, |var| self.satisfaction.occurs_open(var)),
+            })?;
+        let phase = hints.phase(var).unwrap_or_else(|| match &self.initial_phases {
+            Some(phases) => phases.get(var.index()).copied().unwrap_or(true),
+            None => self.polarity != Polarity::False,
+        });
+        Some(Lit { var, neg: !phase })
+    }
+
+    /// Picks a random unassigned variable with probability `random_freq`.
+    fn random_variable(&mut self, model: &Model) -> Option<Var> {
+        if self.random_freq == 0.0 || (self.rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64 >= self.random_freq {
+            return None;
+        }
+        let open: Vec<Var> = model.unassigned().collect();
+        (!open.is_empty()).then(|| open[self.rng.below(open.len())])
+    }
+}
+
+/// Runs the search with the `assumptions` fixed before the first decision.
+///
+/// The result is `Unsat` if the formula has no model extending the
+/// assumptions (including when the assumptions contradict each other),
+/// and `None` if the search was interrupted.
+pub(crate) fn search<F: FormulaSource + ?Sized>(formula: &F, num_vars: usize, assumptions: &[Lit], branching: &mut Branching) -> Option<SolveResult> {
+    // An empty clause can never be satisfied
+    if formula.clauses().any(|clause| clause.is_empty()) {
+        return Some(SolveResult::Unsat);
+    }
+
+    branching.stats.searches += 1;
+    branching.stats.clause_bytes = branching.stats.clause_bytes.max(formula.heap_bytes() as u64);
+    // Counters left by an earlier search are rebuilt in place, or dropped
+    // when scanning
+    let spare = branching.counters.take();
+    if branching.propagation == Propagation::Counters {
+        branching.counters = Some(match spare {
+            Some(mut counters) => {
+                counters.reset(formula, num_vars);
+                counters
+            }
+            None => Counters::new(formula, num_vars),
+        });
+    }
+    // A pure literal may carry a weight of the objective
+    let track_pure = branching.pure_literals && branching.objective.is_none();
+    branching.satisfaction.reset(formula, num_vars, track_pure);
+    if branching.uses_heap() {
+        branching.heap.reset(num_vars, &branching.activity);
+    }
+    // A resumed search keeps the phases it started with
+    if branching.initial_phases.is_none() {
+        branching.initial_phases = branching.polarity.phases(formula, num_vars);
+    }
     let mut model = Model::new(num_vars);
-    match dpll_search(formula, &mut model) {
-        Ok(()) => SolveResult::Sat(model),
-        Err(_) => SolveResult::Unsat,
+    for &lit in assumptions {
+        match model.value(lit.var) {
+            Val::Undef => model.assign(lit.var, if lit.neg { Val::False } else { Val::True }),
+            _ if model.is_true(lit) => {}
+            _ => return Some(SolveResult::Unsat),
+        }
     }
+    let model_bytes = model.heap_bytes() as isize;
+    branching.track(model_bytes);
+    let result = dpll_search(formula, &mut model, branching);
+    branching.track(-model_bytes);
+    match result {
+        Ok(()) => Some(SolveResult::Sat(model)),
+        Err(_) if branching.interrupted => None,
+        Err(_) => Some(SolveResult::Unsat),
+    }
+}
+
+/// Solves a SAT problem warm-started from a previous model or partial assignment.
+///
+/// The assigned variables of `start` are decided first, with their values
+/// in `start` (see [`Hints::from_model`]). When re-solving a slightly
+/// changed formula, starting from the old solution usually finishes
+/// almost immediately. `start` may have fewer or more variables than
+/// `num_vars`; extra variables are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use putnam::solver::dpll::{solve, solve_from, SolveResult};
+/// use putnam::types::{Lit, Var};
+///
+/// let x = |var| Lit::positive(Var::new(var));
+/// let mut formula = vec![vec![x(0), x(1), x(2)], vec![!x(0), !x(1)]];
+/// let SolveResult::Sat(old) = solve(&formula, 3) else { unreachable!() };
+///
+/// // A new constraint that the old model already satisfies: same model
+/// formula.push(vec![x(0), x(2)]);
+/// let SolveResult::Sat(new) = solve_from(&formula, 3, &old) else { unreachable!() };
+/// assert!(old.diff(&new).is_empty());
+/// ```
+pub fn solve_from(formula: &Formula, num_vars: usize, start: &Model) -> SolveResult {
+    solve_with_hints(formula, num_vars, &Hints::from_model(start))
 }
 
 /// Core DPLL search procedure with systematic branching and backtracking.
@@ -109,6 +870,7 @@ pub fn solve(formula: &Formula, num_vars: usize) -> SolveResult {
 ///
 /// * `formula` - The CNF formula being solved
 /// * `model` - Current partial assignment (modified during search)
+/// * `branching` - Branching guidance, recording and replay
 ///
 /// # Returns
 ///
@@ -120,71 +882,160 @@ pub fn solve(formula: &Formula, num_vars: usize) -> SolveResult {
 /// 1. **Unit Propagation**: Apply all forced assignments
 /// 2. **Base Cases**: Check for satisfaction or contradiction
 /// 3. **Variable Selection**: Choose next variable to branch on
-/// 4. **Recursive Branching**: Try True assignment first, then False
+/// 4. **Recursive Branching**: Try the preferred phase first (True by default), then the other
 /// 5. **Backtracking**: Restore state if both branches fail
-fn dpll_search(formula: &Formula, model: &mut Model) -> Result<(), ()> {
+fn dpll_search<F: FormulaSource + ?Sized>(formula: &F, model: &mut Model, branching: &mut Branching) -> Result<(), ()> {
+    // The decision of this node is the last assignment made by the caller
+    let node = model.trail_len().saturating_sub(1);
+    let result = dpll_node(formula, model, branching);
+    if result.is_err() {
+        branching.on_unassigned(model, node);
+    }
+    result
+}
+
+/// Searches below one node of the DPLL tree; see [`dpll_search`].
+fn dpll_node<F: FormulaSource + ?Sized>(formula: &F, model: &mut Model, branching: &mut Branching) -> Result<(), ()> {
     // Step 1: Unit propagation
-    if unit_propagate(formula, model).is_err() {
+    let assigned = model.trail_len();
+    let span = Span::start();
+    let propagated = match &mut branching.counters {
+        Some(counters) => counters.propagate(formula, model),
+        None => unit_propagate(formula, model, &mut branching.queue),
+    };
+    span.stop(&mut branching.profile.propagation);
+    branching.stats.propagations += (model.trail_len() - assigned) as u64;
+    if let Err(conflict) = propagated {
+        branching.stats.conflicts += 1;
+        let span = Span::start();
+        branching.on_conflict(formula.clause(conflict.index()), model);
+        branching.on_assigned(model, assigned.saturating_sub(1), true);
+        branching.schedule_restart(model);
+        span.stop(&mut branching.profile.conflict);
         return Err(());
     }
+    branching.on_assigned(model, assigned.saturating_sub(1), false);
 
     // Step 2: Check if all clauses are satisfied
-    if is_satisfied(formula, model) {
+    let span = Span::start();
+    branching.satisfaction.sync(formula, model);
+    // Pure literals only satisfy clauses, so they never need propagating
+    while let Some(lit) = branching.satisfaction.next_pure(model) {
+        model.assign(lit.var, if lit.neg { Val::False } else { Val::True });
+        branching.satisfaction.sync(formula, model);
+    }
+    if branching.uses_heap() {
+        branching.heap.sync(model, &branching.activity);
+    }
+    let satisfied = branching.satisfaction.is_satisfied();
+    span.stop(&mut branching.profile.satisfaction);
+    if let Some(objective) = &mut branching.objective {
+        if objective.prunes(model) {
+            return Err(());
+        }
+        // Keep searching for a cheaper model, unless none can exist
+        if satisfied {
+            return if objective.record(model) { Ok(()) } else { Err(()) };
+        }
+    }
+    if satisfied {
         return Ok(());
     }
 
-    // Step 3: Choose an unassigned variable
-    let var = match choose_variable(formula, model) {
-        Some(v) => v,
-        None => return Err(()), // No unassigned variables but not satisfied = UNSAT
-    };
-
-    // Step 4: Try assigning True first
-    let mut model_copy = model.clone();
-    model_copy.assign(var, Val::True);
-    if dpll_search(formula, &mut model_copy).is_ok() {
-        *model = model_copy;
-        return Ok(());
+    let spent = branching.fresh && branching.budget.is_some_and(|budget| branching.stats.propagations >= budget);
+    let held = branching.stats.clause_bytes + branching.live_bytes as u64;
+    if branching.memory_limit.is_some_and(|limit| held > limit) {
+        branching.out_of_memory = true;
+        branching.interrupted = true;
+        return Err(());
+    }
+    if spent || branching.interrupt.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+        branching.interrupted = true;
+        return Err(());
     }
 
-    // Step 5: Try assigning False
-    model.assign(var, Val::False);
-    dpll_search(formula, model)
+    // Steps 3 to 5 repeat when a partial restart lands on this node
+    loop {
+        // Step 3: Choose an unassigned variable (or continue a resumed position)
+        let span = Span::start();
+        let step = match branching.resume_step(model) {
+            Some(step) => Some(step),
+            None => branching.decide(formula, model).map(|lit| {
+                branching.fresh = true;
+                (lit, false)
+            }),
+        };
+        span.stop(&mut branching.profile.decision);
+        // No unassigned variables but not satisfied = UNSAT
+        let Some((decision, exhausted)) = step else { return Err(()) };
+        branching.stats.decisions += 1;
+        if let Some(record) = &mut branching.record {
+            record.push(decision);
+        }
+        let var = decision.var;
+        let (first, second) = if decision.neg { (Val::False, Val::True) } else { (Val::True, Val::False) };
+        let depth = branching.path.len();
+        branching.path.push((decision, exhausted));
+
+        // Step 4: Try the preferred phase first, unless a resumed search exhausted it
+        if !exhausted {
+            let mark = branching.counters.as_ref().map(Counters::mark);
+            let satisfied_mark = branching.satisfaction.mark();
+            let heap_mark = branching.heap.mark();
+            let mut model_copy = model.clone();
+            model_copy.assign(var, first);
+            let copy_bytes = model_copy.heap_bytes() as isize;
+            branching.track(copy_bytes);
+            let found = dpll_search(formula, &mut model_copy, branching).is_ok();
+            branching.track(-copy_bytes);
+            if found {
+                *model = model_copy;
+                return Ok(());
+            }
+            if branching.interrupted {
+                return Err(());
+            }
+            if let (Some(counters), Some(mark)) = (&mut branching.counters, mark) {
+                counters.undo_to(mark);
+            }
+            branching.satisfaction.undo_to(formula, satisfied_mark);
+            branching.heap.undo_to(heap_mark, &branching.activity);
+            if let Some(level) = branching.restart {
+                // Levels below `level` are reused; this node decides again if it is the first dropped one
+                branching.path.truncate(depth);
+                if level < depth {
+                    return Err(());
+                }
+                branching.restart = None;
+                continue;
+            }
+            if let Some(step) = branching.path.last_mut() {
+                step.1 = true;
+            }
+        }
+
+        // Step 5: Try the opposite phase
+        model.assign(var, second);
+        let result = dpll_search(formula, model, branching);
+        if result.is_err() && !branching.interrupted {
+            // A pending restart never targets a node in its second branch
+            branching.path.truncate(depth);
+        }
+        return result;
+    }
 }
 
-/// Checks if all clauses in the formula are satisfied by the current model.
-///
-/// A formula is satisfied if every clause contains at least one literal
-/// that evaluates to true under the current variable assignment.
-///
-/// # Arguments
-///
-/// * `formula` - The CNF formula to check
-/// * `model` - The current variable assignment
-///
-/// # Returns
-///
-/// `true` if all clauses are satisfied, `false` otherwise
-///
-/// # Examples
-///
-/// ```no_run
-/// use putnam::types::{Model, Lit, Val};
-/// # use putnam::solver::dpll::*;
-///
-/// let mut model = Model::new(2);
-/// model.assign(0, Val::True);
-/// 
-/// let formula = vec![
-///     vec![Lit { var: 0, neg: false }],  // x₁ (satisfied)
-/// ];
-///
-/// // assert!(is_satisfied(&formula, &model));
-/// ```
-fn is_satisfied(formula: &Formula, model: &Model) -> bool {
-    formula.iter().all(|clause| {
-        clause.iter().any(|lit| model.is_true(*lit))
-    })
+/// Returns the `i`-th element (from 1) of the Luby sequence
+/// 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8, …
+fn luby(mut i: u64) -> u64 {
+    loop {
+        // The smallest k with i <= 2^k - 1
+        let k = 64 - i.leading_zeros();
+        if i == (1u64 << k) - 1 {
+            return 1 << (k - 1);
+        }
+        i -= (1 << (k - 1)) - 1;
+    }
 }
 
 /// Selects the next variable to branch on during DPLL search.
@@ -197,6 +1048,7 @@ fn is_satisfied(formula: &Formula, model: &Model) -> bool {
 ///
 /// * `formula` - The CNF formula being solved
 /// * `model` - The current partial assignment
+/// * `satisfaction` - The clauses `model` satisfies, synchronized with it
 ///
 /// # Returns
 ///
@@ -212,24 +1064,21 @@ fn is_satisfied(formula: &Formula, model: &Model) -> bool {
 /// # Examples
 ///
 /// ```no_run
-/// use putnam::types::{Model, Lit};
+/// use putnam::types::{Model, Lit, Var};
 /// # use putnam::solver::dpll::*;
 ///
 /// let model = Model::new(3);
 /// let formula = vec![
-///     vec![Lit { var: 0, neg: false }, Lit { var: 1, neg: false }],
+///     vec![Lit::positive(Var::new(0)), Lit::positive(Var::new(1))],
 /// ];
 ///
-/// // let var = choose_variable(&formula, &model);
+/// // let var = choose_variable(&formula, &model, &mut satisfaction);
 /// // assert_eq!(var, Some(0));  // Would choose variable 0
 /// ```
-fn choose_variable(formula: &Formula, model: &Model) -> Option<Var> {
+fn choose_variable<F: FormulaSource + ?Sized>(formula: &F, model: &Model, satisfaction: &mut Satisfaction) -> Option<Var> {
     // Simple heuristic: choose first unassigned variable that appears in unsatisfied clauses
-    for clause in formula.iter() {
-        if clause.iter().any(|lit| model.is_true(*lit)) {
-            continue; // This clause is already satisfied
-        }
-        for lit in clause.iter() {
+    for index in satisfaction.open_clauses() {
+        for lit in formula.clause(index) {
             if model.value(lit.var) == Val::Undef {
                 return Some(lit.var);
             }
@@ -238,13 +1087,12 @@ fn choose_variable(formula: &Formula, model: &Model) -> Option<Var> {
     None
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     fn lit(var: usize, neg: bool) -> Lit {
-        Lit { var, neg }
+        Lit::new(Var::new(var), neg)
     }
 
     #[test]
@@ -257,8 +1105,8 @@ mod tests {
         
         match solve(&formula, 2) {
             SolveResult::Sat(model) => {
-                assert_eq!(model.value(0), Val::True);
-                assert_eq!(model.value(1), Val::False);
+                assert_eq!(model.value(Var::new(0)), Val::True);
+                assert_eq!(model.value(Var::new(1)), Val::False);
             }
             SolveResult::Unsat => panic!("Expected SAT"),
         }
@@ -308,4 +1156,58 @@ mod tests {
         let formula = vec![vec![]];
         assert_eq!(solve(&formula, 0), SolveResult::Unsat);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_warm_start_keeps_previous_model() {
+        // (x0 ∨ x1 ∨ x2 ∨ x3) has many models; the warm start must follow the given
+        // one (the search stops as soon as the clause is satisfied, leaving x3 open)
+        let formula = vec![(0..4).map(|v| lit(v, false)).collect()];
+        let mut start = Model::new(4);
+        for (var, val) in [(0, Val::False), (1, Val::False), (2, Val::True), (3, Val::False)] {
+            start.assign(Var::new(var), val);
+        }
+        match solve_from(&formula, 4, &start) {
+            SolveResult::Sat(model) => assert_eq!(model.diff(&start), vec![(Var::new(3), Val::Undef, Val::False)]),
+            SolveResult::Unsat => panic!("Expected SAT"),
+        }
+
+        // A partial start that conflicts with the formula is just a hint
+        let mut start = Model::new(1);
+        start.assign(Var::new(0), Val::False);
+        assert!(matches!(solve_from(&vec![vec![lit(0, false)]], 1, &start), SolveResult::Sat(_)));
+        assert_eq!(solve_from(&vec![vec![lit(0, false)], vec![lit(0, true)]], 1, &start), SolveResult::Unsat);
+    }
+
+    #[test]
+    fn partial_restarts_keep_answers() {
+        let units: Vec<u64> = (1..=15).map(luby).collect();
+        assert_eq!(units, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+
+        let config = SolverConfig {
+            var_decay: Some(0.9),
+            random_decision_freq: 0.1,
+            restart_interval: Some(1),
+            ..SolverConfig::default()
+        };
+        let (formula, num_vars, _) = crate::gen::families::pigeonhole(5);
+        let hints = Hints::default();
+        let mut branching = Branching::new(&hints);
+        branching.configure(&config);
+        assert_eq!(search(&formula, num_vars, &[], &mut branching), Some(SolveResult::Unsat));
+        assert!(branching.restarts > 0);
+
+        for seed in 0..20 {
+            let (formula, num_vars) = crate::gen::random_ksat(14, 60, 3, seed);
+            let mut branching = Branching::new(&hints);
+            branching.configure(&SolverConfig { seed, ..config.clone() });
+            let result = search(&formula, num_vars, &[], &mut branching).unwrap();
+            match (result, solve(&formula, num_vars)) {
+                (SolveResult::Sat(model), SolveResult::Sat(_)) => {
+                    assert!(formula.iter().all(|c| c.iter().any(|&l| model.is_true(l))), "seed {}", seed)
+                }
+                (SolveResult::Unsat, SolveResult::Unsat) => {}
+                _ => panic!("answers differ for seed {}", seed),
+            }
+        }
+    }
+}
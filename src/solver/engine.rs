@@ -0,0 +1,265 @@
+//! Interchangeable solving engines
+//!
+//! [`SatEngine`] is the small incremental interface shared by every engine
+//! of the crate: add clauses, solve under assumptions, read the model and
+//! the statistics. Applications written against it can switch engines with
+//! a configuration string through [`engine`], and bindings to other
+//! languages only need to wrap this one interface.
+//!
+//! | Name        | Engine              | Complete | Notes                                  |
+//! |-------------|---------------------|----------|----------------------------------------|
+//! | `"dpll"`    | [`DpllEngine`]      | yes      | The [`Solver`] front end               |
+//! | `"walksat"` | [`WalkSatEngine`]   | no       | Local search; gives up on UNSAT        |
+//! | `"brute"`   | [`BruteEngine`]     | yes      | At most [`brute::MAX_VARS`] variables |
+//!
+//! The crate has no CDCL engine; asking [`engine`] for `"cdcl"` is an error.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::solver::dpll::SolveResult;
+//! use putnam::solver::engine::engine;
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! for name in ["dpll", "walksat", "brute"] {
+//!     let mut engine = engine(name).unwrap();
+//!     engine.add_clause(vec![x(0), x(1)]);
+//!     engine.add_clause(vec![!x(0), x(1)]);
+//!     assert!(matches!(engine.solve(), Some(SolveResult::Sat(_))));
+//!     assert!(engine.model().unwrap().is_true(x(1)));
+//! }
+//! ```
+
+use super::brute;
+use super::dpll::SolveResult;
+use super::instance::Solver;
+use super::stats::SolveStats;
+use super::walksat::{walksat, WalkSatConfig};
+use crate::types::*;
+
+/// An incremental SAT engine.
+///
+/// Clauses are only ever added; variables are created as clauses mention
+/// them.
+pub trait SatEngine {
+    /// Adds a clause to the formula.
+    fn add_clause(&mut self, clause: Clause);
+
+    /// Solves the formula with the `assumptions` temporarily added as unit
+    /// clauses.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(result)` - The answer
+    /// * `None` - The engine gave up (an incomplete engine found no model,
+    ///   or the formula is beyond the engine's limits)
+    fn solve_assuming(&mut self, assumptions: &[Lit]) -> Option<SolveResult>;
+
+    /// Solves the formula without assumptions.
+    fn solve(&mut self) -> Option<SolveResult> {
+        self.solve_assuming(&[])
+    }
+
+    /// Returns the model found by the last call to
+    /// [`solve_assuming`](SatEngine::solve_assuming), or `None` if it did
+    /// not answer SAT.
+    fn model(&self) -> Option<&Model>;
+
+    /// Returns the statistics of all searches so far.
+    fn stats(&self) -> SolveStats;
+}
+
+/// Creates the engine called `name` (see the [module documentation](self)).
+///
+/// # Returns
+///
+/// * `Ok(engine)` - A fresh engine with default settings
+/// * `Err(message)` - The name is unknown
+pub fn engine(name: &str) -> Result<Box<dyn SatEngine + Send>, String> {
+    match name {
+        "dpll" => Ok(Box::new(DpllEngine::default())),
+        "walksat" => Ok(Box::new(WalkSatEngine::default())),
+        "brute" => Ok(Box::new(BruteEngine::default())),
+        "cdcl" => Err("no CDCL engine is available; use \"dpll\"".to_string()),
+        _ => Err(format!("unknown engine '{}' (expected dpll, walksat or brute)", name)),
+    }
+}
+
+/// Returns the model in a SAT result.
+fn model_of(result: &SolveResult) -> Option<Model> {
+    match result {
+        SolveResult::Sat(model) => Some(model.clone()),
+        SolveResult::Unsat => None,
+    }
+}
+
+/// Returns the number of variables a clause needs.
+fn vars_needed(clause: &[Lit]) -> usize {
+    clause.iter().map(|lit| lit.var.index() + 1).max().unwrap_or(0)
+}
+
+/// The DPLL search of a [`Solver`].
+#[derive(Debug, Default)]
+pub struct DpllEngine {
+    solver: Solver,
+    model: Option<Model>,
+}
+
+impl DpllEngine {
+    /// Wraps a solver, keeping its clauses and configuration.
+    pub fn new(solver: Solver) -> Self {
+        DpllEngine { solver, model: None }
+    }
+
+    /// Returns the underlying solver, for settings beyond this interface.
+    pub fn solver_mut(&mut self) -> &mut Solver {
+        &mut self.solver
+    }
+}
+
+impl SatEngine for DpllEngine {
+    fn add_clause(&mut self, clause: Clause) {
+        self.solver.add_clause(clause);
+    }
+
+    fn solve_assuming(&mut self, assumptions: &[Lit]) -> Option<SolveResult> {
+        let result = self.solver.solve_with_assumptions(assumptions);
+        self.model = model_of(&result);
+        Some(result)
+    }
+
+    fn model(&self) -> Option<&Model> {
+        self.model.as_ref()
+    }
+
+    fn stats(&self) -> SolveStats {
+        self.solver.stats()
+    }
+}
+
+/// WalkSAT local search (see [`walksat`]).
+///
+/// Each call restarts from a fresh random assignment with the next seed.
+/// Flips are counted as decisions and each call as a search.
+#[derive(Debug, Default)]
+pub struct WalkSatEngine {
+    formula: Formula,
+    num_vars: usize,
+    config: WalkSatConfig,
+    model: Option<Model>,
+    stats: SolveStats,
+}
+
+impl WalkSatEngine {
+    /// Creates an empty engine with the given search parameters.
+    pub fn new(config: WalkSatConfig) -> Self {
+        WalkSatEngine { config, ..WalkSatEngine::default() }
+    }
+}
+
+impl SatEngine for WalkSatEngine {
+    fn add_clause(&mut self, clause: Clause) {
+        self.num_vars = self.num_vars.max(vars_needed(&clause));
+        self.formula.push(clause);
+    }
+
+    fn solve_assuming(&mut self, assumptions: &[Lit]) -> Option<SolveResult> {
+        let num_vars = assumptions.iter().map(|lit| lit.var.index() + 1).fold(self.num_vars, usize::max);
+        let (model, flips) = walksat(&self.formula, num_vars, assumptions, &self.config);
+        self.config.seed = self.config.seed.wrapping_add(1);
+        self.stats.searches += 1;
+        self.stats.decisions += flips;
+        self.model = model;
+        self.model.clone().map(SolveResult::Sat)
+    }
+
+    fn model(&self) -> Option<&Model> {
+        self.model.as_ref()
+    }
+
+    fn stats(&self) -> SolveStats {
+        self.stats
+    }
+}
+
+/// Exhaustive enumeration with [`brute::solve`].
+///
+/// Gives up on formulas with more than [`brute::MAX_VARS`] variables.
+/// Each call counts as a search; nothing else is counted.
+#[derive(Debug, Default)]
+pub struct BruteEngine {
+    formula: Formula,
+    num_vars: usize,
+    model: Option<Model>,
+    stats: SolveStats,
+}
+
+impl SatEngine for BruteEngine {
+    fn add_clause(&mut self, clause: Clause) {
+        self.num_vars = self.num_vars.max(vars_needed(&clause));
+        self.formula.push(clause);
+    }
+
+    fn solve_assuming(&mut self, assumptions: &[Lit]) -> Option<SolveResult> {
+        let num_vars = assumptions.iter().map(|lit| lit.var.index() + 1).fold(self.num_vars, usize::max);
+        self.model = None;
+        if num_vars > brute::MAX_VARS {
+            return None;
+        }
+        let mut formula = self.formula.clone();
+        formula.extend(assumptions.iter().map(|&lit| vec![lit]));
+        let result = brute::solve(&formula, num_vars);
+        self.stats.searches += 1;
+        self.model = model_of(&result);
+        Some(result)
+    }
+
+    fn model(&self) -> Option<&Model> {
+        self.model.as_ref()
+    }
+
+    fn stats(&self) -> SolveStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complete_engines_agree_under_assumptions() {
+        for seed in 0..30 {
+            let (formula, num_vars) = crate::gen::random_ksat(8, 34, 3, seed);
+            let mut engines = [engine("dpll").unwrap(), engine("brute").unwrap(), engine("walksat").unwrap()];
+            for engine in engines.iter_mut() {
+                formula.iter().for_each(|clause| engine.add_clause(clause.clone()));
+            }
+            let assumptions = [Lit { var: Var::new(0), neg: seed % 2 == 0 }, Lit { var: Var::new(num_vars - 1), neg: false }];
+            let answers: Vec<_> = engines.iter_mut().map(|e| e.solve_assuming(&assumptions)).collect();
+            assert!(answers[0].is_some());
+            let sat = matches!(answers[0], Some(SolveResult::Sat(_)));
+            assert_eq!(matches!(answers[1], Some(SolveResult::Sat(_))), sat, "seed {}", seed);
+            // WalkSAT never claims UNSAT
+            assert!(!matches!(answers[2], Some(SolveResult::Unsat)));
+            for engine in &engines {
+                if let Some(model) = engine.model() {
+                    assert!(formula.iter().all(|c| c.iter().any(|&l| model.is_true(l))), "seed {}", seed);
+                    assert!(assumptions.iter().all(|&l| model.is_true(l)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_and_missing_engines() {
+        assert!(engine("cdcl").is_err());
+        assert!(engine("minisat").is_err());
+
+        let mut brute = BruteEngine::default();
+        brute.add_clause(vec![Lit { var: Var::new(brute::MAX_VARS), neg: false }]);
+        assert_eq!(brute.solve(), None);
+        assert_eq!(brute.stats().searches, 0);
+    }
+}
@@ -0,0 +1,211 @@
+//! Enumeration of all models, in parallel over disjoint cubes
+//!
+//! [`Solver::models`] lists the models of a solver's formula *projected*
+//! onto a set of variables: every assignment of those variables that
+//! extends to a model is reported exactly once, as the literals of the
+//! projection variables in their order. After each model, a clause
+//! blocking its projection is added, and the search runs again until the
+//! formula is unsatisfiable. Projection variables that a model leaves
+//! unassigned are reported false (the formula is satisfied either way; the
+//! other value turns up as a model of its own).
+//!
+//! To spread the work over threads, the space of projected assignments is
+//! split into disjoint *cubes* fixing the first few projection variables
+//! in every combination. Worker threads take cubes from a shared list and
+//! enumerate each under its cube as assumptions, on their own copy of the
+//! solver, so that their blocking clauses never meet. Since every projected
+//! assignment lies in exactly one cube, the merged stream holds no
+//! duplicates without keeping a table of the models seen. The order of the
+//! models depends on the scheduling of the threads.
+//!
+//! Dropping the stream stops the workers.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::solver::Solver;
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! // (x0 ∨ x1) ∧ (¬x1 ∨ x2): four models, three once x2 is projected away
+//! let solver = Solver::from_formula(vec![vec![x(0), x(1)], vec![!x(1), x(2)]], 3);
+//! let all = [Var::new(0), Var::new(1), Var::new(2)];
+//! assert_eq!(solver.models(&all, 4).count(), 4);
+//!
+//! let mut projected: Vec<Vec<Lit>> = solver.models(&all[..2], 2).collect();
+//! projected.sort();
+//! assert_eq!(projected, vec![vec![x(0), x(1)], vec![x(0), !x(1)], vec![!x(0), x(1)]]);
+//! ```
+
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::types::*;
+
+use super::dpll::SolveResult;
+use super::Solver;
+
+/// Cubes per worker thread, so that threads finishing easy cubes early
+/// find more work
+const CUBES_PER_THREAD: usize = 4;
+
+/// Models waiting to be consumed before the workers pause.
+const QUEUE: usize = 1024;
+
+/// Stream of the projected models of a formula, see the
+/// [module documentation](self).
+///
+/// Created by [`Solver::models`].
+pub struct ModelStream {
+    models: Receiver<Vec<Lit>>,
+    /// The worker threads, joined once the stream is exhausted
+    workers: Vec<JoinHandle<()>>,
+    /// Set when the stream is dropped
+    stop: Arc<AtomicBool>,
+}
+
+impl Solver {
+    /// Enumerates the models of the formula projected onto `projection`,
+    /// on `threads` worker threads.
+    ///
+    /// # Arguments
+    ///
+    /// * `projection` - The variables to report; their assignments are
+    ///   enumerated without repetition
+    /// * `threads` - The number of worker threads (at least one is used)
+    ///
+    /// # Returns
+    ///
+    /// A stream of the projected models, each as the literals of
+    /// `projection` in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a projection variable does not exist.
+    pub fn models(&self, projection: &[Var], threads: usize) -> ModelStream {
+        if let Some(var) = projection.iter().find(|var| var.index() >= self.num_vars()) {
+            panic!("projection on unknown variable {} (solver has {})", var, self.num_vars());
+        }
+        let threads = threads.max(1);
+        // Fix enough variables for the cubes to outnumber the threads
+        let wanted = if threads == 1 { 1 } else { threads * CUBES_PER_THREAD };
+        let split = (wanted.next_power_of_two().trailing_zeros() as usize).min(projection.len());
+        let cubes: Arc<Vec<Vec<Lit>>> = Arc::new(
+            (0..1usize << split)
+                .map(|bits| (0..split).map(|i| Lit::new(projection[i], bits >> i & 1 == 1)).collect())
+                .collect(),
+        );
+        let next = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, models) = mpsc::sync_channel(QUEUE);
+        let workers = (0..threads)
+            .map(|_| {
+                let mut solver = self.clone();
+                solver.set_caching(false);
+                let (projection, cubes, next, stop, sender) =
+                    (projection.to_vec(), Arc::clone(&cubes), Arc::clone(&next), Arc::clone(&stop), sender.clone());
+                thread::spawn(move || {
+                    while let Some(cube) = cubes.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        if !enumerate_cube(&mut solver, &projection, cube, &stop, &sender) {
+                            return;
+                        }
+                    }
+                })
+            })
+            .collect();
+        ModelStream { models, workers, stop }
+    }
+}
+
+/// Sends the projected models within `cube`, blocking each in `solver`,
+/// returning `false` once the stream is dropped.
+fn enumerate_cube(
+    solver: &mut Solver,
+    projection: &[Var],
+    cube: &[Lit],
+    stop: &AtomicBool,
+    sender: &SyncSender<Vec<Lit>>,
+) -> bool {
+    loop {
+        let Some(result) = solver.run_interruptible(cube, Some(stop), None) else { return false };
+        let SolveResult::Sat(model) = result else { return true };
+        let projected: Vec<Lit> =
+            projection.iter().map(|&var| Lit::new(var, model.value(var) != Val::True)).collect();
+        // The blocking clause holds the negated cube, so it never cuts into
+        // another cube
+        let block: Clause = projected.iter().map(|&lit| !lit).collect();
+        if sender.send(projected).is_err() {
+            return false;
+        }
+        if block.len() == cube.len() {
+            // The cube fixes the whole projection
+            return true;
+        }
+        solver.add_clause(block);
+    }
+}
+
+impl Iterator for ModelStream {
+    type Item = Vec<Lit>;
+
+    /// Waits for the next model.
+    ///
+    /// # Panics
+    ///
+    /// Resumes the panic of a worker thread once the other models are
+    /// consumed, if a worker panicked.
+    fn next(&mut self) -> Option<Vec<Lit>> {
+        if let Ok(model) = self.models.recv() {
+            return Some(model);
+        }
+        // Every worker has finished
+        let panics: Vec<_> = self.workers.drain(..).filter_map(|worker| worker.join().err()).collect();
+        if let Some(payload) = panics.into_iter().next() {
+            panic::resume_unwind(payload);
+        }
+        None
+    }
+}
+
+impl Drop for ModelStream {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn threads_find_each_projected_model_once() {
+        for seed in 0..6 {
+            let (formula, num_vars) = crate::gen::random_ksat(10, 25, 3, seed);
+            let projection: Vec<Var> = (0..num_vars).filter(|var| var % 3 != 1).map(Var::new).collect();
+            // Projected assignments that extend to a model
+            let expected: HashSet<Vec<Lit>> = crate::solver::brute::models(&formula, num_vars)
+                .map(|model| projection.iter().map(|&var| Lit::new(var, model.value(var) == Val::False)).collect())
+                .collect();
+            let solver = Solver::from_formula(formula, num_vars);
+            for threads in [1, 3] {
+                let models: Vec<Vec<Lit>> = solver.models(&projection, threads).collect();
+                let distinct: HashSet<Vec<Lit>> = models.iter().cloned().collect();
+                assert_eq!(distinct.len(), models.len(), "seed {} threads {}", seed, threads);
+                assert_eq!(distinct, expected, "seed {} threads {}", seed, threads);
+            }
+        }
+    }
+
+    #[test]
+    fn dropping_the_stream_stops_the_workers() {
+        let solver = Solver::from_formula(Vec::new(), 20);
+        let projection: Vec<Var> = Var::range(20).collect();
+        let mut models = solver.models(&projection, 2);
+        assert!(models.next().is_some());
+        drop(models);
+    }
+}
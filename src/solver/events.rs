@@ -0,0 +1,434 @@
+//! Event logs for analyzing long runs after the fact
+//!
+//! When a run of hours ends without an answer, its statistics say how much
+//! work was done but not how it went. With [`Solver::set_event_log`] the
+//! solver keeps a timeline of [`Event`]s instead: every search started,
+//! every partial restart, every simplification of the clauses, every
+//! search stopped early and every answer. Each event carries the time since
+//! the log was enabled and the number of conflicts so far, a clock that
+//! does not depend on the machine. The search learns no clauses, so there
+//! are no clause database reductions to log.
+//!
+//! [`Solver::take_events`] hands the events over as they accumulate, so a
+//! driver that solves in slices (see
+//! [`solve_limited`](Solver::solve_limited)) can stream them to a file with
+//! an [`EventWriter`], and a run that is killed leaves the events up to its
+//! last slice behind. [`read_events`] reads both formats back:
+//!
+//! - [`EventFormat::Jsonl`]: one JSON object per line, for `jq` and
+//!   scripts;
+//! - [`EventFormat::Binary`]: a short header and one record per event,
+//!   its kind as a byte and its numbers as LEB128 varints, which usually
+//!   takes under ten bytes per event.
+//!
+//! ```text
+//! {"time":0.000012,"conflicts":0,"event":"search","assumptions":0}
+//! {"time":0.004180,"conflicts":240,"event":"restart","restarts":1,"kept":3,"trail":57}
+//! {"time":0.009911,"conflicts":517,"event":"answer","satisfiable":false}
+//! ```
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::solver::events::{read_events, Event, EventFormat, EventWriter};
+//! use putnam::solver::Solver;
+//!
+//! let (formula, num_vars) = putnam::gen::random_ksat(20, 91, 3, 1);
+//! let mut solver = Solver::from_formula(formula, num_vars);
+//! solver.set_event_log(true);
+//! solver.solve();
+//!
+//! let mut writer = EventWriter::new(Vec::new(), EventFormat::Binary)?;
+//! for event in solver.take_events() {
+//!     writer.write(&event)?;
+//! }
+//! let events = read_events(writer.into_inner().as_slice())?;
+//! assert_eq!(events[0].event, Event::Search { assumptions: 0 });
+//! assert!(matches!(events.last().unwrap().event, Event::Answer { .. }));
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, Instant};
+
+use super::Solver;
+
+/// First bytes of a binary event log.
+const MAGIC: &[u8; 8] = b"PTNEVT2\n";
+
+/// Something that happened in a [`Solver`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A search started; queries answered from the cache run none
+    Search {
+        /// Number of assumptions of the query
+        assumptions: u64,
+    },
+    /// A partial restart
+    Restart {
+        /// Restarts of the search so far, this one included
+        restarts: u64,
+        /// Leading decisions kept
+        kept: u64,
+        /// Variables assigned when the restart was scheduled
+        trail: u64,
+    },
+    /// The clauses were simplified
+    Simplification {
+        /// The simplification applied
+        method: Simplification,
+        /// Clauses removed
+        clauses_removed: u64,
+        /// Literals removed from the remaining clauses
+        literals_removed: u64,
+        /// Clauses added (units and binary clauses found by probing)
+        clauses_added: u64,
+    },
+    /// A search stopped before answering: interrupted, out of propagation
+    /// budget or over the memory limit
+    Stopped {
+        /// Whether the memory limit stopped it
+        out_of_memory: bool,
+    },
+    /// A query was answered
+    Answer {
+        /// Whether the formula is satisfiable under the assumptions
+        satisfiable: bool,
+    },
+}
+
+/// Kind of an [`Event::Simplification`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Simplification {
+    /// Propagation of the top-level units
+    Units,
+    /// [Distillation](crate::preprocess::distill)
+    Distillation,
+    /// [Failed literal probing](crate::preprocess::probe)
+    Probing,
+    /// [Variable elimination](crate::preprocess::eliminate)
+    Elimination,
+}
+
+impl Simplification {
+    const ALL: [Simplification; 4] =
+        [Simplification::Units, Simplification::Distillation, Simplification::Probing, Simplification::Elimination];
+
+    fn name(self) -> &'static str {
+        match self {
+            Simplification::Units => "units",
+            Simplification::Distillation => "distillation",
+            Simplification::Probing => "probing",
+            Simplification::Elimination => "elimination",
+        }
+    }
+}
+
+/// An [`Event`] with the moment it happened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimedEvent {
+    /// Time since the log was enabled, in whole microseconds
+    pub time: Duration,
+    /// Conflicts of all searches of the solver so far
+    pub conflicts: u64,
+    /// What happened
+    pub event: Event,
+}
+
+/// The events of a solver not yet taken.
+#[derive(Clone, Debug)]
+pub(crate) struct EventLog {
+    start: Instant,
+    events: Vec<TimedEvent>,
+}
+
+impl EventLog {
+    /// Logs `event`, which happened at `at` after `conflicts` conflicts.
+    pub fn push(&mut self, at: Instant, conflicts: u64, event: Event) {
+        // Whole microseconds, as the files store them
+        let time = Duration::from_micros(at.saturating_duration_since(self.start).as_micros() as u64);
+        self.events.push(TimedEvent { time, conflicts, event });
+    }
+}
+
+impl Solver {
+    /// Enables or disables the event log, see the
+    /// [module documentation](self).
+    ///
+    /// Enabling starts the clock of the event times; disabling drops the
+    /// events not yet taken. Enabling an enabled log changes nothing.
+    pub fn set_event_log(&mut self, enabled: bool) {
+        let log = self.event_log_mut();
+        match (enabled, log.is_some()) {
+            (true, false) => *log = Some(EventLog { start: Instant::now(), events: Vec::new() }),
+            (false, _) => *log = None,
+            (true, true) => {}
+        }
+    }
+
+    /// Removes and returns the events logged so far, oldest first.
+    pub fn take_events(&mut self) -> Vec<TimedEvent> {
+        self.event_log_mut().as_mut().map_or_else(Vec::new, |log| std::mem::take(&mut log.events))
+    }
+}
+
+/// Encoding of an event log file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EventFormat {
+    /// One JSON object per line
+    #[default]
+    Jsonl,
+    /// Compact binary records
+    Binary,
+}
+
+/// Writes events to a log file, one at a time.
+#[derive(Debug)]
+pub struct EventWriter<W: Write> {
+    writer: W,
+    format: EventFormat,
+}
+
+impl<W: Write> EventWriter<W> {
+    /// Starts a log in `format`, writing the header of binary logs.
+    pub fn new(mut writer: W, format: EventFormat) -> io::Result<Self> {
+        if format == EventFormat::Binary {
+            writer.write_all(MAGIC)?;
+        }
+        Ok(EventWriter { writer, format })
+    }
+
+    /// Appends `event` to the log.
+    pub fn write(&mut self, event: &TimedEvent) -> io::Result<()> {
+        match self.format {
+            EventFormat::Jsonl => writeln!(self.writer, "{}", to_json(event)),
+            EventFormat::Binary => self.writer.write_all(&to_binary(event)),
+        }
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Formats `event` as a JSON object.
+fn to_json(event: &TimedEvent) -> String {
+    let fields = match event.event {
+        Event::Search { assumptions } => format!("\"search\",\"assumptions\":{}", assumptions),
+        Event::Restart { restarts, kept, trail } => {
+            format!("\"restart\",\"restarts\":{},\"kept\":{},\"trail\":{}", restarts, kept, trail)
+        }
+        Event::Simplification { method, clauses_removed, literals_removed, clauses_added } => format!(
+            "\"simplification\",\"method\":\"{}\",\"clauses_removed\":{},\"literals_removed\":{},\"clauses_added\":{}",
+            method.name(),
+            clauses_removed,
+            literals_removed,
+            clauses_added
+        ),
+        Event::Stopped { out_of_memory } => format!("\"stopped\",\"out_of_memory\":{}", out_of_memory),
+        Event::Answer { satisfiable } => format!("\"answer\",\"satisfiable\":{}", satisfiable),
+    };
+    format!(
+        "{{\"time\":{}.{:06},\"conflicts\":{},\"event\":{}}}",
+        event.time.as_secs(),
+        event.time.subsec_micros(),
+        event.conflicts,
+        fields
+    )
+}
+
+/// Encodes `event` as a binary record: its kind, then its numbers as
+/// varints.
+fn to_binary(event: &TimedEvent) -> Vec<u8> {
+    let (kind, fields) = match event.event {
+        Event::Search { assumptions } => (0, vec![assumptions]),
+        Event::Restart { restarts, kept, trail } => (1, vec![restarts, kept, trail]),
+        Event::Simplification { method, clauses_removed, literals_removed, clauses_added } => {
+            let method = Simplification::ALL.iter().position(|&other| other == method).unwrap_or(0) as u64;
+            (2, vec![method, clauses_removed, literals_removed, clauses_added])
+        }
+        Event::Stopped { out_of_memory } => (3, vec![out_of_memory as u64]),
+        Event::Answer { satisfiable } => (4, vec![satisfiable as u64]),
+    };
+    let mut record = vec![kind];
+    for mut value in [event.time.as_micros() as u64, event.conflicts].into_iter().chain(fields) {
+        while value >= 0x80 {
+            record.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        record.push(value as u8);
+    }
+    record
+}
+
+/// Reads an event log written by an [`EventWriter`], in either format.
+///
+/// A log cut short by a killed run ends in a partial record (or line),
+/// which is ignored; every complete event before it is returned.
+///
+/// # Returns
+///
+/// * `Ok(Vec<TimedEvent>)` - The events, oldest first
+/// * `Err(io::Error)` - If reading fails, or with `ErrorKind::InvalidData`
+///   if a complete record or line is malformed
+pub fn read_events<R: BufRead>(mut reader: R) -> io::Result<Vec<TimedEvent>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let mut events = Vec::new();
+    if reader.fill_buf()?.starts_with(MAGIC) {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let mut pos = MAGIC.len();
+        while pos < data.len() {
+            match from_binary(&data, &mut pos) {
+                Ok(event) => events.push(event),
+                Err(None) => break,
+                Err(Some(message)) => return Err(invalid(format!("event {}: {}", events.len() + 1, message))),
+            }
+        }
+        return Ok(events);
+    }
+    let mut line = String::new();
+    for lineno in 1.. {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let complete = line.ends_with('\n');
+        let text = line.trim();
+        if text.is_empty() {
+            continue;
+        }
+        match from_json(text) {
+            Some(event) => events.push(event),
+            None if !complete => break,
+            None => return Err(invalid(format!("line {}: malformed event", lineno))),
+        }
+    }
+    Ok(events)
+}
+
+/// Decodes the binary record at `pos`, moving `pos` past it.
+///
+/// Fails with `None` if the data ends within the record, and with a
+/// message if the record is malformed.
+fn from_binary(data: &[u8], pos: &mut usize) -> Result<TimedEvent, Option<String>> {
+    let kind = *data.get(*pos).ok_or(None)?;
+    *pos += 1;
+    let mut varint = || {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *data.get(*pos).ok_or(None)?;
+            *pos += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Some("number too large".to_string()))
+    };
+    let time = Duration::from_micros(varint()?);
+    let conflicts = varint()?;
+    let event = match kind {
+        0 => Event::Search { assumptions: varint()? },
+        1 => Event::Restart { restarts: varint()?, kept: varint()?, trail: varint()? },
+        2 => {
+            let method = *Simplification::ALL.get(varint()? as usize).ok_or(Some("unknown simplification".to_string()))?;
+            Event::Simplification { method, clauses_removed: varint()?, literals_removed: varint()?, clauses_added: varint()? }
+        }
+        3 => Event::Stopped { out_of_memory: varint()? != 0 },
+        4 => Event::Answer { satisfiable: varint()? != 0 },
+        other => return Err(Some(format!("unknown kind {}", other))),
+    };
+    Ok(TimedEvent { time, conflicts, event })
+}
+
+/// Parses a line written by [`to_json`].
+fn from_json(line: &str) -> Option<TimedEvent> {
+    fn unquote(text: &str) -> Option<&str> {
+        text.trim().strip_prefix('"')?.strip_suffix('"')
+    }
+    let body = line.strip_prefix('{')?.strip_suffix('}')?;
+    let mut fields = Vec::new();
+    for field in body.split(',') {
+        let (key, value) = field.split_once(':')?;
+        let value = unquote(value).unwrap_or(value.trim());
+        fields.push((unquote(key)?, value));
+    }
+    let get = |key: &str| fields.iter().find(|&&(name, _)| name == key).map(|&(_, value)| value);
+    let number = |key: &str| get(key)?.parse::<u64>().ok();
+    let flag = |key: &str| get(key)?.parse::<bool>().ok();
+
+    let seconds: f64 = get("time")?.parse().ok().filter(|seconds: &f64| seconds.is_finite() && *seconds >= 0.0)?;
+    let event = match get("event")? {
+        "search" => Event::Search { assumptions: number("assumptions")? },
+        "restart" => Event::Restart { restarts: number("restarts")?, kept: number("kept")?, trail: number("trail")? },
+        "simplification" => Event::Simplification {
+            method: *Simplification::ALL.iter().find(|method| Some(method.name()) == get("method"))?,
+            clauses_removed: number("clauses_removed")?,
+            literals_removed: number("literals_removed")?,
+            clauses_added: number("clauses_added")?,
+        },
+        "stopped" => Event::Stopped { out_of_memory: flag("out_of_memory")? },
+        "answer" => Event::Answer { satisfiable: flag("satisfiable")? },
+        _ => return None,
+    };
+    Some(TimedEvent { time: Duration::from_micros((seconds * 1e6).round() as u64), conflicts: number("conflicts")?, event })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_formats_roundtrip_and_tolerate_truncation() {
+        let at = |micros, conflicts, event| TimedEvent { time: Duration::from_micros(micros), conflicts, event };
+        let events = vec![
+            at(3, 0, Event::Simplification {
+                method: Simplification::Probing,
+                clauses_removed: 0,
+                literals_removed: 0,
+                clauses_added: 12,
+            }),
+            at(17, 0, Event::Search { assumptions: 2 }),
+            at(1_500_000, 300, Event::Restart { restarts: 1, kept: 4, trail: 37 }),
+            at(86_400_000_001, 1 << 40, Event::Stopped { out_of_memory: true }),
+            at(86_400_000_002, 1 << 40, Event::Answer { satisfiable: false }),
+        ];
+        for format in [EventFormat::Jsonl, EventFormat::Binary] {
+            let mut writer = EventWriter::new(Vec::new(), format).unwrap();
+            for event in &events {
+                writer.write(event).unwrap();
+            }
+            let data = writer.into_inner();
+            assert_eq!(read_events(data.as_slice()).unwrap(), events, "{:?}", format);
+            // Cut within the last event
+            assert_eq!(read_events(&data[..data.len() - 2]).unwrap(), &events[..4], "{:?}", format);
+        }
+        assert!(read_events("{\"time\":1.0,\"event\":\"search\"}\n".as_bytes()).is_err());
+        assert!(read_events(&b"PTNEVT2\n\x09\x00\x00"[..]).is_err());
+    }
+
+    #[test]
+    fn searches_log_restarts_and_answers() {
+        let (formula, num_vars) = crate::gen::random_ksat(40, 180, 3, 3);
+        let mut solver = Solver::from_formula(formula, num_vars);
+        solver.set_config(super::super::SolverConfig { restart_interval: Some(1), ..solver.config().clone() });
+        solver.set_event_log(true);
+        solver.probe(1000);
+        let answer = solver.solve();
+        let events = solver.take_events();
+        assert!(matches!(events[0].event, Event::Simplification { method: Simplification::Probing, .. }));
+        assert!(events.iter().any(|event| matches!(event.event, Event::Restart { .. })));
+        let last = events.last().unwrap();
+        assert_eq!(last.event, Event::Answer { satisfiable: matches!(answer, super::super::dpll::SolveResult::Sat(_)) });
+        assert_eq!(last.conflicts, solver.stats().conflicts);
+        assert!(events.windows(2).all(|pair| pair[0].time <= pair[1].time && pair[0].conflicts <= pair[1].conflicts));
+        assert!(solver.take_events().is_empty());
+    }
+}
@@ -0,0 +1,138 @@
+//! Failed assumptions
+//!
+//! When a query is unsatisfiable under its assumptions, the *failed
+//! assumptions* are a subset of them that the formula already refutes on
+//! its own: an assumption core. Core-guided algorithms (MaxSAT by core
+//! relaxation, MUS extraction over selector literals) are driven by these
+//! sets, and run faster the smaller they are.
+//!
+//! The search learns no clauses, so it cannot read a small core off its
+//! final conflict as clause-learning solvers do. [`Solver`](super::Solver)
+//! starts from the assumptions themselves, narrowed down to a contradicting
+//! pair or a literal refuted by a top-level unit when there is one, and
+//! can then shrink the set with further satisfiability checks, as
+//! [`CoreMinimization`] selects:
+//!
+//! - *Deletion* drops one assumption at a time and keeps it out if the
+//!   rest is still refuted: one check per assumption.
+//! - *QuickXplain* splits the set in halves and recurses into the half that
+//!   still needs the other; for small cores of large sets it needs far
+//!   fewer checks (about `k log(n / k)` for a core of `k` out of `n`).
+//!
+//! Both only remove assumptions a check proved unnecessary, so when the
+//! effort runs out the set is larger but still a core. Once they finish,
+//! the core is minimal: dropping any single assumption makes it
+//! satisfiable.
+
+use crate::types::Lit;
+
+/// Method of shrinking failed-assumption sets, see the
+/// [module documentation](self).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CoreMinimization {
+    /// Report the failed assumptions as found, without extra checks
+    #[default]
+    None,
+    /// Try dropping each assumption in turn
+    Deletion,
+    /// Junker's QuickXplain divide and conquer
+    QuickXplain,
+}
+
+/// Shrinks the assumption core `core` with at most `effort` calls of
+/// `refuted`, which tells whether the formula is unsatisfiable under a set
+/// of assumptions.
+pub(crate) fn minimize(
+    core: Vec<Lit>,
+    method: CoreMinimization,
+    effort: u64,
+    mut refuted: impl FnMut(&[Lit]) -> bool,
+) -> Vec<Lit> {
+    let mut checks = 0;
+    // A check that is not run proves nothing, so its set counts as satisfiable
+    let mut check = |assumptions: &[Lit]| {
+        if checks >= effort {
+            return false;
+        }
+        checks += 1;
+        refuted(assumptions)
+    };
+    match method {
+        CoreMinimization::None => core,
+        CoreMinimization::Deletion => {
+            let mut core = core;
+            let mut at = 0;
+            while at < core.len() {
+                let mut without = core.clone();
+                without.remove(at);
+                if check(&without) {
+                    core = without;
+                } else {
+                    at += 1;
+                }
+            }
+            core
+        }
+        CoreMinimization::QuickXplain => {
+            if core.is_empty() || check(&[]) {
+                return Vec::new();
+            }
+            quick_xplain(&mut Vec::new(), false, &core, &mut check)
+        }
+    }
+}
+
+/// Returns a minimal subset of `candidates` that is refuted together with
+/// `background`, given that `background` plus all of `candidates` is.
+/// `background` is known not to be refuted on its own unless `added`
+/// (it gained assumptions since that was checked).
+fn quick_xplain(
+    background: &mut Vec<Lit>,
+    added: bool,
+    candidates: &[Lit],
+    check: &mut impl FnMut(&[Lit]) -> bool,
+) -> Vec<Lit> {
+    if added && check(background) {
+        return Vec::new();
+    }
+    if let [only] = candidates {
+        return vec![*only];
+    }
+    let (first, second) = candidates.split_at(candidates.len() / 2);
+    let base = background.len();
+    background.extend_from_slice(first);
+    let from_second = quick_xplain(background, true, second, check);
+    background.truncate(base);
+    background.extend_from_slice(&from_second);
+    let mut from_first = quick_xplain(background, !from_second.is_empty(), first, check);
+    background.truncate(base);
+    from_first.extend(from_second);
+    from_first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll::{solve, SolveResult};
+    use crate::types::Var;
+
+    #[test]
+    fn both_methods_find_minimal_cores() {
+        let x = |var| Lit::positive(Var::new(var));
+        // (x0 ∨ x1) ∧ (¬x2 ∨ x3 ∨ x4): only ¬x0, ¬x1 together are refuted
+        let formula = vec![vec![x(0), x(1)], vec![!x(2), x(3), x(4)]];
+        let refuted = |assumptions: &[Lit]| {
+            let mut with = formula.clone();
+            with.extend(assumptions.iter().map(|&lit| vec![lit]));
+            solve(&with, 6) == SolveResult::Unsat
+        };
+        let assumptions = vec![x(4), !x(0), x(2), x(5), !x(1), !x(3)];
+        for method in [CoreMinimization::Deletion, CoreMinimization::QuickXplain] {
+            let mut core = minimize(assumptions.clone(), method, u64::MAX, refuted);
+            core.sort();
+            assert_eq!(core, vec![!x(0), !x(1)], "{:?}", method);
+            // No effort, no change
+            assert_eq!(minimize(assumptions.clone(), method, 0, refuted), assumptions);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Implication graphs
+//!
+//! The assignments of a model form a directed acyclic graph: every
+//! propagated literal has an edge from each variable whose value made its
+//! reason clause unit (see [`Model::reason`]), while decisions and
+//! assumptions have no incoming edges. Decisions open the decision levels;
+//! a propagated literal lives on the level of the last decision before it.
+//! When the model falsifies a clause, the graph has a conflict node whose
+//! predecessors are the variables of that clause.
+//!
+//! [`ImplicationGraph`] is a read-only snapshot of that graph, meant for
+//! custom analyses (cuts, dominators, explanations) and visualizations
+//! built outside the solver.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::solver::graph::ImplicationGraph;
+//! use putnam::types::{ClauseId, Lit, Model, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! // x0 → x1, x0 ∧ x1 → x2
+//! let formula = vec![vec![!x(0), x(1)], vec![!x(0), !x(1), x(2)]];
+//! let mut model = Model::new(3);
+//! model.assign(Var::new(0), putnam::types::Val::True);
+//! model.imply(x(1), ClauseId::new(0));
+//! model.imply(x(2), ClauseId::new(1));
+//!
+//! let graph = ImplicationGraph::new(&formula, &model);
+//! assert_eq!(graph.decisions().map(|node| node.lit).collect::<Vec<_>>(), vec![x(0)]);
+//! assert_eq!(graph.predecessors(Var::new(2)), &[Var::new(0), Var::new(1)]);
+//! assert_eq!(graph.successors(Var::new(0)), vec![Var::new(1), Var::new(2)]);
+//! assert!(graph.conflict().is_none());
+//! ```
+
+use std::fmt::Write;
+
+use crate::store::FormulaSource;
+use crate::types::*;
+
+/// An assigned variable of the implication graph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    /// The literal made true by the assignment
+    pub lit: Lit,
+    /// Decision level: the number of decisions up to and including this
+    /// assignment (0 for assignments forced before any decision)
+    pub level: usize,
+    /// Position of the assignment on the trail
+    pub position: usize,
+    /// The clause that forced the literal, `None` for decisions
+    pub reason: Option<ClauseId>,
+    /// The variables of the other literals of the reason clause, whose
+    /// values made it unit
+    pub predecessors: Vec<Var>,
+}
+
+impl Node {
+    /// Returns `true` if the node is a decision (or an assumption).
+    pub fn is_decision(&self) -> bool {
+        self.reason.is_none()
+    }
+}
+
+/// The conflict node of an implication graph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    /// The clause falsified by the model
+    pub clause: ClauseId,
+    /// The variables of the falsified clause
+    pub predecessors: Vec<Var>,
+}
+
+/// A read-only snapshot of the implication graph of a model.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImplicationGraph {
+    /// Nodes in trail order
+    nodes: Vec<Node>,
+    /// Position in `nodes` of each variable's node
+    index: Vec<Option<usize>>,
+    /// The conflict node, if the model falsifies a clause
+    conflict: Option<Conflict>,
+}
+
+impl ImplicationGraph {
+    /// Builds the implication graph of `model`.
+    ///
+    /// Variables assigned several times (the model was reused after
+    /// backtracking) appear once, at their last assignment. The conflict
+    /// node, if any, is the first clause of `formula` that the model
+    /// falsifies.
+    ///
+    /// # Arguments
+    ///
+    /// * `formula` - The clauses the reasons of `model` refer to
+    /// * `model` - The assignments
+    ///
+    /// # Panics
+    ///
+    /// Panics if a reason of `model` is not a clause of `formula`.
+    pub fn new<F: FormulaSource + ?Sized>(formula: &F, model: &Model) -> Self {
+        let mut graph = ImplicationGraph { index: vec![None; model.num_vars()], ..Default::default() };
+        let last: Vec<usize> = {
+            let mut last = vec![0; model.num_vars()];
+            for position in 0..model.trail_len() {
+                last[model.trail_var(position).index()] = position;
+            }
+            last
+        };
+        let mut level = 0;
+        for position in 0..model.trail_len() {
+            let var = model.trail_var(position);
+            if last[var.index()] != position || model.value(var) == Val::Undef {
+                continue;
+            }
+            let reason = model.reason(var);
+            let predecessors = match reason {
+                Some(id) => formula.clause(id.index()).iter().map(|l| l.var).filter(|&v| v != var).collect(),
+                None => {
+                    level += 1;
+                    Vec::new()
+                }
+            };
+            graph.index[var.index()] = Some(graph.nodes.len());
+            graph.nodes.push(Node {
+                lit: Lit::new(var, model.value(var) == Val::False),
+                level,
+                position,
+                reason,
+                predecessors,
+            });
+        }
+        graph.conflict = formula
+            .clauses()
+            .enumerate()
+            .find(|(_, clause)| clause.iter().all(|l| model.value(l.var) != Val::Undef && !model.is_true(*l)))
+            .map(|(index, clause)| Conflict {
+                clause: ClauseId::new(index),
+                predecessors: clause.iter().map(|l| l.var).collect(),
+            });
+        graph
+    }
+
+    /// Returns the nodes in trail order.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Returns the node of `var`, or `None` if it is unassigned.
+    pub fn node(&self, var: Var) -> Option<&Node> {
+        self.nodes.get((*self.index.get(var.index())?)?)
+    }
+
+    /// Returns the decision level of `var`, or `None` if it is unassigned.
+    pub fn level(&self, var: Var) -> Option<usize> {
+        self.node(var).map(|node| node.level)
+    }
+
+    /// Returns the highest decision level of the graph.
+    pub fn decision_level(&self) -> usize {
+        self.nodes.last().map_or(0, |node| node.level)
+    }
+
+    /// Iterates over the decisions, in trail order.
+    pub fn decisions(&self) -> impl Iterator<Item = &Node> + '_ {
+        self.nodes.iter().filter(|node| node.is_decision())
+    }
+
+    /// Returns the variables with an edge into `var` (empty for decisions
+    /// and unassigned variables).
+    pub fn predecessors(&self, var: Var) -> &[Var] {
+        self.node(var).map_or(&[], |node| &node.predecessors)
+    }
+
+    /// Returns the variables with an edge from `var`, in trail order.
+    pub fn successors(&self, var: Var) -> Vec<Var> {
+        self.nodes.iter().filter(|node| node.predecessors.contains(&var)).map(|node| node.lit.var).collect()
+    }
+
+    /// Returns the conflict node, if the model falsifies a clause.
+    pub fn conflict(&self) -> Option<&Conflict> {
+        self.conflict.as_ref()
+    }
+
+    /// Renders the graph in Graphviz DOT format.
+    ///
+    /// Nodes are labelled with their DIMACS literal and decision level;
+    /// edges are labelled with the 0-based index of the reason clause.
+    /// Decisions are drawn as boxes, the conflict node as `conflict`.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph implications {\n");
+        for node in &self.nodes {
+            let shape = if node.is_decision() { "box" } else { "ellipse" };
+            let _ = writeln!(out, "  v{} [label=\"{}@{}\", shape={}];", node.lit.var, node.lit.to_dimacs(), node.level, shape);
+            if let Some(reason) = node.reason {
+                for pred in &node.predecessors {
+                    let _ = writeln!(out, "  v{} -> v{} [label=\"{}\"];", pred, node.lit.var, reason);
+                }
+            }
+        }
+        if let Some(conflict) = &self.conflict {
+            let _ = writeln!(out, "  conflict [shape=octagon];");
+            for pred in &conflict.predecessors {
+                let _ = writeln!(out, "  v{} -> conflict [label=\"{}\"];", pred, conflict.clause);
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    use crate::solver::unit::unit_propagate;
+
+    #[test]
+    fn propagation_builds_levels_and_conflicts() {
+        let x = |var| Lit::positive(Var::new(var));
+        // x0 → x1 → x2, x3 → ¬x2
+        let formula = vec![vec![!x(0), x(1)], vec![!x(1), x(2)], vec![!x(3), !x(2)]];
+        let mut model = Model::new(4);
+        model.assign(Var::new(0), Val::True);
+        assert_eq!(unit_propagate(&formula, &mut model, &mut VecDeque::new()), Ok(()));
+        let graph = ImplicationGraph::new(&formula, &model);
+        assert_eq!(graph.level(Var::new(2)), Some(1));
+        assert_eq!(graph.node(Var::new(3)).map(|node| node.lit), Some(!x(3)));
+        assert_eq!(graph.predecessors(Var::new(3)), &[Var::new(2)]);
+        assert!(graph.conflict().is_none());
+
+        // A second decision that contradicts the propagated literals
+        let mut model = Model::new(4);
+        model.assign(Var::new(3), Val::True);
+        model.assign(Var::new(0), Val::True);
+        assert!(unit_propagate(&formula, &mut model, &mut VecDeque::new()).is_err());
+        let graph = ImplicationGraph::new(&formula, &model);
+        assert_eq!(graph.decision_level(), 2);
+        assert!(graph.conflict().is_some());
+        assert!(graph.to_dot().contains("conflict [shape=octagon]"));
+    }
+}
@@ -0,0 +1,201 @@
+//! Indexed heap of decision candidates
+//!
+//! The activity-based heuristics branch on the highest-scoring unassigned
+//! variable of the open clauses. Scanning the open clauses for it costs
+//! time proportional to the formula at every decision; instead, the
+//! candidates sit in a binary max-heap ordered by score, with each
+//! variable's position indexed so that a score change restores the heap
+//! order in logarithmic time.
+//!
+//! The heap follows the model's trail like the [`counters`](super::counters):
+//! assigned variables leave it, and return when the search backtracks past
+//! their assignment. Variables that occur in no open clause are parked
+//! out of the heap when they reach the top; they can only become
+//! candidates again once the search backtracks past the point where they
+//! were parked, since assignments never reopen a clause.
+
+use crate::types::*;
+
+/// Score of `var`, 0 for variables beyond the end of `scores`.
+fn score(scores: &[f64], var: Var) -> f64 {
+    scores.get(var.index()).copied().unwrap_or(0.0)
+}
+
+/// Whether `a` comes before `b`: the higher score first, the lower
+/// variable on ties.
+fn before(scores: &[f64], a: Var, b: Var) -> bool {
+    let (sa, sb) = (score(scores, a), score(scores, b));
+    sa > sb || (sa == sb && a < b)
+}
+
+/// Unassigned variables ordered by score, maintained along the trail.
+#[derive(Default)]
+pub(crate) struct VarHeap {
+    /// The binary heap
+    heap: Vec<Var>,
+    /// Position of each variable in `heap`, if it is in the heap
+    position: Vec<Option<usize>>,
+    /// Number of trail entries reflected in the heap
+    synced: usize,
+    /// Variables taken out of the heap, with the trail length from which
+    /// on they stay out
+    removed: Vec<(usize, Var)>,
+}
+
+impl VarHeap {
+    /// Fills the heap with all `num_vars` variables, reusing the allocations.
+    pub fn reset(&mut self, num_vars: usize, scores: &[f64]) {
+        self.heap.clear();
+        self.position.clear();
+        self.position.resize(num_vars, None);
+        self.synced = 0;
+        self.removed.clear();
+        for var in (0..num_vars).map(Var::new) {
+            self.insert(var, scores);
+        }
+    }
+
+    /// Returns the number of trail entries reflected in the heap.
+    pub fn mark(&self) -> usize {
+        self.synced
+    }
+
+    /// Takes the variables assigned since the last synchronization out of
+    /// the heap. The trail of `model` must extend the trail the heap was
+    /// last synchronized with.
+    pub fn sync(&mut self, model: &Model, scores: &[f64]) {
+        while self.synced < model.trail_len() {
+            let var = model.trail_var(self.synced);
+            self.synced += 1;
+            if self.remove(var, scores) {
+                self.removed.push((self.synced, var));
+            }
+        }
+    }
+
+    /// Returns the variables removed after the trail length `mark` to the
+    /// heap.
+    pub fn undo_to(&mut self, mark: usize, scores: &[f64]) {
+        while let Some(&(at, var)) = self.removed.last() {
+            if at <= mark {
+                break;
+            }
+            self.removed.pop();
+            self.insert(var, scores);
+        }
+        self.synced = self.synced.min(mark);
+    }
+
+    /// Returns the highest-scoring variable for which `open` holds, parking
+    /// the variables above it until the search backtracks.
+    pub fn top(&mut self, scores: &[f64], open: impl Fn(Var) -> bool) -> Option<Var> {
+        loop {
+            let &var = self.heap.first()?;
+            if open(var) {
+                return Some(var);
+            }
+            self.remove(var, scores);
+            self.removed.push((self.synced, var));
+        }
+    }
+
+    /// Restores the heap order after the score of `var` changed.
+    pub fn update(&mut self, var: Var, scores: &[f64]) {
+        if let Some(&Some(at)) = self.position.get(var.index()) {
+            let at = self.sift_up(at, scores);
+            self.sift_down(at, scores);
+        }
+    }
+
+    /// Adds `var` unless it is in the heap already.
+    fn insert(&mut self, var: Var, scores: &[f64]) {
+        if self.position[var.index()].is_some() {
+            return;
+        }
+        self.heap.push(var);
+        self.position[var.index()] = Some(self.heap.len() - 1);
+        self.sift_up(self.heap.len() - 1, scores);
+    }
+
+    /// Removes `var`, returning whether it was in the heap.
+    fn remove(&mut self, var: Var, scores: &[f64]) -> bool {
+        let Some(at) = self.position.get_mut(var.index()).and_then(Option::take) else { return false };
+        let last = self.heap.pop().expect("a variable with a position is in the heap");
+        if at < self.heap.len() {
+            self.heap[at] = last;
+            self.position[last.index()] = Some(at);
+            let at = self.sift_up(at, scores);
+            self.sift_down(at, scores);
+        }
+        true
+    }
+
+    /// Moves the entry at `at` up to its place, returning its new position.
+    fn sift_up(&mut self, mut at: usize, scores: &[f64]) -> usize {
+        while at > 0 {
+            let parent = (at - 1) / 2;
+            if !before(scores, self.heap[at], self.heap[parent]) {
+                break;
+            }
+            self.swap(at, parent);
+            at = parent;
+        }
+        at
+    }
+
+    /// Moves the entry at `at` down to its place.
+    fn sift_down(&mut self, mut at: usize, scores: &[f64]) {
+        loop {
+            let mut first = at;
+            for child in [2 * at + 1, 2 * at + 2] {
+                if child < self.heap.len() && before(scores, self.heap[child], self.heap[first]) {
+                    first = child;
+                }
+            }
+            if first == at {
+                return;
+            }
+            self.swap(at, first);
+            at = first;
+        }
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.position[self.heap[a].index()] = Some(a);
+        self.position[self.heap[b].index()] = Some(b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_scores_and_the_trail() {
+        let mut scores = vec![1.0, 3.0, 2.0, 3.0];
+        let mut heap = VarHeap::default();
+        heap.reset(4, &scores);
+        // Ties go to the lower variable
+        assert_eq!(heap.top(&scores, |_| true), Some(Var::new(1)));
+
+        let mut model = Model::new(4);
+        model.assign(Var::new(1), Val::True);
+        heap.sync(&model, &scores);
+        let mark = heap.mark();
+        assert_eq!(heap.top(&scores, |_| true), Some(Var::new(3)));
+        scores[0] = 5.0;
+        heap.update(Var::new(0), &scores);
+        assert_eq!(heap.top(&scores, |_| true), Some(Var::new(0)));
+
+        // Variables not open are parked until the search backtracks
+        model.assign(Var::new(0), Val::False);
+        heap.sync(&model, &scores);
+        assert_eq!(heap.top(&scores, |var| var != Var::new(3)), Some(Var::new(2)));
+        assert_eq!(heap.top(&scores, |_| true), Some(Var::new(2)));
+        heap.undo_to(mark, &scores);
+        assert_eq!(heap.top(&scores, |_| true), Some(Var::new(0)));
+        heap.undo_to(0, &scores);
+        assert_eq!(heap.heap.len(), 4);
+    }
+}
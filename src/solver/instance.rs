@@ -0,0 +1,2163 @@
+//! Stateful solver front end
+//!
+//! [`Solver`] owns a formula that is built up clause by clause and keeps
+//! per-variable configuration (phase hints and a static decision order)
+//! across calls to
+//! [`Solver::solve`]. Each call runs the DPLL search of [`super::dpll`] on
+//! the current formula, optionally under assumptions (literals fixed for
+//! that call only); formulas of a [special class](super::special) are
+//! decided without search.
+//!
+//! Top-level unit clauses are propagated as the formula is loaded (see
+//! [`units`](crate::preprocess::units)): clauses they satisfy are dropped
+//! and literals they falsify are removed, so that the search does not carry
+//! them through every branch and contradicting units are found before it
+//! starts. Clauses that repeat an earlier clause (up to the order of
+//! their literals) are dropped as well. [`Solver::simplify`] cleans the
+//! whole formula on demand between queries.
+//!
+//! Answers are cached: repeating a query on an unchanged formula with the
+//! same assumptions and hints returns the cached answer without searching
+//! again.
+//!
+//! Every added clause gets a [`ClauseHandle`] that stays valid for the
+//! life of the solver, and [`Solver::remove_clause`] retracts the clause
+//! again. The solver keeps the clauses as added for this; since the search
+//! learns no clauses, the only clauses that depend on others are those
+//! derived by the load-time simplification, distillation, probing and
+//! variable elimination, and a removal rebuilds the stored formula from the
+//! remaining clauses as added.
+//!
+//! Variables that future clauses or assumptions will mention can be
+//! *frozen* ([`Solver::freeze`]), as in MiniSat's `SimpSolver`:
+//! [`Solver::eliminate`] leaves frozen ones alone, and later clauses and
+//! assumptions may only mention variables it kept. Freezing counts, so
+//! independent users can each freeze a variable and [`melt`](Solver::melt)
+//! it again.
+//!
+//! After an unsatisfiable query, [`Solver::failed_assumptions`] reports a
+//! subset of the assumptions that is refuted on its own, optionally
+//! minimized (see [`failed`](super::failed)).
+//!
+//! # Threads
+//!
+//! A configured `Solver` is `Send`, so it can be moved to a worker thread
+//! (as [`Solver::solve_async`] does) without rebuilding its state, and it is
+//! `Sync`, so read-only views such as [`Solver::formula`] can be shared
+//! between threads while no search runs. Models are `Send + Sync` as well;
+//! their symbol tables are shared through `Arc`. These properties are
+//! checked at compile time below.
+//!
+//! # Example
+//!
+//! ```
+//! use putnam::solver::Solver;
+//! use putnam::solver::dpll::SolveResult;
+//! use putnam::types::{Lit, Val};
+//!
+//! let mut solver = Solver::new();
+//! let a = solver.new_var();
+//! let b = solver.new_var();
+//! solver.add_clause(vec![Lit { var: a, neg: false }, Lit { var: b, neg: false }]);
+//!
+//! // Domain knowledge: a is usually false
+//! solver.set_phase(a, false);
+//! match solver.solve() {
+//!     SolveResult::Sat(model) => {
+//!         assert_eq!(model.value(a), Val::False);
+//!         assert_eq!(model.value(b), Val::True);
+//!     }
+//!     SolveResult::Unsat => unreachable!(),
+//! }
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::preprocess::distill::{distill, DistillStats};
+use crate::preprocess::eliminate::{eliminate, EliminationStats, Reconstruction};
+use crate::preprocess::probe::{probe, ProbeStats};
+use crate::preprocess::units::{simplify_units, UnitStats};
+use crate::types::*;
+
+use super::background::{self, SolveFuture};
+use super::bnb::Objective;
+use super::checkpoint::Checkpoint;
+use super::config::SolverConfig;
+use super::failed::{minimize, CoreMinimization};
+use super::metadata::ClauseMetadata;
+use super::optimize::Optimization;
+use super::special;
+use super::dpll::{search, Branching, HeuristicState, Hints, Propagation, Scratch, SolveResult};
+use super::events::{Event, EventLog, Simplification};
+use super::profile::{Profile, Span};
+use super::stats::SolveStats;
+
+// Compile-time checks for the thread-safety guarantees documented above
+const _: () = {
+    const fn send<T: Send>() {}
+    const fn sync<T: Sync>() {}
+    send::<Solver>();
+    sync::<Solver>();
+    send::<Model>();
+    sync::<Model>();
+    sync::<Formula>();
+    send::<SolveResult>();
+    send::<SolveFuture>();
+    send::<SolveStats>();
+};
+
+/// Cache key: formula fingerprint, variable count, and normalized assumptions.
+type QueryKey = (u64, usize, Vec<Lit>);
+
+/// Number of answers the cache keeps before evicting the least recently
+/// used one.
+const CACHE_CAPACITY: usize = 256;
+
+/// Sorted assumptions, decision path and heuristic state of an interrupted
+/// search.
+type Position = (Vec<Lit>, Vec<(Lit, bool)>, HeuristicState);
+
+/// Stable identifier of a clause added to a [`Solver`], for
+/// [`remove_clause`](Solver::remove_clause).
+///
+/// Unlike a [`ClauseId`], which is a position in the stored (simplified)
+/// formula, a handle never changes meaning: handles are numbered in the
+/// order the clauses were added, and are not reused after a removal.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClauseHandle(usize);
+
+impl ClauseHandle {
+    /// Returns the position of the clause among all clauses ever added.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// Error of [`Solver::try_add_clause`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClauseError {
+    /// The clause mentions a variable the solver does not have
+    UnknownVariable {
+        /// The largest offending variable
+        var: Var,
+        /// The number of variables of the solver
+        num_vars: usize,
+    },
+    /// The clause mentions a variable [`Solver::eliminate`] removed
+    EliminatedVariable {
+        /// The first offending variable
+        var: Var,
+    },
+}
+
+impl fmt::Display for ClauseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClauseError::UnknownVariable { var, num_vars } => {
+                write!(f, "clause mentions variable {} but the solver has {} variables", var, num_vars)
+            }
+            ClauseError::EliminatedVariable { var } => {
+                write!(f, "clause mentions variable {}, which was eliminated", var)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClauseError {}
+
+/// A SAT solver instance holding a formula and branching preferences.
+#[derive(Clone, Debug, Default)]
+pub struct Solver {
+    /// The clauses added so far, after load-time simplification
+    formula: Formula,
+    /// Every clause as added, by handle; `None` once removed
+    added: Vec<Option<Clause>>,
+    /// User data attached to the clauses
+    metadata: ClauseMetadata,
+    /// Number of variables (at least one more than the largest variable used)
+    num_vars: usize,
+    /// Whether clauses with unknown variables are rejected instead of
+    /// allocating them
+    fixed_vars: bool,
+    /// Whether clauses are stored as given, without load-time simplification
+    keep_clauses: bool,
+    /// Positions of the clauses, by the hash of their sorted literals
+    clause_index: HashMap<u64, Vec<usize>>,
+    /// Number of duplicate clauses dropped
+    duplicates: u64,
+    /// Values fixed by unit clauses at the last unit simplification
+    units: Vec<Val>,
+    /// Whether unit clauses were added since the last unit simplification
+    units_pending: bool,
+    /// Branching guidance passed to the search
+    hints: Hints,
+    /// How often each variable is frozen (missing entries are zero)
+    frozen: Vec<u32>,
+    /// Whether each variable was eliminated (missing entries are `false`)
+    eliminated: Vec<bool>,
+    /// Completion of models to the eliminated variables, once any were
+    reconstruction: Option<Reconstruction>,
+    /// Heuristic parameters
+    config: SolverConfig,
+    /// Hash of the clause sequence, updated as clauses are added
+    fingerprint: u64,
+    /// Answers of previous queries (`None` for UNSAT), with the tick of
+    /// their last use
+    cache: HashMap<QueryKey, (Option<Model>, u64)>,
+    /// Tick of the last cache lookup
+    cache_tick: u64,
+    /// Whether caching is switched off
+    cache_disabled: bool,
+    /// Number of queries answered from the cache
+    cache_hits: u64,
+    /// Whether decisions are recorded
+    recording: bool,
+    /// Decisions of the last recorded search
+    decisions: Vec<Lit>,
+    /// Decisions to replay in the next searches
+    replay: Option<Vec<Lit>>,
+    /// Whether the last replay could not be followed
+    replay_diverged: bool,
+    /// Statistics accumulated over all searches
+    stats: SolveStats,
+    /// Statistics of the last query
+    last_stats: SolveStats,
+    /// Time spent in each phase (with the `profiling` feature)
+    profile: Profile,
+    /// Propagation limit of each [`solve_limited`](Solver::solve_limited) call
+    budget: Option<u64>,
+    /// Memory limit of each [`solve_limited`](Solver::solve_limited) call, in bytes
+    memory_limit: Option<u64>,
+    /// Whether the last search was stopped by the memory limit
+    out_of_memory: bool,
+    /// Stop request for [`solve_limited`](Solver::solve_limited), shared with clones
+    interrupt: Arc<AtomicBool>,
+    /// Position of the last search, if it was interrupted
+    position: Option<Position>,
+    /// Buffers of the search, kept between queries
+    scratch: Scratch,
+    /// Failed assumptions of the last query, if it was unsatisfiable
+    failed: Vec<Lit>,
+    /// Model of the last query, if it was satisfiable
+    model: Option<Model>,
+    /// How failed assumptions are minimized
+    core_minimization: CoreMinimization,
+    /// Maximum number of checks spent minimizing one set of failed assumptions
+    core_effort: u64,
+    /// How optimal models are searched for
+    optimization: Optimization,
+    /// Events not yet taken, if logging
+    events: Option<EventLog>,
+}
+
+impl Solver {
+    /// Creates a solver with no variables and no clauses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a solver for an existing formula.
+    ///
+    /// # Arguments
+    ///
+    /// * `formula` - The initial clauses
+    /// * `num_vars` - The number of variables (raised if the formula uses more)
+    pub fn from_formula(formula: Formula, num_vars: usize) -> Self {
+        let mut solver = Solver { num_vars, ..Solver::default() };
+        for clause in formula {
+            solver.add_clause(clause);
+        }
+        solver.propagate_units();
+        solver
+    }
+
+    /// Allocates a fresh variable.
+    pub fn new_var(&mut self) -> Var {
+        self.num_vars += 1;
+        Var::new(self.num_vars - 1)
+    }
+
+    /// Adds a clause, allocating any variables it mentions that do not exist
+    /// yet (see [`set_auto_grow`](Solver::set_auto_grow)).
+    ///
+    /// # Returns
+    ///
+    /// A handle for removing the clause later.
+    ///
+    /// # Panics
+    ///
+    /// Panics if automatic growth is disabled and the clause mentions an
+    /// unknown variable; [`try_add_clause`](Solver::try_add_clause) returns
+    /// an error instead.
+    pub fn add_clause(&mut self, clause: Clause) -> ClauseHandle {
+        match self.try_add_clause(clause) {
+            Ok(handle) => handle,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Adds a clause, checking its variables first.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(handle)` - The clause was added, with this handle; with
+    ///   automatic growth, variables it mentions that do not exist yet were
+    ///   allocated
+    /// * `Err(ClauseError)` - Automatic growth is disabled and the clause
+    ///   mentions an unknown variable, or it mentions an eliminated
+    ///   variable; the solver is unchanged
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::solver::instance::ClauseError;
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// let mut solver = Solver::new();
+    /// let x = solver.new_var();
+    /// solver.set_auto_grow(false);
+    ///
+    /// assert!(solver.try_add_clause(vec![Lit::positive(x)]).is_ok());
+    /// assert_eq!(
+    ///     solver.try_add_clause(vec![Lit::negative(Var::new(3))]),
+    ///     Err(ClauseError::UnknownVariable { var: Var::new(3), num_vars: 1 })
+    /// );
+    /// ```
+    pub fn try_add_clause(&mut self, clause: Clause) -> Result<ClauseHandle, ClauseError> {
+        let max = clause.iter().map(|l| l.var).max();
+        if let Some(max) = max.filter(|max| self.fixed_vars && max.index() >= self.num_vars) {
+            return Err(ClauseError::UnknownVariable { var: max, num_vars: self.num_vars });
+        }
+        if let Some(lit) = clause.iter().find(|l| self.is_eliminated(l.var)) {
+            return Err(ClauseError::EliminatedVariable { var: lit.var });
+        }
+        // Both checks passed: only now may the clause change the solver
+        if let Some(max) = max {
+            self.num_vars = self.num_vars.max(max.index() + 1);
+        }
+        self.added.push(Some(clause.clone()));
+        self.load_clause(clause);
+        Ok(ClauseHandle(self.added.len() - 1))
+    }
+
+    /// Removes the clause added with `handle`.
+    ///
+    /// The stored formula is rebuilt from the remaining clauses as they
+    /// were added, so that no simplification derived with the help of the
+    /// removed clause survives it; the next query propagates the top-level
+    /// units again. Cached answers of the old formula no longer apply, and
+    /// eliminated variables are back.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the clause was removed, `false` if it had been removed
+    /// already or the handle belongs to another solver.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::solver::dpll::SolveResult;
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// let x = Lit::positive(Var::new(0));
+    /// let mut solver = Solver::new();
+    /// solver.add_clause(vec![x, Lit::positive(Var::new(1))]);
+    /// let unit = solver.add_clause(vec![!x]);
+    /// solver.add_clause(vec![x]);
+    /// assert_eq!(solver.solve(), SolveResult::Unsat);
+    ///
+    /// assert!(solver.remove_clause(unit));
+    /// assert!(matches!(solver.solve(), SolveResult::Sat(_)));
+    /// assert!(!solver.remove_clause(unit));
+    /// ```
+    pub fn remove_clause(&mut self, handle: ClauseHandle) -> bool {
+        let Some(slot) = self.added.get_mut(handle.0) else { return false };
+        if slot.take().is_none() {
+            return false;
+        }
+        self.metadata.remove(handle);
+        self.formula.clear();
+        self.units.clear();
+        self.duplicates = 0;
+        self.eliminated.clear();
+        self.reconstruction = None;
+        self.replace_clauses(Vec::new());
+        let added = std::mem::take(&mut self.added);
+        for clause in added.iter().flatten() {
+            self.load_clause(clause.clone());
+        }
+        self.added = added;
+        self.units_pending = true;
+        true
+    }
+
+    /// Returns the clauses added and not removed, with their handles.
+    pub fn added_clauses(&self) -> impl Iterator<Item = (ClauseHandle, &Clause)> {
+        self.added.iter().enumerate().filter_map(|(i, clause)| Some((ClauseHandle(i), clause.as_ref()?)))
+    }
+
+    /// Returns the user data attached to the clauses.
+    pub(crate) fn metadata(&self) -> &ClauseMetadata {
+        &self.metadata
+    }
+
+    /// Returns the user data attached to the clauses, for changing it.
+    pub(crate) fn metadata_mut(&mut self) -> &mut ClauseMetadata {
+        &mut self.metadata
+    }
+
+    /// Returns the event log, `None` while logging is off.
+    pub(crate) fn event_log_mut(&mut self) -> &mut Option<EventLog> {
+        &mut self.events
+    }
+
+    /// Logs `event` as happening now, if logging.
+    fn log_event(&mut self, event: Event) {
+        if let Some(log) = &mut self.events {
+            log.push(Instant::now(), self.stats.conflicts, event);
+        }
+    }
+
+    /// Simplifies `clause` by the known units, unless load-time
+    /// simplification is off, and stores it.
+    fn load_clause(&mut self, mut clause: Clause) {
+        if !self.keep_clauses {
+            let value = |lit: Lit| match (self.units.get(lit.var.index()), lit.neg) {
+                (Some(Val::True), false) | (Some(Val::False), true) => Val::True,
+                (Some(Val::True), true) | (Some(Val::False), false) => Val::False,
+                _ => Val::Undef,
+            };
+            if clause.iter().any(|&l| value(l) == Val::True) {
+                return;
+            }
+            clause.retain(|&l| value(l) == Val::Undef);
+            self.units_pending |= clause.len() <= 1;
+        }
+        self.push_clause(clause);
+    }
+
+    /// Appends a clause, updating the fingerprint, unless it duplicates a
+    /// stored clause and load-time simplification is on.
+    fn push_clause(&mut self, clause: Clause) {
+        if !self.keep_clauses {
+            let sorted = normalize(&clause);
+            let mut hasher = DefaultHasher::new();
+            sorted.hash(&mut hasher);
+            let positions = self.clause_index.entry(hasher.finish()).or_default();
+            if positions.iter().any(|&i| normalize(&self.formula[i]) == sorted) {
+                self.duplicates += 1;
+                return;
+            }
+            positions.push(self.formula.len());
+        }
+        let mut hasher = DefaultHasher::new();
+        self.fingerprint.hash(&mut hasher);
+        clause.hash(&mut hasher);
+        self.fingerprint = hasher.finish();
+        self.formula.push(clause);
+    }
+
+    /// Replaces the clauses by a rewritten `formula`.
+    fn replace_clauses(&mut self, formula: Formula) {
+        self.fingerprint = 0;
+        self.clause_index.clear();
+        for clause in formula {
+            self.push_clause(clause);
+        }
+    }
+
+    /// Propagates the unit clauses added since the last call through the
+    /// whole formula, unless load-time simplification is switched off.
+    pub(crate) fn propagate_units(&mut self) {
+        if self.keep_clauses || !self.units_pending {
+            return;
+        }
+        self.clean_clauses(false);
+    }
+
+    /// Propagates the top-level units through the whole formula, first
+    /// dropping tautologies if `tautologies` is set.
+    fn clean_clauses(&mut self, tautologies: bool) -> UnitStats {
+        self.units_pending = false;
+        let mut formula = std::mem::take(&mut self.formula);
+        let span = Span::start();
+        let before = formula.len();
+        if tautologies {
+            formula.retain(|clause| !clause.iter().any(|&lit| clause.contains(&!lit)));
+        }
+        let removed = (before - formula.len()) as u64;
+        let mut stats = simplify_units(&mut formula, self.num_vars);
+        stats.clauses_removed += removed;
+        span.stop(&mut self.profile.simplification);
+        self.log_event(Event::Simplification {
+            method: Simplification::Units,
+            clauses_removed: stats.clauses_removed,
+            literals_removed: stats.literals_removed,
+            clauses_added: 0,
+        });
+        self.units = vec![Val::Undef; self.num_vars];
+        for clause in &formula {
+            if let [lit] = clause[..] {
+                self.units[lit.var.index()] = if lit.neg { Val::False } else { Val::True };
+            }
+        }
+        self.replace_clauses(formula);
+        stats
+    }
+
+    /// Sets whether the formula is simplified as it is loaded (the default):
+    /// top-level unit clauses are propagated into the other clauses, and
+    /// duplicate clauses are dropped (see
+    /// [`duplicate_clauses`](Solver::duplicate_clauses)).
+    ///
+    /// New clauses are simplified by the units known so far when they are
+    /// added; new units are propagated through the whole formula when the
+    /// solver is created from a formula and before the next search. Switch
+    /// simplification off before adding clauses that must be kept exactly as
+    /// given, for example to relate an unsatisfiable core to the input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// let formula = vec![vec![x(0)], vec![!x(0), x(1), x(2)], vec![x(0), x(2)], vec![x(2), x(1), !x(0)]];
+    /// assert_eq!(Solver::from_formula(formula.clone(), 3).formula(), &vec![vec![x(0)], vec![x(1), x(2)]]);
+    ///
+    /// let mut solver = Solver::new();
+    /// solver.set_load_simplification(false);
+    /// for clause in &formula {
+    ///     solver.add_clause(clause.clone());
+    /// }
+    /// assert_eq!(solver.formula(), &formula);
+    /// ```
+    pub fn set_load_simplification(&mut self, enabled: bool) {
+        self.keep_clauses = !enabled;
+    }
+
+    /// Returns whether the formula is simplified as it is loaded.
+    pub fn load_simplification(&self) -> bool {
+        !self.keep_clauses
+    }
+
+    /// Returns the number of clauses dropped because an equal clause (with
+    /// the same literals, in any order) was already stored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// let mut solver = Solver::new();
+    /// solver.add_clause(vec![x(0), !x(1)]);
+    /// solver.add_clause(vec![!x(1), x(0)]);
+    /// solver.add_clause(vec![x(0), x(1)]);
+    ///
+    /// assert_eq!(solver.formula().len(), 2);
+    /// assert_eq!(solver.duplicate_clauses(), 1);
+    /// ```
+    pub fn duplicate_clauses(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// Sets whether clauses may mention variables that do not exist yet,
+    /// which are then allocated (the default). Without automatic growth,
+    /// variables must come from [`new_var`](Solver::new_var) or the count
+    /// given to [`from_formula`](Solver::from_formula).
+    pub fn set_auto_grow(&mut self, enabled: bool) {
+        self.fixed_vars = !enabled;
+    }
+
+    /// Returns whether clauses may allocate new variables.
+    pub fn auto_grow(&self) -> bool {
+        !self.fixed_vars
+    }
+
+    /// Cleans the formula between queries: propagates the top-level unit
+    /// clauses, removes the clauses they satisfy and the literals they
+    /// falsify, and drops tautologies. With load-time simplification on,
+    /// clauses that turn into duplicates of others are dropped as well.
+    ///
+    /// Clauses added after a unit are simplified by it as they are loaded,
+    /// but the clauses added before it are only cleaned at the next query;
+    /// this does it now, and also when load-time simplification is off. The
+    /// formula stays logically equivalent.
+    ///
+    /// # Returns
+    ///
+    /// The changes made; `clauses_removed` includes the tautologies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// let mut solver = Solver::new();
+    /// solver.add_clause(vec![x(0), x(1)]);
+    /// solver.add_clause(vec![!x(0), x(2), x(3)]);
+    /// solver.add_clause(vec![x(2), !x(2)]);
+    /// solver.add_clause(vec![x(0)]);
+    ///
+    /// let stats = solver.simplify();
+    /// assert_eq!((stats.clauses_removed, stats.literals_removed), (2, 1));
+    /// assert_eq!(solver.formula(), &vec![vec![x(0)], vec![x(2), x(3)]]);
+    /// ```
+    pub fn simplify(&mut self) -> UnitStats {
+        self.clean_clauses(true)
+    }
+
+    /// Simplifies the clauses by distillation, spending at most about
+    /// `budget` propagation steps (see [`distill`]).
+    ///
+    /// The formula stays logically equivalent, so this can run before the
+    /// first query or between queries (inprocessing); answers to
+    /// assumptions are unaffected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// // ¬x0 implies both x3 and ¬x3, so every clause shrinks to (x0)
+    /// let mut solver = Solver::from_formula(vec![vec![x(0), x(1), x(2)], vec![x(0), x(3)], vec![x(0), !x(3)]], 4);
+    /// let stats = solver.distill(1000);
+    /// assert_eq!(stats.clauses_strengthened, 3);
+    /// assert!(solver.formula().iter().all(|clause| *clause == vec![x(0)]));
+    /// ```
+    pub fn distill(&mut self, budget: u64) -> DistillStats {
+        let mut formula = std::mem::take(&mut self.formula);
+        let span = Span::start();
+        let stats = distill(&mut formula, self.num_vars, budget);
+        span.stop(&mut self.profile.simplification);
+        self.log_event(Event::Simplification {
+            method: Simplification::Distillation,
+            clauses_removed: stats.clauses_removed,
+            literals_removed: stats.literals_removed,
+            clauses_added: 0,
+        });
+        self.units_pending |= formula.iter().any(|clause| clause.len() <= 1);
+        self.replace_clauses(formula);
+        stats
+    }
+
+    /// Adds the binary clauses and units found by probing, spending at
+    /// most about `budget` propagation steps (see [`probe`]).
+    ///
+    /// Like [`distill`](Solver::distill), this keeps the formula logically
+    /// equivalent and can run between queries; the new binary clauses
+    /// shorten the propagation chains of later searches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// // ¬x0 implies x1 and x2, which contradict each other
+    /// let mut solver = Solver::from_formula(vec![vec![x(0), x(1)], vec![x(0), x(2)], vec![!x(1), !x(2), x(3)], vec![!x(1), !x(2), !x(3)]], 4);
+    /// let stats = solver.probe(1000);
+    /// assert_eq!(stats.failed_literals, 1);
+    /// assert!(solver.formula().contains(&vec![x(0)]));
+    /// ```
+    pub fn probe(&mut self, budget: u64) -> ProbeStats {
+        let mut formula = std::mem::take(&mut self.formula);
+        let span = Span::start();
+        let stats = probe(&mut formula, self.num_vars, budget);
+        span.stop(&mut self.profile.simplification);
+        self.log_event(Event::Simplification {
+            method: Simplification::Probing,
+            clauses_removed: 0,
+            literals_removed: 0,
+            clauses_added: stats.failed_literals + stats.binaries_added,
+        });
+        self.units_pending |= stats.failed_literals > 0;
+        self.replace_clauses(formula);
+        stats
+    }
+
+    /// Substitutes equivalent variables and eliminates variables by
+    /// resolution, computing at most about `budget` resolvents (see
+    /// [`eliminate`]); frozen variables are kept.
+    ///
+    /// Unlike the other simplifications, this keeps satisfiability but not
+    /// the models: the models of later queries are completed to the
+    /// eliminated variables, so they still satisfy the clauses as added,
+    /// but model counting, enumeration and backbones see the simplified
+    /// formula. Later clauses and assumptions must not mention eliminated
+    /// variables ([`try_add_clause`](Solver::try_add_clause) rejects such
+    /// clauses), so freeze the variables they will use first. An
+    /// interrupted search starts over.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::solver::dpll::SolveResult;
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// // x0 ↔ x1, x1 ∨ x2, ¬x2 ∨ x3
+    /// let formula = vec![vec![!x(0), x(1)], vec![x(0), !x(1)], vec![x(1), x(2)], vec![!x(2), x(3)]];
+    /// let mut solver = Solver::from_formula(formula.clone(), 4);
+    /// solver.freeze(Var::new(3));
+    /// let stats = solver.eliminate(1000);
+    /// assert!(stats.variables < 4 && !solver.is_eliminated(Var::new(3)));
+    ///
+    /// let SolveResult::Sat(model) = solver.solve_with_assumptions(&[!x(3)]) else { unreachable!() };
+    /// assert!(formula.iter().all(|clause| clause.iter().any(|&lit| model.is_true(lit))));
+    /// let removed = Var::range(4).find(|&var| solver.is_eliminated(var)).unwrap();
+    /// assert!(solver.try_add_clause(vec![Lit::positive(removed)]).is_err());
+    /// ```
+    pub fn eliminate(&mut self, budget: u64) -> EliminationStats {
+        let mut formula = std::mem::take(&mut self.formula);
+        let before = formula.len() as u64;
+        let frozen: Vec<Var> = Var::range(self.frozen.len()).filter(|&var| self.is_frozen(var)).collect();
+        let span = Span::start();
+        let (stats, reconstruction) = eliminate(&mut formula, self.num_vars, &frozen, budget);
+        span.stop(&mut self.profile.simplification);
+        self.log_event(Event::Simplification {
+            method: Simplification::Elimination,
+            clauses_removed: before.saturating_sub(stats.clauses),
+            literals_removed: 0,
+            clauses_added: stats.clauses.saturating_sub(before),
+        });
+        for var in reconstruction.removed() {
+            if var.index() >= self.eliminated.len() {
+                self.eliminated.resize(var.index() + 1, false);
+            }
+            self.eliminated[var.index()] = true;
+        }
+        match &mut self.reconstruction {
+            Some(earlier) => earlier.append(reconstruction),
+            None => self.reconstruction = Some(reconstruction),
+        }
+        self.position = None;
+        self.units_pending |= formula.iter().any(|clause| clause.len() <= 1);
+        self.replace_clauses(formula);
+        stats
+    }
+
+    /// Returns the number of variables.
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// Returns the clauses added so far.
+    pub fn formula(&self) -> &Formula {
+        &self.formula
+    }
+
+    /// Sets the value the search tries first when branching on `var`.
+    ///
+    /// Phase hints bias which model is found (and how quickly), never
+    /// whether one is found. Variables without a hint are tried `true` first.
+    /// Changing a hint discards the cached answers, so that a repeated query
+    /// searches again under the new hint.
+    pub fn set_phase(&mut self, var: Var, value: bool) {
+        if var.index() >= self.hints.phases.len() {
+            self.hints.phases.resize(var.index() + 1, None);
+        }
+        self.hints.phases[var.index()] = Some(value);
+        self.cache.clear();
+    }
+
+    /// Removes the phase hint of `var`.
+    pub fn clear_phase(&mut self, var: Var) {
+        if let Some(phase) = self.hints.phases.get_mut(var.index()) {
+            *phase = None;
+        }
+        self.cache.clear();
+    }
+
+    /// Returns the phase hint of `var`, if any.
+    pub fn phase(&self, var: Var) -> Option<bool> {
+        self.hints.phases.get(var.index()).copied().flatten()
+    }
+
+    /// Sets a static decision order.
+    ///
+    /// The search branches on the unassigned variables of `order` first, in
+    /// the given order, before falling back to its own heuristic. Structured
+    /// encodings (planning, BMC) often have known good orders, such as
+    /// following the time steps. An empty order restores the default.
+    /// Like a phase hint, a new order discards the cached answers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::solver::dpll::SolveResult;
+    /// use putnam::types::{Lit, Val, Var};
+    ///
+    /// // Exactly one of x0, x1
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// let mut solver = Solver::from_formula(vec![vec![x(0), x(1)], vec![!x(0), !x(1)]], 2);
+    /// solver.set_decision_order(vec![Var::new(1), Var::new(0)]);
+    ///
+    /// let SolveResult::Sat(model) = solver.solve() else { unreachable!() };
+    /// assert_eq!(model.value(Var::new(1)), Val::True);
+    /// assert_eq!(model.value(Var::new(0)), Val::False);
+    /// ```
+    pub fn set_decision_order(&mut self, order: Vec<Var>) {
+        self.hints.order = order;
+        self.cache.clear();
+    }
+
+    /// Returns the static decision order.
+    pub fn decision_order(&self) -> &[Var] {
+        &self.hints.order
+    }
+
+    /// Protects `var` from being eliminated by
+    /// [`eliminate`](Solver::eliminate), until it is melted as often as it
+    /// was frozen.
+    ///
+    /// Freeze every variable that later clauses or assumptions may mention.
+    /// The load-time simplification, [`distill`](Solver::distill) and
+    /// [`probe`](Solver::probe) keep the formula equivalent and never
+    /// eliminate a variable, so they need no freezing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    ///
+    /// let mut solver = Solver::new();
+    /// let x = solver.new_var();
+    /// solver.freeze(x);
+    /// solver.freeze(x);
+    /// solver.melt(x);
+    /// assert!(solver.is_frozen(x));
+    /// solver.melt(x);
+    /// assert!(!solver.is_frozen(x));
+    /// ```
+    pub fn freeze(&mut self, var: Var) {
+        if var.index() >= self.frozen.len() {
+            self.frozen.resize(var.index() + 1, 0);
+        }
+        self.frozen[var.index()] += 1;
+    }
+
+    /// Undoes one [`freeze`](Solver::freeze) of `var`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `var` is not frozen.
+    pub fn melt(&mut self, var: Var) {
+        match self.frozen.get_mut(var.index()) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => panic!("melting variable {} that is not frozen", var),
+        }
+    }
+
+    /// Returns whether `var` is frozen.
+    pub fn is_frozen(&self, var: Var) -> bool {
+        self.frozen.get(var.index()).is_some_and(|&count| count > 0)
+    }
+
+    /// Returns whether [`eliminate`](Solver::eliminate) removed `var`.
+    pub fn is_eliminated(&self, var: Var) -> bool {
+        self.eliminated.get(var.index()).copied().unwrap_or(false)
+    }
+
+    /// Selects the unit propagation engine (scanning by default).
+    ///
+    /// The engine never changes the answer or the model found, only how
+    /// fast it is found. Selecting one discards the cached answers, so that
+    /// the next query runs on it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::solver::dpll::Propagation;
+    ///
+    /// let (formula, num_vars, _) = putnam::gen::families::pigeonhole(4);
+    /// let mut solver = Solver::from_formula(formula, num_vars);
+    /// let scanned = solver.solve();
+    ///
+    /// solver.set_propagation(Propagation::Counters);
+    /// assert_eq!(solver.solve(), scanned);
+    /// assert_eq!(solver.cache_hits(), 0);
+    /// ```
+    pub fn set_propagation(&mut self, propagation: Propagation) {
+        self.config.propagation = propagation;
+        self.cache.clear();
+    }
+
+    /// Returns the selected unit propagation engine.
+    pub fn propagation(&self) -> Propagation {
+        self.config.propagation
+    }
+
+    /// Replaces the heuristic parameters (see [`SolverConfig`]), discarding
+    /// the cached answers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a parameter is out of range (see [`SolverConfig::validate`]).
+    pub fn set_config(&mut self, config: SolverConfig) {
+        if let Err(e) = config.validate() {
+            panic!("invalid solver configuration: {}", e);
+        }
+        self.config = config;
+        self.cache.clear();
+    }
+
+    /// Returns the heuristic parameters.
+    pub fn config(&self) -> &SolverConfig {
+        &self.config
+    }
+
+    /// Decides satisfiability of the current formula.
+    pub fn solve(&mut self) -> SolveResult {
+        self.solve_with_assumptions(&[])
+    }
+
+    /// Decides satisfiability of the current formula with the `assumptions`
+    /// fixed to true for this call only.
+    ///
+    /// If the same query (same clauses, variables and set of assumptions)
+    /// was answered before, the cached answer is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an assumption refers to a variable that does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::solver::dpll::SolveResult;
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// let mut solver = Solver::from_formula(vec![vec![x(0), x(1)]], 2);
+    ///
+    /// assert_eq!(solver.solve_with_assumptions(&[!x(0), !x(1)]), SolveResult::Unsat);
+    /// assert!(matches!(solver.solve_with_assumptions(&[!x(0)]), SolveResult::Sat(_)));
+    ///
+    /// // The same query again (in any order) is served from the cache
+    /// assert_eq!(solver.solve_with_assumptions(&[!x(1), !x(0)]), SolveResult::Unsat);
+    /// assert_eq!(solver.cache_hits(), 1);
+    /// ```
+    pub fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> SolveResult {
+        self.query(assumptions, None, None).expect("search without an interrupt flag completes")
+    }
+
+    /// Like [`solve_with_assumptions`](Solver::solve_with_assumptions), but
+    /// stops early when the [`interrupt_flag`](Solver::interrupt_flag) is set,
+    /// the [propagation budget](Solver::set_propagation_budget) is spent or
+    /// the [memory limit](Solver::set_memory_limit) is exceeded.
+    ///
+    /// All are checked at every branching point. An interrupted search
+    /// returns `None` and clears the flag. Its position is kept (see
+    /// [`checkpoint`](Solver::checkpoint)): the next search of the same
+    /// query continues from there instead of starting over.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an assumption refers to a variable that does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::Ordering;
+    /// use putnam::solver::Solver;
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// let mut solver = Solver::from_formula(vec![vec![x(0), x(1), x(2)], vec![!x(0), x(1), x(2)]], 3);
+    ///
+    /// // Requested before the search reaches its first branching point
+    /// solver.interrupt_flag().store(true, Ordering::Relaxed);
+    /// assert_eq!(solver.solve_limited(&[]), None);
+    /// assert!(solver.solve_limited(&[]).is_some());
+    /// ```
+    pub fn solve_limited(&mut self, assumptions: &[Lit]) -> Option<SolveResult> {
+        let interrupt = Arc::clone(&self.interrupt);
+        let budget = self.budget;
+        let result = self.query(assumptions, Some(&interrupt), budget);
+        if result.is_none() {
+            interrupt.store(false, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Returns the flag that interrupts [`solve_limited`](Solver::solve_limited).
+    ///
+    /// Storing `true` from any thread (or a signal handler) stops the
+    /// running search at its next branching point. Clones of the solver
+    /// share the flag. Searches started by [`solve`](Solver::solve) and
+    /// [`solve_with_assumptions`](Solver::solve_with_assumptions) ignore it.
+    pub fn interrupt_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.interrupt)
+    }
+
+    /// Returns the statistics accumulated over all searches so far.
+    pub fn stats(&self) -> SolveStats {
+        self.stats
+    }
+
+    /// Returns the statistics of the last query alone.
+    ///
+    /// A query answered from the cache did no work and reports zeros.
+    pub fn last_stats(&self) -> SolveStats {
+        self.last_stats
+    }
+
+    /// Limits each [`solve_limited`](Solver::solve_limited) call to about
+    /// `budget` propagations (`None` removes the limit).
+    ///
+    /// The budget is checked at branching points, so a call may overshoot
+    /// it by one round of propagation; [`last_stats`](Solver::last_stats)
+    /// reports what was actually consumed. A call always makes at least one
+    /// new decision before stopping, so repeated calls continuing from the
+    /// saved position finish eventually. This allows time slicing between
+    /// many solvers in one thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    ///
+    /// let (formula, num_vars, _) = putnam::gen::families::pigeonhole(5);
+    /// let mut solver = Solver::from_formula(formula, num_vars);
+    /// solver.set_propagation_budget(Some(100));
+    ///
+    /// let mut slices = 1;
+    /// while solver.solve_limited(&[]).is_none() {
+    ///     assert!(solver.last_stats().propagations >= 100);
+    ///     slices += 1;
+    /// }
+    /// assert!(slices > 1);
+    /// ```
+    pub fn set_propagation_budget(&mut self, budget: Option<u64>) {
+        self.budget = budget;
+    }
+
+    /// Returns the propagation budget of each `solve_limited` call.
+    pub fn propagation_budget(&self) -> Option<u64> {
+        self.budget
+    }
+
+    /// Limits the memory of each [`solve_limited`](Solver::solve_limited)
+    /// call to about `bytes` (`None` removes the limit).
+    ///
+    /// The memory counted is the one of the
+    /// [statistics](super::stats::SolveStats): the clause database searched
+    /// and the partial assignments of the search. It is checked at
+    /// branching points; a search over the limit stops there and returns
+    /// `None`, and [`out_of_memory`](Solver::out_of_memory) tells it from an
+    /// interrupt. Searching again stops the same way unless the limit is
+    /// raised, so a caller out of memory should give up with an unknown
+    /// answer rather than retry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    ///
+    /// let (formula, num_vars, _) = putnam::gen::families::pigeonhole(5);
+    /// let mut solver = Solver::from_formula(formula, num_vars);
+    /// solver.set_memory_limit(Some(1000));
+    /// assert_eq!(solver.solve_limited(&[]), None);
+    /// assert!(solver.out_of_memory());
+    ///
+    /// solver.set_memory_limit(None);
+    /// assert!(solver.solve_limited(&[]).is_some());
+    /// assert!(!solver.out_of_memory());
+    /// ```
+    pub fn set_memory_limit(&mut self, bytes: Option<u64>) {
+        self.memory_limit = bytes;
+    }
+
+    /// Returns the memory limit of each `solve_limited` call, in bytes.
+    pub fn memory_limit(&self) -> Option<u64> {
+        self.memory_limit
+    }
+
+    /// Returns whether the last search was stopped by the
+    /// [memory limit](Solver::set_memory_limit).
+    pub fn out_of_memory(&self) -> bool {
+        self.out_of_memory
+    }
+
+    /// Returns the time spent in each phase over the solver's lifetime.
+    ///
+    /// All zeros unless the crate is built with the `profiling` feature
+    /// (see [`profile`](super::profile)). In
+    /// [deterministic](SolverConfig::deterministic) mode only the visits
+    /// are reported, the times are zero.
+    pub fn profile(&self) -> Profile {
+        if self.config.deterministic {
+            return self.profile.without_times();
+        }
+        self.profile
+    }
+
+    /// Estimates how much of the search tree the last search covered.
+    ///
+    /// Each decision splits the remaining tree in two halves, so a decision
+    /// at depth `d` (counting from 0) whose first branch is exhausted adds
+    /// `2^-(d+1)` to the covered fraction. The estimate grows monotonically
+    /// while an interrupted search is continued, but unit propagation makes
+    /// real subtrees uneven, so it is no prediction of the remaining time.
+    ///
+    /// # Returns
+    ///
+    /// The covered fraction of the interrupted search, `1.0` if the last
+    /// search finished, and `0.0` before the first search
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    ///
+    /// let (formula, num_vars, _) = putnam::gen::families::pigeonhole(5);
+    /// let mut solver = Solver::from_formula(formula, num_vars);
+    /// solver.set_propagation_budget(Some(100));
+    ///
+    /// assert_eq!(solver.progress(), 0.0);
+    /// while solver.solve_limited(&[]).is_none() {
+    ///     assert!(solver.progress() < 1.0);
+    /// }
+    /// assert_eq!(solver.progress(), 1.0);
+    /// ```
+    pub fn progress(&self) -> f64 {
+        match &self.position {
+            Some((_, path, _)) => path
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, exhausted))| *exhausted)
+                .map(|(depth, _)| 0.5f64.powi(depth as i32 + 1))
+                .sum(),
+            None if self.stats.searches > 0 => 1.0,
+            None => 0.0,
+        }
+    }
+
+    /// Captures the branching hints, statistics and search position.
+    ///
+    /// The position is that of the last search if it was interrupted, and
+    /// empty otherwise; it comes with the heuristic scores, phases and
+    /// restart counters of that search. Save it with
+    /// [`write_checkpoint`](super::checkpoint::write_checkpoint).
+    pub fn checkpoint(&self) -> Checkpoint {
+        let (assumptions, path, heuristic) = self.position.clone().unwrap_or_else(|| {
+            let state = HeuristicState {
+                heuristic: self.config.heuristic,
+                polarity: self.config.polarity,
+                ..HeuristicState::default()
+            };
+            (Vec::new(), Vec::new(), state)
+        });
+        Checkpoint {
+            num_vars: self.num_vars,
+            num_clauses: self.formula.len(),
+            fingerprint: self.fingerprint,
+            assumptions,
+            phases: self.hints.phases.clone(),
+            order: self.hints.order.clone(),
+            stats: self.stats,
+            path,
+            heuristic,
+        }
+    }
+
+    /// Restores the hints, statistics and search position of a checkpoint.
+    ///
+    /// The next search of the checkpointed query continues where the
+    /// interrupted one stopped.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The checkpoint was loaded
+    /// * `Err(io::Error)` - With `ErrorKind::InvalidInput` if the checkpoint
+    ///   was taken for a different formula, or with a heuristic or polarity
+    ///   other than the configured ones (see [`set_config`](Solver::set_config))
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::solver::dpll::SolveResult;
+    /// use putnam::solver::checkpoint::{read_checkpoint, write_checkpoint};
+    /// use putnam::types::{Lit, Val, Var};
+    ///
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// let formula = vec![vec![x(0), x(1)]];
+    ///
+    /// // A search interrupted after refuting the branch x0 = true
+    /// let mut file = Vec::new();
+    /// write_checkpoint(&mut file, &Solver::from_formula(formula.clone(), 2).checkpoint())?;
+    /// file.extend_from_slice(b"f 1\n");
+    /// let mut solver = Solver::from_formula(formula, 2);
+    /// solver.resume(&read_checkpoint(file.as_slice())?)?;
+    ///
+    /// let SolveResult::Sat(model) = solver.solve() else { unreachable!() };
+    /// assert_eq!(model.value(Var::new(0)), Val::False);
+    ///
+    /// let mut other = Solver::from_formula(vec![vec![x(0)]], 2);
+    /// assert!(other.resume(&solver.checkpoint()).is_err());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn resume(&mut self, checkpoint: &Checkpoint) -> io::Result<()> {
+        if (checkpoint.num_vars, checkpoint.num_clauses, checkpoint.fingerprint)
+            != (self.num_vars, self.formula.len(), self.fingerprint)
+        {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "checkpoint was taken for a different formula"));
+        }
+        let state = &checkpoint.heuristic;
+        if (state.heuristic, state.polarity) != (self.config.heuristic, self.config.polarity) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "checkpoint was taken with a different heuristic or polarity",
+            ));
+        }
+        self.hints.phases = checkpoint.phases.clone();
+        self.hints.order = checkpoint.order.clone();
+        self.cache.clear();
+        self.stats = checkpoint.stats;
+        self.position = match checkpoint.path.is_empty() {
+            true => None,
+            false => Some((checkpoint.assumptions.clone(), checkpoint.path.clone(), checkpoint.heuristic.clone())),
+        };
+        Ok(())
+    }
+
+    /// Answers a query, and determines its failed assumptions if it is
+    /// unsatisfiable.
+    fn query(&mut self, assumptions: &[Lit], interrupt: Option<&AtomicBool>, budget: Option<u64>) -> Option<SolveResult> {
+        self.failed.clear();
+        self.model = None;
+        self.out_of_memory = false;
+        let result = self.answer(assumptions, interrupt, budget)?;
+        self.log_event(Event::Answer { satisfiable: matches!(result, SolveResult::Sat(_)) });
+        let result = match result {
+            SolveResult::Sat(model) => {
+                let model = self.complete(model);
+                self.model = Some(model.clone());
+                SolveResult::Sat(model)
+            }
+            SolveResult::Unsat => {
+                if !assumptions.is_empty() {
+                    self.failed = self.failed_core(assumptions);
+                }
+                SolveResult::Unsat
+            }
+        };
+        Some(result)
+    }
+
+    /// Assigns the eliminated variables in `model`, a model of the stored
+    /// formula, to make it a model of the clauses as added.
+    ///
+    /// The values and reasons of the search stay; once variables were
+    /// eliminated, unassigned variables become false.
+    fn complete(&self, mut model: Model) -> Model {
+        let Some(reconstruction) = &self.reconstruction else { return model };
+        for (var, val) in reconstruction.reconstruct(&model).iter() {
+            if model.value(var) != val {
+                model.assign(var, val);
+            }
+        }
+        model
+    }
+
+    /// Finds the failed assumptions of an unsatisfiable query and
+    /// minimizes them as configured.
+    ///
+    /// The checks of the minimization count towards the statistics of the
+    /// query; they leave the recorded decisions alone.
+    fn failed_core(&mut self, assumptions: &[Lit]) -> Vec<Lit> {
+        let assumed = normalize(assumptions);
+        // A literal assumed with both signs, or against a top-level unit
+        let contradicting = assumed.windows(2).find(|pair| pair[0].var == pair[1].var);
+        if let Some(pair) = contradicting {
+            return pair.to_vec();
+        }
+        let refuted_by_unit = self.formula.iter().find_map(|clause| match clause[..] {
+            [unit] => assumed.binary_search(&!unit).ok(),
+            _ => None,
+        });
+        if let Some(at) = refuted_by_unit {
+            return vec![assumed[at]];
+        }
+        let query_stats = self.last_stats;
+        let decisions = std::mem::take(&mut self.decisions);
+        let mut work = SolveStats::default();
+        let core = minimize(assumed, self.core_minimization, self.core_effort, |subset| {
+            let refuted = self.answer(subset, None, None) == Some(SolveResult::Unsat);
+            work += self.last_stats;
+            refuted
+        });
+        self.decisions = decisions;
+        self.last_stats = query_stats;
+        self.last_stats += work;
+        core
+    }
+
+    /// Answers a query from the cache or by searching.
+    fn answer(&mut self, assumptions: &[Lit], interrupt: Option<&AtomicBool>, budget: Option<u64>) -> Option<SolveResult> {
+        self.check_assumptions(assumptions);
+        self.propagate_units();
+        self.last_stats = SolveStats::default();
+        // Recording and replay need an actual search
+        if self.cache_disabled || self.recording || self.replay.is_some() {
+            return self.run_interruptible(assumptions, interrupt, budget);
+        }
+
+        let key = (self.fingerprint, self.num_vars, normalize(assumptions));
+        self.cache_tick += 1;
+        if let Some((answer, used)) = self.cache.get_mut(&key) {
+            self.cache_hits += 1;
+            *used = self.cache_tick;
+            return Some(match answer {
+                Some(model) => SolveResult::Sat(model.clone()),
+                None => SolveResult::Unsat,
+            });
+        }
+
+        let result = self.run_interruptible(assumptions, interrupt, budget)?;
+        let answer = match &result {
+            SolveResult::Sat(model) => Some(model.clone()),
+            SolveResult::Unsat => None,
+        };
+        if self.cache.len() >= CACHE_CAPACITY {
+            let oldest = self.cache.iter().min_by_key(|(_, (_, used))| *used).map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.cache.remove(&oldest);
+            }
+        }
+        self.cache.insert(key, (answer, self.cache_tick));
+        Some(result)
+    }
+
+    /// Solves on a background thread, returning a future of the answer.
+    ///
+    /// The search runs on a snapshot of the solver (clauses, hints, replay),
+    /// so the solver stays usable while it runs; recorded decisions and the
+    /// cache of the snapshot are discarded. Dropping the future cancels the
+    /// search. See [`background`](super::background) for an example.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an assumption refers to a variable that does not exist.
+    pub fn solve_async(&self, assumptions: &[Lit]) -> SolveFuture {
+        self.check_assumptions(assumptions);
+        background::spawn(self.clone(), assumptions.to_vec())
+    }
+
+    pub(crate) fn check_assumptions(&self, assumptions: &[Lit]) {
+        if let Some(lit) = assumptions.iter().find(|l| l.var.index() >= self.num_vars) {
+            panic!("assumption on unknown variable {} (solver has {})", lit.var, self.num_vars);
+        }
+        if let Some(lit) = assumptions.iter().find(|l| self.is_eliminated(l.var)) {
+            panic!("assumption on eliminated variable {}", lit.var);
+        }
+    }
+
+    /// Runs the DPLL search with the configured hints, recording and replay,
+    /// stopping with `None` once `interrupt` is set or `budget` is spent.
+    ///
+    /// The answer cache is neither consulted nor updated.
+    pub(crate) fn run_interruptible(
+        &mut self,
+        assumptions: &[Lit],
+        interrupt: Option<&AtomicBool>,
+        budget: Option<u64>,
+    ) -> Option<SolveResult> {
+        self.propagate_units();
+        self.log_event(Event::Search { assumptions: assumptions.len() as u64 });
+        let span = Span::start();
+        let special = self.solve_special(assumptions);
+        span.stop(&mut self.profile.special);
+        if let Some(result) = special {
+            self.last_stats = SolveStats { searches: 1, ..SolveStats::default() };
+            self.stats += self.last_stats;
+            return Some(result);
+        }
+        let assumed = normalize(assumptions);
+        let mut branching = Branching::new(&self.hints);
+        branching.reuse(std::mem::take(&mut self.scratch));
+        branching.interrupt = interrupt;
+        branching.budget = budget;
+        // Like the budget, the memory limit only applies to searches that
+        // may stop
+        if interrupt.is_some() {
+            branching.memory_limit = self.memory_limit;
+        }
+        branching.configure(&self.config);
+        if let Some((_, path, state)) = self.position.as_ref().filter(|(a, _, _)| *a == assumed) {
+            branching.resume = path;
+            branching.restore(state);
+        }
+        if self.recording {
+            branching.record = Some(Vec::new());
+        }
+        if let Some(replay) = &self.replay {
+            branching.replay = replay;
+        }
+        if self.events.is_some() {
+            branching.events = Some(Vec::new());
+        }
+        let result = search(&self.formula, self.num_vars, assumptions, &mut branching);
+        let state = result.is_none().then(|| branching.heuristic_state());
+        self.scratch = branching.scratch();
+        self.out_of_memory = branching.out_of_memory;
+        // The search counts its conflicts from zero
+        if let (Some(log), Some(events)) = (&mut self.events, branching.events.take()) {
+            for (at, conflicts, event) in events {
+                log.push(at, self.stats.conflicts + conflicts, event);
+            }
+        }
+        let (record, diverged, path) = (branching.record, branching.diverged, branching.path);
+        if self.config.deterministic {
+            branching.stats.clause_bytes = 0;
+            branching.stats.search_bytes = 0;
+        }
+        self.stats += branching.stats;
+        self.last_stats = branching.stats;
+        self.profile += branching.profile;
+        self.position = state.map(|state| (assumed, path, state));
+        if result.is_none() {
+            self.log_event(Event::Stopped { out_of_memory: self.out_of_memory });
+        }
+        if let Some(record) = record {
+            self.decisions = record;
+        }
+        self.replay_diverged = diverged;
+        result
+    }
+
+    /// Runs one branch-and-bound search for a model minimizing `objective`,
+    /// returning the best model found with its cost.
+    ///
+    /// Objective variables without a phase hint are tried with the value
+    /// that costs nothing first. The answer cache is neither consulted nor
+    /// updated.
+    pub(crate) fn run_objective(&mut self, objective: Objective) -> Option<(Model, u64)> {
+        self.propagate_units();
+        let mut hints = self.hints.clone();
+        objective.suggest_phases(&mut hints);
+        let mut branching = Branching::new(&hints);
+        branching.reuse(std::mem::take(&mut self.scratch));
+        branching.configure(&self.config);
+        branching.objective = Some(objective);
+        search(&self.formula, self.num_vars, &[], &mut branching).expect("search without an interrupt flag completes");
+        self.scratch = branching.scratch();
+        if self.config.deterministic {
+            branching.stats.clause_bytes = 0;
+            branching.stats.search_bytes = 0;
+        }
+        self.stats += branching.stats;
+        self.last_stats = branching.stats;
+        self.profile += branching.profile;
+        branching.objective.and_then(|objective| objective.best)
+    }
+
+    /// Enables or disables recording of decisions.
+    ///
+    /// While recording, every search logs its branching decisions, which
+    /// can be read with [`decisions`](Solver::decisions), saved with
+    /// [`write_decisions`](super::replay::write_decisions) and fed back with
+    /// [`set_replay`](Solver::set_replay). Recorded searches bypass the cache.
+    pub fn set_recording(&mut self, enabled: bool) {
+        self.recording = enabled;
+    }
+
+    /// Returns the decisions of the last recorded search.
+    ///
+    /// Each entry is the literal tried first at a branching point, in the
+    /// order the search reached them.
+    pub fn decisions(&self) -> &[Lit] {
+        &self.decisions
+    }
+
+    /// Forces subsequent searches down a recorded path.
+    ///
+    /// The search takes the given decisions in order instead of consulting
+    /// hints or heuristics, and falls back to them once the recording is
+    /// exhausted. If a recorded decision is impossible (its variable is
+    /// already assigned, e.g. because the formula changed), the replay is
+    /// abandoned and [`replay_diverged`](Solver::replay_diverged) reports it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// let mut solver = Solver::from_formula(vec![vec![x(0), x(1)], vec![!x(0), x(2)]], 3);
+    /// solver.set_phase(Var::new(0), false);
+    /// solver.set_recording(true);
+    /// let original = solver.solve();
+    /// let decisions = solver.decisions().to_vec();
+    ///
+    /// // Without the phase hint, the replay still reproduces the same run
+    /// let mut fresh = Solver::from_formula(solver.formula().clone(), 3);
+    /// fresh.set_replay(decisions);
+    /// assert_eq!(fresh.solve(), original);
+    /// assert!(!fresh.replay_diverged());
+    /// ```
+    pub fn set_replay(&mut self, decisions: Vec<Lit>) {
+        self.replay = Some(decisions);
+    }
+
+    /// Stops replaying decisions.
+    pub fn clear_replay(&mut self) {
+        self.replay = None;
+    }
+
+    /// Returns `true` if the last search could not follow the replayed decisions.
+    pub fn replay_diverged(&self) -> bool {
+        self.replay_diverged
+    }
+
+    /// Enables or disables the answer cache (enabled by default).
+    ///
+    /// Disabling the cache also discards its contents. The cache keeps the
+    /// answers of the last 256 distinct queries, and is also discarded when
+    /// the hints or the heuristic parameters change, since these may change
+    /// the model found.
+    pub fn set_caching(&mut self, enabled: bool) {
+        self.cache_disabled = !enabled;
+        if !enabled {
+            self.cache.clear();
+        }
+    }
+
+    /// Returns the number of queries answered from the cache.
+    pub fn cache_hits(&self) -> u64 {
+        self.cache_hits
+    }
+
+    /// Returns the failed assumptions of the last query: a subset of its
+    /// assumptions under which the formula is unsatisfiable.
+    ///
+    /// Empty if the last query was satisfiable, interrupted or made without
+    /// assumptions (or if the formula is unsatisfiable on its own and the
+    /// minimization found out). Without [minimization](Solver::set_core_minimization)
+    /// the set is all of the assumptions, unless two of them contradict
+    /// each other or one contradicts a top-level unit clause.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::solver::dpll::SolveResult;
+    /// use putnam::solver::failed::CoreMinimization;
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// let mut solver = Solver::from_formula(vec![vec![x(0), x(1)], vec![x(2), x(3)]], 4);
+    /// solver.set_core_minimization(CoreMinimization::QuickXplain, 100);
+    ///
+    /// assert_eq!(solver.solve_with_assumptions(&[x(2), !x(0), !x(3), !x(1)]), SolveResult::Unsat);
+    /// assert_eq!(solver.failed_assumptions(), &[!x(0), !x(1)]);
+    /// ```
+    pub fn failed_assumptions(&self) -> &[Lit] {
+        &self.failed
+    }
+
+    /// Returns the value of `lit` in the model of the last query, like
+    /// IPASIR's `val`.
+    ///
+    /// `Undef` if the last query was not satisfiable, or if its model
+    /// leaves the variable unassigned (the formula is then satisfied with
+    /// either value) or predates the variable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::types::{Lit, Val, Var};
+    ///
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// let mut solver = Solver::from_formula(vec![vec![x(0), x(1)], vec![!x(0)]], 2);
+    /// solver.solve();
+    /// assert_eq!(solver.value(x(1)), Val::True);
+    /// assert_eq!(solver.value(x(0)), Val::False);
+    /// assert_eq!(solver.value(!x(0)), Val::True);
+    ///
+    /// solver.solve_with_assumptions(&[!x(1)]);
+    /// assert_eq!(solver.value(x(1)), Val::Undef);
+    /// ```
+    pub fn value(&self, lit: Lit) -> Val {
+        match &self.model {
+            Some(model) if lit.var.index() < model.num_vars() => model.lit_value(lit),
+            _ => Val::Undef,
+        }
+    }
+
+    /// Sets how failed assumptions are minimized, spending at most `effort`
+    /// satisfiability checks (each a search under a subset of the
+    /// assumptions) per unsatisfiable query. Off by default.
+    ///
+    /// A minimization that runs out of effort still returns a valid, if
+    /// larger, set; one that finishes returns a minimal set.
+    pub fn set_core_minimization(&mut self, method: CoreMinimization, effort: u64) {
+        self.core_minimization = method;
+        self.core_effort = effort;
+    }
+
+    /// Returns the minimization method and effort of failed assumptions.
+    pub fn core_minimization(&self) -> (CoreMinimization, u64) {
+        (self.core_minimization, self.core_effort)
+    }
+
+    /// Sets how [`minimize_true`](Solver::minimize_true) and
+    /// [`maximize_true`](Solver::maximize_true) search for an optimal model
+    /// (iterative tightening by default).
+    pub fn set_optimization(&mut self, optimization: Optimization) {
+        self.optimization = optimization;
+    }
+
+    /// Returns how optimal models are searched for.
+    pub fn optimization(&self) -> Optimization {
+        self.optimization
+    }
+}
+
+impl Solver {
+    /// Decides the query with a special-class algorithm, if enabled and
+    /// applicable.
+    fn solve_special(&self, assumptions: &[Lit]) -> Option<SolveResult> {
+        let steered = self.recording
+            || self.replay.is_some()
+            || self.position.is_some()
+            || !self.hints.order.is_empty()
+            || self.hints.phases.iter().any(Option::is_some);
+        if !self.config.special_classes || steered {
+            return None;
+        }
+        if assumptions.is_empty() {
+            return special::solve_special(&self.formula, self.num_vars);
+        }
+        let mut formula = self.formula.clone();
+        formula.extend(assumptions.iter().map(|&lit| vec![lit]));
+        special::solve_special(&formula, self.num_vars)
+    }
+}
+
+/// Sorts and deduplicates literals (assumptions or clauses), so that equal
+/// sets compare equal.
+fn normalize(lits: &[Lit]) -> Vec<Lit> {
+    let mut normalized = lits.to_vec();
+    normalized.sort();
+    normalized.dedup();
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll::{Heuristic, Polarity};
+
+    fn lit(var: usize, neg: bool) -> Lit {
+        Lit::new(Var::new(var), neg)
+    }
+
+    #[test]
+    fn phases_select_the_model() {
+        // (x0 ∨ x1 ∨ x2)
+        let mut solver = Solver::from_formula(vec![vec![lit(0, false), lit(1, false), lit(2, false)]], 3);
+        solver.set_phase(Var::new(0), false);
+        solver.set_phase(Var::new(1), false);
+        assert_eq!(solver.phase(Var::new(1)), Some(false));
+        match solver.solve() {
+            SolveResult::Sat(model) => {
+                assert_eq!(model.value(Var::new(0)), Val::False);
+                assert_eq!(model.value(Var::new(1)), Val::False);
+                assert_eq!(model.value(Var::new(2)), Val::True);
+            }
+            SolveResult::Unsat => panic!("Expected SAT"),
+        }
+
+        solver.clear_phase(Var::new(0));
+        assert_eq!(solver.phase(Var::new(0)), None);
+        solver.set_caching(false);
+        match solver.solve() {
+            SolveResult::Sat(model) => assert_eq!(model.value(Var::new(0)), Val::True),
+            SolveResult::Unsat => panic!("Expected SAT"),
+        }
+    }
+
+    #[test]
+    fn polarity_applies_without_hints() {
+        // (¬x0 ∨ ¬x1 ∨ x2) ∧ (¬x0 ∨ x1 ∨ x3) ∧ (x0 ∨ x2 ∨ x3)
+        let formula = vec![
+            vec![lit(0, true), lit(1, true), lit(2, false)],
+            vec![lit(0, true), lit(1, false), lit(3, false)],
+            vec![lit(0, false), lit(2, false), lit(3, false)],
+        ];
+        let first_decision = |polarity, hint: Option<bool>| {
+            let mut solver = Solver::from_formula(formula.clone(), 4);
+            solver.set_config(SolverConfig { polarity, special_classes: false, ..SolverConfig::default() });
+            if let Some(value) = hint {
+                solver.set_phase(Var::new(0), value);
+            }
+            let SolveResult::Sat(model) = solver.solve() else { panic!("expected SAT") };
+            model.value(Var::new(0))
+        };
+        assert_eq!(first_decision(Polarity::True, None), Val::True);
+        assert_eq!(first_decision(Polarity::False, None), Val::False);
+        assert_eq!(first_decision(Polarity::Occurrence, None), Val::False);
+        assert_eq!(first_decision(Polarity::JeroslowWang, None), Val::False);
+        // Hints take precedence
+        assert_eq!(first_decision(Polarity::False, Some(true)), Val::True);
+    }
+
+    #[test]
+    fn decision_order_combines_with_phases() {
+        // x0 → x1 → x2 (as clauses), and x3 free
+        let formula = vec![vec![lit(0, true), lit(1, false)], vec![lit(1, true), lit(2, false)]];
+        let mut solver = Solver::from_formula(formula, 4);
+        solver.set_decision_order(vec![Var::new(9), Var::new(2), Var::new(3)]); // 9 is out of range and ignored
+        solver.set_phase(Var::new(2), false);
+        solver.set_phase(Var::new(3), false);
+        match solver.solve() {
+            SolveResult::Sat(model) => {
+                // ¬x2 propagates ¬x1 and ¬x0 before the heuristic is consulted
+                assert_eq!(model.value(Var::new(0)), Val::False);
+                assert_eq!(model.value(Var::new(1)), Val::False);
+                assert_eq!(model.value(Var::new(3)), Val::Undef); // formula already satisfied
+            }
+            SolveResult::Unsat => panic!("Expected SAT"),
+        }
+        assert_eq!(solver.decision_order(), &[Var::new(9), Var::new(2), Var::new(3)]);
+    }
+
+    #[test]
+    fn cache_is_keyed_by_formula() {
+        let mut solver = Solver::from_formula(vec![vec![lit(0, false), lit(1, false)]], 2);
+        let first = solver.solve();
+        assert_eq!(solver.solve(), first);
+        assert_eq!(solver.cache_hits(), 1);
+
+        // A new clause changes the fingerprint: no stale answer
+        solver.add_clause(vec![lit(0, true)]);
+        solver.add_clause(vec![lit(1, true)]);
+        assert_eq!(solver.solve(), SolveResult::Unsat);
+        assert_eq!(solver.cache_hits(), 1);
+        // A new variable changes the query as well
+        solver.new_var();
+        assert_eq!(solver.solve(), SolveResult::Unsat);
+        assert_eq!(solver.cache_hits(), 1);
+    }
+
+    #[test]
+    fn cache_follows_hints_and_is_bounded() {
+        let mut solver = Solver::from_formula(vec![vec![lit(0, false), lit(1, false)]], 2);
+        solver.set_phase(Var::new(0), true);
+        solver.solve();
+        // A new hint must not return the model found under the old one
+        solver.set_phase(Var::new(0), false);
+        let SolveResult::Sat(model) = solver.solve() else { panic!("Expected SAT") };
+        assert_eq!(model.value(Var::new(0)), Val::False);
+        assert_eq!(solver.cache_hits(), 0);
+
+        // Each new variable makes a new query
+        for _ in 0..2 * CACHE_CAPACITY {
+            solver.new_var();
+            solver.solve();
+        }
+        assert!(solver.cache.len() <= CACHE_CAPACITY);
+    }
+
+    #[test]
+    fn record_and_replay() {
+        // A random 3-SAT instance with plenty of branching points
+        let (formula, num_vars) = crate::gen::random_ksat(12, 50, 3, 3);
+        let mut solver = Solver::from_formula(formula.clone(), num_vars);
+        solver.set_recording(true);
+        let original = solver.solve();
+        let decisions = solver.decisions().to_vec();
+        assert!(!decisions.is_empty());
+
+        // A differently configured solver follows the recording exactly
+        let mut replayer = Solver::from_formula(formula, num_vars);
+        replayer.set_decision_order(Var::range(num_vars).rev().collect());
+        replayer.set_recording(true);
+        replayer.set_replay(decisions.clone());
+        assert_eq!(replayer.solve(), original);
+        assert_eq!(replayer.decisions(), &decisions[..]);
+        assert!(!replayer.replay_diverged());
+
+        // An impossible recording is detected
+        replayer.add_clause(vec![decisions[0]]);
+        replayer.set_replay(vec![decisions[0]]);
+        replayer.solve();
+        assert!(replayer.replay_diverged());
+    }
+
+    #[test]
+    fn solver_moves_between_threads() {
+        let (formula, num_vars) = crate::gen::random_ksat(10, 30, 3, 1);
+        let solver = Solver::from_formula(formula, num_vars);
+
+        // Share read-only views, then move the solver into a worker
+        let sizes: Vec<usize> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2).map(|_| scope.spawn(|| solver.formula().len())).collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(sizes, vec![30, 30]);
+
+        let expected = solver.clone().solve();
+        let worker = std::thread::spawn(move || {
+            let mut solver = solver;
+            solver.solve()
+        });
+        assert_eq!(worker.join().unwrap(), expected);
+    }
+
+    #[test]
+    fn clauses_allocate_variables() {
+        let mut solver = Solver::new();
+        solver.add_clause(vec![lit(4, false)]);
+        assert_eq!(solver.num_vars(), 5);
+        assert_eq!(solver.new_var(), Var::new(5));
+        solver.add_clause(vec![lit(4, true)]);
+        assert_eq!(solver.solve(), SolveResult::Unsat);
+    }
+
+    #[test]
+    fn interrupt_from_another_thread() {
+        // Far too hard for plain DPLL to finish during the test
+        let (formula, num_vars, _) = crate::gen::families::pigeonhole(12);
+        let mut solver = Solver::from_formula(formula, num_vars);
+        let flag = solver.interrupt_flag();
+        let stopper = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            flag.store(true, Ordering::Relaxed);
+        });
+        assert_eq!(solver.solve_limited(&[]), None);
+        stopper.join().unwrap();
+
+        // The work of the interrupted search is still reported
+        let stats = solver.stats();
+        assert_eq!(stats.searches, 1);
+        assert!(stats.decisions > 0 && stats.conflicts > 0);
+        assert!(!solver.interrupt_flag().load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn load_time_units_keep_answers() {
+        for seed in 0..30 {
+            let (mut formula, num_vars) = crate::gen::random_ksat(8, 24, 3, seed);
+            formula.insert(3, vec![lit(seed as usize % 8, seed % 2 == 0)]);
+            let mut solver = Solver::from_formula(formula.clone(), num_vars);
+            // A clause over fixed variables added later is simplified too
+            solver.add_clause(vec![lit(seed as usize % 8, true), lit(7, false)]);
+            formula.push(vec![lit(seed as usize % 8, true), lit(7, false)]);
+            assert!(solver.formula().len() <= formula.len());
+            for assumptions in [vec![], vec![lit(1, false)], vec![lit(2, true), lit(5, false)]] {
+                let mut assumed = formula.clone();
+                assumed.extend(assumptions.iter().map(|&l| vec![l]));
+                let result = solver.solve_with_assumptions(&assumptions);
+                let expected = crate::solver::brute::solve(&assumed, num_vars);
+                assert_eq!(matches!(result, SolveResult::Sat(_)), matches!(expected, SolveResult::Sat(_)), "seed {}", seed);
+                if let SolveResult::Sat(model) = result {
+                    assert!(assumed.iter().all(|c| c.iter().any(|&l| model.is_true(l))), "seed {}", seed);
+                }
+            }
+        }
+
+        // Contradicting units leave the empty clause before any search
+        let solver = Solver::from_formula(vec![vec![lit(0, false)], vec![lit(0, true), lit(1, false)], vec![lit(1, true)]], 2);
+        assert_eq!(solver.formula(), &vec![Vec::<Lit>::new()]);
+    }
+
+    #[test]
+    fn removal_undoes_derived_simplifications() {
+        let mut solver = Solver::new();
+        let unit = solver.add_clause(vec![lit(0, false)]);
+        let implication = solver.add_clause(vec![lit(0, true), lit(1, false)]);
+        solver.add_clause(vec![lit(1, true), lit(2, false)]);
+        solver.add_clause(vec![lit(3, false), lit(2, false)]);
+        // The unit simplified x1 and x2 to units as well
+        assert_eq!(solver.solve_with_assumptions(&[lit(2, true)]), SolveResult::Unsat);
+        assert!(solver.remove_clause(unit));
+        assert!(matches!(solver.solve_with_assumptions(&[lit(2, true)]), SolveResult::Sat(_)));
+        assert!(solver.remove_clause(implication));
+        assert!(!solver.remove_clause(implication));
+        let remaining: Vec<ClauseHandle> = solver.added_clauses().map(|(handle, _)| handle).collect();
+        assert_eq!(remaining.iter().map(|handle| handle.index()).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(solver.formula().len(), 2);
+    }
+
+    #[test]
+    fn cached_answers_do_no_work() {
+        // Not a special class as long as the unit stays, so the search runs
+        let mut solver = Solver::new();
+        solver.set_load_simplification(false);
+        solver.add_clause(vec![lit(0, false), lit(1, false), lit(2, false)]);
+        solver.add_clause(vec![lit(0, true)]);
+        solver.solve();
+        let stats = solver.stats();
+        assert_eq!(stats.searches, 1);
+        assert_eq!((stats.propagations, stats.decisions), (1, 1));
+        solver.solve();
+        assert_eq!(solver.stats(), stats);
+    }
+
+    #[test]
+    fn interrupted_searches_continue() {
+        let (formula, num_vars, _) = crate::gen::families::pigeonhole(6);
+        let expected = Solver::from_formula(formula.clone(), num_vars).solve();
+        let mut solver = Solver::from_formula(formula, num_vars);
+
+        // Interrupt repeatedly, passing the position on through a file
+        let mut interruptions = 0;
+        let result = loop {
+            let flag = solver.interrupt_flag();
+            let stopper = std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(2));
+                flag.store(true, Ordering::Relaxed);
+            });
+            let result = solver.solve_limited(&[]);
+            stopper.join().unwrap();
+            if let Some(result) = result {
+                break result;
+            }
+            interruptions += 1;
+            let mut file = Vec::new();
+            crate::solver::checkpoint::write_checkpoint(&mut file, &solver.checkpoint()).unwrap();
+            let checkpoint = crate::solver::checkpoint::read_checkpoint(file.as_slice()).unwrap();
+            solver = Solver::from_formula(solver.formula().clone(), num_vars);
+            solver.resume(&checkpoint).unwrap();
+        };
+        assert_eq!(result, expected);
+        assert_eq!(solver.stats().searches, interruptions + 1);
+    }
+
+    #[test]
+    fn checkpoints_keep_the_heuristic_state() {
+        use crate::solver::checkpoint::{read_checkpoint, write_checkpoint};
+
+        let (formula, num_vars, _) = crate::gen::families::pigeonhole(6);
+        let config = SolverConfig { heuristic: Heuristic::Chb, restart_interval: Some(4), ..SolverConfig::default() };
+        let mut solver = Solver::from_formula(formula.clone(), num_vars);
+        solver.set_config(config.clone());
+        solver.set_propagation_budget(Some(2000));
+
+        // The scores and counters carry on through every resumption
+        let mut last = HeuristicState::default();
+        while solver.solve_limited(&[]).is_none() {
+            let mut file = Vec::new();
+            write_checkpoint(&mut file, &solver.checkpoint()).unwrap();
+            let checkpoint = read_checkpoint(file.as_slice()).unwrap();
+            assert_eq!(checkpoint, solver.checkpoint());
+            let state = &checkpoint.heuristic;
+            assert!(state.conflicts > last.conflicts && state.restarts >= last.restarts);
+            assert!(state.activity.iter().any(|&activity| activity > 0.0));
+            last = state.clone();
+
+            let mut other = Solver::from_formula(formula.clone(), num_vars);
+            assert!(other.resume(&checkpoint).is_err());
+            solver = Solver::from_formula(formula.clone(), num_vars);
+            solver.set_config(config.clone());
+            solver.set_propagation_budget(Some(2000));
+            solver.resume(&checkpoint).unwrap();
+        }
+        assert!(last.restarts > 0);
+    }
+
+    #[test]
+    fn progress_never_decreases() {
+        let (formula, num_vars, _) = crate::gen::families::pigeonhole(6);
+        let mut solver = Solver::from_formula(formula, num_vars);
+        solver.set_propagation_budget(Some(500));
+
+        let mut last = 0.0;
+        while solver.solve_limited(&[]).is_none() {
+            assert!((last..1.0).contains(&solver.progress()));
+            last = solver.progress();
+        }
+        assert!(last > 0.5);
+        assert_eq!(solver.progress(), 1.0);
+    }
+
+    #[test]
+    fn propagation_engines_agree() {
+        for seed in 0..40 {
+            let (formula, num_vars) = crate::gen::random_ksat(12, 52, 3, seed);
+            let mut scan = Solver::from_formula(formula.clone(), num_vars);
+            let mut counters = Solver::from_formula(formula, num_vars);
+            counters.set_propagation(Propagation::Counters);
+            match (scan.solve(), counters.solve()) {
+                (SolveResult::Sat(a), SolveResult::Sat(b)) => assert!(a.diff(&b).is_empty(), "seed {}", seed),
+                (a, b) => assert_eq!(a, b, "seed {}", seed),
+            }
+            assert_eq!(scan.stats().decisions, counters.stats().decisions, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn budgets_interleave_solvers() {
+        let (php, php_vars, _) = crate::gen::families::pigeonhole(4);
+        let (random, random_vars) = crate::gen::random_ksat(20, 85, 3, 3);
+        let mut solvers = [Solver::from_formula(php, php_vars), Solver::from_formula(random, random_vars)];
+        let expected: Vec<SolveResult> = solvers.iter().map(|s| s.clone().solve()).collect();
+        for solver in &mut solvers {
+            solver.set_propagation_budget(Some(1));
+        }
+
+        // Round-robin with the smallest budget still finishes both
+        let mut results = [None, None];
+        while results.iter().any(Option::is_none) {
+            for (solver, result) in solvers.iter_mut().zip(&mut results) {
+                if result.is_none() {
+                    *result = solver.solve_limited(&[]);
+                    assert!(result.is_some() || solver.last_stats().decisions > 0);
+                }
+            }
+        }
+        let results: Vec<SolveResult> = results.into_iter().flatten().collect();
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn configurations_agree_on_answers() {
+        let configs = [
+            SolverConfig { var_decay: Some(0.9), ..SolverConfig::default() },
+            SolverConfig { random_decision_freq: 0.5, seed: 3, ..SolverConfig::default() },
+            SolverConfig { heuristic: Heuristic::Lrb, polarity: Polarity::JeroslowWang, ..SolverConfig::default() },
+            SolverConfig {
+                propagation: Propagation::Counters,
+                heuristic: Heuristic::Chb,
+                restart_interval: Some(2),
+                ..SolverConfig::default()
+            },
+            SolverConfig {
+                propagation: Propagation::Counters,
+                heuristic: Heuristic::Activity,
+                polarity: Polarity::False,
+                restart_interval: Some(1),
+                var_decay: Some(1.0),
+                random_decision_freq: 1.0,
+                seed: 9,
+                special_classes: false,
+                deterministic: true,
+                pure_literals: true,
+            },
+            SolverConfig { pure_literals: true, var_decay: Some(0.9), ..SolverConfig::default() },
+        ];
+        for seed in 0..20 {
+            let (formula, num_vars) = crate::gen::random_ksat(12, 52, 3, seed);
+            let expected = matches!(Solver::from_formula(formula.clone(), num_vars).solve(), SolveResult::Sat(_));
+            for config in &configs {
+                let mut solver = Solver::from_formula(formula.clone(), num_vars);
+                solver.set_config(config.clone());
+                match solver.solve() {
+                    SolveResult::Sat(model) => {
+                        assert!(expected, "seed {}", seed);
+                        assert!(formula.iter().all(|c| c.iter().any(|&l| model.is_true(l))), "seed {}", seed);
+                    }
+                    SolveResult::Unsat => assert!(!expected, "seed {}", seed),
+                }
+                // Equal seeds give equal searches
+                let mut again = Solver::from_formula(formula.clone(), num_vars);
+                again.set_config(config.clone());
+                again.solve();
+                assert_eq!(again.stats(), solver.stats());
+                if config.deterministic {
+                    assert_eq!((solver.stats().clause_bytes, solver.stats().search_bytes), (0, 0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reused_buffers_do_not_leak_between_queries() {
+        let (formula, num_vars) = crate::gen::random_ksat(14, 60, 3, 5);
+        let mut solver = Solver::from_formula(formula, num_vars);
+        solver.set_caching(false);
+        solver.set_config(SolverConfig {
+            propagation: Propagation::Counters,
+            heuristic: Heuristic::Lrb,
+            special_classes: false,
+            // Clones hold tighter allocations, so byte counts would differ
+            deterministic: true,
+            ..SolverConfig::default()
+        });
+        for query in 0..20 {
+            let assumptions = [lit(query % 14, query % 3 == 0), lit((query * 5 + 1) % 14, query % 2 == 0)];
+            // A clone starts with empty buffers
+            let mut fresh = solver.clone();
+            let expected = fresh.solve_with_assumptions(&assumptions);
+            assert_eq!(solver.solve_with_assumptions(&assumptions), expected, "query {}", query);
+            assert_eq!(solver.last_stats(), fresh.last_stats(), "query {}", query);
+        }
+    }
+
+    #[test]
+    fn special_classes_take_assumptions() {
+        // x0 → x1 → x2 is 2-SAT, also with assumptions
+        let mut solver = Solver::from_formula(vec![vec![lit(0, true), lit(1, false)], vec![lit(1, true), lit(2, false)]], 3);
+        assert_eq!(solver.solve_with_assumptions(&[lit(0, false), lit(2, true)]), SolveResult::Unsat);
+        let SolveResult::Sat(model) = solver.solve_with_assumptions(&[lit(0, false)]) else { panic!("expected SAT") };
+        assert_eq!(model.value(Var::new(2)), Val::True);
+        assert_eq!(solver.stats().decisions, 0);
+
+        solver.set_config(SolverConfig { special_classes: false, ..SolverConfig::default() });
+        assert!(matches!(solver.solve_with_assumptions(&[lit(2, true)]), SolveResult::Sat(_)));
+        assert_eq!(solver.last_stats().propagations, 2);
+    }
+
+    #[test]
+    fn elimination_keeps_frozen_variables_and_completes_models() {
+        use crate::testing::{random_formula, verify_model, FormulaConfig};
+
+        let config = FormulaConfig { num_vars: 8, num_clauses: 20, max_clause_len: 3 };
+        for seed in 0..40 {
+            let formula = random_formula(&config, seed);
+            let mut solver = Solver::from_formula(formula.clone(), 8);
+            solver.freeze(Var::new(0));
+            solver.freeze(Var::new(1));
+            solver.eliminate(1000);
+            assert!(!solver.is_eliminated(Var::new(0)) && !solver.is_eliminated(Var::new(1)));
+            for assumptions in [vec![], vec![lit(0, false)], vec![lit(0, true), lit(1, false)]] {
+                let mut assumed = formula.clone();
+                assumed.extend(assumptions.iter().map(|&l| vec![l]));
+                match solver.solve_with_assumptions(&assumptions) {
+                    SolveResult::Sat(model) => assert!(verify_model(&assumed, &model), "seed {}", seed),
+                    SolveResult::Unsat => assert_eq!(crate::solver::brute::solve(&assumed, 8), SolveResult::Unsat, "seed {}", seed),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rejected_clauses_leave_the_solver_unchanged() {
+        // x0 ↔ x1, with x0 eliminated
+        let mut solver = Solver::from_formula(vec![vec![lit(0, true), lit(1, false)], vec![lit(0, false), lit(1, true)]], 2);
+        solver.freeze(Var::new(1));
+        solver.eliminate(1000);
+        assert!(solver.is_eliminated(Var::new(0)));
+
+        let clause = vec![lit(0, false), lit(5, false)];
+        assert_eq!(solver.try_add_clause(clause), Err(ClauseError::EliminatedVariable { var: Var::new(0) }));
+        assert_eq!(solver.num_vars(), 2);
+    }
+}
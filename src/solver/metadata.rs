@@ -0,0 +1,157 @@
+//! User data attached to clauses
+//!
+//! Applications that generate clauses usually know where each one came
+//! from: an input line, a constraint name, a rule of the encoding. Any such
+//! value can be attached to a clause through its [`ClauseHandle`] with
+//! [`Solver::set_clause_data`], and read back with
+//! [`Solver::clause_data`] by its type. The reports of this module name
+//! clauses by handle, and [`Solver::data_of`] turns a report into the
+//! attached data:
+//!
+//! - [`Solver::mus`]: a minimal unsatisfiable subset of the clauses, for
+//!   explaining why the formula has no model;
+//! - [`Solver::violated_clauses`]: the clauses an assignment does not
+//!   satisfy, for finding out why an expected model is rejected;
+//! - [`Solver::witnesses`]: the literal satisfying each clause, for finding
+//!   out why a clause expected to be violated holds.
+//!
+//! All refer to the clauses as added, before any simplification, and the
+//! data of a clause is dropped when the clause is removed.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::solver::Solver;
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! let mut solver = Solver::new();
+//! for (clause, line) in [(vec![x(0), x(1)], 1), (vec![!x(0)], 2), (vec![x(2)], 3), (vec![!x(1)], 4)] {
+//!     let handle = solver.add_clause(clause);
+//!     solver.set_clause_data(handle, format!("input.cnf:{}", line));
+//! }
+//! let mus = solver.mus().unwrap();
+//! let lines: Vec<Option<&String>> = solver.data_of(&mus);
+//! assert_eq!(lines, [Some(&"input.cnf:1".to_string()), Some(&"input.cnf:2".to_string()), Some(&"input.cnf:4".to_string())]);
+//! ```
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use super::instance::ClauseHandle;
+use super::Solver;
+use crate::mus::checker::Checker;
+use crate::types::*;
+
+/// The data attached to the clauses of a solver.
+#[derive(Clone, Default)]
+pub(crate) struct ClauseMetadata {
+    data: HashMap<ClauseHandle, Arc<dyn Any + Send + Sync>>,
+}
+
+impl ClauseMetadata {
+    /// Drops the data of `handle`.
+    pub fn remove(&mut self, handle: ClauseHandle) {
+        self.data.remove(&handle);
+    }
+}
+
+/// Lists the clauses with data; the values themselves are opaque.
+impl fmt::Debug for ClauseMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut handles: Vec<_> = self.data.keys().collect();
+        handles.sort();
+        f.debug_struct("ClauseMetadata").field("clauses", &handles).finish()
+    }
+}
+
+impl Solver {
+    /// Attaches `data` to the clause of `handle`, replacing any data it
+    /// had. Copies of the solver share the value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the clause was removed or `handle` belongs to another
+    /// solver.
+    pub fn set_clause_data<T: Any + Send + Sync>(&mut self, handle: ClauseHandle, data: T) {
+        if !self.added_clauses().any(|(added, _)| added == handle) {
+            panic!("no clause with handle {}", handle.index());
+        }
+        self.metadata_mut().data.insert(handle, Arc::new(data));
+    }
+
+    /// Returns the data attached to the clause of `handle`, if it has data
+    /// of type `T`.
+    pub fn clause_data<T: Any>(&self, handle: ClauseHandle) -> Option<&T> {
+        self.metadata().data.get(&handle)?.downcast_ref()
+    }
+
+    /// Returns the data of type `T` of each clause of `handles`, in order.
+    pub fn data_of<T: Any>(&self, handles: &[ClauseHandle]) -> Vec<Option<&T>> {
+        handles.iter().map(|&handle| self.clause_data(handle)).collect()
+    }
+
+    /// Finds a minimal unsatisfiable subset of the clauses as added.
+    ///
+    /// # Returns
+    ///
+    /// The handles of the subset in increasing order, or `None` if the
+    /// clauses are satisfiable.
+    pub fn mus(&self) -> Option<Vec<ClauseHandle>> {
+        let (handles, clauses): (Vec<ClauseHandle>, Formula) =
+            self.added_clauses().map(|(handle, clause)| (handle, clause.clone())).unzip();
+        let all: Vec<usize> = (0..clauses.len()).collect();
+        match Checker::new(&clauses, self.num_vars()).check_or_shrink(&all) {
+            Ok(_) => None,
+            Err(mus) => Some(mus.into_iter().map(|index| handles[index]).collect()),
+        }
+    }
+
+    /// Returns the handles of the clauses as added that `model` does not
+    /// satisfy, in increasing order. A clause whose literals are partly
+    /// unassigned and otherwise false counts as not satisfied.
+    pub fn violated_clauses(&self, model: &Model) -> Vec<ClauseHandle> {
+        let satisfied = |clause: &Clause| clause.iter().any(|&lit| lit.var.index() < model.num_vars() && model.is_true(lit));
+        self.added_clauses().filter(|(_, clause)| !satisfied(clause)).map(|(handle, _)| handle).collect()
+    }
+
+    /// Returns the handle of each clause as added, in increasing order,
+    /// together with a literal of the clause that `model` makes true, or
+    /// `None` if the clause is violated; see
+    /// [`witnesses`](crate::testing::witnesses).
+    pub fn witnesses(&self, model: &Model) -> Vec<(ClauseHandle, Option<Lit>)> {
+        let is_true = |lit: &Lit| lit.var.index() < model.num_vars() && model.is_true(*lit);
+        self.added_clauses().map(|(handle, clause)| (handle, clause.iter().copied().find(is_true))).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_follows_the_clauses() {
+        let x = |var| Lit::positive(Var::new(var));
+        let mut solver = Solver::new();
+        let first = solver.add_clause(vec![x(0), x(1)]);
+        let second = solver.add_clause(vec![!x(1)]);
+        solver.set_clause_data(first, 7usize);
+        solver.set_clause_data(second, "no x1");
+        assert_eq!(solver.clause_data::<usize>(first), Some(&7));
+        assert_eq!(solver.clause_data::<String>(second), None);
+
+        let mut model = Model::new(2);
+        model.assign(Var::new(1), Val::True);
+        let violated = solver.violated_clauses(&model);
+        assert_eq!(solver.data_of::<&str>(&violated), [Some(&"no x1")]);
+        assert_eq!(solver.witnesses(&model), [(first, Some(x(1))), (second, None)]);
+
+        let copy = solver.clone();
+        solver.remove_clause(second);
+        assert_eq!(solver.clause_data::<&str>(second), None);
+        assert_eq!(copy.clause_data::<&str>(second), Some(&"no x1"));
+        assert_eq!(solver.mus(), None);
+    }
+}
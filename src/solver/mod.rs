@@ -4,10 +4,68 @@
 //! The implementation is based on the DPLL (Davis-Putnam-Logemann-Loveland) algorithm
 //! with unit propagation.
 //!
+//! The search backtracks chronologically and learns no clauses, so there is
+//! no learned clause database to inspect: no LBD, activity or age metadata
+//! and no deletion policy to customize. The closest equivalents are the
+//! clauses derived by [`refute`](crate::proof::refute), which form an
+//! explicit resolution proof, and the [`graph`] of a model's implications,
+//! from which conflict clauses can be computed outside the solver.
+//!
 //! # Modules
 //!
 //! - [`unit`]: Unit propagation implementation for constraint propagation
+//! - [`counters`]: Counter-based unit propagation, an alternative engine
+//! - [`satisfied`]: Incremental tracking of the clauses a model satisfies
+//! - [`heap`]: Indexed heap of the unassigned variables by activity
 //! - [`dpll`]: Main DPLL algorithm with systematic search and backtracking
+//! - [`graph`]: Read-only implication graphs of models
+//! - [`brute`]: Exhaustive brute-force solver used as a correctness oracle
+//! - [`walksat`]: Incomplete local search for satisfiable formulas
+//! - [`engine`]: The [`SatEngine`](engine::SatEngine) interface over all engines
+//! - [`special`]: Polynomial algorithms for 2-SAT, Horn and XOR formulas
+//! - [`instance`]: The stateful [`Solver`] front end with per-variable configuration
+//! - [`failed`]: Failed assumptions of unsatisfiable queries and their minimization
+//! - [`propagate`]: Unit propagation under assumptions as a query of its own
+//! - [`metadata`]: User data attached to clauses, and reports naming clauses
+//! - [`optimize`]: Models with the fewest or most true variables
+//! - [`bnb`]: Branch and bound over a linear objective within one search
+//! - [`enumerate`]: Enumeration of all projected models on several threads
+//! - [`count`]: Exact and approximate model counting
+//! - [`backbone`]: The literals true in every model
+//! - [`replay`]: Reading and writing recorded decision logs
+//! - [`events`]: Timestamped logs of searches, restarts and simplifications
+//! - [`background`]: Futures for solving on a background thread
+//! - [`stats`]: Counters describing the work of the search
+//! - [`profile`]: Time spent in each phase (with the `profiling` feature)
+//! - [`checkpoint`]: Saving and resuming the position of interrupted searches
+//! - [`config`]: Tunable search parameters ([`SolverConfig`])
 
 pub(crate) mod unit;
+pub(crate) mod counters;
+pub(crate) mod satisfied;
+pub(crate) mod heap;
 pub mod dpll;
+pub mod graph;
+pub mod brute;
+pub mod walksat;
+pub mod engine;
+pub mod special;
+pub mod instance;
+pub mod failed;
+pub mod propagate;
+pub mod metadata;
+pub mod optimize;
+pub mod bnb;
+pub mod enumerate;
+pub mod count;
+pub mod backbone;
+pub mod replay;
+pub mod events;
+pub mod background;
+pub mod stats;
+pub mod profile;
+pub mod checkpoint;
+pub mod config;
+
+pub use config::SolverConfig;
+pub use instance::Solver;
@@ -0,0 +1,142 @@
+//! Models with the fewest or most true variables
+//!
+//! Encodings usually admit many models, and the simplest one, with as few
+//! (or as many) variables set as possible, is often the one wanted.
+//! [`Solver::minimize_true`] and [`Solver::maximize_true`] find such a
+//! model by iterative tightening: after each model, a
+//! [totalizer](crate::encode::cardinality) over the selected variables
+//! bounds the count below the model's, and the search runs again until the
+//! bound cannot be met. Maximizing the true variables minimizes the false
+//! ones.
+//!
+//! The totalizer is added to the solver once, on fresh variables, and the
+//! bounds are assumptions, so the solver's formula keeps its models: later
+//! queries are unaffected apart from the extra variables.
+//!
+//! Alternatively, [`Optimization::BranchAndBound`] finds the optimum in a
+//! single search that prunes with the best count so far (see
+//! [`bnb`](super::bnb)), adding nothing to the formula. Tightening profits
+//! from the propagation through the totalizer; branch and bound avoids
+//! repeating the search from scratch for every improvement.
+
+use super::bnb::Objective;
+use super::dpll::SolveResult;
+use super::Solver;
+use crate::encode::cardinality::totalizer;
+use crate::types::*;
+
+/// Method of searching for an optimal model, see the
+/// [module documentation](self).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Optimization {
+    /// Solve again under ever stronger totalizer bounds
+    #[default]
+    Tightening,
+    /// Prune a single search with the best model so far
+    BranchAndBound,
+}
+
+impl Solver {
+    /// Finds a model with the fewest true variables among `vars`.
+    ///
+    /// # Returns
+    ///
+    /// An optimal model, which assigns every variable of `vars` and may
+    /// also assign the totalizer's variables, or `None` if the formula is
+    /// unsatisfiable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::Solver;
+    /// use putnam::types::{Lit, Val, Var};
+    ///
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// // (x0 ∨ x1) ∧ (x1 ∨ x2) ∧ (x2 ∨ x0): any two variables, at least
+    /// let mut solver = Solver::from_formula(vec![vec![x(0), x(1)], vec![x(1), x(2)], vec![x(2), x(0)]], 3);
+    /// let vars = [Var::new(0), Var::new(1), Var::new(2)];
+    /// let model = solver.minimize_true(&vars).unwrap();
+    /// assert_eq!(vars.iter().filter(|&&var| model.value(var) == Val::True).count(), 2);
+    /// ```
+    pub fn minimize_true(&mut self, vars: &[Var]) -> Option<Model> {
+        let lits: Vec<Lit> = vars.iter().map(|&var| Lit::positive(var)).collect();
+        self.minimize_count(&lits)
+    }
+
+    /// Finds a model with the most true variables among `vars`.
+    ///
+    /// # Returns
+    ///
+    /// An optimal model, as for [`minimize_true`](Solver::minimize_true),
+    /// or `None` if the formula is unsatisfiable.
+    pub fn maximize_true(&mut self, vars: &[Var]) -> Option<Model> {
+        let lits: Vec<Lit> = vars.iter().map(|&var| Lit::negative(var)).collect();
+        self.minimize_count(&lits)
+    }
+
+    /// Finds a model with the fewest true literals among `lits`.
+    fn minimize_count(&mut self, lits: &[Lit]) -> Option<Model> {
+        if self.optimization() == Optimization::BranchAndBound {
+            let terms: Vec<(Lit, u64)> = lits.iter().map(|&lit| (lit, 1)).collect();
+            return self.run_objective(Objective::new(&terms)).map(|(model, _)| model);
+        }
+        // The search stops once every clause is satisfied, so the literals
+        // it left unassigned can all be made false
+        let cost = |model: &mut Model| {
+            for &lit in lits {
+                if model.value(lit.var) == Val::Undef {
+                    model.assign(lit.var, if lit.neg { Val::True } else { Val::False });
+                }
+            }
+            lits.iter().filter(|&&lit| model.is_true(lit)).count()
+        };
+        let SolveResult::Sat(mut best) = self.solve() else { return None };
+        let mut bound = cost(&mut best);
+        if bound == 0 {
+            return Some(best);
+        }
+        let (clauses, outputs, num_vars) = totalizer(lits, self.num_vars());
+        while self.num_vars() < num_vars {
+            self.new_var();
+        }
+        for clause in clauses {
+            self.add_clause(clause);
+        }
+        // Fewer than `bound` true literals: output `bound - 1` stays false
+        while bound > 0 {
+            match self.solve_with_assumptions(&[!outputs[bound - 1]]) {
+                SolveResult::Sat(mut model) => {
+                    bound = cost(&mut model);
+                    best = model;
+                }
+                SolveResult::Unsat => break,
+            }
+        }
+        Some(best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn optimum_matches_exhaustive_search() {
+        for seed in 0..10 {
+            let (formula, num_vars) = crate::gen::random_ksat(8, 14, 3, seed);
+            let vars: Vec<Var> = (0..num_vars).step_by(2).map(Var::new).collect();
+            let count = |model: &Model| vars.iter().filter(|&&var| model.value(var) == Val::True).count() as u32;
+            // Counts of true selected variables over all models
+            let counts: Vec<u32> = crate::solver::brute::models(&formula, num_vars).map(|model| count(&model)).collect();
+            for optimization in [Optimization::Tightening, Optimization::BranchAndBound] {
+                let mut solver = Solver::from_formula(formula.clone(), num_vars);
+                solver.set_optimization(optimization);
+                let fewest = solver.minimize_true(&vars);
+                assert_eq!(fewest.as_ref().map(count), counts.iter().copied().min(), "seed {}", seed);
+                // The solver is reusable, and the totalizer does not constrain it
+                let most = solver.maximize_true(&vars);
+                assert_eq!(most.as_ref().map(count), counts.iter().copied().max(), "seed {}", seed);
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Phase profiling
+//!
+//! With the `profiling` feature, the solver counts how often it enters each
+//! major phase of its work and how long it spends there, reported by
+//! [`Solver::profile`]. This shows where optimization pays off without an
+//! external profiler.
+//!
+//! Without the feature, the timing code compiles to nothing: [`Span`] is a
+//! zero-sized type whose methods are empty, and every [`Profile`] stays zero.
+//! [`ENABLED`] tells which build is running.
+//!
+//! ```text
+//! cargo run --release --features profiling --bin putnam hard.cnf -- --stats
+//! ```
+//!
+//! [`Solver::profile`]: super::Solver::profile
+
+use std::fmt;
+use std::ops::AddAssign;
+use std::time::Duration;
+#[cfg(feature = "profiling")]
+use std::time::Instant;
+
+/// Whether the crate was built with the `profiling` feature.
+pub const ENABLED: bool = cfg!(feature = "profiling");
+
+/// Visits of one phase and the time spent in it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PhaseProfile {
+    /// Number of times the phase was entered
+    pub visits: u64,
+    /// Total time spent in the phase
+    pub time: Duration,
+}
+
+/// Time spent in the phases of the solver.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    /// Unit propagation, with either engine
+    pub propagation: PhaseProfile,
+    /// Checking whether the assignment satisfies every clause
+    pub satisfaction: PhaseProfile,
+    /// Choosing decision literals
+    pub decision: PhaseProfile,
+    /// Bumping variable activities after conflicts
+    pub conflict: PhaseProfile,
+    /// Classifying formulas and running the special-class algorithms
+    pub special: PhaseProfile,
+    /// Simplifying the formula by distillation
+    pub simplification: PhaseProfile,
+}
+
+impl Profile {
+    /// Returns the profile with the visits kept and every time set to zero.
+    pub fn without_times(&self) -> Profile {
+        let clear = |phase: PhaseProfile| PhaseProfile { time: Duration::ZERO, ..phase };
+        Profile {
+            propagation: clear(self.propagation),
+            satisfaction: clear(self.satisfaction),
+            decision: clear(self.decision),
+            conflict: clear(self.conflict),
+            special: clear(self.special),
+            simplification: clear(self.simplification),
+        }
+    }
+
+    /// Returns the phases with their names.
+    fn phases(&self) -> [(&'static str, PhaseProfile); 6] {
+        [
+            ("propagation", self.propagation),
+            ("satisfaction", self.satisfaction),
+            ("decision", self.decision),
+            ("conflict", self.conflict),
+            ("special", self.special),
+            ("simplification", self.simplification),
+        ]
+    }
+}
+
+impl AddAssign for PhaseProfile {
+    fn add_assign(&mut self, other: PhaseProfile) {
+        self.visits += other.visits;
+        self.time += other.time;
+    }
+}
+
+impl AddAssign for Profile {
+    fn add_assign(&mut self, other: Profile) {
+        self.propagation += other.propagation;
+        self.satisfaction += other.satisfaction;
+        self.decision += other.decision;
+        self.conflict += other.conflict;
+        self.special += other.special;
+        self.simplification += other.simplification;
+    }
+}
+
+/// Prints one `phase: visits, seconds` line per phase.
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, phase)) in self.phases().iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}: {} visits, {:.6} s", name, phase.visits, phase.time.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+/// A running measurement of one phase visit.
+pub(crate) struct Span {
+    #[cfg(feature = "profiling")]
+    start: Instant,
+}
+
+impl Span {
+    /// Starts measuring.
+    #[inline(always)]
+    pub(crate) fn start() -> Span {
+        Span {
+            #[cfg(feature = "profiling")]
+            start: Instant::now(),
+        }
+    }
+
+    /// Adds the visit and its duration to `phase`.
+    #[inline(always)]
+    pub(crate) fn stop(self, _phase: &mut PhaseProfile) {
+        #[cfg(feature = "profiling")]
+        {
+            _phase.visits += 1;
+            _phase.time += self.start.elapsed();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_count_only_when_enabled() {
+        let mut profile = Profile::default();
+        for _ in 0..3 {
+            Span::start().stop(&mut profile.decision);
+        }
+        assert_eq!(profile.decision.visits, if ENABLED { 3 } else { 0 });
+        assert_eq!(profile.propagation, PhaseProfile::default());
+        assert_eq!(std::mem::size_of::<Span>() == 0, !ENABLED);
+    }
+}
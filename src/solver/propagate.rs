@@ -0,0 +1,137 @@
+//! Unit propagation as a query of its own
+//!
+//! [`Solver::propagate`] applies assumptions to the solver's formula and
+//! runs unit propagation to a fixpoint, without deciding anything. The
+//! answer lists every literal the assumptions imply by propagation, or, if
+//! propagation falsifies a clause, that clause together with the
+//! assumptions it was derived from. This is the primitive behind
+//! explanations ("why is this literal forced?") and the lookahead of tools
+//! built around the solver; it costs one propagation rather than a search.
+//!
+//! Propagation runs on the formula as stored, after load-time
+//! simplification, so the top-level units are among the implied literals
+//! and conflict clauses may be shortened versions of the clauses added.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::solver::propagate::PropagationResult;
+//! use putnam::solver::Solver;
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! // x0 → x1 → x2, and x1 excludes x3
+//! let mut solver = Solver::from_formula(vec![vec![!x(0), x(1)], vec![!x(1), x(2)], vec![!x(1), !x(3)]], 4);
+//! let PropagationResult::Implied(implied) = solver.propagate(&[x(0)]) else { unreachable!() };
+//! assert_eq!(implied, vec![x(1), x(2), !x(3)]);
+//!
+//! let PropagationResult::Conflict { clause, failed } = solver.propagate(&[x(2), x(3), x(0)]) else { unreachable!() };
+//! assert_eq!(clause, vec![!x(1), !x(3)]);
+//! assert_eq!(failed, vec![x(3), x(0)]);
+//! ```
+
+use std::collections::VecDeque;
+
+use super::unit::unit_propagate;
+use super::Solver;
+use crate::types::*;
+
+/// Answer of [`Solver::propagate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PropagationResult {
+    /// Propagation reached a fixpoint: the literals it made true beyond
+    /// the assumptions, in the order they were derived
+    Implied(Vec<Lit>),
+    /// Propagation falsified a clause
+    Conflict {
+        /// The falsified clause; empty if two assumptions contradict each
+        /// other or the formula contains the empty clause
+        clause: Clause,
+        /// The assumptions the conflict was derived from, in the order
+        /// they were given
+        failed: Vec<Lit>,
+    },
+}
+
+impl Solver {
+    /// Applies `assumptions` and runs unit propagation, without search.
+    ///
+    /// # Returns
+    ///
+    /// The implied literals, or the conflict and the assumptions behind
+    /// it, see the [module documentation](self).
+    ///
+    /// # Panics
+    ///
+    /// Panics if an assumption refers to a variable the solver does not have.
+    pub fn propagate(&mut self, assumptions: &[Lit]) -> PropagationResult {
+        self.check_assumptions(assumptions);
+        self.propagate_units();
+        let formula = self.formula();
+        if formula.iter().any(|clause| clause.is_empty()) {
+            return PropagationResult::Conflict { clause: Vec::new(), failed: Vec::new() };
+        }
+        let mut model = Model::new(self.num_vars());
+        for &lit in assumptions {
+            match model.lit_value(lit) {
+                Val::False => return PropagationResult::Conflict { clause: Vec::new(), failed: vec![!lit, lit] },
+                Val::True => {}
+                Val::Undef => model.assign(lit.var, if lit.neg { Val::False } else { Val::True }),
+            }
+        }
+        if unit_propagate(formula, &mut model, &mut VecDeque::new()).is_ok() {
+            let implied = (0..model.trail_len())
+                .map(|index| model.trail_var(index))
+                .filter(|&var| model.reason(var).is_some())
+                .map(|var| Lit::new(var, model.value(var) == Val::False))
+                .collect();
+            return PropagationResult::Implied(implied);
+        }
+        let clause = formula
+            .iter()
+            .find(|clause| clause.iter().all(|&lit| model.lit_value(lit) == Val::False))
+            .expect("a propagation conflict falsifies a clause")
+            .clone();
+        // Follow the reasons back to the assumptions
+        let mut reached = vec![false; self.num_vars()];
+        let mut pending: Vec<Var> = clause.iter().map(|lit| lit.var).collect();
+        while let Some(var) = pending.pop() {
+            if std::mem::replace(&mut reached[var.index()], true) {
+                continue;
+            }
+            if let Some(reason) = model.reason(var) {
+                pending.extend(formula[reason.index()].iter().map(|lit| lit.var).filter(|&other| other != var));
+            }
+        }
+        // Unmarking the variables keeps repeated assumptions out
+        let failed = assumptions.iter().copied().filter(|lit| std::mem::take(&mut reached[lit.var.index()])).collect();
+        PropagationResult::Conflict { clause, failed }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll::SolveResult;
+
+    #[test]
+    fn implied_literals_hold_in_every_model() {
+        for seed in 0..20 {
+            let (formula, num_vars) = crate::gen::random_ksat(8, 30, 3, seed);
+            let mut solver = Solver::from_formula(formula, num_vars);
+            let assumptions = [Lit::new(Var::new(seed as usize % 8), seed % 2 == 0)];
+            match solver.propagate(&assumptions) {
+                PropagationResult::Implied(implied) => {
+                    for lit in implied {
+                        assert_eq!(solver.solve_with_assumptions(&[assumptions[0], !lit]), SolveResult::Unsat, "seed {}", seed);
+                    }
+                }
+                PropagationResult::Conflict { clause, failed } => {
+                    assert!(failed.iter().all(|lit| assumptions.contains(lit)));
+                    assert!(clause.is_empty() || solver.formula().contains(&clause));
+                    assert_eq!(solver.solve_with_assumptions(&failed), SolveResult::Unsat, "seed {}", seed);
+                }
+            }
+        }
+    }
+}
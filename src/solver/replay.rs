@@ -0,0 +1,93 @@
+//! Decision log files
+//!
+//! A [`Solver`](super::Solver) can record the branching decisions of a
+//! search and replay them later (see [`Solver::set_recording`] and
+//! [`Solver::set_replay`]), which makes bug reports reproducible even when
+//! heuristics change. This module reads and writes such recordings.
+//!
+//! The format is line based: each decision is a DIMACS literal (1-based,
+//! negative when the variable was set to false first). Blank lines and
+//! lines starting with `c` are comments. The log contains decisions only:
+//! the Luby partial restarts of
+//! [`restart_interval`](super::SolverConfig::restart_interval) are off while
+//! a search is recorded or replayed, so the decisions follow the
+//! depth-first order and no restart points need to be stored.
+//!
+//! ```text
+//! c putnam decisions
+//! 3
+//! -1
+//! 7
+//! ```
+//!
+//! [`Solver::set_recording`]: super::Solver::set_recording
+//! [`Solver::set_replay`]: super::Solver::set_replay
+
+use std::io::{self, BufRead, Write};
+
+use crate::types::*;
+
+/// Writes a decision log.
+///
+/// # Examples
+///
+/// ```
+/// use putnam::solver::replay::write_decisions;
+/// use putnam::types::{Lit, Var};
+///
+/// let mut out = Vec::new();
+/// write_decisions(&mut out, &[Lit::positive(Var::new(2)), Lit::negative(Var::new(0))])?;
+/// assert_eq!(String::from_utf8(out).unwrap(), "c putnam decisions\n3\n-1\n");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn write_decisions<W: Write>(mut writer: W, decisions: &[Lit]) -> io::Result<()> {
+    writeln!(writer, "c putnam decisions")?;
+    for lit in decisions {
+        writeln!(writer, "{}", lit.to_dimacs())?;
+    }
+    Ok(())
+}
+
+/// Reads a decision log written by [`write_decisions`].
+///
+/// # Returns
+///
+/// * `Ok(Vec<Lit>)` - The decisions in order
+/// * `Err(io::Error)` - If reading fails, or with `ErrorKind::InvalidData`
+///   if a line is not a non-zero integer
+pub fn read_decisions<R: BufRead>(reader: R) -> io::Result<Vec<Lit>> {
+    let mut decisions = Vec::new();
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('c') {
+            continue;
+        }
+        match line.parse::<i64>().ok().and_then(Lit::from_dimacs) {
+            Some(lit) => decisions.push(lit),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: expected a non-zero literal, got '{}'", lineno + 1, line),
+                ))
+            }
+        }
+    }
+    Ok(decisions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let decisions = vec![Lit::negative(Var::new(4)), Lit::positive(Var::new(0))];
+        let mut out = Vec::new();
+        write_decisions(&mut out, &decisions).unwrap();
+        assert_eq!(read_decisions(out.as_slice()).unwrap(), decisions);
+
+        assert!(read_decisions("0\n".as_bytes()).is_err());
+        assert!(read_decisions("x\n".as_bytes()).is_err());
+    }
+}
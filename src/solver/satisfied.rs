@@ -0,0 +1,240 @@
+//! Incremental tracking of satisfied clauses
+//!
+//! The search asks two questions at every node: whether the model satisfies
+//! every clause, and which clauses are still open (not satisfied) to branch
+//! on. Rescanning the formula answers both in time proportional to its
+//! size; instead, each clause keeps a count of its true literals, updated
+//! through occurrence lists as the trail grows and rolled back to a mark
+//! when the search backtracks, like the [`counters`](super::counters) of
+//! the propagation engine.
+//!
+//! Assignments only ever satisfy more clauses along a branch, so the first
+//! open clause never moves backwards. The tracker remembers where it was
+//! found for each trail length, and the next node below starts looking
+//! there instead of at the first clause.
+//!
+//! For pure literal elimination, each literal can also keep a count of the
+//! open clauses it occurs in. A literal becomes pure when the count of its
+//! complement drops to zero; such literals are collected as the counts
+//! change, so finding them never scans the formula either.
+
+use crate::store::FormulaSource;
+use crate::types::*;
+
+/// Index of a literal in the occurrence lists.
+fn code(lit: Lit) -> usize {
+    2 * lit.var.index() + lit.neg as usize
+}
+
+/// Per-clause true literal counts maintained along the trail.
+#[derive(Default)]
+pub(crate) struct Satisfaction {
+    /// Clauses containing each literal (see [`code`])
+    occurs: Vec<Vec<usize>>,
+    /// Number of true literals of each clause
+    satisfied: Vec<u32>,
+    /// Number of clauses without a true literal
+    open: usize,
+    /// Literals made true so far, in trail order
+    applied: Vec<Lit>,
+    /// Trail lengths with the first clause that was open at that length,
+    /// increasing in both
+    cursors: Vec<(usize, usize)>,
+    /// Open clauses containing each literal, if pure literals are tracked
+    open_occurrences: Option<Vec<u32>>,
+    /// Literals that may have become pure since the last backtrack
+    pure: Vec<Lit>,
+}
+
+impl Satisfaction {
+    /// Rebuilds the counts for `formula` with `num_vars` variables and no
+    /// assignments, reusing the allocations, with per-literal counts if
+    /// `track_pure`.
+    pub fn reset<F: FormulaSource + ?Sized>(&mut self, formula: &F, num_vars: usize, track_pure: bool) {
+        for list in &mut self.occurs {
+            list.clear();
+        }
+        self.occurs.resize_with(2 * num_vars, Vec::new);
+        for (index, clause) in formula.clauses().enumerate() {
+            for &lit in clause {
+                self.occurs[code(lit)].push(index);
+            }
+        }
+        self.satisfied.clear();
+        self.satisfied.resize(formula.num_clauses(), 0);
+        self.open = formula.num_clauses();
+        self.applied.clear();
+        self.cursors.clear();
+        self.pure.clear();
+        self.open_occurrences = track_pure.then(|| {
+            let mut counts = self.open_occurrences.take().unwrap_or_default();
+            counts.clear();
+            counts.extend(self.occurs.iter().map(|list| list.len() as u32));
+            counts
+        });
+        if let Some(counts) = &self.open_occurrences {
+            let pure = (0..counts.len()).filter(|&code| counts[code] > 0 && counts[code ^ 1] == 0);
+            self.pure.extend(pure.map(|code| Lit::new(Var::new(code / 2), code % 2 == 1)));
+        }
+    }
+
+    /// Returns the number of trail entries reflected in the counts.
+    pub fn mark(&self) -> usize {
+        self.applied.len()
+    }
+
+    /// Rolls the counts back to an earlier [`mark`](Satisfaction::mark).
+    ///
+    /// The mark must have been taken with no pure literal left unassigned,
+    /// as the search does before every decision.
+    pub fn undo_to<F: FormulaSource + ?Sized>(&mut self, formula: &F, mark: usize) {
+        while self.applied.len() > mark {
+            let lit = self.applied.pop().expect("applied is longer than mark");
+            for &index in &self.occurs[code(lit)] {
+                self.satisfied[index] -= 1;
+                if self.satisfied[index] == 0 {
+                    self.open += 1;
+                    if let Some(counts) = &mut self.open_occurrences {
+                        for &lit in formula.clause(index) {
+                            counts[code(lit)] += 1;
+                        }
+                    }
+                }
+            }
+        }
+        self.pure.clear();
+        while self.cursors.last().is_some_and(|&(at, _)| at > mark) {
+            self.cursors.pop();
+        }
+    }
+
+    /// Brings the counts up to date with `model`, whose trail must extend
+    /// the trail they were last synchronized with.
+    pub fn sync<F: FormulaSource + ?Sized>(&mut self, formula: &F, model: &Model) {
+        while self.applied.len() < model.trail_len() {
+            let var = model.trail_var(self.applied.len());
+            let lit = Lit { var, neg: model.value(var) == Val::False };
+            self.applied.push(lit);
+            for &index in &self.occurs[code(lit)] {
+                if self.satisfied[index] == 0 {
+                    self.open -= 1;
+                    if let Some(counts) = &mut self.open_occurrences {
+                        for &other in formula.clause(index) {
+                            counts[code(other)] -= 1;
+                            if counts[code(other)] == 0 && counts[code(!other)] > 0 {
+                                self.pure.push(!other);
+                            }
+                        }
+                    }
+                }
+                self.satisfied[index] += 1;
+            }
+        }
+    }
+
+    /// Returns an unassigned literal that occurs in open clauses while its
+    /// complement does not, if pure literals are tracked and there is one.
+    pub fn next_pure(&mut self, model: &Model) -> Option<Lit> {
+        let counts = self.open_occurrences.as_ref()?;
+        while let Some(lit) = self.pure.pop() {
+            if model.value(lit.var) == Val::Undef && counts[code(lit)] > 0 && counts[code(!lit)] == 0 {
+                return Some(lit);
+            }
+        }
+        None
+    }
+
+    /// Returns `true` if every clause has a true literal.
+    pub fn is_satisfied(&self) -> bool {
+        self.open == 0
+    }
+
+    /// Returns `true` if `var` occurs in a clause without a true literal.
+    pub fn occurs_open(&self, var: Var) -> bool {
+        [Lit::positive(var), Lit::negative(var)]
+            .into_iter()
+            .any(|lit| self.occurs[code(lit)].iter().any(|&index| self.satisfied[index] == 0))
+    }
+
+    /// Iterates over the indices of the clauses without a true literal, in
+    /// formula order.
+    pub fn open_clauses(&mut self) -> impl Iterator<Item = usize> + '_ {
+        let mut first = self.cursors.last().map_or(0, |&(_, first)| first);
+        while self.satisfied.get(first).is_some_and(|&count| count > 0) {
+            first += 1;
+        }
+        let at = self.applied.len();
+        match self.cursors.last_mut() {
+            Some(last) if last.0 == at => last.1 = first,
+            _ => self.cursors.push((at, first)),
+        }
+        let satisfied = &self.satisfied;
+        (first..satisfied.len()).filter(move |&index| satisfied[index] == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_follow_the_trail_and_roll_back() {
+        let x = |var| Lit::positive(Var::new(var));
+        // (x0 ∨ x1) ∧ (¬x0 ∨ x2) ∧ (x1 ∨ ¬x2)
+        let formula = vec![vec![x(0), x(1)], vec![!x(0), x(2)], vec![x(1), !x(2)]];
+        let mut satisfaction = Satisfaction::default();
+        satisfaction.reset(&formula, 3, false);
+        let mut model = Model::new(3);
+        satisfaction.sync(&formula, &model);
+        assert_eq!(satisfaction.open_clauses().collect::<Vec<_>>(), vec![0, 1, 2]);
+
+        model.assign(Var::new(0), Val::True);
+        satisfaction.sync(&formula, &model);
+        let mark = satisfaction.mark();
+        assert_eq!(satisfaction.open_clauses().collect::<Vec<_>>(), vec![1, 2]);
+        assert!(satisfaction.occurs_open(Var::new(2)));
+
+        let mut branch = model.clone();
+        branch.assign(Var::new(2), Val::True);
+        branch.assign(Var::new(1), Val::True);
+        satisfaction.sync(&formula, &branch);
+        assert!(satisfaction.is_satisfied());
+        assert_eq!(satisfaction.open_clauses().count(), 0);
+        assert!(!satisfaction.occurs_open(Var::new(2)));
+
+        // Back at the mark, the clauses the branch satisfied are open again
+        satisfaction.undo_to(&formula, mark);
+        assert!(!satisfaction.is_satisfied());
+        assert_eq!(satisfaction.open_clauses().collect::<Vec<_>>(), vec![1, 2]);
+        satisfaction.undo_to(&formula, 0);
+        assert_eq!(satisfaction.open_clauses().next(), Some(0));
+    }
+
+    #[test]
+    fn pure_literals_appear_as_clauses_close() {
+        let x = |var| Lit::positive(Var::new(var));
+        // (x0 ∨ ¬x1) ∧ (x1 ∨ x2) ∧ (¬x2 ∨ x3): x0 and x3 are pure
+        let formula = vec![vec![x(0), !x(1)], vec![x(1), x(2)], vec![!x(2), x(3)]];
+        let mut satisfaction = Satisfaction::default();
+        satisfaction.reset(&formula, 4, true);
+        let mut model = Model::new(4);
+        satisfaction.sync(&formula, &model);
+        let mut pure = Vec::new();
+        while let Some(lit) = satisfaction.next_pure(&model) {
+            pure.push(lit);
+        }
+        pure.sort_by_key(|lit| lit.var);
+        assert_eq!(pure, vec![x(0), x(3)]);
+
+        // With x0 and x3 true, ¬x1 and ¬x2 are gone: x1 and x2 become pure
+        model.assign(Var::new(0), Val::True);
+        model.assign(Var::new(3), Val::True);
+        satisfaction.sync(&formula, &model);
+        let lit = satisfaction.next_pure(&model).unwrap();
+        assert!(lit == x(1) || lit == x(2));
+        model.assign(lit.var, if lit.neg { Val::False } else { Val::True });
+        satisfaction.sync(&formula, &model);
+        assert!(satisfaction.is_satisfied());
+        assert_eq!(satisfaction.next_pure(&model), None);
+    }
+}
@@ -0,0 +1,449 @@
+//! Polynomial-time algorithms for special formula classes
+//!
+//! Some classes of CNF formulas can be decided without search:
+//!
+//! - **Trivial**: only unit clauses, decided by reading them off
+//! - **2-SAT**: at most two literals per clause, decided through the
+//!   strongly connected components of the implication graph
+//! - **Horn**: at most one positive literal per clause, decided by forward
+//!   chaining from the facts (the minimal model)
+//! - **XOR**: every clause belongs to the complete CNF encoding of a parity
+//!   constraint, decided by Gaussian elimination over GF(2)
+//!
+//! [`solve`] (re-exported as `putnam::solve`) recognizes these classes with
+//! [`classify`] and falls back to the DPLL search for everything else, so
+//! callers need not know which algorithm applies. [`Solver`](super::Solver)
+//! does the same unless [`SolverConfig::special_classes`] is switched off.
+//!
+//! [`SolverConfig::special_classes`]: super::SolverConfig::special_classes
+
+use std::collections::HashMap;
+
+use crate::types::*;
+
+use super::dpll::{self, SolveResult};
+
+/// Largest parity constraint recognized in CNF (it takes 2^(k-1) clauses).
+const MAX_XOR_VARS: usize = 16;
+
+/// Class of a formula, from most to least specific.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FormulaClass {
+    /// No clause has more than one literal
+    Trivial,
+    /// No clause has more than two literals
+    TwoSat,
+    /// No clause has more than one positive literal
+    Horn,
+    /// The clauses encode a system of parity constraints
+    Xor,
+    /// None of the above
+    General,
+}
+
+/// Determines the most specific class of `formula`.
+///
+/// # Examples
+///
+/// ```
+/// use putnam::solver::special::{classify, FormulaClass};
+/// use putnam::types::{Lit, Var};
+///
+/// let x = |var| Lit::positive(Var::new(var));
+/// assert_eq!(classify(&vec![vec![x(0), !x(1)], vec![x(1)]]), FormulaClass::TwoSat);
+/// assert_eq!(classify(&vec![vec![!x(0), !x(1), x(2)]]), FormulaClass::Horn);
+/// assert_eq!(classify(&vec![vec![x(0), x(1), x(2)]]), FormulaClass::General);
+/// ```
+pub fn classify(formula: &Formula) -> FormulaClass {
+    let longest = formula.iter().map(Vec::len).max().unwrap_or(0);
+    if longest <= 1 {
+        FormulaClass::Trivial
+    } else if longest == 2 {
+        FormulaClass::TwoSat
+    } else if formula.iter().all(|clause| clause.iter().filter(|l| !l.neg).count() <= 1) {
+        FormulaClass::Horn
+    } else if xor_constraints(formula).is_some() {
+        FormulaClass::Xor
+    } else {
+        FormulaClass::General
+    }
+}
+
+/// Solves `formula`, using a polynomial algorithm when its class allows.
+///
+/// Answers agree with [`dpll::solve`], but models may differ. Models from
+/// the special algorithms assign every variable occurring in the formula.
+/// As there, `num_vars` is raised if the formula uses more variables.
+///
+/// # Examples
+///
+/// ```
+/// use putnam::solve;
+/// use putnam::solver::dpll::SolveResult;
+/// use putnam::types::{Lit, Var};
+///
+/// // A long implication chain x0 → x1 → … → x999 with x0 and ¬x999: 2-SAT
+/// let x = |var| Lit::positive(Var::new(var));
+/// let mut formula: Vec<_> = (0..999).map(|i| vec![!x(i), x(i + 1)]).collect();
+/// formula.push(vec![x(0)]);
+/// formula.push(vec![!x(999)]);
+/// assert_eq!(solve(&formula, 1000), SolveResult::Unsat);
+/// ```
+pub fn solve(formula: &Formula, num_vars: usize) -> SolveResult {
+    let num_vars = formula.iter().flatten().map(|l| l.var.index() + 1).max().unwrap_or(0).max(num_vars);
+    solve_special(formula, num_vars).unwrap_or_else(|| dpll::solve(formula, num_vars))
+}
+
+/// Solves `formula` if it belongs to a special class, and returns `None`
+/// for general formulas.
+pub fn solve_special(formula: &Formula, num_vars: usize) -> Option<SolveResult> {
+    if formula.iter().any(Vec::is_empty) {
+        return Some(SolveResult::Unsat);
+    }
+    match classify(formula) {
+        FormulaClass::Trivial | FormulaClass::TwoSat => Some(solve_2sat(formula, num_vars)),
+        FormulaClass::Horn => Some(solve_horn(formula, num_vars)),
+        FormulaClass::Xor => xor_constraints(formula).map(|system| solve_xor(&system, num_vars)),
+        FormulaClass::General => None,
+    }
+}
+
+/// Node of a literal in the implication graph.
+fn node(lit: Lit) -> usize {
+    2 * lit.var.index() + lit.neg as usize
+}
+
+/// Decides a formula with at most two literals per clause.
+///
+/// # Panics
+///
+/// Panics if a clause has more than two literals.
+pub fn solve_2sat(formula: &Formula, num_vars: usize) -> SolveResult {
+    // Clause (a ∨ b) gives the implications ¬a → b and ¬b → a
+    let mut edges = vec![Vec::new(); 2 * num_vars];
+    for clause in formula {
+        match clause[..] {
+            [] => return SolveResult::Unsat,
+            [a] => edges[node(!a)].push(node(a)),
+            [a, b] => {
+                edges[node(!a)].push(node(b));
+                edges[node(!b)].push(node(a));
+            }
+            _ => panic!("clause with {} literals in a 2-SAT formula", clause.len()),
+        }
+    }
+    let component = strongly_connected_components(&edges);
+
+    let mut model = Model::new(num_vars);
+    for lit in formula.iter().flatten() {
+        if model.value(lit.var) != Val::Undef {
+            continue;
+        }
+        let (pos, neg) = (component[2 * lit.var.index()], component[2 * lit.var.index() + 1]);
+        if pos == neg {
+            return SolveResult::Unsat;
+        }
+        // Components are numbered in reverse topological order
+        model.assign(lit.var, if pos < neg { Val::True } else { Val::False });
+    }
+    SolveResult::Sat(model)
+}
+
+/// Numbers the strongly connected components of a graph (Tarjan's
+/// algorithm, iterative); components are numbered in reverse topological order.
+fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<usize> {
+    const UNVISITED: usize = usize::MAX;
+    let n = edges.len();
+    let mut index = vec![UNVISITED; n];
+    let mut low = vec![0; n];
+    let mut component = vec![UNVISITED; n];
+    let mut stack = Vec::new();
+    let mut counter = 0;
+    let mut components = 0;
+    for root in 0..n {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        // (node, next edge to follow)
+        let mut calls = vec![(root, 0)];
+        index[root] = counter;
+        low[root] = counter;
+        counter += 1;
+        stack.push(root);
+        while let Some(&mut (v, ref mut next)) = calls.last_mut() {
+            if let Some(&w) = edges[v].get(*next) {
+                *next += 1;
+                if index[w] == UNVISITED {
+                    index[w] = counter;
+                    low[w] = counter;
+                    counter += 1;
+                    stack.push(w);
+                    calls.push((w, 0));
+                } else if component[w] == UNVISITED {
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+            if low[v] == index[v] {
+                while let Some(w) = stack.pop() {
+                    component[w] = components;
+                    if w == v {
+                        break;
+                    }
+                }
+                components += 1;
+            }
+        }
+    }
+    component
+}
+
+/// Decides a Horn formula by computing its minimal model.
+///
+/// # Panics
+///
+/// Panics if a clause has more than one positive literal.
+pub fn solve_horn(formula: &Formula, num_vars: usize) -> SolveResult {
+    // Number of body atoms (negative literals) of each clause not yet derived
+    let mut pending: Vec<usize> = formula.iter().map(|clause| clause.iter().filter(|l| l.neg).count()).collect();
+    let mut watchers = vec![Vec::new(); num_vars];
+    for (index, clause) in formula.iter().enumerate() {
+        assert!(clause.iter().filter(|l| !l.neg).count() <= 1, "clause with several positive literals in a Horn formula");
+        for lit in clause.iter().filter(|l| l.neg) {
+            watchers[lit.var.index()].push(index);
+        }
+    }
+
+    let mut derived = vec![false; num_vars];
+    let mut queue: Vec<usize> = (0..formula.len()).filter(|&i| pending[i] == 0).collect();
+    while let Some(index) = queue.pop() {
+        // All body atoms hold: the head must hold, or the clause is violated
+        let Some(head) = formula[index].iter().find(|l| !l.neg) else {
+            return SolveResult::Unsat;
+        };
+        if derived[head.var.index()] {
+            continue;
+        }
+        derived[head.var.index()] = true;
+        for &watcher in &watchers[head.var.index()] {
+            pending[watcher] -= 1;
+            if pending[watcher] == 0 {
+                queue.push(watcher);
+            }
+        }
+    }
+
+    let mut model = Model::new(num_vars);
+    for lit in formula.iter().flatten() {
+        if model.value(lit.var) == Val::Undef {
+            model.assign(lit.var, if derived[lit.var.index()] { Val::True } else { Val::False });
+        }
+    }
+    SolveResult::Sat(model)
+}
+
+/// A parity constraint: the XOR of `vars` equals `parity`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XorConstraint {
+    /// The variables, sorted and distinct
+    pub vars: Vec<Var>,
+    /// The required parity of the number of true variables
+    pub parity: bool,
+}
+
+/// Recovers the parity constraints encoded by the clauses, or `None` if
+/// some clause is not part of a complete encoding.
+///
+/// A constraint over `k` variables is encoded by the `2^(k-1)` clauses that
+/// each exclude one assignment of the wrong parity.
+///
+/// # Examples
+///
+/// ```
+/// use putnam::solver::special::{xor_constraints, XorConstraint};
+/// use putnam::types::{Lit, Var};
+///
+/// let x = |var| Lit::positive(Var::new(var));
+/// // x0 ⊕ x1 = 1
+/// let formula = vec![vec![x(0), x(1)], vec![!x(0), !x(1)]];
+/// assert_eq!(xor_constraints(&formula), Some(vec![XorConstraint { vars: vec![Var::new(0), Var::new(1)], parity: true }]));
+/// ```
+pub fn xor_constraints(formula: &Formula) -> Option<Vec<XorConstraint>> {
+    // Excluded assignments (as negation bit masks) per variable set
+    let mut groups: HashMap<Vec<Var>, Vec<u32>> = HashMap::new();
+    for clause in formula {
+        let mut lits = clause.clone();
+        lits.sort();
+        let vars: Vec<Var> = lits.iter().map(|l| l.var).collect();
+        if vars.is_empty() || vars.len() > MAX_XOR_VARS || vars.windows(2).any(|w| w[0] == w[1]) {
+            return None;
+        }
+        let mask = lits.iter().enumerate().fold(0u32, |mask, (i, l)| mask | (l.neg as u32) << i);
+        groups.entry(vars).or_default().push(mask);
+    }
+
+    let mut constraints = Vec::with_capacity(groups.len());
+    for (vars, mut masks) in groups {
+        masks.sort_unstable();
+        masks.dedup();
+        // A clause excludes the assignment making all its literals false,
+        // which sets each variable to its negation flag
+        let excluded = masks[0].count_ones() % 2 == 1;
+        if masks.len() != 1 << (vars.len() - 1) || masks.iter().any(|m| (m.count_ones() % 2 == 1) != excluded) {
+            return None;
+        }
+        constraints.push(XorConstraint { vars, parity: !excluded });
+    }
+    constraints.sort_by(|a, b| a.vars.cmp(&b.vars));
+    Some(constraints)
+}
+
+/// Decides a system of parity constraints by Gaussian elimination.
+///
+/// Variables left free by the system are set to false.
+pub fn solve_xor(system: &[XorConstraint], num_vars: usize) -> SolveResult {
+    const BITS: usize = 64;
+    let words = num_vars.div_ceil(BITS);
+    let mut rows: Vec<(Vec<u64>, bool)> = system
+        .iter()
+        .map(|constraint| {
+            let mut row = vec![0u64; words];
+            for var in constraint.vars.iter().map(|v| v.index()) {
+                row[var / BITS] ^= 1 << (var % BITS);
+            }
+            (row, constraint.parity)
+        })
+        .collect();
+
+    // Reduce to row echelon form, remembering the pivot of each row
+    let mut pivots = Vec::new();
+    let mut rank = 0;
+    for var in 0..num_vars {
+        let bit = |row: &Vec<u64>| row[var / BITS] >> (var % BITS) & 1 == 1;
+        let Some(found) = (rank..rows.len()).find(|&r| bit(&rows[r].0)) else { continue };
+        rows.swap(rank, found);
+        let (pivot_row, pivot_parity) = rows[rank].clone();
+        for (r, (row, parity)) in rows.iter_mut().enumerate() {
+            if r != rank && bit(row) {
+                row.iter_mut().zip(&pivot_row).for_each(|(a, b)| *a ^= b);
+                *parity ^= pivot_parity;
+            }
+        }
+        pivots.push(var);
+        rank += 1;
+    }
+    // A remaining row 0 = 1 is a contradiction
+    if rows[rank..].iter().any(|(_, parity)| *parity) {
+        return SolveResult::Unsat;
+    }
+
+    // Free variables are false, so each pivot equals its row's parity
+    let mut model = Model::new(num_vars);
+    let mut assigned = vec![false; num_vars];
+    for (&var, (_, parity)) in pivots.iter().zip(&rows) {
+        model.assign(Var::new(var), if *parity { Val::True } else { Val::False });
+        assigned[var] = true;
+    }
+    for constraint in system {
+        for &var in &constraint.vars {
+            if !assigned[var.index()] {
+                model.assign(var, Val::False);
+                assigned[var.index()] = true;
+            }
+        }
+    }
+    SolveResult::Sat(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::brute;
+
+    fn lit(var: usize, neg: bool) -> Lit {
+        Lit::new(Var::new(var), neg)
+    }
+
+    fn check(formula: &Formula, num_vars: usize, class: FormulaClass) {
+        assert_eq!(classify(formula), class, "{:?}", formula);
+        let expected = brute::solve(formula, num_vars);
+        match solve_special(formula, num_vars).expect("special class") {
+            SolveResult::Sat(model) => {
+                assert!(matches!(expected, SolveResult::Sat(_)), "{:?}", formula);
+                assert!(formula.iter().all(|c| c.iter().any(|&l| model.is_true(l))), "{:?}", formula);
+            }
+            SolveResult::Unsat => assert_eq!(expected, SolveResult::Unsat, "{:?}", formula),
+        }
+    }
+
+    #[test]
+    fn special_classes_agree_with_brute_force() {
+        for seed in 0..60 {
+            let (formula, num_vars) = crate::gen::random_ksat(8, 10 + (seed as usize % 12), 2, seed);
+            check(&formula, num_vars, FormulaClass::TwoSat);
+
+            // Horn: keep at most the first literal of each 3-clause positive
+            let (mut formula, num_vars) = crate::gen::random_ksat(8, 8 + (seed as usize % 20), 3, seed);
+            for clause in &mut formula {
+                for l in clause.iter_mut().skip(1) {
+                    l.neg = true;
+                }
+            }
+            check(&formula, num_vars, FormulaClass::Horn);
+        }
+    }
+
+    #[test]
+    fn xor_systems_are_eliminated() {
+        // x0 ⊕ x1 ⊕ x2 = 1, x1 ⊕ x2 = 0 (so x0 = 1), plus x0 ⊕ x3 ⊕ x4 = 0
+        let mut formula = Vec::new();
+        let mut encode = |vars: &[usize], parity: bool| {
+            for bits in 0u32..1 << vars.len() {
+                if (bits.count_ones() % 2 == 1) != parity {
+                    formula.push(vars.iter().enumerate().map(|(i, &v)| lit(v, bits >> i & 1 == 1)).collect());
+                }
+            }
+        };
+        encode(&[0, 1, 2], true);
+        encode(&[1, 2], false);
+        encode(&[0, 3, 4], false);
+        check(&formula, 5, FormulaClass::Xor);
+        let SolveResult::Sat(model) = solve(&formula, 5) else { panic!("expected SAT") };
+        assert_eq!(model.value(Var::new(0)), Val::True);
+
+        // Adding x3 ⊕ x4 = 0 contradicts x0 ⊕ x3 ⊕ x4 = 0 with x0 = 1
+        formula.push(vec![lit(3, false), lit(4, true)]);
+        formula.push(vec![lit(3, true), lit(4, false)]);
+        check(&formula, 5, FormulaClass::Xor);
+
+        // An incomplete encoding is not a parity constraint
+        formula.pop();
+        assert_eq!(xor_constraints(&formula), None);
+    }
+
+    #[test]
+    fn variables_beyond_num_vars() {
+        // Malformed input, as from a fuzzer: the count is raised, not trusted
+        for formula in [vec![vec![lit(5, false)]], vec![vec![lit(0, false), lit(3, true)], vec![lit(3, false)]]] {
+            let SolveResult::Sat(model) = solve(&formula, 1) else { panic!("expected SAT") };
+            assert!(formula.iter().all(|c| c.iter().any(|&l| model.is_true(l))));
+        }
+        let formula = vec![vec![lit(0, false), lit(1, false), lit(6, false)], vec![lit(6, true)], vec![lit(0, true)], vec![lit(1, true)]];
+        assert_eq!(solve(&formula, 2), SolveResult::Unsat);
+        assert_eq!(dpll::solve(&formula, 0), SolveResult::Unsat);
+    }
+
+    #[test]
+    fn deep_implication_chains() {
+        // Deep enough to overflow a recursive Tarjan implementation
+        let n = 200_000;
+        let mut formula: Formula = (0..n - 1).map(|i| vec![lit(i, true), lit(i + 1, false)]).collect();
+        formula.push(vec![lit(0, false)]);
+        let SolveResult::Sat(model) = solve_2sat(&formula, n) else { panic!("expected SAT") };
+        assert_eq!(model.value(Var::new(n - 1)), Val::True);
+    }
+}
@@ -0,0 +1,163 @@
+//! Search statistics
+//!
+//! [`SolveStats`] counts the work done by the DPLL search and the memory it
+//! needed. A [`Solver`] accumulates the statistics of all its searches,
+//! including interrupted ones, and reports them through [`Solver::stats`].
+//! [`peak_rss`] adds the process-wide view where the platform provides it.
+//!
+//! Besides the human-readable [`Display`](fmt::Display) form, statistics can
+//! be written as CSV records ([`SolveStats::CSV_HEADER`] and
+//! [`SolveStats::to_csv`]) for aggregating batch runs in a spreadsheet, or
+//! as JSON objects ([`SolveStats::to_json`]) for line-based event streams.
+//!
+//! [`Solver`]: super::Solver
+//! [`Solver::stats`]: super::Solver::stats
+
+use std::fmt;
+use std::ops::AddAssign;
+
+/// Counters describing the work of one or more searches.
+///
+/// # Examples
+///
+/// ```
+/// use putnam::solver::Solver;
+///
+/// let (formula, num_vars, _) = putnam::gen::families::pigeonhole(3);
+/// let mut solver = Solver::from_formula(formula, num_vars);
+/// solver.solve();
+///
+/// let stats = solver.stats();
+/// assert_eq!(stats.searches, 1);
+/// assert!(stats.conflicts > 0);
+/// assert!(stats.clause_bytes > 0 && stats.search_bytes > 0);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SolveStats {
+    /// Number of searches run (cached answers are not counted)
+    pub searches: u64,
+    /// Number of branching points
+    pub decisions: u64,
+    /// Number of literals assigned by unit propagation
+    pub propagations: u64,
+    /// Number of branches closed by a falsified clause
+    pub conflicts: u64,
+    /// Size of the largest clause database searched, in bytes
+    pub clause_bytes: u64,
+    /// Peak memory held by the partial assignments of a search, in bytes
+    pub search_bytes: u64,
+}
+
+impl AddAssign for SolveStats {
+    fn add_assign(&mut self, other: SolveStats) {
+        self.searches += other.searches;
+        self.decisions += other.decisions;
+        self.propagations += other.propagations;
+        self.conflicts += other.conflicts;
+        self.clause_bytes = self.clause_bytes.max(other.clause_bytes);
+        self.search_bytes = self.search_bytes.max(other.search_bytes);
+    }
+}
+
+impl SolveStats {
+    /// Column names of [`to_csv`](SolveStats::to_csv), comma-separated.
+    pub const CSV_HEADER: &'static str = "searches,decisions,propagations,conflicts,clause_bytes,search_bytes";
+
+    /// Returns the counters as one comma-separated CSV record, in the order
+    /// of [`CSV_HEADER`](SolveStats::CSV_HEADER) and without a line break.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::stats::SolveStats;
+    ///
+    /// let stats = SolveStats { searches: 1, decisions: 5, conflicts: 2, ..SolveStats::default() };
+    /// assert_eq!(stats.to_csv(), "1,5,0,2,0,0");
+    /// ```
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{}",
+            self.searches, self.decisions, self.propagations, self.conflicts, self.clause_bytes, self.search_bytes
+        )
+    }
+
+    /// Returns the counters as a one-line JSON object whose keys are the
+    /// field names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::solver::stats::SolveStats;
+    ///
+    /// let stats = SolveStats { searches: 1, decisions: 5, ..SolveStats::default() };
+    /// assert_eq!(
+    ///     stats.to_json(),
+    ///     r#"{"searches":1,"decisions":5,"propagations":0,"conflicts":0,"clause_bytes":0,"search_bytes":0}"#
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let fields: Vec<String> = Self::CSV_HEADER
+            .split(',')
+            .zip(self.to_csv().split(','))
+            .map(|(name, value)| format!("\"{}\":{}", name, value))
+            .collect();
+        format!("{{{}}}", fields.join(","))
+    }
+}
+
+/// Prints one `name: value` line per counter.
+impl fmt::Display for SolveStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "searches: {}", self.searches)?;
+        writeln!(f, "decisions: {}", self.decisions)?;
+        writeln!(f, "propagations: {}", self.propagations)?;
+        writeln!(f, "conflicts: {}", self.conflicts)?;
+        writeln!(f, "clause bytes: {}", self.clause_bytes)?;
+        write!(f, "search bytes: {}", self.search_bytes)
+    }
+}
+
+/// Returns the peak resident set size of the process in bytes.
+///
+/// This covers everything the process allocated, not just the solver. It
+/// is read from `/proc/self/status` on Linux; on other platforms, or if
+/// the value cannot be read, the result is `None`.
+pub fn peak_rss() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find_map(|line| line.strip_prefix("VmHWM:"))?;
+        let kilobytes: u64 = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+        Some(kilobytes * 1024)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peaks_are_not_summed() {
+        let mut total = SolveStats { searches: 1, clause_bytes: 100, search_bytes: 40, ..SolveStats::default() };
+        total += SolveStats { searches: 1, clause_bytes: 80, search_bytes: 60, ..SolveStats::default() };
+        assert_eq!(total.searches, 2);
+        assert_eq!((total.clause_bytes, total.search_bytes), (100, 60));
+    }
+
+    #[test]
+    fn csv_records_match_the_header() {
+        let stats = SolveStats { searches: 1, decisions: 2, propagations: 3, conflicts: 4, clause_bytes: 5, search_bytes: 6 };
+        assert_eq!(stats.to_csv(), "1,2,3,4,5,6");
+        assert_eq!(SolveStats::CSV_HEADER.split(',').count(), stats.to_csv().split(',').count());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn peak_rss_is_reported_on_linux() {
+        assert!(peak_rss().is_some_and(|bytes| bytes > 0));
+    }
+}
@@ -7,16 +7,20 @@
 //! This process continues recursively until no more unit clauses exist or
 //! a contradiction is found.
 
+use std::collections::VecDeque;
+
+use crate::store::FormulaSource;
 use crate::types::*;
 
-/// Type alias for contradiction errors, containing the variable that caused the conflict
-type Contradict = Var;
+/// Type alias for contradiction errors, containing the clause the conflict falsified
+type Contradict = ClauseId;
 
 /// Performs unit propagation on the given formula and model.
 ///
 /// Unit propagation is a form of constraint propagation that identifies unit clauses
 /// (clauses with exactly one unassigned literal) and forces the assignment of those
-/// literals to satisfy the clauses.
+/// literals to satisfy the clauses. Each forced literal records its clause as the
+/// reason (see [`Model::reason`]).
 ///
 /// The algorithm works by:
 /// 1. Finding all initial unit clauses
@@ -28,26 +32,31 @@ type Contradict = Var;
 ///
 /// * `formula` - The CNF formula to propagate on
 /// * `model` - The current variable assignments (will be modified)
+/// * `queue` - Work queue, cleared first; passing the same queue to every
+///   call reuses its allocation
 ///
 /// # Returns
 ///
 /// * `Ok(())` - Propagation completed successfully without conflicts
-/// * `Err(var)` - A contradiction was found involving the specified variable
+/// * `Err(clause)` - A contradiction was found: the model falsifies `clause`
 ///
 /// # Examples
 ///
-/// ```no_run
-/// use putnam::types::{Model, Lit, Formula};
-/// use putnam::solver::unit::unit_propagate;
+/// The module is private to the crate;
+/// [`Solver::propagate`](crate::solver::Solver::propagate) runs the
+/// propagation on a solver's formula:
 ///
-/// let mut model = Model::new(2);
-/// let formula = vec![
-///     vec![Lit { var: 0, neg: false }],  // Unit clause: x₁
-/// ];
+/// ```
+/// use putnam::solver::propagate::PropagationResult;
+/// use putnam::solver::Solver;
+/// use putnam::types::{Lit, Var};
 ///
-/// match unit_propagate(&formula, &mut model) {
-///     Ok(()) => println!("Propagation successful"),
-///     Err(var) => println!("Contradiction at variable {}", var),
+/// let x = |var| Lit::positive(Var::new(var));
+/// // Unit clause x₁, and ¬x₁ ∨ ¬x₂
+/// let mut solver = Solver::from_formula(vec![vec![x(0)], vec![!x(0), !x(1)]], 2);
+/// match solver.propagate(&[]) {
+///     PropagationResult::Implied(implied) => assert_eq!(implied, vec![x(0), !x(1)]),
+///     PropagationResult::Conflict { clause, .. } => panic!("contradiction in {:?}", clause),
 /// }
 /// ```
 ///
@@ -62,39 +71,73 @@ type Contradict = Var;
 ///
 /// O(L × P) where L is the number of literals in the formula and P is the number
 /// of propagation steps.
-pub fn unit_propagate(formula: &Formula, model: &mut Model) -> Result<(), Contradict> {
-    use std::collections::VecDeque;
-    let mut queue: VecDeque<Lit> = formula
-        .iter()
-        .filter(|c| c.len() == 1)
-        .map(|c| c[0])
-        .collect();
-    while let Some(lit) = queue.pop_front() {
+pub fn unit_propagate<F: FormulaSource + ?Sized>(
+    formula: &F,
+    model: &mut Model,
+    queue: &mut VecDeque<(Lit, ClauseId)>,
+) -> Result<(), Contradict> {
+    queue.clear();
+    // Seed the queue with every clause that is unit under the current model,
+    // so that assignments made by the caller (e.g. branching decisions) are
+    // propagated as well.
+    collect_units(formula, model, queue)?;
+    while let Some((lit, reason)) = queue.pop_front() {
         match model.value(lit.var) {
             Val::True | Val::False if model.is_true(lit) => continue,
-            Val::True | Val::False => return Err(lit.var),
-            Val::Undef => {
-                let val = if lit.neg { Val::False } else { Val::True };
-                model.assign(lit.var, val);
-            }
+            // Its reason clause is falsified now
+            Val::True | Val::False => return Err(reason),
+            Val::Undef => model.imply(lit, reason),
         }
         // Check for new unit clauses after this assignment
-        for clause in formula.iter() {
-            if clause.iter().any(|l| model.is_true(*l)) {
-                continue; // Clause is satisfied
-            }
-            let unassigned: Vec<Lit> = clause.iter()
-                .filter(|l| model.value(l.var) == Val::Undef)
-                .copied()
-                .collect();
-            
-            if unassigned.is_empty() {
-                return Err(lit.var); // Empty clause = contradiction
-            }
-            if unassigned.len() == 1 {
-                queue.push_back(unassigned[0]); // New unit clause
-            }
+        collect_units(formula, model, queue)?;
+    }
+    Ok(())
+}
+
+/// Scans the formula and queues the remaining literal of every unit clause,
+/// together with the clause.
+///
+/// Returns `Err(clause)` if some non-empty clause is falsified by the model.
+/// Empty input clauses are left to the caller, which treats them as UNSAT.
+fn collect_units<F: FormulaSource + ?Sized>(formula: &F, model: &Model, queue: &mut VecDeque<(Lit, ClauseId)>) -> Result<(), Contradict> {
+    for (index, clause) in formula.clauses().enumerate() {
+        if clause.iter().any(|l| model.is_true(*l)) {
+            continue; // Clause is satisfied
+        }
+        // The first unassigned literal and whether there is a second one
+        let mut unassigned = clause.iter().filter(|l| model.value(l.var) == Val::Undef);
+        match (unassigned.next(), unassigned.next()) {
+            (None, _) if !clause.is_empty() => return Err(ClauseId::new(index)), // Falsified clause = contradiction
+            (Some(&unit), None) => queue.push_back((unit, ClauseId::new(index))), // New unit clause
+            _ => {}
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assignments_of_the_caller_are_propagated() {
+        let x = |var| Lit::positive(Var::new(var));
+        // x0 → x1 → x2, without any unit clause
+        let formula = vec![vec![!x(0), x(1)], vec![!x(1), x(2)]];
+        let mut model = Model::new(3);
+        model.assign(Var::new(0), Val::True);
+        assert_eq!(unit_propagate(&formula, &mut model, &mut VecDeque::new()), Ok(()));
+        assert!(model.is_true(x(1)) && model.is_true(x(2)));
+        assert_eq!(model.reason(Var::new(0)), None);
+        assert_eq!(model.reason(Var::new(2)), Some(ClauseId::new(1)));
+
+        // A clause the assignment falsifies is a contradiction; empty input
+        // clauses are left to the caller
+        let formula = vec![vec![!x(0), x(1)], vec![!x(0), !x(1)], vec![]];
+        let mut model = Model::new(2);
+        assert_eq!(unit_propagate(&formula, &mut model, &mut VecDeque::new()), Ok(()));
+        model.assign(Var::new(0), Val::True);
+        assert_eq!(unit_propagate(&formula, &mut model, &mut VecDeque::new()), Err(ClauseId::new(1)));
+        assert_eq!(crate::solver::dpll::solve(&vec![Vec::new()], 2), crate::solver::dpll::SolveResult::Unsat);
+    }
+}
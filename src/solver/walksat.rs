@@ -0,0 +1,160 @@
+//! WalkSAT local search
+//!
+//! WalkSAT starts from a random assignment and repeatedly repairs a random
+//! falsified clause by flipping one of its variables: a variable whose flip
+//! falsifies no other clause if there is one, otherwise a random variable
+//! with probability `noise`, and otherwise the one whose flip falsifies the
+//! fewest clauses. It often finds models of large satisfiable random
+//! formulas far faster than systematic search, but it is incomplete: when
+//! the flips run out it cannot tell an unsatisfiable formula from a hard
+//! satisfiable one.
+
+use crate::rng::Rng;
+use crate::types::*;
+
+/// Parameters of [`walksat`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct WalkSatConfig {
+    /// Maximum number of flips before giving up
+    pub max_flips: u64,
+    /// Probability in `[0, 1]` of a random flip when every flip breaks a clause
+    pub noise: f64,
+    /// Seed of the random choices
+    pub seed: u64,
+}
+
+impl Default for WalkSatConfig {
+    fn default() -> Self {
+        WalkSatConfig { max_flips: 100_000, noise: 0.5, seed: 0 }
+    }
+}
+
+/// Searches for a model of `formula` with the `assumptions` fixed.
+///
+/// # Returns
+///
+/// A tuple containing:
+/// * `Option<Model>` - A complete model, or `None` if none was found within
+///   the flip limit (which proves nothing)
+/// * `u64` - The number of flips made
+///
+/// # Examples
+///
+/// ```
+/// use putnam::solver::walksat::{walksat, WalkSatConfig};
+///
+/// let (formula, num_vars) = putnam::gen::random_ksat(50, 150, 3, 1);
+/// let (model, _) = walksat(&formula, num_vars, &[], &WalkSatConfig::default());
+/// let model = model.expect("an easy satisfiable formula");
+/// assert!(formula.iter().all(|clause| clause.iter().any(|&lit| model.is_true(lit))));
+/// ```
+pub fn walksat(formula: &Formula, num_vars: usize, assumptions: &[Lit], config: &WalkSatConfig) -> (Option<Model>, u64) {
+    let mut rng = Rng::new(config.seed);
+    let mut values: Vec<bool> = (0..num_vars).map(|_| rng.coin()).collect();
+    let mut frozen = vec![false; num_vars];
+    for lit in assumptions {
+        if frozen[lit.var.index()] && values[lit.var.index()] == lit.neg {
+            return (None, 0); // Contradictory assumptions
+        }
+        values[lit.var.index()] = !lit.neg;
+        frozen[lit.var.index()] = true;
+    }
+    if formula.iter().any(|clause| clause.iter().all(|lit| frozen[lit.var.index()])
+        && !clause.iter().any(|lit| values[lit.var.index()] != lit.neg))
+    {
+        return (None, 0); // Falsified by the assumptions alone
+    }
+
+    // Occurrence lists indexed by literal code, true-literal counts and the
+    // set of falsified clauses with each clause's position in it
+    let code = |lit: Lit| 2 * lit.var.index() + lit.neg as usize;
+    let is_true = |values: &[bool], lit: Lit| values[lit.var.index()] != lit.neg;
+    let mut occurs = vec![Vec::new(); 2 * num_vars];
+    for (index, clause) in formula.iter().enumerate() {
+        for &lit in clause {
+            occurs[code(lit)].push(index);
+        }
+    }
+    let mut num_true: Vec<usize> = formula.iter().map(|c| c.iter().filter(|&&l| is_true(&values, l)).count()).collect();
+    let mut falsified = Vec::new();
+    let mut position = vec![usize::MAX; formula.len()];
+    for (index, &count) in num_true.iter().enumerate() {
+        if count == 0 {
+            position[index] = falsified.len();
+            falsified.push(index);
+        }
+    }
+
+    let mut flips = 0;
+    while !falsified.is_empty() {
+        if flips == config.max_flips {
+            return (None, flips);
+        }
+        let clause = &formula[falsified[rng.below(falsified.len())]];
+        // Clauses falsified by flipping a variable of the clause
+        let breaks = |var: Var, values: &[bool], num_true: &[usize]| {
+            let now_true = Lit { var, neg: !values[var.index()] };
+            occurs[code(now_true)].iter().filter(|&&c| num_true[c] == 1).count()
+        };
+        let candidates: Vec<Var> = clause.iter().map(|l| l.var).filter(|v| !frozen[v.index()]).collect();
+        let scored: Vec<(Var, usize)> = candidates.iter().map(|&v| (v, breaks(v, &values, &num_true))).collect();
+        let var = match scored.iter().find(|(_, b)| *b == 0) {
+            Some(&(var, _)) => var,
+            None if rng.unit() < config.noise => candidates[rng.below(candidates.len())],
+            None => scored.iter().min_by_key(|(_, b)| *b).expect("clauses with only frozen variables are rejected").0,
+        };
+
+        flips += 1;
+        let was_true = Lit { var, neg: !values[var.index()] };
+        values[var.index()] = !values[var.index()];
+        for &index in &occurs[code(was_true)] {
+            num_true[index] -= 1;
+            if num_true[index] == 0 {
+                position[index] = falsified.len();
+                falsified.push(index);
+            }
+        }
+        for &index in &occurs[code(!was_true)] {
+            num_true[index] += 1;
+            if num_true[index] == 1 {
+                let last = *falsified.last().expect("the clause is falsified");
+                falsified.swap_remove(position[index]);
+                position[last] = position[index];
+            }
+        }
+    }
+
+    let mut model = Model::new(num_vars);
+    for (var, &value) in Var::range(num_vars).zip(&values) {
+        model.assign(var, if value { Val::True } else { Val::False });
+    }
+    (Some(model), flips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn models_satisfy_formula_and_assumptions() {
+        let mut found = 0;
+        for seed in 0..20 {
+            let (formula, num_vars) = crate::gen::random_ksat(30, 110, 3, seed);
+            let assumptions = [Lit::negative(Var::new(0)), Lit::positive(Var::new(5))];
+            let config = WalkSatConfig { seed, ..WalkSatConfig::default() };
+            if let (Some(model), _) = walksat(&formula, num_vars, &assumptions, &config) {
+                found += 1;
+                assert!(formula.iter().all(|clause| clause.iter().any(|&lit| model.is_true(lit))));
+                assert!(assumptions.iter().all(|&lit| model.is_true(lit)));
+            }
+        }
+        assert!(found > 10);
+    }
+
+    #[test]
+    fn gives_up_on_unsatisfiable_formulas() {
+        let (formula, num_vars, _) = crate::gen::families::pigeonhole(3);
+        let config = WalkSatConfig { max_flips: 1000, ..WalkSatConfig::default() };
+        assert_eq!(walksat(&formula, num_vars, &[], &config), (None, 1000));
+    }
+}
@@ -0,0 +1,337 @@
+//! Clause storage
+//!
+//! The search only enumerates clauses and looks them up by index, so it can
+//! run over any store implementing [`FormulaSource`] without first copying
+//! the clauses into a [`Formula`]. Three stores are provided:
+//!
+//! - [`Formula`] (a vector of clause vectors), the representation used
+//!   everywhere else in the crate,
+//! - [`ClauseDb`], an arena keeping all literals in one allocation, with
+//!   in-place compaction when clauses are deleted,
+//! - [`FlatFormula`], a read-only view of literals and clause boundaries
+//!   held elsewhere, e.g. in a buffer shared between solvers or loaded from
+//!   a memory-mapped file.
+//!
+//! [`dpll::solve_source`](crate::solver::dpll::solve_source) decides any of
+//! them.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::solver::dpll::{solve_source, SolveResult};
+//! use putnam::store::{ClauseDb, FormulaSource};
+//! use putnam::types::{Lit, Var};
+//!
+//! let x = |var| Lit::positive(Var::new(var));
+//! let mut db = ClauseDb::new();
+//! db.push(&[x(0), x(1)]);
+//! db.push(&[!x(0)]);
+//!
+//! assert_eq!(db.num_clauses(), 2);
+//! assert_eq!(db.clause(1), &[!x(0)]);
+//! assert!(matches!(solve_source(&db.as_flat(), 2), SolveResult::Sat(_)));
+//! ```
+
+use crate::types::*;
+
+/// Read access to the clauses of a formula.
+pub trait FormulaSource {
+    /// Returns the number of clauses.
+    fn num_clauses(&self) -> usize;
+
+    /// Returns the literals of clause `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not below [`num_clauses`](FormulaSource::num_clauses).
+    fn clause(&self, index: usize) -> &[Lit];
+
+    /// Returns the clauses in order.
+    fn clauses(&self) -> impl Iterator<Item = &[Lit]> + '_ {
+        (0..self.num_clauses()).map(|index| self.clause(index))
+    }
+
+    /// Returns the heap memory owned by the store, in bytes (zero for
+    /// borrowed views).
+    fn heap_bytes(&self) -> usize;
+}
+
+impl FormulaSource for [Clause] {
+    fn num_clauses(&self) -> usize {
+        self.len()
+    }
+
+    fn clause(&self, index: usize) -> &[Lit] {
+        &self[index]
+    }
+
+    fn clauses(&self) -> impl Iterator<Item = &[Lit]> + '_ {
+        self.iter().map(Vec::as_slice)
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.iter().map(|clause| clause.capacity() * std::mem::size_of::<Lit>()).sum()
+    }
+}
+
+impl FormulaSource for Formula {
+    fn num_clauses(&self) -> usize {
+        self.len()
+    }
+
+    fn clause(&self, index: usize) -> &[Lit] {
+        &self[index]
+    }
+
+    fn clauses(&self) -> impl Iterator<Item = &[Lit]> + '_ {
+        self.iter().map(Vec::as_slice)
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * std::mem::size_of::<Clause>() + self.as_slice().heap_bytes()
+    }
+}
+
+/// An arena of clauses: all literals in one vector, with the end of each
+/// clause recorded separately.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClauseDb {
+    /// The literals of all clauses, back to back
+    lits: Vec<Lit>,
+    /// End offset (exclusive) of each clause in `lits`
+    ends: Vec<usize>,
+}
+
+impl ClauseDb {
+    /// Creates an empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a clause and returns its index.
+    pub fn push(&mut self, clause: &[Lit]) -> usize {
+        self.lits.extend_from_slice(clause);
+        self.ends.push(self.lits.len());
+        self.ends.len() - 1
+    }
+
+    /// Deletes the clauses for which `keep` returns `false` and compacts
+    /// the arena.
+    ///
+    /// The remaining clauses move down over the deleted ones, keeping their
+    /// order, and the allocations shrink once they are more than twice as
+    /// large as the live clauses, so memory stays proportional to the live
+    /// clauses however many are deleted over time. Indices held elsewhere
+    /// (occurrence lists, watches, reasons) are updated with the returned
+    /// map, e.g. by [`remap_references`].
+    ///
+    /// # Arguments
+    ///
+    /// * `keep` - Called with the index and literals of each clause, in order
+    ///
+    /// # Returns
+    ///
+    /// The new index of each old clause, `None` for deleted ones
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::store::{remap_references, ClauseDb, FormulaSource};
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// let x = |var| Lit::positive(Var::new(var));
+    /// let mut db: ClauseDb = vec![vec![x(0), x(1)], vec![x(1), x(2), x(3)], vec![!x(1)]].into_iter().collect();
+    /// let mut occurs_x1 = vec![0, 1];
+    ///
+    /// let moved = db.retain(|_, clause| clause.len() < 3);
+    /// remap_references(&mut occurs_x1, &moved);
+    ///
+    /// assert_eq!(moved, vec![Some(0), None, Some(1)]);
+    /// assert_eq!(db.clause(1), &[!x(1)]);
+    /// assert_eq!(occurs_x1, vec![0]);
+    /// ```
+    pub fn retain<F: FnMut(usize, &[Lit]) -> bool>(&mut self, mut keep: F) -> Vec<Option<usize>> {
+        let mut moved = Vec::with_capacity(self.ends.len());
+        let (mut start, mut written, mut kept) = (0, 0, 0);
+        for index in 0..self.ends.len() {
+            let end = self.ends[index];
+            if keep(index, &self.lits[start..end]) {
+                self.lits.copy_within(start..end, written);
+                written += end - start;
+                self.ends[kept] = written;
+                moved.push(Some(kept));
+                kept += 1;
+            } else {
+                moved.push(None);
+            }
+            start = end;
+        }
+        self.lits.truncate(written);
+        self.ends.truncate(kept);
+        if self.lits.capacity() > 2 * self.lits.len() {
+            self.lits.shrink_to_fit();
+        }
+        if self.ends.capacity() > 2 * self.ends.len() {
+            self.ends.shrink_to_fit();
+        }
+        moved
+    }
+
+    /// Returns a borrowed read-only view of the clauses.
+    pub fn as_flat(&self) -> FlatFormula<'_> {
+        FlatFormula { lits: &self.lits, ends: &self.ends }
+    }
+}
+
+impl FromIterator<Clause> for ClauseDb {
+    fn from_iter<I: IntoIterator<Item = Clause>>(clauses: I) -> Self {
+        let mut db = ClauseDb::new();
+        for clause in clauses {
+            db.push(&clause);
+        }
+        db
+    }
+}
+
+impl FormulaSource for ClauseDb {
+    fn num_clauses(&self) -> usize {
+        self.ends.len()
+    }
+
+    fn clause(&self, index: usize) -> &[Lit] {
+        self.as_flat().get(index)
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.lits.capacity() * std::mem::size_of::<Lit>() + self.ends.capacity() * std::mem::size_of::<usize>()
+    }
+}
+
+/// Updates clause indices after [`ClauseDb::retain`]: references to
+/// deleted clauses are dropped, the others renumbered, in order.
+///
+/// # Arguments
+///
+/// * `references` - Clause indices from before the compaction
+/// * `moved` - The map returned by [`ClauseDb::retain`]
+pub fn remap_references(references: &mut Vec<usize>, moved: &[Option<usize>]) {
+    references.retain_mut(|index| match moved[*index] {
+        Some(new) => {
+            *index = new;
+            true
+        }
+        None => false,
+    });
+}
+
+/// A read-only formula over borrowed literals and clause end offsets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlatFormula<'a> {
+    lits: &'a [Lit],
+    ends: &'a [usize],
+}
+
+impl<'a> FlatFormula<'a> {
+    /// Creates a view of the clauses `lits[ends[i-1]..ends[i]]` (the first
+    /// clause starting at 0).
+    ///
+    /// # Returns
+    ///
+    /// `None` if the offsets decrease or exceed `lits`
+    pub fn new(lits: &'a [Lit], ends: &'a [usize]) -> Option<Self> {
+        let ordered = ends.windows(2).all(|w| w[0] <= w[1]);
+        (ordered && ends.last().is_none_or(|&end| end <= lits.len())).then_some(FlatFormula { lits, ends })
+    }
+
+    /// Returns clause `index` with the lifetime of the underlying buffers.
+    fn get(self, index: usize) -> &'a [Lit] {
+        let start = if index == 0 { 0 } else { self.ends[index - 1] };
+        &self.lits[start..self.ends[index]]
+    }
+}
+
+impl FormulaSource for FlatFormula<'_> {
+    fn num_clauses(&self) -> usize {
+        self.ends.len()
+    }
+
+    fn clause(&self, index: usize) -> &[Lit] {
+        self.get(index)
+    }
+
+    fn heap_bytes(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll::{solve_source, SolveResult};
+
+    #[test]
+    fn stores_agree_with_formulas() {
+        for seed in 0..20 {
+            let (formula, num_vars) = crate::gen::random_ksat(8, 36, 3, seed);
+            let db: ClauseDb = formula.iter().cloned().collect();
+            assert!(db.clauses().eq(formula.clauses()));
+
+            // Same clauses in the same order: the very same search
+            assert_eq!(solve_source(&db.as_flat(), num_vars), crate::solver::dpll::solve(&formula, num_vars), "seed {}", seed);
+        }
+
+        // Variables beyond the count given are covered, as by `solve`
+        let x = |var| Lit::positive(Var::new(var));
+        let formula = vec![vec![x(5), x(1)], vec![!x(5)]];
+        let db: ClauseDb = formula.iter().cloned().collect();
+        for result in [solve_source(&formula, 2), solve_source(&db.as_flat(), 2)] {
+            let SolveResult::Sat(model) = result else { panic!("satisfiable") };
+            assert!(crate::testing::verify_model(&formula, &model));
+        }
+    }
+
+    #[test]
+    fn compaction_keeps_memory_proportional() {
+        let (formula, num_vars) = crate::gen::random_ksat(20, 400, 3, 7);
+        let mut db: ClauseDb = formula.iter().cloned().collect();
+        let mut occurs = vec![Vec::new(); 2 * num_vars];
+        for (index, clause) in db.clauses().enumerate() {
+            for &lit in clause {
+                occurs[2 * lit.var.index() + lit.neg as usize].push(index);
+            }
+        }
+        let full = db.heap_bytes();
+
+        // Delete all clauses with x0 and then most of the rest
+        let moved = db.retain(|_, clause| clause.iter().all(|l| l.var.index() != 0));
+        let moved_again = db.retain(|index, _| index % 8 == 0);
+        for list in &mut occurs {
+            remap_references(list, &moved);
+            remap_references(list, &moved_again);
+        }
+        let expected: Formula = formula
+            .iter()
+            .filter(|clause| clause.iter().all(|l| l.var.index() != 0))
+            .step_by(8)
+            .cloned()
+            .collect();
+        assert!(db.clauses().eq(expected.clauses()));
+        for (index, clause) in db.clauses().enumerate() {
+            for &lit in clause {
+                assert!(occurs[2 * lit.var.index() + lit.neg as usize].contains(&index));
+            }
+        }
+        assert_eq!(occurs.iter().map(Vec::len).sum::<usize>(), db.clauses().map(<[Lit]>::len).sum::<usize>());
+        let live: usize = expected.iter().map(|c| c.len() * std::mem::size_of::<Lit>() + std::mem::size_of::<usize>()).sum();
+        assert!(db.heap_bytes() <= 2 * live);
+        assert!(db.heap_bytes() < full / 4);
+    }
+
+    #[test]
+    fn flat_views_check_offsets() {
+        let lits = [Lit::positive(Var::new(0)), Lit::negative(Var::new(1))];
+        assert_eq!(FlatFormula::new(&lits, &[1, 1, 2]).map(|f| f.num_clauses()), Some(3));
+        assert!(FlatFormula::new(&lits, &[2, 1]).is_none());
+        assert!(FlatFormula::new(&lits, &[3]).is_none());
+    }
+}
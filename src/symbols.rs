@@ -0,0 +1,166 @@
+//! Symbol tables for named variables
+//!
+//! Encodings of real problems quickly accumulate thousands of variables,
+//! and raw indices make models hard to read. A [`SymbolTable`] keeps a
+//! bidirectional mapping between variables and human-readable names. It
+//! can be attached to a [`Model`](crate::types::Model) so that the model
+//! can be queried and printed by name.
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::Arc;
+//! use putnam::symbols::SymbolTable;
+//! use putnam::types::{Lit, Val};
+//! use putnam::solver::dpll::SolveResult;
+//!
+//! let mut symbols = SymbolTable::new();
+//! let a = symbols.var("task3_slot2");
+//! let b = symbols.var("task4_slot2");
+//! // Not both tasks in slot 2, and task 3 must be there
+//! let formula = vec![
+//!     vec![Lit { var: a, neg: true }, Lit { var: b, neg: true }],
+//!     vec![Lit { var: a, neg: false }],
+//! ];
+//!
+//! match putnam::solve(&formula, symbols.len()) {
+//!     SolveResult::Sat(model) => {
+//!         let model = model.with_symbols(Arc::new(symbols));
+//!         assert_eq!(model.get("task3_slot2"), Some(Val::True));
+//!         assert_eq!(model.get("task4_slot2"), Some(Val::False));
+//!         assert_eq!(model.to_string(), "task3_slot2=true task4_slot2=false");
+//!     }
+//!     SolveResult::Unsat => unreachable!(),
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use crate::types::Var;
+
+/// A bidirectional mapping between variables and names.
+///
+/// Not every variable needs a name: auxiliary variables introduced by
+/// encodings typically stay anonymous.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SymbolTable {
+    /// Name of each variable, indexed by variable
+    names: Vec<Option<String>>,
+    /// Variable of each name
+    vars: HashMap<String, Var>,
+}
+
+impl SymbolTable {
+    /// Creates an empty symbol table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the variable named `name`, allocating a fresh one if needed.
+    ///
+    /// Fresh variables are numbered after every variable the table knows
+    /// about, so allocating names one by one yields `0, 1, 2, …`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::symbols::SymbolTable;
+    /// use putnam::types::Var;
+    ///
+    /// let mut symbols = SymbolTable::new();
+    /// assert_eq!(symbols.var("x"), Var::new(0));
+    /// assert_eq!(symbols.var("y"), Var::new(1));
+    /// assert_eq!(symbols.var("x"), Var::new(0));
+    /// ```
+    pub fn var(&mut self, name: &str) -> Var {
+        if let Some(&var) = self.vars.get(name) {
+            return var;
+        }
+        let var = Var::new(self.names.len());
+        self.names.push(Some(name.to_string()));
+        self.vars.insert(name.to_string(), var);
+        var
+    }
+
+    /// Allocates a fresh anonymous variable.
+    pub fn fresh(&mut self) -> Var {
+        self.names.push(None);
+        Var::new(self.names.len() - 1)
+    }
+
+    /// Names an existing variable.
+    ///
+    /// # Returns
+    ///
+    /// `false` (leaving the table unchanged) if the variable already has a
+    /// different name or the name is taken by another variable.
+    pub fn bind(&mut self, var: Var, name: &str) -> bool {
+        match (self.name(var), self.lookup(name)) {
+            (Some(existing), _) => existing == name,
+            (None, Some(_)) => false,
+            (None, None) => {
+                if var.index() >= self.names.len() {
+                    self.names.resize(var.index() + 1, None);
+                }
+                self.names[var.index()] = Some(name.to_string());
+                self.vars.insert(name.to_string(), var);
+                true
+            }
+        }
+    }
+
+    /// Returns the variable named `name`, if any.
+    pub fn lookup(&self, name: &str) -> Option<Var> {
+        self.vars.get(name).copied()
+    }
+
+    /// Returns the name of `var`, if any.
+    pub fn name(&self, var: Var) -> Option<&str> {
+        self.names.get(var.index())?.as_deref()
+    }
+
+    /// Returns the number of variables covered by the table (one more than
+    /// the largest named or allocated variable).
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` if the table covers no variables.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Iterates over `(var, name)` pairs in variable order.
+    pub fn iter(&self) -> impl Iterator<Item = (Var, &str)> {
+        Var::range(self.names.len()).zip(&self.names).filter_map(|(var, name)| Some((var, name.as_deref()?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_map_both_ways() {
+        let mut symbols = SymbolTable::new();
+        let a = symbols.var("a");
+        let aux = symbols.fresh();
+        let b = symbols.var("b");
+        assert_eq!((a.index(), aux.index(), b.index()), (0, 1, 2));
+        assert_eq!(symbols.lookup("b"), Some(b));
+        assert_eq!(symbols.name(aux), None);
+        assert_eq!(symbols.iter().collect::<Vec<_>>(), vec![(a, "a"), (b, "b")]);
+    }
+
+    #[test]
+    fn bind_rejects_conflicts() {
+        let mut symbols = SymbolTable::new();
+        let x = Var::new;
+        assert!(symbols.bind(x(4), "d"));
+        assert_eq!(symbols.len(), 5);
+        assert!(symbols.bind(x(4), "d"));
+        assert!(!symbols.bind(x(4), "e")); // variable already named
+        assert!(!symbols.bind(x(1), "d")); // name already taken
+        assert_eq!(symbols.var("x"), x(5));
+    }
+}
@@ -0,0 +1,405 @@
+//! Differential testing harness
+//!
+//! This module provides the infrastructure for checking one solving engine
+//! against another on many random formulas:
+//!
+//! 1. Generate a random formula ([`random_formula`])
+//! 2. Run both engines on it
+//! 3. Verify every returned model against the formula ([`verify_model`]),
+//!    or name the literal satisfying each clause ([`witnesses`])
+//! 4. Cross-check SAT/UNSAT answers between the two engines ([`cross_check`])
+//! 5. Shrink any mismatch to a minimal counterexample ([`shrink`])
+//!
+//! [`differential`] ties all of these steps together.
+//!
+//! # Examples
+//!
+//! ```
+//! use putnam::solver::{brute, dpll};
+//! use putnam::testing::{differential, FormulaConfig};
+//!
+//! let config = FormulaConfig::default();
+//! assert!(differential(dpll::solve, brute::solve, &config, 200, 42).is_ok());
+//! ```
+
+use crate::rng::Rng;
+use crate::solver::dpll::SolveResult;
+use crate::types::*;
+
+/// Shape of the random formulas produced by [`random_formula`].
+#[derive(Clone, Debug)]
+pub struct FormulaConfig {
+    /// Number of variables the formula ranges over
+    pub num_vars: usize,
+    /// Number of clauses to generate
+    pub num_clauses: usize,
+    /// Maximum clause length (lengths are drawn from `1..=max_clause_len`)
+    pub max_clause_len: usize,
+}
+
+impl Default for FormulaConfig {
+    /// Small formulas around the 3-SAT phase transition, cheap enough for
+    /// the brute-force oracle.
+    fn default() -> Self {
+        Self { num_vars: 8, num_clauses: 34, max_clause_len: 3 }
+    }
+}
+
+/// The ways in which two engines can disagree on a formula.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mismatch {
+    /// The given engine (0 = first, 1 = second) returned a model that does
+    /// not satisfy the formula.
+    InvalidModel(usize),
+    /// The given engine (0 = first, 1 = second) answered UNSAT although the
+    /// other engine found a valid model.
+    WrongUnsat(usize),
+}
+
+/// A formula on which two engines disagree, together with the disagreement.
+#[derive(Clone, Debug)]
+pub struct Counterexample {
+    /// The (shrunk) formula exhibiting the mismatch
+    pub formula: Formula,
+    /// Number of variables of the formula
+    pub num_vars: usize,
+    /// What went wrong
+    pub mismatch: Mismatch,
+    /// Seed of the iteration that produced the original formula
+    pub seed: u64,
+}
+
+/// Generates a random CNF formula with the shape described by `config`.
+///
+/// Each clause draws its length uniformly from `1..=max_clause_len` and
+/// each literal picks a uniformly random variable and polarity. Clauses may
+/// contain repeated or complementary literals; engines must cope with that.
+///
+/// # Arguments
+///
+/// * `config` - Size and shape of the formula
+/// * `seed` - Seed for the pseudo-random generator
+///
+/// # Returns
+///
+/// The generated formula. Equal seeds produce equal formulas.
+pub fn random_formula(config: &FormulaConfig, seed: u64) -> Formula {
+    let mut rng = Rng::new(seed);
+    if config.num_vars == 0 || config.max_clause_len == 0 {
+        return vec![Vec::new(); config.num_clauses];
+    }
+    (0..config.num_clauses)
+        .map(|_| {
+            let len = 1 + rng.below(config.max_clause_len);
+            (0..len)
+                .map(|_| Lit { var: Var::new(rng.below(config.num_vars)), neg: rng.coin() })
+                .collect()
+        })
+        .collect()
+}
+
+/// Checks that a model satisfies every clause of the formula.
+///
+/// # Arguments
+///
+/// * `formula` - The CNF formula
+/// * `model` - The assignment to check
+///
+/// # Returns
+///
+/// `true` if every clause contains at least one true literal
+///
+/// # Examples
+///
+/// ```
+/// use putnam::testing::verify_model;
+/// use putnam::types::{Lit, Model, Val, Var};
+///
+/// let formula = vec![vec![Lit::negative(Var::new(0))]];
+/// let mut model = Model::new(1);
+/// model.assign(Var::new(0), Val::False);
+/// assert!(verify_model(&formula, &model));
+/// ```
+pub fn verify_model(formula: &Formula, model: &Model) -> bool {
+    formula.iter().all(|clause| clause.iter().any(|lit| model.is_true(*lit)))
+}
+
+/// Names, for every clause of the formula, a literal of the clause that the
+/// model makes true.
+///
+/// The result is a lightweight certificate of satisfiability: it can be
+/// checked with [`check_witnesses`] without the model, and the witness of
+/// each clause shows why an encoder's clause holds when it was expected to
+/// be violated.
+///
+/// # Arguments
+///
+/// * `formula` - The CNF formula
+/// * `model` - The assignment; unassigned variables and variables beyond
+///   the model satisfy no literal
+///
+/// # Returns
+///
+/// The first true literal of each clause, in clause order, or `None` for
+/// each clause the model does not satisfy
+///
+/// # Examples
+///
+/// ```
+/// use putnam::testing::{check_witnesses, witnesses};
+/// use putnam::types::{Lit, Model, Val, Var};
+///
+/// let x = |var| Lit::positive(Var::new(var));
+/// let formula = vec![vec![x(0), x(1)], vec![!x(0), x(1)], vec![!x(1)]];
+/// let mut model = Model::new(2);
+/// model.assign(Var::new(0), Val::True);
+/// model.assign(Var::new(1), Val::False);
+///
+/// let map = witnesses(&formula, &model);
+/// assert_eq!(map, vec![Some(x(0)), None, Some(!x(1))]);
+/// assert_eq!(check_witnesses(&formula, &map), Err(1));
+/// ```
+pub fn witnesses(formula: &Formula, model: &Model) -> Vec<Option<Lit>> {
+    formula
+        .iter()
+        .map(|clause| clause.iter().copied().find(|&lit| lit.var.index() < model.num_vars() && model.is_true(lit)))
+        .collect()
+}
+
+/// Checks a witness map as produced by [`witnesses`]: every clause must
+/// have a witness that occurs in it, and no two witnesses may be the two
+/// literals of one variable. Such a map proves the formula satisfiable,
+/// since setting every witness true satisfies all clauses.
+///
+/// # Returns
+///
+/// * `Ok(())` - The map is a valid certificate
+/// * `Err(index)` - The index of the first clause without a witness, with a
+///   witness it does not contain, or with a witness contradicting the one
+///   of an earlier clause; or `formula.len()` if the lengths differ
+pub fn check_witnesses(formula: &Formula, witnesses: &[Option<Lit>]) -> Result<(), usize> {
+    let mut values = std::collections::HashMap::new();
+    for (index, (clause, witness)) in formula.iter().zip(witnesses).enumerate() {
+        let Some(lit) = *witness else { return Err(index) };
+        if !clause.contains(&lit) || *values.entry(lit.var).or_insert(lit.neg) != lit.neg {
+            return Err(index);
+        }
+    }
+    if witnesses.len() != formula.len() {
+        return Err(witnesses.len().min(formula.len()));
+    }
+    Ok(())
+}
+
+/// Runs two engines on one formula and cross-checks their answers.
+///
+/// SAT answers are verified against the formula. An UNSAT answer from one
+/// engine is refuted when the other engine produced a valid model.
+///
+/// # Arguments
+///
+/// * `formula` - The CNF formula
+/// * `num_vars` - The number of variables in the formula
+/// * `a` - The first engine
+/// * `b` - The second engine
+///
+/// # Returns
+///
+/// * `Ok(())` - Both engines agree and every model is valid
+/// * `Err(mismatch)` - The first problem found
+pub fn cross_check<A, B>(formula: &Formula, num_vars: usize, a: A, b: B) -> Result<(), Mismatch>
+where
+    A: Fn(&Formula, usize) -> SolveResult,
+    B: Fn(&Formula, usize) -> SolveResult,
+{
+    let results = [a(formula, num_vars), b(formula, num_vars)];
+
+    for (engine, result) in results.iter().enumerate() {
+        if let SolveResult::Sat(model) = result {
+            if !verify_model(formula, model) {
+                return Err(Mismatch::InvalidModel(engine));
+            }
+        }
+    }
+    match (&results[0], &results[1]) {
+        (SolveResult::Unsat, SolveResult::Sat(_)) => Err(Mismatch::WrongUnsat(0)),
+        (SolveResult::Sat(_), SolveResult::Unsat) => Err(Mismatch::WrongUnsat(1)),
+        _ => Ok(()),
+    }
+}
+
+/// Shrinks a mismatching formula to a locally minimal one.
+///
+/// Clauses and then individual literals are greedily removed as long as the
+/// two engines still disagree (in any way) on the result. The process is
+/// repeated until no single removal preserves the mismatch.
+///
+/// # Arguments
+///
+/// * `formula` - A formula on which [`cross_check`] fails
+/// * `num_vars` - The number of variables in the formula
+/// * `a` - The first engine
+/// * `b` - The second engine
+///
+/// # Returns
+///
+/// A formula on which the engines still disagree, with no removable clause
+/// or literal. If the input does not exhibit a mismatch it is returned as is.
+pub fn shrink<A, B>(formula: &Formula, num_vars: usize, a: A, b: B) -> Formula
+where
+    A: Fn(&Formula, usize) -> SolveResult,
+    B: Fn(&Formula, usize) -> SolveResult,
+{
+    let fails = |f: &Formula| cross_check(f, num_vars, &a, &b).is_err();
+    let mut current = formula.clone();
+
+    let mut progress = true;
+    while progress {
+        progress = false;
+
+        let mut i = 0;
+        while i < current.len() {
+            let mut candidate = current.clone();
+            candidate.remove(i);
+            if fails(&candidate) {
+                current = candidate;
+                progress = true;
+            } else {
+                i += 1;
+            }
+        }
+
+        for c in 0..current.len() {
+            let mut j = 0;
+            while j < current[c].len() {
+                let mut candidate = current.clone();
+                candidate[c].remove(j);
+                if fails(&candidate) {
+                    current = candidate;
+                    progress = true;
+                } else {
+                    j += 1;
+                }
+            }
+        }
+    }
+    current
+}
+
+/// Runs a full differential test of two engines on random formulas.
+///
+/// For each iteration a formula is generated with seed `seed + iteration`,
+/// both engines are run and cross-checked, and the first mismatch is shrunk
+/// and reported.
+///
+/// # Arguments
+///
+/// * `a` - The first engine
+/// * `b` - The second engine (typically a trusted oracle such as [`brute::solve`](crate::solver::brute::solve))
+/// * `config` - Shape of the generated formulas
+/// * `iterations` - How many formulas to try
+/// * `seed` - Base seed for formula generation
+///
+/// # Returns
+///
+/// * `Ok(())` - No mismatch was found
+/// * `Err(counterexample)` - A shrunk counterexample for the first mismatch
+pub fn differential<A, B>(
+    a: A,
+    b: B,
+    config: &FormulaConfig,
+    iterations: usize,
+    seed: u64,
+) -> Result<(), Counterexample>
+where
+    A: Fn(&Formula, usize) -> SolveResult,
+    B: Fn(&Formula, usize) -> SolveResult,
+{
+    for iteration in 0..iterations as u64 {
+        let iteration_seed = seed.wrapping_add(iteration);
+        let formula = random_formula(config, iteration_seed);
+        if cross_check(&formula, config.num_vars, &a, &b).is_ok() {
+            continue;
+        }
+
+        let formula = shrink(&formula, config.num_vars, &a, &b);
+        let mismatch = cross_check(&formula, config.num_vars, &a, &b)
+            .expect_err("shrinking preserves the mismatch");
+        return Err(Counterexample { formula, num_vars: config.num_vars, mismatch, seed: iteration_seed });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{brute, dpll};
+
+    /// Always answers UNSAT – a deliberately broken engine.
+    fn always_unsat(_: &Formula, _: usize) -> SolveResult {
+        SolveResult::Unsat
+    }
+
+    #[test]
+    fn random_formula_is_deterministic() {
+        let config = FormulaConfig::default();
+        let f1 = random_formula(&config, 7);
+        let f2 = random_formula(&config, 7);
+
+        assert_eq!(f1.len(), config.num_clauses);
+        assert_eq!(f1, f2);
+        for clause in &f1 {
+            assert!(!clause.is_empty() && clause.len() <= config.max_clause_len);
+            assert!(clause.iter().all(|l| l.var < Var::new(config.num_vars)));
+        }
+    }
+
+    #[test]
+    fn dpll_agrees_with_brute_force() {
+        let config = FormulaConfig { num_vars: 6, num_clauses: 25, max_clause_len: 3 };
+        let result = differential(dpll::solve, brute::solve, &config, 300, 1);
+        assert!(result.is_ok(), "{:?}", result);
+    }
+
+    #[test]
+    fn broken_engine_is_caught_and_shrunk() {
+        let config = FormulaConfig::default();
+        let cex = differential(always_unsat, brute::solve, &config, 100, 3)
+            .expect_err("always_unsat must be caught");
+
+        assert_eq!(cex.mismatch, Mismatch::WrongUnsat(0));
+        // The empty formula is SAT, so shrinking removes every clause.
+        assert!(cex.formula.is_empty());
+    }
+
+    #[test]
+    fn invalid_model_is_reported() {
+        // (x0) solved by an engine that returns an empty model
+        let formula = vec![vec![Lit::positive(Var::new(0))]];
+        let bogus = |_: &Formula, n: usize| SolveResult::Sat(Model::new(n));
+
+        assert_eq!(cross_check(&formula, 1, bogus, brute::solve), Err(Mismatch::InvalidModel(0)));
+    }
+
+    #[test]
+    fn witnesses_certify_models() {
+        for seed in 0..20 {
+            let config = FormulaConfig { num_vars: 8, num_clauses: 30, max_clause_len: 3 };
+            let formula = random_formula(&config, seed);
+            let map = match dpll::solve(&formula, config.num_vars) {
+                SolveResult::Sat(model) => witnesses(&formula, &model),
+                SolveResult::Unsat => continue,
+            };
+            assert!(map.iter().zip(&formula).all(|(witness, clause)| witness.is_some_and(|lit| clause.contains(&lit))));
+            assert_eq!(check_witnesses(&formula, &map), Ok(()));
+            assert_eq!(check_witnesses(&formula, &map[1..]), Err(formula.len() - 1));
+        }
+
+        // Witnesses outside their clause, or of both polarities of a variable
+        let x = |var| Lit::positive(Var::new(var));
+        let formula = vec![vec![x(0), x(1)], vec![!x(0), x(1)]];
+        assert_eq!(check_witnesses(&formula, &[Some(x(0)), Some(x(1))]), Ok(()));
+        assert_eq!(check_witnesses(&formula, &[Some(!x(0)), Some(x(1))]), Err(0));
+        assert_eq!(check_witnesses(&formula, &[Some(x(0)), Some(!x(0))]), Err(1));
+    }
+}
@@ -1,18 +1,85 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::symbols::SymbolTable;
+
 /// Variable identifier using 0-based indexing.
 ///
-/// Variables are represented as simple integers, with the first variable
-/// being 0, the second being 1, and so on. This is converted from
-/// DIMACS format which uses 1-based indexing.
+/// Variables are numbered from 0: the first variable is `Var::new(0)`, the
+/// second `Var::new(1)`, and so on. DIMACS uses 1-based numbering instead
+/// (see [`Lit::from_dimacs`]). Per-variable tables are indexed with
+/// [`index`](Var::index), so that a plain count or position cannot be
+/// mistaken for a variable.
 ///
 /// # Examples
 ///
 /// ```
 /// use putnam::types::Var;
-/// 
-/// let first_var: Var = 0;
-/// let second_var: Var = 1;
+///
+/// let first = Var::new(0);
+/// let second = Var::new(1);
+/// assert!(first < second);
+/// assert_eq!(second.index(), 1);
+/// assert_eq!(Var::try_new(usize::MAX), None);
 /// ```
-pub type Var = usize;
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Var(u32);
+
+impl Var {
+    /// The largest variable index, chosen so that literal codes
+    /// (`2 * index + 1`) still fit in 32 bits.
+    pub const MAX_INDEX: usize = (u32::MAX >> 1) as usize;
+
+    /// Returns the variable with the given 0-based index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` exceeds [`Var::MAX_INDEX`].
+    pub fn new(index: usize) -> Var {
+        match Var::try_new(index) {
+            Some(var) => var,
+            None => panic!("variable index {} exceeds the maximum {}", index, Var::MAX_INDEX),
+        }
+    }
+
+    /// Returns the variable with the given 0-based index, or `None` if it
+    /// exceeds [`Var::MAX_INDEX`].
+    pub fn try_new(index: usize) -> Option<Var> {
+        (index <= Var::MAX_INDEX).then_some(Var(index as u32))
+    }
+
+    /// Returns the 0-based index of the variable.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Returns the first `count` variables, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::types::Var;
+    ///
+    /// let vars: Vec<Var> = Var::range(3).collect();
+    /// assert_eq!(vars, vec![Var::new(0), Var::new(1), Var::new(2)]);
+    /// ```
+    pub fn range(count: usize) -> impl DoubleEndedIterator<Item = Var> + ExactSizeIterator {
+        (0..count).map(Var::new)
+    }
+}
+
+impl From<Var> for usize {
+    fn from(var: Var) -> usize {
+        var.index()
+    }
+}
+
+/// Prints the 0-based index.
+impl fmt::Display for Var {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
 
 /// A literal represents a variable or its negation.
 ///
@@ -29,10 +96,11 @@ pub type Var = usize;
 /// ```
 /// use putnam::types::{Lit, Var};
 ///
-/// let x1 = Lit { var: 0, neg: false };  // Represents x₁
-/// let not_x1 = Lit { var: 0, neg: true };   // Represents ¬x₁
+/// let x1 = Lit { var: Var::new(0), neg: false };  // Represents x₁
+/// let not_x1 = Lit::negative(Var::new(0));         // Represents ¬x₁
+/// assert_eq!(not_x1, !x1);
 /// ```
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Lit {
     /// The variable this literal refers to
     pub var: Var,
@@ -40,6 +108,71 @@ pub struct Lit {
     pub neg: bool,
 }
 
+impl Lit {
+    /// Returns the literal of `var` with the given sign.
+    pub fn new(var: Var, neg: bool) -> Lit {
+        Lit { var, neg }
+    }
+
+    /// Returns the positive literal of `var`.
+    pub fn positive(var: Var) -> Lit {
+        Lit { var, neg: false }
+    }
+
+    /// Returns the negative literal of `var`.
+    pub fn negative(var: Var) -> Lit {
+        Lit { var, neg: true }
+    }
+
+    /// Converts a DIMACS literal (a non-zero integer whose absolute value is
+    /// the 1-based variable number and whose sign is the polarity).
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Lit)` - The literal
+    /// * `None` - `n` is zero (the clause terminator) or its variable
+    ///   exceeds [`Var::MAX_INDEX`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// assert_eq!(Lit::from_dimacs(-3), Some(Lit::negative(Var::new(2))));
+    /// assert_eq!(Lit::from_dimacs(0), None);
+    /// assert_eq!(Lit::from_dimacs(-3).unwrap().to_dimacs(), -3);
+    /// ```
+    pub fn from_dimacs(n: i64) -> Option<Lit> {
+        let index = usize::try_from(n.unsigned_abs()).ok()?.checked_sub(1)?;
+        Some(Lit { var: Var::try_new(index)?, neg: n < 0 })
+    }
+
+    /// Returns the DIMACS form of the literal (see
+    /// [`from_dimacs`](Lit::from_dimacs)).
+    pub fn to_dimacs(self) -> i64 {
+        let n = self.var.index() as i64 + 1;
+        if self.neg { -n } else { n }
+    }
+}
+
+impl std::ops::Not for Lit {
+    type Output = Lit;
+
+    /// Returns the complementary literal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::types::{Lit, Var};
+    ///
+    /// let x1 = Lit::positive(Var::new(0));
+    /// assert_eq!(!x1, Lit::negative(Var::new(0)));
+    /// ```
+    fn not(self) -> Lit {
+        Lit { var: self.var, neg: !self.neg }
+    }
+}
+
 /// A clause is a disjunction (OR) of literals.
 ///
 /// In CNF format, each clause represents a logical OR of its literals.
@@ -49,12 +182,12 @@ pub struct Lit {
 /// # Examples
 ///
 /// ```
-/// use putnam::types::{Clause, Lit};
+/// use putnam::types::{Clause, Lit, Var};
 ///
 /// // Represents (x₁ ∨ ¬x₂)
 /// let clause: Clause = vec![
-///     Lit { var: 0, neg: false },  // x₁
-///     Lit { var: 1, neg: true },   // ¬x₂
+///     Lit::positive(Var::new(0)),  // x₁
+///     Lit::negative(Var::new(1)),  // ¬x₂
 /// ];
 /// ```
 pub type Clause = Vec<Lit>;
@@ -68,16 +201,52 @@ pub type Clause = Vec<Lit>;
 /// # Examples
 ///
 /// ```
-/// use putnam::types::{Formula, Clause, Lit};
+/// use putnam::types::{Formula, Lit, Var};
 ///
 /// // Represents (x₁ ∨ x₂) ∧ (¬x₁ ∨ x₃)
+/// let x = |index| Lit::positive(Var::new(index));
 /// let formula: Formula = vec![
-///     vec![Lit { var: 0, neg: false }, Lit { var: 1, neg: false }],  // (x₁ ∨ x₂)
-///     vec![Lit { var: 0, neg: true }, Lit { var: 2, neg: false }],   // (¬x₁ ∨ x₃)
+///     vec![x(0), x(1)],   // (x₁ ∨ x₂)
+///     vec![!x(0), x(2)],  // (¬x₁ ∨ x₃)
 /// ];
 /// ```
 pub type Formula = Vec<Clause>;
 
+/// Identifies a clause by its 0-based position in the formula, in the order
+/// the clauses are enumerated by
+/// [`FormulaSource::clauses`](crate::store::FormulaSource::clauses).
+///
+/// # Examples
+///
+/// ```
+/// use putnam::types::ClauseId;
+///
+/// let id = ClauseId::new(2);
+/// assert_eq!(id.index(), 2);
+/// assert_eq!(id.to_string(), "2");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClauseId(usize);
+
+impl ClauseId {
+    /// Returns the id of the clause at position `index`.
+    pub fn new(index: usize) -> ClauseId {
+        ClauseId(index)
+    }
+
+    /// Returns the position of the clause in the formula.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// Prints the 0-based position.
+impl fmt::Display for ClauseId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// The truth value of a variable in the current model.
 ///
 /// During the search process, variables can be assigned `True` or `False`,
@@ -102,6 +271,28 @@ pub enum Val {
     Undef,
 }
 
+/// Converts `Some(b)` to the value `b` and `None` to `Undef`.
+impl From<Option<bool>> for Val {
+    fn from(value: Option<bool>) -> Val {
+        match value {
+            Some(true) => Val::True,
+            Some(false) => Val::False,
+            None => Val::Undef,
+        }
+    }
+}
+
+/// Converts `True` and `False` to `Some` and `Undef` to `None`.
+impl From<Val> for Option<bool> {
+    fn from(val: Val) -> Option<bool> {
+        match val {
+            Val::True => Some(true),
+            Val::False => Some(false),
+            Val::Undef => None,
+        }
+    }
+}
+
 /// A model represents the current state of variable assignments.
 ///
 /// The model tracks both the current truth values of all variables and
@@ -111,18 +302,28 @@ pub enum Val {
 /// # Examples
 ///
 /// ```
-/// use putnam::types::{Model, Val};
+/// use putnam::types::{Model, Val, Var};
 ///
+/// let x1 = Var::new(0);
 /// let mut model = Model::new(3);  // Create model for 3 variables
-/// model.assign(0, Val::True);     // Assign x₁ = true
-/// assert_eq!(model.value(0), Val::True);
+/// model.assign(x1, Val::True);    // Assign x₁ = true
+/// assert_eq!(model.value(x1), Val::True);
+///
+/// // Models are cloned when the search explores a branch
+/// let copy = model.clone();
+/// assert_eq!(copy.value(x1), Val::True);
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Model {
     /// Current truth value for each variable
     vals: Vec<Val>,
     /// Assignment trail for backtracking (in assignment order)
     trail: Vec<Var>,
+    /// Clause that forced each variable, `None` for decisions and
+    /// assignments made from outside the search
+    reasons: Vec<Option<ClauseId>>,
+    /// Optional variable names for querying and printing
+    symbols: Option<Arc<SymbolTable>>,
 }
 
 impl Model {
@@ -139,15 +340,13 @@ impl Model {
     /// # Examples
     ///
     /// ```
-    /// use putnam::types::{Model, Val};
+    /// use putnam::types::{Model, Val, Var};
     ///
     /// let model = Model::new(3);
-    /// assert_eq!(model.value(0), Val::Undef);
-    /// assert_eq!(model.value(1), Val::Undef);
-    /// assert_eq!(model.value(2), Val::Undef);
+    /// assert!(Var::range(3).all(|var| model.value(var) == Val::Undef));
     /// ```
     pub fn new(n: usize) -> Self {
-        Self { vals: vec![Val::Undef; n], trail: Vec::new() }
+        Self { vals: vec![Val::Undef; n], trail: Vec::new(), reasons: vec![None; n], symbols: None }
     }
     /// Gets the current truth value of a variable.
     ///
@@ -162,14 +361,81 @@ impl Model {
     /// # Examples
     ///
     /// ```
-    /// use putnam::types::{Model, Val};
+    /// use putnam::types::{Model, Val, Var};
+    ///
+    /// let mut model = Model::new(2);
+    /// model.assign(Var::new(0), Val::True);
+    /// assert_eq!(model.value(Var::new(0)), Val::True);
+    /// assert_eq!(model.value(Var::new(1)), Val::Undef);
+    /// ```
+    pub fn value(&self, v: Var) -> Val { self.vals[v.index()] }
+    /// Returns the number of variables of the model.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::types::Model;
+    ///
+    /// assert_eq!(Model::new(3).num_vars(), 3);
+    /// ```
+    pub fn num_vars(&self) -> usize { self.vals.len() }
+    /// Returns the number of variables of the model, the same as
+    /// [`num_vars`](Model::num_vars); this is the length of [`iter`](Model::iter).
+    pub fn len(&self) -> usize { self.vals.len() }
+    /// Returns `true` if the model has no variables.
+    pub fn is_empty(&self) -> bool { self.vals.is_empty() }
+    /// Returns `true` if every variable of the model is assigned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::types::{Model, Val, Var};
     ///
     /// let mut model = Model::new(2);
-    /// model.assign(0, Val::True);
-    /// assert_eq!(model.value(0), Val::True);
-    /// assert_eq!(model.value(1), Val::Undef);
+    /// model.assign(Var::new(0), Val::True);
+    /// assert!(!model.is_complete());
+    /// model.assign(Var::new(1), Val::False);
+    /// assert!(model.is_complete());
+    /// ```
+    pub fn is_complete(&self) -> bool {
+        !self.vals.contains(&Val::Undef)
+    }
+    /// Iterates over `(var, value)` pairs for every variable of the model,
+    /// in increasing variable order.
+    ///
+    /// # Examples
+    ///
     /// ```
-    pub fn value(&self, v: Var) -> Val { self.vals[v] }
+    /// use putnam::types::{Model, Val, Var};
+    ///
+    /// let mut model = Model::new(3);
+    /// model.assign(Var::new(1), Val::False);
+    /// let pairs: Vec<(Var, Val)> = model.iter().collect();
+    /// assert_eq!(pairs, vec![(Var::new(0), Val::Undef), (Var::new(1), Val::False), (Var::new(2), Val::Undef)]);
+    /// ```
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (Var, Val)> + ExactSizeIterator + '_ {
+        Var::range(self.vals.len()).zip(self.vals.iter().copied())
+    }
+    /// Iterates over the assigned variables and their values, in increasing
+    /// variable order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::types::{Model, Val, Var};
+    ///
+    /// let mut model = Model::new(3);
+    /// model.assign(Var::new(2), Val::True);
+    /// assert_eq!(model.assigned().collect::<Vec<_>>(), vec![(Var::new(2), Val::True)]);
+    /// assert_eq!(model.unassigned().collect::<Vec<_>>(), vec![Var::new(0), Var::new(1)]);
+    /// ```
+    pub fn assigned(&self) -> impl DoubleEndedIterator<Item = (Var, Val)> + '_ {
+        self.iter().filter(|&(_, val)| val != Val::Undef)
+    }
+    /// Iterates over the unassigned variables, in increasing variable order.
+    pub fn unassigned(&self) -> impl DoubleEndedIterator<Item = Var> + '_ {
+        self.iter().filter(|&(_, val)| val == Val::Undef).map(|(var, _)| var)
+    }
     /// Assigns a truth value to a variable and records it in the trail.
     ///
     /// This method both sets the variable's value and adds it to the assignment
@@ -183,43 +449,71 @@ impl Model {
     /// # Examples
     ///
     /// ```
-    /// use putnam::types::{Model, Val};
+    /// use putnam::types::{Model, Val, Var};
     ///
+    /// let (x1, x2) = (Var::new(0), Var::new(1));
     /// let mut model = Model::new(2);
-    /// model.assign(0, Val::True);
-    /// model.assign(1, Val::False);
-    /// assert_eq!(model.value(0), Val::True);
-    /// assert_eq!(model.value(1), Val::False);
+    /// model.assign(x1, Val::True);
+    /// model.assign(x2, Val::False);
+    /// assert_eq!(model.value(x1), Val::True);
+    /// assert_eq!(model.value(x2), Val::False);
     /// ```
     pub fn assign(&mut self, v: Var, val: Val) {
-        self.vals[v] = val;
+        self.vals[v.index()] = val;
+        self.reasons[v.index()] = None;
         self.trail.push(v);
     }
-    /// Creates a deep copy of this model.
+    /// Assigns the value that makes `lit` true, recording `reason` as the
+    /// clause that forced it.
     ///
-    /// This is used during the DPLL search to create independent copies
-    /// for exploring different branches of the search tree.
+    /// Unit propagation assigns through this method, so that conflict
+    /// analysis, core extraction and proof logging can walk back from any
+    /// propagated literal to the clauses it was derived from.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// A new `Model` with the same variable assignments and trail
+    /// * `lit` - The literal made true
+    /// * `reason` - The clause that became unit and forced `lit`
     ///
     /// # Examples
     ///
     /// ```
-    /// use putnam::types::{Model, Val};
+    /// use putnam::types::{ClauseId, Lit, Model, Val, Var};
     ///
-    /// let mut original = Model::new(2);
-    /// original.assign(0, Val::True);
-    /// 
-    /// let copy = original.clone();
-    /// assert_eq!(copy.value(0), Val::True);
+    /// let mut model = Model::new(2);
+    /// model.assign(Var::new(0), Val::True);
+    /// model.imply(Lit::negative(Var::new(1)), ClauseId::new(4));
+    /// assert_eq!(model.value(Var::new(1)), Val::False);
+    /// assert_eq!(model.reason(Var::new(1)), Some(ClauseId::new(4)));
+    /// assert_eq!(model.reason(Var::new(0)), None);
     /// ```
-    pub fn clone(&self) -> Self {
-        Self {
-            vals: self.vals.clone(),
-            trail: self.trail.clone(),
-        }
+    pub fn imply(&mut self, lit: Lit, reason: ClauseId) {
+        self.assign(lit.var, if lit.neg { Val::False } else { Val::True });
+        self.reasons[lit.var.index()] = Some(reason);
+    }
+    /// Returns the clause that forced the value of `v`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(id)` - `v` was assigned by unit propagation on clause `id`
+    /// * `None` - `v` is unassigned, a decision, an assumption, or was
+    ///   assigned with [`assign`](Model::assign)
+    pub fn reason(&self, v: Var) -> Option<ClauseId> {
+        self.reasons[v.index()]
+    }
+    /// Returns the number of assignments made so far (including overwrites).
+    pub(crate) fn trail_len(&self) -> usize {
+        self.trail.len()
+    }
+    /// Returns the variable of the `index`-th assignment.
+    pub(crate) fn trail_var(&self, index: usize) -> Var {
+        self.trail[index]
+    }
+    /// Returns the heap memory held by the model, in bytes.
+    pub(crate) fn heap_bytes(&self) -> usize {
+        self.vals.capacity() * std::mem::size_of::<Val>()
+            + self.trail.capacity() * std::mem::size_of::<Var>()
+            + self.reasons.capacity() * std::mem::size_of::<Option<ClauseId>>()
     }
     /// Checks if a literal is satisfied by the current assignment.
     ///
@@ -238,22 +532,239 @@ impl Model {
     /// # Examples
     ///
     /// ```
-    /// use putnam::types::{Model, Lit, Val};
+    /// use putnam::types::{Model, Lit, Val, Var};
     ///
+    /// let (x1, x2) = (Var::new(0), Var::new(1));
     /// let mut model = Model::new(2);
-    /// model.assign(0, Val::True);
-    /// model.assign(1, Val::False);
+    /// model.assign(x1, Val::True);
+    /// model.assign(x2, Val::False);
     ///
-    /// let pos_lit = Lit { var: 0, neg: false };  // x₁
-    /// let neg_lit = Lit { var: 1, neg: true };   // ¬x₂
+    /// let pos_lit = Lit::positive(x1);  // x₁
+    /// let neg_lit = Lit::negative(x2);  // ¬x₂
     ///
     /// assert!(model.is_true(pos_lit));  // x₁ is true
     /// assert!(model.is_true(neg_lit));  // ¬x₂ is true (since x₂ is false)
     /// ```
     pub fn is_true(&self, l: Lit) -> bool {
+        self.lit_value(l) == Val::True
+    }
+    /// Gets the truth value of a literal: the value of its variable, negated
+    /// for a negative literal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::types::{Lit, Model, Val, Var};
+    ///
+    /// let mut model = Model::new(2);
+    /// model.assign(Var::new(0), Val::True);
+    /// assert_eq!(model.lit_value(Lit::negative(Var::new(0))), Val::False);
+    /// assert_eq!(model.lit_value(Lit::negative(Var::new(1))), Val::Undef);
+    /// ```
+    pub fn lit_value(&self, l: Lit) -> Val {
         match (self.value(l.var), l.neg) {
-            (Val::True,  false) | (Val::False, true) => true,
-            _                                        => false,
+            (Val::True, true) => Val::False,
+            (Val::False, true) => Val::True,
+            (val, _) => val,
+        }
+    }
+    /// Attaches a symbol table so that variables can be queried by name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use putnam::symbols::SymbolTable;
+    /// use putnam::types::{Model, Val};
+    ///
+    /// let mut symbols = SymbolTable::new();
+    /// let x = symbols.var("x");
+    /// let mut model = Model::new(1).with_symbols(Arc::new(symbols));
+    /// model.assign(x, Val::True);
+    /// assert_eq!(model.get("x"), Some(Val::True));
+    /// assert_eq!(model.get("y"), None);
+    /// ```
+    pub fn with_symbols(mut self, symbols: Arc<SymbolTable>) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+    /// Returns the attached symbol table, if any.
+    pub fn symbols(&self) -> Option<&SymbolTable> {
+        self.symbols.as_deref()
+    }
+    /// Gets the value of the variable with the given name.
+    ///
+    /// # Returns
+    ///
+    /// `None` if no symbol table is attached, the name is unknown, or the
+    /// named variable lies outside the model.
+    pub fn get(&self, name: &str) -> Option<Val> {
+        let var = self.symbols.as_ref()?.lookup(name)?;
+        self.vals.get(var.index()).copied()
+    }
+    /// Returns the values of all variables, indexed by variable, with
+    /// `None` for the unassigned ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::types::{Model, Val, Var};
+    ///
+    /// let mut model = Model::new(3);
+    /// model.assign(Var::new(0), Val::True);
+    /// model.assign(Var::new(2), Val::False);
+    /// assert_eq!(model.to_vec(), vec![Some(true), None, Some(false)]);
+    ///
+    /// // Back again, and to plain booleans once complete
+    /// assert_eq!(Model::from(model.to_vec()).to_vec(), model.to_vec());
+    /// assert!(Vec::<bool>::try_from(&model).is_err());
+    /// model.assign(Var::new(1), Val::True);
+    /// assert_eq!(Vec::<bool>::try_from(&model), Ok(vec![true, true, false]));
+    /// ```
+    pub fn to_vec(&self) -> Vec<Option<bool>> {
+        self.vals.iter().map(|&val| val.into()).collect()
+    }
+    /// Returns the canonical form of the model: every unassigned variable
+    /// set to false, and the trail in variable order without reasons.
+    ///
+    /// Which variables a search leaves unassigned, and the order it assigns
+    /// the others in, depend on its heuristics; the canonical form depends
+    /// only on the values of the assigned variables. Output derived from it
+    /// stays the same as long as the solver finds the same model, which
+    /// keeps golden files and diffs stable. The symbol table is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::types::{Model, Val, Var};
+    ///
+    /// let mut model = Model::new(3);
+    /// model.assign(Var::new(2), Val::True);
+    /// model.assign(Var::new(0), Val::True);
+    ///
+    /// let canonical = model.canonical();
+    /// assert_eq!(canonical.to_vec(), vec![Some(true), Some(false), Some(true)]);
+    ///
+    /// // The same values found in another order
+    /// let mut other = Model::new(3);
+    /// for var in [1, 0, 2] {
+    ///     other.assign(Var::new(var), if var == 1 { Val::False } else { Val::True });
+    /// }
+    /// assert_ne!(other, model);
+    /// assert_eq!(other.canonical(), canonical);
+    /// ```
+    pub fn canonical(&self) -> Model {
+        let mut canonical = Model::from(self.vals.iter().map(|&val| val == Val::True).collect::<Vec<bool>>());
+        canonical.symbols = self.symbols.clone();
+        canonical
+    }
+    /// Reports the variables whose values differ between two models.
+    ///
+    /// Models of different sizes are compared as if the shorter one had its
+    /// missing variables set to `Val::Undef`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The model to compare against
+    ///
+    /// # Returns
+    ///
+    /// `(var, value in self, value in other)` for every differing variable,
+    /// in increasing variable order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use putnam::types::{Model, Val, Var};
+    ///
+    /// let x = Var::new;
+    /// let mut a = Model::new(3);
+    /// a.assign(x(0), Val::True);
+    /// a.assign(x(1), Val::False);
+    /// let mut b = Model::new(3);
+    /// b.assign(x(0), Val::True);
+    /// b.assign(x(1), Val::True);
+    /// b.assign(x(2), Val::False);
+    ///
+    /// assert_eq!(a.diff(&b), vec![(x(1), Val::False, Val::True), (x(2), Val::Undef, Val::False)]);
+    /// ```
+    pub fn diff(&self, other: &Model) -> Vec<(Var, Val, Val)> {
+        let value = |m: &Model, v: Var| m.vals.get(v.index()).copied().unwrap_or(Val::Undef);
+        Var::range(self.vals.len().max(other.vals.len()))
+            .map(|v| (v, value(self, v), value(other, v)))
+            .filter(|(_, a, b)| a != b)
+            .collect()
+    }
+}
+
+/// Builds a model assigning the variables in increasing order, leaving
+/// those given as `None` unassigned.
+impl From<Vec<Option<bool>>> for Model {
+    fn from(values: Vec<Option<bool>>) -> Model {
+        let mut model = Model::new(values.len());
+        for (var, value) in Var::range(values.len()).zip(values) {
+            if let Some(value) = value {
+                model.assign(var, Val::from(Some(value)));
+            }
+        }
+        model
+    }
+}
+
+/// Builds a complete model assigning the variables in increasing order.
+impl From<Vec<bool>> for Model {
+    fn from(values: Vec<bool>) -> Model {
+        values.into_iter().map(Some).collect::<Vec<_>>().into()
+    }
+}
+
+impl From<&Model> for Vec<Option<bool>> {
+    fn from(model: &Model) -> Vec<Option<bool>> {
+        model.to_vec()
+    }
+}
+
+/// Exports a complete model as plain booleans.
+impl TryFrom<&Model> for Vec<bool> {
+    type Error = IncompleteModel;
+
+    fn try_from(model: &Model) -> Result<Vec<bool>, IncompleteModel> {
+        model.iter().map(|(var, val)| Option::from(val).ok_or(IncompleteModel { var })).collect()
+    }
+}
+
+/// Error of converting a model that leaves a variable unassigned to
+/// plain booleans.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IncompleteModel {
+    /// The first unassigned variable
+    pub var: Var,
+}
+
+impl fmt::Display for IncompleteModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "variable {} is unassigned", self.var)
+    }
+}
+
+impl std::error::Error for IncompleteModel {}
+
+/// Prints the assigned variables as space-separated `name=value` pairs,
+/// using the attached symbol table for names and `x<var>` otherwise.
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (var, val) in self.assigned() {
+            let value = if val == Val::True { "true" } else { "false" };
+            if !first {
+                f.write_str(" ")?;
+            }
+            first = false;
+            match self.symbols().and_then(|s| s.name(var)) {
+                Some(name) => write!(f, "{}={}", name, value)?,
+                None => write!(f, "x{}={}", var, value)?,
+            }
         }
+        Ok(())
     }
 }